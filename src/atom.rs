@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::ptr;
+use std::sync::{Mutex, OnceLock};
+
+/// An interned string, which is compared by pointer and has a precomputed hash.
+/// Atoms are used for names which are compared many times in selector matching,
+/// e.g. tag names, ids and classes.
+#[derive(Clone, Copy)]
+pub struct Atom(&'static AtomEntry);
+
+struct AtomEntry {
+    string: Box<str>,
+    hash: u32,
+}
+
+impl Atom {
+    /// Interned strings live until the end of the process.
+    pub fn new(string: &str) -> Atom {
+        static ATOMS: OnceLock<Mutex<HashMap<&'static str, &'static AtomEntry>>> = OnceLock::new();
+        let mut atoms = ATOMS.get_or_init(Default::default).lock().unwrap();
+        if let Some(entry) = atoms.get(string) {
+            return Atom(entry);
+        }
+        let entry: &'static AtomEntry = Box::leak(Box::new(AtomEntry {
+            string: string.into(),
+            hash: fnv1a(string),
+        }));
+        atoms.insert(&*entry.string, entry);
+        Atom(entry)
+    }
+
+    /// Hash which is stable across runs, e.g. for a Bloom filter.
+    pub fn get_hash(&self) -> u32 {
+        self.0.hash
+    }
+}
+
+// refer: http://www.isthe.com/chongo/tech/comp/fnv/
+fn fnv1a(string: &str) -> u32 {
+    string.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+impl Deref for Atom {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0.string
+    }
+}
+
+impl PartialEq for Atom {
+    fn eq(&self, other: &Atom) -> bool {
+        ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Atom {}
+
+impl PartialEq<str> for Atom {
+    fn eq(&self, other: &str) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<&str> for Atom {
+    fn eq(&self, other: &&str) -> bool {
+        **self == **other
+    }
+}
+
+impl Hash for Atom {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u32(self.0.hash);
+    }
+}
+
+impl From<&str> for Atom {
+    fn from(string: &str) -> Atom {
+        Atom::new(string)
+    }
+}
+
+impl fmt::Debug for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl fmt::Display for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
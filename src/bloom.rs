@@ -0,0 +1,124 @@
+// refer: https://github.com/servo/servo/blob/master/components/selectors/bloom.rs
+
+use crate::atom::Atom;
+use crate::dom::ElementData;
+
+const KEY_SIZE: usize = 12;
+const ARRAY_SIZE: usize = 1 << KEY_SIZE;
+const KEY_MASK: u32 = (1 << KEY_SIZE) - 1;
+
+/// A counting Bloom filter with 8-bit counters, which supports removal of hashes.
+/// A saturated counter is never decremented, so that a removal never causes a false negative.
+pub struct BloomFilter {
+    counters: Box<[u8; ARRAY_SIZE]>,
+}
+
+impl BloomFilter {
+    pub fn new() -> BloomFilter {
+        BloomFilter {
+            counters: Box::new([0; ARRAY_SIZE]),
+        }
+    }
+
+    pub fn insert_hash(&mut self, hash: u32) {
+        for index in [hash1(hash), hash2(hash)] {
+            let counter = &mut self.counters[index];
+            *counter = counter.saturating_add(1);
+        }
+    }
+
+    pub fn remove_hash(&mut self, hash: u32) {
+        for index in [hash1(hash), hash2(hash)] {
+            let counter = &mut self.counters[index];
+            if *counter != u8::MAX {
+                *counter -= 1;
+            }
+        }
+    }
+
+    /// `false` means that the hash is definitely not in the filter.
+    pub fn might_contain_hash(&self, hash: u32) -> bool {
+        self.counters[hash1(hash)] != 0 && self.counters[hash2(hash)] != 0
+    }
+}
+
+impl Default for BloomFilter {
+    fn default() -> BloomFilter {
+        BloomFilter::new()
+    }
+}
+
+fn hash1(hash: u32) -> usize {
+    (hash & KEY_MASK) as usize
+}
+
+fn hash2(hash: u32) -> usize {
+    ((hash >> KEY_SIZE) & KEY_MASK) as usize
+}
+
+/// A Bloom filter of tag names, ids and classes of the ancestors of the element being styled.
+/// Elements are pushed before styling their children, and popped after that.
+#[derive(Default)]
+pub struct StyleBloom {
+    filter: BloomFilter,
+    /// Hashes inserted by each pushed element.
+    pushed_hashes: Vec<Vec<u32>>,
+}
+
+impl StyleBloom {
+    pub fn new() -> StyleBloom {
+        StyleBloom::default()
+    }
+
+    pub fn push(&mut self, elm: &ElementData) {
+        let hashes = element_hashes(elm);
+        for hash in &hashes {
+            self.filter.insert_hash(*hash);
+        }
+        self.pushed_hashes.push(hashes);
+    }
+
+    pub fn pop(&mut self) {
+        if let Some(hashes) = self.pushed_hashes.pop() {
+            for hash in hashes {
+                self.filter.remove_hash(hash);
+            }
+        }
+    }
+
+    /// Return `false` if some hash is definitely not of an ancestor.
+    pub fn might_contain_all(&self, hashes: &[u32]) -> bool {
+        hashes
+            .iter()
+            .all(|hash| self.filter.might_contain_hash(*hash))
+    }
+}
+
+fn element_hashes(elm: &ElementData) -> Vec<u32> {
+    std::iter::once(elm.local_name())
+        .chain(elm.id())
+        .chain(elm.classes())
+        .map(Atom::get_hash)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counting_bloom_filter() {
+        let mut filter = BloomFilter::new();
+        let (a, b) = (Atom::new("a").get_hash(), Atom::new("b").get_hash());
+
+        filter.insert_hash(a);
+        filter.insert_hash(a);
+        assert!(filter.might_contain_hash(a));
+        assert!(!filter.might_contain_hash(b));
+
+        filter.remove_hash(a);
+        assert!(filter.might_contain_hash(a));
+        filter.remove_hash(a);
+        assert!(!filter.might_contain_hash(a));
+    }
+}
@@ -0,0 +1,601 @@
+// refer: https://drafts.csswg.org/css-cascade/#computed
+
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+
+use crate::cssom::{Color, FontFamily, GenericFontFamily, GridLine, RepeatCount};
+use crate::layout::font::{FontStyle, FontWeight};
+use crate::properties::{LonghandId, DEFAULT_FONT_SIZE};
+use crate::style::{Display, PropertyMap, WordBreak};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LengthOrAuto {
+    Length(f32),
+    /// Percentage of the containing block, where `1.0` is `100%`.
+    Percentage(f32),
+    Auto,
+    /// Intrinsic sizes, which are only for `width`.
+    // refer: https://drafts.csswg.org/css-sizing-3/#sizing-values
+    MinContent,
+    MaxContent,
+    FitContent,
+}
+
+impl LengthOrAuto {
+    pub fn is_auto(&self) -> bool {
+        matches!(self, LengthOrAuto::Auto)
+    }
+
+    /// Resolve a percentage against `basis`, which is the size of the containing block.
+    /// When `basis` is `None`, the percentage is treated as `auto`.
+    pub fn resolve(&self, basis: Option<f32>) -> LengthOrAuto {
+        match (*self, basis) {
+            (LengthOrAuto::Percentage(percentage), Some(basis)) => {
+                LengthOrAuto::Length(percentage * basis)
+            }
+            (LengthOrAuto::Percentage(_), None) => LengthOrAuto::Auto,
+            (value, _) => value,
+        }
+    }
+
+    /// Length in px, or `None` when it is `auto`.
+    /// Percentages must be resolved before, otherwise they are treated as `auto`.
+    /// Intrinsic sizes are also `None`, since they depend on the content.
+    pub fn non_auto(&self) -> Option<f32> {
+        match *self {
+            LengthOrAuto::Length(px) => Some(px),
+            _ => None,
+        }
+    }
+
+    /// Length in px, where `auto` is treated as `0`.
+    /// Percentages must be resolved before, otherwise they are treated as `0`.
+    pub fn to_px(&self) -> f32 {
+        match *self {
+            LengthOrAuto::Length(px) => px,
+            _ => 0.,
+        }
+    }
+}
+
+/// Value of `min-width` and `max-width` etc.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LengthPercentage {
+    Length(f32),
+    /// Percentage of the containing block, where `1.0` is `100%`.
+    Percentage(f32),
+}
+
+impl LengthPercentage {
+    /// Resolve a percentage against `basis`, or return `None` when `basis` is `None`.
+    pub fn resolve(&self, basis: Option<f32>) -> Option<f32> {
+        match (*self, basis) {
+            (LengthPercentage::Length(px), _) => Some(px),
+            (LengthPercentage::Percentage(percentage), Some(basis)) => Some(percentage * basis),
+            (LengthPercentage::Percentage(_), None) => None,
+        }
+    }
+}
+
+// refer: https://www.w3.org/TR/CSS21/visuren.html#choose-position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Position {
+    Static,
+    Relative,
+    Absolute,
+    Fixed,
+    Sticky,
+}
+
+// refer: https://www.w3.org/TR/CSS21/visuren.html#float-position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Float {
+    None,
+    Left,
+    Right,
+}
+
+// refer: https://www.w3.org/TR/CSS21/visuren.html#flow-control
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Clear {
+    None,
+    Left,
+    Right,
+    Both,
+}
+
+/// Vertical alignment, which is only applied to table cells for now.
+// refer: https://www.w3.org/TR/CSS21/tables.html#height-layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerticalAlign {
+    Baseline,
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Which box `width` and `height` are applied to.
+// refer: https://drafts.csswg.org/css-sizing-3/#box-sizing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoxSizing {
+    ContentBox,
+    BorderBox,
+}
+
+// refer: https://www.w3.org/TR/CSS21/tables.html#width-layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableLayout {
+    Auto,
+    Fixed,
+}
+
+// refer: https://www.w3.org/TR/CSS21/tables.html#borders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BorderCollapse {
+    Separate,
+    Collapse,
+}
+
+// refer: https://www.w3.org/TR/CSS21/tables.html#caption-position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaptionSide {
+    Top,
+    Bottom,
+}
+
+// refer: https://drafts.csswg.org/css-flexbox-1/#flex-direction-property
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlexDirection {
+    Row,
+    RowReverse,
+    Column,
+    ColumnReverse,
+}
+
+// refer: https://drafts.csswg.org/css-flexbox-1/#flex-wrap-property
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlexWrap {
+    Nowrap,
+    Wrap,
+    WrapReverse,
+}
+
+// refer: https://drafts.csswg.org/css-flexbox-1/#flex-basis-property
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlexBasis {
+    /// The size of the content.
+    Content,
+    /// `auto` means the main size property of the item.
+    Size(LengthOrAuto),
+}
+
+/// Distribution of free space between items, lines or tracks.
+// refer: https://drafts.csswg.org/css-align-3/#propdef-justify-content
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JustifyContent {
+    /// `normal` and `stretch`, which are `flex-start` in flex containers,
+    /// and stretch `auto` tracks in grid containers.
+    Normal,
+    FlexStart,
+    FlexEnd,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+}
+
+/// Alignment of items in the cross axis, where `normal` is `stretch`.
+// refer: https://drafts.csswg.org/css-align-3/#propdef-align-items
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlignItems {
+    Stretch,
+    FlexStart,
+    FlexEnd,
+    Center,
+    Baseline,
+}
+
+/// Distribution of free space between lines in the cross axis, where `normal` is `stretch`.
+// refer: https://drafts.csswg.org/css-align-3/#propdef-align-content
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlignContent {
+    Stretch,
+    FlexStart,
+    FlexEnd,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+}
+
+// refer: https://drafts.csswg.org/css-grid-2/#typedef-track-breadth
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrackBreadth {
+    Length(f32),
+    /// Percentage of the size of the grid container, where `1.0` is `100%`.
+    Percentage(f32),
+    /// `fr`
+    Flex(f32),
+    Auto,
+}
+
+/// Minimum and maximum track sizing functions, which are the same breadth except for `minmax()`.
+/// The minimum of a flexible breadth is `auto`.
+// refer: https://drafts.csswg.org/css-grid-2/#algo-terms
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackSize {
+    pub min: TrackBreadth,
+    pub max: TrackBreadth,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackListItem {
+    Size(TrackSize),
+    Repeat(RepeatCount, Vec<TrackSize>),
+}
+
+/// Named grid areas of `grid-template-areas`.
+// refer: https://drafts.csswg.org/css-grid-2/#grid-template-areas-property
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridTemplateAreas {
+    pub rows: usize,
+    pub columns: usize,
+    pub areas: Vec<NamedArea>,
+}
+
+/// A rectangle of cells, where ranges are indices of tracks from `0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedArea {
+    pub name: String,
+    pub rows: Range<usize>,
+    pub columns: Range<usize>,
+}
+
+// refer: https://drafts.csswg.org/css-grid-2/#grid-auto-flow-property
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridAutoFlow {
+    /// `column`, otherwise `row`.
+    pub column: bool,
+    pub dense: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineHeight {
+    /// Multiplied by the font size of the element.
+    Number(f32),
+    Length(f32),
+}
+
+/// Inherited font properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontStruct {
+    pub font_size: f32,
+    /// Families as specified, which are tried in order for each character.
+    pub font_family: Vec<FontFamily>,
+    pub font_weight: FontWeight,
+    pub font_style: FontStyle,
+}
+
+/// Inherited text properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InheritedTextStruct {
+    pub color: Color,
+    pub line_height: LineHeight,
+    pub word_break: WordBreak,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxStruct {
+    pub display: Display,
+    pub width: LengthOrAuto,
+    pub height: LengthOrAuto,
+    /// `None` means `auto`, which is `0` except for flex items.
+    pub min_width: Option<LengthPercentage>,
+    /// `None` means `auto`, which is `0` except for flex items.
+    pub min_height: Option<LengthPercentage>,
+    /// `None` means `none`.
+    pub max_width: Option<LengthPercentage>,
+    /// `None` means `none`.
+    pub max_height: Option<LengthPercentage>,
+    pub box_sizing: BoxSizing,
+    pub position: Position,
+    pub top: LengthOrAuto,
+    pub right: LengthOrAuto,
+    pub bottom: LengthOrAuto,
+    pub left: LengthOrAuto,
+    /// `None` means `auto`.
+    pub z_index: Option<i32>,
+    pub float: Float,
+    pub clear: Clear,
+    pub vertical_align: VerticalAlign,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarginStruct {
+    pub margin_top: LengthOrAuto,
+    pub margin_right: LengthOrAuto,
+    pub margin_bottom: LengthOrAuto,
+    pub margin_left: LengthOrAuto,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BorderStruct {
+    pub border_top_width: f32,
+    pub border_right_width: f32,
+    pub border_bottom_width: f32,
+    pub border_left_width: f32,
+    /// `None` renders no border.
+    pub border_color: Option<Color>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaddingStruct {
+    pub padding_top: f32,
+    pub padding_right: f32,
+    pub padding_bottom: f32,
+    pub padding_left: f32,
+}
+
+/// Properties of flex containers and flex items, and box alignment and gaps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlexStruct {
+    pub flex_direction: FlexDirection,
+    pub flex_wrap: FlexWrap,
+    pub flex_grow: f32,
+    pub flex_shrink: f32,
+    pub flex_basis: FlexBasis,
+    pub order: i32,
+    pub justify_content: JustifyContent,
+    pub align_items: AlignItems,
+    /// `None` means `auto`, which is `align-items` of the parent.
+    pub align_self: Option<AlignItems>,
+    pub align_content: AlignContent,
+    /// `normal` is `0`.
+    pub row_gap: LengthPercentage,
+    /// `normal` is `0`.
+    pub column_gap: LengthPercentage,
+}
+
+/// Properties of grid containers and grid items.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridStruct {
+    pub grid_template_columns: Vec<TrackListItem>,
+    pub grid_template_rows: Vec<TrackListItem>,
+    /// `None` means `none`.
+    pub grid_template_areas: Option<GridTemplateAreas>,
+    /// Sizes of implicit tracks, which are repeated.
+    pub grid_auto_columns: Vec<TrackSize>,
+    pub grid_auto_rows: Vec<TrackSize>,
+    pub grid_auto_flow: GridAutoFlow,
+    pub grid_row_start: GridLine,
+    pub grid_row_end: GridLine,
+    pub grid_column_start: GridLine,
+    pub grid_column_end: GridLine,
+    pub justify_items: AlignItems,
+    /// `None` means `auto`, which is `justify-items` of the parent.
+    pub justify_self: Option<AlignItems>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStruct {
+    pub table_layout: TableLayout,
+}
+
+/// Inherited properties of tables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InheritedTableStruct {
+    pub border_collapse: BorderCollapse,
+    /// Horizontal and vertical spacing between cells.
+    pub border_spacing: (f32, f32),
+    pub caption_side: CaptionSide,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackgroundStruct {
+    /// `None` means `transparent`.
+    pub background_color: Option<Color>,
+}
+
+/// Computed values of an element, grouped into style structs.
+/// Inherited structs are shared with the parent, and reset structs are shared with initial values,
+/// until a property in the struct is declared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedValues {
+    pub font: Arc<FontStruct>,
+    pub inherited_text: Arc<InheritedTextStruct>,
+    pub box_: Arc<BoxStruct>,
+    pub margin: Arc<MarginStruct>,
+    pub border: Arc<BorderStruct>,
+    pub padding: Arc<PaddingStruct>,
+    pub flex: Arc<FlexStruct>,
+    pub grid: Arc<GridStruct>,
+    pub table: Arc<TableStruct>,
+    pub inherited_table: Arc<InheritedTableStruct>,
+    pub background: Arc<BackgroundStruct>,
+}
+
+impl ComputedValues {
+    pub fn initial() -> &'static ComputedValues {
+        static INITIAL_VALUES: OnceLock<ComputedValues> = OnceLock::new();
+        INITIAL_VALUES.get_or_init(|| ComputedValues {
+            font: Arc::new(FontStruct {
+                font_size: DEFAULT_FONT_SIZE,
+                font_family: vec![FontFamily::Generic(GenericFontFamily::Serif)],
+                font_weight: FontWeight::NORMAL,
+                font_style: FontStyle::Normal,
+            }),
+            inherited_text: Arc::new(InheritedTextStruct {
+                color: Color::new(0, 0, 0, 1.0),
+                line_height: LineHeight::Number(1.2),
+                word_break: WordBreak::Normal,
+            }),
+            box_: Arc::new(BoxStruct {
+                display: Display::Inline,
+                width: LengthOrAuto::Auto,
+                height: LengthOrAuto::Auto,
+                min_width: None,
+                min_height: None,
+                max_width: None,
+                max_height: None,
+                box_sizing: BoxSizing::ContentBox,
+                position: Position::Static,
+                top: LengthOrAuto::Auto,
+                right: LengthOrAuto::Auto,
+                bottom: LengthOrAuto::Auto,
+                left: LengthOrAuto::Auto,
+                z_index: None,
+                float: Float::None,
+                clear: Clear::None,
+                vertical_align: VerticalAlign::Baseline,
+            }),
+            margin: Arc::new(MarginStruct {
+                margin_top: LengthOrAuto::Length(0.),
+                margin_right: LengthOrAuto::Length(0.),
+                margin_bottom: LengthOrAuto::Length(0.),
+                margin_left: LengthOrAuto::Length(0.),
+            }),
+            border: Arc::new(BorderStruct {
+                border_top_width: 0.,
+                border_right_width: 0.,
+                border_bottom_width: 0.,
+                border_left_width: 0.,
+                border_color: None,
+            }),
+            padding: Arc::new(PaddingStruct {
+                padding_top: 0.,
+                padding_right: 0.,
+                padding_bottom: 0.,
+                padding_left: 0.,
+            }),
+            flex: Arc::new(FlexStruct {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Nowrap,
+                flex_grow: 0.,
+                flex_shrink: 1.,
+                flex_basis: FlexBasis::Size(LengthOrAuto::Auto),
+                order: 0,
+                justify_content: JustifyContent::Normal,
+                align_items: AlignItems::Stretch,
+                align_self: None,
+                align_content: AlignContent::Stretch,
+                row_gap: LengthPercentage::Length(0.),
+                column_gap: LengthPercentage::Length(0.),
+            }),
+            grid: Arc::new(GridStruct {
+                grid_template_columns: vec![],
+                grid_template_rows: vec![],
+                grid_template_areas: None,
+                grid_auto_columns: vec![TrackSize {
+                    min: TrackBreadth::Auto,
+                    max: TrackBreadth::Auto,
+                }],
+                grid_auto_rows: vec![TrackSize {
+                    min: TrackBreadth::Auto,
+                    max: TrackBreadth::Auto,
+                }],
+                grid_auto_flow: GridAutoFlow {
+                    column: false,
+                    dense: false,
+                },
+                grid_row_start: GridLine::Auto,
+                grid_row_end: GridLine::Auto,
+                grid_column_start: GridLine::Auto,
+                grid_column_end: GridLine::Auto,
+                justify_items: AlignItems::Stretch,
+                justify_self: None,
+            }),
+            table: Arc::new(TableStruct {
+                table_layout: TableLayout::Auto,
+            }),
+            inherited_table: Arc::new(InheritedTableStruct {
+                border_collapse: BorderCollapse::Separate,
+                border_spacing: (0., 0.),
+                caption_side: CaptionSide::Top,
+            }),
+            background: Arc::new(BackgroundStruct {
+                background_color: None,
+            }),
+        })
+    }
+
+    /// Values of a node which has no declaration, e.g. a text node.
+    pub fn inherit_from(parent: &ComputedValues) -> ComputedValues {
+        let initial = ComputedValues::initial();
+        ComputedValues {
+            font: parent.font.clone(),
+            inherited_text: parent.inherited_text.clone(),
+            box_: initial.box_.clone(),
+            margin: initial.margin.clone(),
+            border: initial.border.clone(),
+            padding: initial.padding.clone(),
+            flex: initial.flex.clone(),
+            grid: initial.grid.clone(),
+            table: initial.table.clone(),
+            inherited_table: parent.inherited_table.clone(),
+            background: initial.background.clone(),
+        }
+    }
+
+    /// Whether all style structs are shared with `other`, which is cheaper than comparing values.
+    pub fn ptr_eq(&self, other: &ComputedValues) -> bool {
+        Arc::ptr_eq(&self.font, &other.font)
+            && Arc::ptr_eq(&self.inherited_text, &other.inherited_text)
+            && Arc::ptr_eq(&self.box_, &other.box_)
+            && Arc::ptr_eq(&self.margin, &other.margin)
+            && Arc::ptr_eq(&self.border, &other.border)
+            && Arc::ptr_eq(&self.padding, &other.padding)
+            && Arc::ptr_eq(&self.flex, &other.flex)
+            && Arc::ptr_eq(&self.grid, &other.grid)
+            && Arc::ptr_eq(&self.table, &other.table)
+            && Arc::ptr_eq(&self.inherited_table, &other.inherited_table)
+            && Arc::ptr_eq(&self.background, &other.background)
+    }
+
+    /// Compute values from declared values of an element.
+    /// Declarations which can not be converted are ignored.
+    pub fn compute(declared: &PropertyMap, parent: Option<&ComputedValues>) -> ComputedValues {
+        let parent = parent.unwrap_or(ComputedValues::initial());
+        let mut style = ComputedValues::inherit_from(parent);
+
+        // `em` in other properties is relative to the computed font size.
+        if let Some(value) = declared.get("font-size") {
+            LonghandId::FontSize.cascade(value, parent, &mut style);
+        }
+
+        for (name, value) in declared {
+            if let Some(id) = LonghandId::from_name(name) {
+                if id != LonghandId::FontSize {
+                    id.cascade(value, parent, &mut style);
+                }
+            } else if let Some(longhands) = LonghandId::from_shorthand(name) {
+                // A longhand remaining with its shorthand is declared later in cascade.
+                for (index, id) in longhands.iter().enumerate() {
+                    if !declared.contains_key(id.name()) {
+                        id.cascade(&value.component(index), parent, &mut style);
+                    }
+                }
+            }
+        }
+
+        // Absolutely positioned boxes, flex items and grid items are not floated,
+        // and they and floats are block-level, where parts of tables are blocks.
+        // refer: https://www.w3.org/TR/CSS21/visuren.html#dis-pos-flo
+        // refer: https://drafts.csswg.org/css-flexbox-1/#flex-items
+        // refer: https://drafts.csswg.org/css-grid-2/#grid-items
+        let box_ = &style.box_;
+        let is_out_of_flow = matches!(box_.position, Position::Absolute | Position::Fixed);
+        let is_grid_or_flex_item = matches!(parent.box_.display, Display::Flex | Display::Grid);
+        if (is_out_of_flow || is_grid_or_flex_item) && box_.float != Float::None {
+            Arc::make_mut(&mut style.box_).float = Float::None;
+        }
+        let box_ = &style.box_;
+        let is_inline_level = matches!(box_.display, Display::Inline | Display::InlineBlock);
+        if (is_out_of_flow || is_grid_or_flex_item || box_.float != Float::None)
+            && (is_inline_level || box_.display.is_table_internal())
+        {
+            Arc::make_mut(&mut style.box_).display = Display::Block;
+        }
+
+        style
+    }
+}
@@ -1,13 +1,76 @@
+use std::ops::RangeInclusive;
+
+use crate::atom::Atom;
+use crate::layout::font::{FontStyle, FontWeight};
+use crate::media_queries::MediaList;
+
 pub struct Stylesheet {
     pub rules: Vec<Rule>,
+    /// `@import` rules which are not resolved yet.
+    pub imports: Vec<ImportRule>,
+    pub font_faces: Vec<FontFaceRule>,
 }
 
 impl Stylesheet {
     pub fn new(rules: Vec<Rule>) -> Stylesheet {
-        Stylesheet { rules }
+        Stylesheet {
+            rules,
+            imports: vec![],
+            font_faces: vec![],
+        }
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRule {
+    pub url: String,
+    pub media: MediaList,
+}
+
+impl ImportRule {
+    pub fn new(url: String, media: MediaList) -> ImportRule {
+        ImportRule { url, media }
+    }
+}
+
+// refer: https://drafts.csswg.org/css-fonts-4/#font-face-rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFaceRule {
+    pub family_name: String,
+    pub sources: Vec<FontFaceSource>,
+    /// `font-weight` range, which has a single value for non-variable fonts.
+    pub weight: (FontWeight, FontWeight),
+    pub style: FontStyle,
+    /// Code points supported by the font face. An empty list means all code points.
+    pub unicode_range: Vec<RangeInclusive<u32>>,
+}
+
+impl FontFaceRule {
+    pub fn new(family_name: String, sources: Vec<FontFaceSource>) -> FontFaceRule {
+        FontFaceRule {
+            family_name,
+            sources,
+            weight: (FontWeight::NORMAL, FontWeight::NORMAL),
+            style: FontStyle::Normal,
+            unicode_range: vec![],
+        }
+    }
+
+    pub fn contains(&self, codepoint: char) -> bool {
+        self.unicode_range.is_empty()
+            || self
+                .unicode_range
+                .iter()
+                .any(|range| range.contains(&(codepoint as u32)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontFaceSource {
+    Url { url: String, format: Option<String> },
+    Local(String),
+}
+
 pub struct Rule {
     pub selectors: Vec<Selector>,
     pub declarations: Vec<Declaration>,
@@ -41,6 +104,7 @@ impl Origin {
 
 pub enum Selector {
     Simple(SimpleSelector),
+    Complex(ComplexSelector),
 }
 
 pub type Specificity = (usize, usize, usize);
@@ -48,30 +112,72 @@ pub type Specificity = (usize, usize, usize);
 impl Selector {
     pub fn specificity(&self) -> Specificity {
         // http://www.w3.org/TR/selectors/#specificity
-        let Selector::Simple(simple) = self;
-        let a = simple.id.iter().count();
-        let b = simple.class.len();
-        let c = simple.tag_name.iter().count();
-        (a, b, c)
+        match self {
+            Selector::Simple(simple) => simple.specificity(),
+            Selector::Complex(complex) => complex.compounds().fold((0, 0, 0), |acc, simple| {
+                let (a, b, c) = simple.specificity();
+                (acc.0 + a, acc.1 + b, acc.2 + c)
+            }),
+        }
+    }
+
+    /// The rightmost compound selector, which is matched against the element itself.
+    pub fn subject(&self) -> &SimpleSelector {
+        match self {
+            Selector::Simple(simple) => simple,
+            Selector::Complex(complex) => &complex.subject,
+        }
     }
 }
 
 pub struct SimpleSelector {
-    pub tag_name: Option<String>,
-    pub id: Option<String>,
-    pub class: Vec<String>,
+    pub tag_name: Option<Atom>,
+    pub id: Option<Atom>,
+    pub class: Vec<Atom>,
 }
 
 impl SimpleSelector {
-    pub fn new(tag_name: Option<String>, id: Option<String>, class: Vec<String>) -> SimpleSelector {
+    pub fn new(tag_name: Option<Atom>, id: Option<Atom>, class: Vec<Atom>) -> SimpleSelector {
         SimpleSelector {
             tag_name,
             id,
             class,
         }
     }
+
+    fn specificity(&self) -> Specificity {
+        let a = self.id.iter().count();
+        let b = self.class.len();
+        let c = self.tag_name.iter().count();
+        (a, b, c)
+    }
+}
+
+/// Compound selectors joined by combinators, e.g. `div > .note p`.
+// refer: https://drafts.csswg.org/selectors-4/#complex
+pub struct ComplexSelector {
+    pub subject: SimpleSelector,
+    /// Compound selectors on the left of `subject`, from right to left,
+    /// with the combinator on the right of each compound selector.
+    pub ancestors: Vec<(Combinator, SimpleSelector)>,
+}
+
+impl ComplexSelector {
+    pub fn compounds(&self) -> impl Iterator<Item = &SimpleSelector> {
+        std::iter::once(&self.subject).chain(self.ancestors.iter().map(|(_, simple)| simple))
+    }
 }
 
+// refer: https://drafts.csswg.org/selectors-4/#combinators
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combinator {
+    /// ` `
+    Descendant,
+    /// `>`
+    Child,
+}
+
+#[derive(Clone)]
 pub struct Declaration {
     pub name: String,
     pub value: Value,
@@ -90,6 +196,18 @@ pub enum Value {
     Length(f32, Unit),
     Number(f32),
     ColorValue(Color),
+    /// Unparsed tokens of a custom property, or of a value which contains `var()`.
+    Unparsed(String),
+    CssWide(CssWideKeyword),
+    FontFamily(Vec<FontFamily>),
+    /// `grid-template-columns` etc., where `none` is empty.
+    TrackList(Vec<TrackListItem>),
+    /// Grid lines separated by `/`, e.g. `grid-area: 1 / 2 / span 3`.
+    GridPlacement(Vec<GridLine>),
+    /// Rows of `grid-template-areas`, where `none` is empty.
+    GridTemplateAreas(Vec<String>),
+    /// Lengths separated by whitespace, e.g. `border-spacing: 2px 4px`.
+    Lengths(Vec<(f32, Unit)>),
     None,
 }
 
@@ -101,11 +219,146 @@ impl Value {
             _ => 0.0,
         }
     }
+
+    /// Value of the `index`-th longhand of a shorthand.
+    /// Each longhand is set to a component of a value which has several components,
+    /// and to the whole value otherwise.
+    pub fn component(&self, index: usize) -> Value {
+        match self {
+            Value::GridPlacement(lines) => {
+                // A missing line is the same name as the line at the opposite side, or `auto`.
+                // refer: https://drafts.csswg.org/css-grid-2/#placement-shorthands
+                let opposite = match index {
+                    0 => None,
+                    3 => Some(1),
+                    _ => Some(0),
+                };
+                let line = match (lines.get(index), opposite) {
+                    (Some(line), _) => line.clone(),
+                    (None, Some(opposite)) => match self.component(opposite) {
+                        Value::GridPlacement(mut line) => match line.pop() {
+                            Some(GridLine::Name(name)) => GridLine::Name(name),
+                            _ => GridLine::Auto,
+                        },
+                        _ => GridLine::Auto,
+                    },
+                    (None, None) => GridLine::Auto,
+                };
+                Value::GridPlacement(vec![line])
+            }
+            value => value.clone(),
+        }
+    }
+}
+
+// refer: https://drafts.csswg.org/css-cascade/#defaulting-keywords
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CssWideKeyword {
+    Initial,
+    Inherit,
+    Unset,
+    Revert,
+    RevertLayer,
+}
+
+impl CssWideKeyword {
+    pub fn from_ident(ident: &str) -> Option<CssWideKeyword> {
+        match &*ident.to_ascii_lowercase() {
+            "initial" => Some(CssWideKeyword::Initial),
+            "inherit" => Some(CssWideKeyword::Inherit),
+            "unset" => Some(CssWideKeyword::Unset),
+            "revert" => Some(CssWideKeyword::Revert),
+            "revert-layer" => Some(CssWideKeyword::RevertLayer),
+            _ => None,
+        }
+    }
+}
+
+// refer: https://drafts.csswg.org/css-fonts-4/#font-family-prop
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontFamily {
+    /// A quoted string, or a sequence of identifiers joined by a space.
+    Named(String),
+    Generic(GenericFontFamily),
+}
+
+// refer: https://drafts.csswg.org/css-fonts-4/#generic-font-families
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GenericFontFamily {
+    Serif,
+    SansSerif,
+    Cursive,
+    Fantasy,
+    Monospace,
+    SystemUi,
+    UiMonospace,
+    Emoji,
+}
+
+impl GenericFontFamily {
+    pub fn from_ident(ident: &str) -> Option<GenericFontFamily> {
+        match &*ident.to_ascii_lowercase() {
+            "serif" => Some(GenericFontFamily::Serif),
+            "sans-serif" => Some(GenericFontFamily::SansSerif),
+            "cursive" => Some(GenericFontFamily::Cursive),
+            "fantasy" => Some(GenericFontFamily::Fantasy),
+            "monospace" => Some(GenericFontFamily::Monospace),
+            "system-ui" => Some(GenericFontFamily::SystemUi),
+            "ui-monospace" => Some(GenericFontFamily::UiMonospace),
+            "emoji" => Some(GenericFontFamily::Emoji),
+            _ => None,
+        }
+    }
+}
+
+// refer: https://drafts.csswg.org/css-grid-2/#typedef-track-breadth
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackBreadth {
+    Length(f32, Unit),
+    /// `fr`
+    Flex(f32),
+    Auto,
+}
+
+// refer: https://drafts.csswg.org/css-grid-2/#typedef-track-size
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackSize {
+    Breadth(TrackBreadth),
+    /// `minmax(min, max)`
+    MinMax(TrackBreadth, TrackBreadth),
+}
+
+// refer: https://drafts.csswg.org/css-grid-2/#repeat-notation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepeatCount {
+    Count(usize),
+    /// As many repetitions as fit in the grid container.
+    AutoFill,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackListItem {
+    Size(TrackSize),
+    Repeat(RepeatCount, Vec<TrackSize>),
+}
+
+/// A grid line of `grid-row-start` etc.
+// refer: https://drafts.csswg.org/css-grid-2/#typedef-grid-row-start-grid-line
+#[derive(Debug, Clone, PartialEq)]
+pub enum GridLine {
+    Auto,
+    /// A line number, which is negative when counted from the end of the explicit grid.
+    Line(i32),
+    Span(usize),
+    /// A named area, or a line of a named area such as `a-start`.
+    Name(String),
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Unit {
     Px,
+    Em,
+    Percent,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -120,4 +373,30 @@ impl Color {
     pub fn new(r: u8, g: u8, b: u8, a: f32) -> Color {
         Color { r, g, b, a }
     }
+
+    /// Color from a named color keyword, which supports only basic colors.
+    // refer: https://drafts.csswg.org/css-color/#named-colors
+    pub fn from_keyword(keyword: &str) -> Option<Color> {
+        let (r, g, b) = match &*keyword.to_ascii_lowercase() {
+            "transparent" => return Some(Color::new(0, 0, 0, 0.)),
+            "black" => (0, 0, 0),
+            "silver" => (192, 192, 192),
+            "gray" | "grey" => (128, 128, 128),
+            "white" => (255, 255, 255),
+            "maroon" => (128, 0, 0),
+            "red" => (255, 0, 0),
+            "purple" => (128, 0, 128),
+            "fuchsia" => (255, 0, 255),
+            "green" => (0, 128, 0),
+            "lime" => (0, 255, 0),
+            "olive" => (128, 128, 0),
+            "yellow" => (255, 255, 0),
+            "navy" => (0, 0, 128),
+            "blue" => (0, 0, 255),
+            "teal" => (0, 128, 128),
+            "aqua" => (0, 255, 255),
+            _ => return None,
+        };
+        Some(Color::new(r, g, b, 1.))
+    }
 }
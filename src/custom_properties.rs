@@ -0,0 +1,184 @@
+// refer: https://drafts.csswg.org/css-variables/
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::cssom::CssWideKeyword;
+
+/// Map from custom property names (with `--` prefix) to their token lists,
+/// which are kept as source text.
+pub type CustomPropertyMap = HashMap<String, String>;
+
+/// Compute custom properties of an element.
+/// `inherited` has computed values of the parent, and `specified` has declarations of the element.
+/// A property which is in a dependency cycle or is `initial` is dropped.
+/// A property which refers an undefined property without fallback is also dropped
+/// even if the parent has a value, because it is invalid at computed-value time
+/// and becomes the guaranteed-invalid value.
+// refer: https://drafts.csswg.org/css-variables/#cycles
+pub fn compute(
+    inherited: &Arc<CustomPropertyMap>,
+    specified: CustomPropertyMap,
+) -> Arc<CustomPropertyMap> {
+    if specified.is_empty() {
+        return inherited.clone();
+    }
+
+    let mut resolver = Resolver {
+        inherited,
+        specified: &specified,
+        computed: HashMap::new(),
+        invalid: HashSet::new(),
+        in_cycle: HashSet::new(),
+        stack: vec![],
+    };
+    for name in specified.keys() {
+        resolver.resolve(name);
+    }
+
+    let mut computed = CustomPropertyMap::clone(inherited);
+    for name in specified.keys() {
+        if resolver.in_cycle.contains(name) || resolver.invalid.contains(name) {
+            computed.remove(name);
+        } else if let Some(value) = resolver.computed.remove(name) {
+            computed.insert(name.clone(), value);
+        }
+    }
+    Arc::new(computed)
+}
+
+struct Resolver<'a> {
+    inherited: &'a CustomPropertyMap,
+    specified: &'a CustomPropertyMap,
+    computed: CustomPropertyMap,
+    invalid: HashSet<String>,
+    in_cycle: HashSet<String>,
+    /// Properties being resolved, to detect cycles.
+    stack: Vec<String>,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve(&mut self, name: &str) -> Option<String> {
+        if let Some(value) = self.computed.get(name) {
+            return Some(value.clone());
+        }
+        if self.invalid.contains(name) || self.in_cycle.contains(name) {
+            return None;
+        }
+        if let Some(start) = self.stack.iter().position(|n| n == name) {
+            for n in &self.stack[start..] {
+                self.in_cycle.insert(n.clone());
+            }
+            return None;
+        }
+
+        let specified = self.specified;
+        let raw = match specified.get(name) {
+            Some(raw) => raw,
+            // Inherited values have already been substituted.
+            None => return self.inherited.get(name).cloned(),
+        };
+        match CssWideKeyword::from_ident(raw) {
+            // The initial value is the guaranteed-invalid value.
+            Some(CssWideKeyword::Initial) => {
+                self.invalid.insert(name.to_string());
+                return None;
+            }
+            // `revert` has been resolved in cascade.
+            Some(_) => return self.inherited.get(name).cloned(),
+            None => {}
+        }
+
+        self.stack.push(name.to_string());
+        let value = substitute(raw, &mut |n| self.resolve(n));
+        self.stack.pop();
+
+        if self.in_cycle.contains(name) {
+            return None;
+        }
+        match value {
+            Some(value) => {
+                self.computed.insert(name.to_string(), value.clone());
+                Some(value)
+            }
+            // Invalid at computed-value time, so the value is the guaranteed-invalid value.
+            // refer: https://drafts.csswg.org/css-variables/#invalid-at-computed-value-time
+            None => {
+                self.invalid.insert(name.to_string());
+                None
+            }
+        }
+    }
+}
+
+/// Replace every `var(<name> [, <fallback>]?)` in `value`.
+/// Return `None` if a reference can not be resolved and has no fallback.
+// refer: https://drafts.csswg.org/css-variables/#substitute-a-var
+pub fn substitute<F>(value: &str, lookup: &mut F) -> Option<String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some(start) = find_var(rest) {
+        result.push_str(&rest[..start]);
+        let args_start = start + "var(".len();
+        let args_end = args_start + find_close_paren(&rest[args_start..])?;
+        let args = &rest[args_start..args_end];
+
+        let (name, fallback) = match args.find(',') {
+            Some(i) => (args[..i].trim(), Some(&args[i + 1..])),
+            None => (args.trim(), None),
+        };
+        if !name.starts_with("--") {
+            return None;
+        }
+        let replacement = match lookup(name) {
+            Some(value) => value,
+            None => substitute(fallback?.trim(), lookup)?,
+        };
+        result.push_str(&replacement);
+        rest = &rest[args_end + 1..];
+    }
+    result.push_str(rest);
+    Some(result.trim().to_string())
+}
+
+/// Find `var(` which is not a part of another function name, e.g. `--var(`.
+fn find_var(value: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(i) = value[offset..].find("var(") {
+        let start = offset + i;
+        let is_name_part = value[..start]
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if !is_name_part {
+            return Some(start);
+        }
+        offset = start + "var(".len();
+    }
+    None
+}
+
+/// Return the position of `)` closing the function whose arguments start at `value`.
+fn find_close_paren(value: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in value.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '(' => depth += 1,
+            None if c == ')' => {
+                if depth == 0 {
+                    return Some(i);
+                }
+                depth -= 1;
+            }
+            None => {}
+        }
+    }
+    None
+}
@@ -1,5 +1,8 @@
-use std::collections::{HashMap, HashSet};
+use crate::atom::Atom;
+use crate::restyle::{RestyleDamage, StyleData};
+use std::collections::HashMap;
 
+/// A tree of nodes to build a `Document`, e.g. by the HTML parser.
 #[derive(Debug)]
 pub struct Node {
     pub children: Vec<Node>,
@@ -28,30 +31,561 @@ pub enum NodeType {
     Element(ElementData),
 }
 
-pub type AttrMap = HashMap<String, String>;
+/// Attributes of an element in source order, where each name appears once.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AttrMap {
+    attributes: Vec<(String, String)>,
+}
+
+impl AttrMap {
+    pub fn new() -> AttrMap {
+        AttrMap::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Set the value of `name`, which keeps its position if it already exists.
+    /// Return the old value.
+    pub fn insert(&mut self, name: String, value: String) -> Option<String> {
+        match self.attributes.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, old)) => Some(std::mem::replace(old, value)),
+            None => {
+                self.attributes.push((name, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let index = self.attributes.iter().position(|(n, _)| n == name)?;
+        Some(self.attributes.remove(index).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.attributes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    /// Names and values in source order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.attributes.iter().map(|(name, value)| (name, value))
+    }
+}
+
+/// Index of a node in `Document`, which is stable while the document lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub(crate) fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A node in `Document` with links to its relatives.
+#[derive(Debug)]
+pub struct NodeData {
+    pub node_type: NodeType,
+    parent: Option<NodeId>,
+    first_child: Option<NodeId>,
+    last_child: Option<NodeId>,
+    prev_sibling: Option<NodeId>,
+    next_sibling: Option<NodeId>,
+}
+
+impl NodeData {
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn first_child(&self) -> Option<NodeId> {
+        self.first_child
+    }
+
+    pub fn last_child(&self) -> Option<NodeId> {
+        self.last_child
+    }
+
+    pub fn prev_sibling(&self) -> Option<NodeId> {
+        self.prev_sibling
+    }
+
+    pub fn next_sibling(&self) -> Option<NodeId> {
+        self.next_sibling
+    }
+
+    pub fn as_element(&self) -> Option<&ElementData> {
+        match &self.node_type {
+            NodeType::Element(elm) => Some(elm),
+            NodeType::Text(_) => None,
+        }
+    }
+}
+
+/// Nodes stored in an arena, which are mutated through `NodeId`s.
+/// A removed node stays in the arena, so that it can be inserted again.
+#[derive(Debug)]
+pub struct Document {
+    nodes: Vec<NodeData>,
+    /// Style data of each node, which is kept apart from `nodes` so that a restyle can read
+    /// ancestors while storing styles.
+    style_data: Vec<StyleData>,
+    /// States of elements before the first change since the last restyle.
+    snapshots: HashMap<NodeId, ElementSnapshot>,
+    /// Elements which have each id, including removed ones.
+    ids: HashMap<Atom, Vec<NodeId>>,
+    root: NodeId,
+}
+
+impl Document {
+    pub fn new(root: Node) -> Document {
+        let mut document = Document {
+            nodes: vec![],
+            style_data: vec![],
+            snapshots: HashMap::new(),
+            ids: HashMap::new(),
+            root: NodeId(0),
+        };
+        document.root = document.create_node(root);
+        document
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn node(&self, id: NodeId) -> &NodeData {
+        &self.nodes[id.0]
+    }
+
+    pub fn style_data(&self, id: NodeId) -> &StyleData {
+        &self.style_data[id.0]
+    }
+
+    pub fn children(&self, id: NodeId) -> Children<'_> {
+        Children {
+            document: self,
+            next: self.node(id).first_child,
+        }
+    }
+
+    /// Add a tree of nodes, which is not in the document until inserted.
+    pub fn create_node(&mut self, node: Node) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(NodeData {
+            node_type: node.node_type,
+            parent: None,
+            first_child: None,
+            last_child: None,
+            prev_sibling: None,
+            next_sibling: None,
+        });
+        self.style_data.push(StyleData::default());
+        self.update_id(id, None);
+        for child in node.children {
+            let child = self.create_node(child);
+            self.insert_before(id, child, None);
+        }
+        id
+    }
+
+    pub fn create_element(&mut self, name: &str, attrs: AttrMap) -> NodeId {
+        self.create_node(Node::new_element(name.into(), attrs, vec![]))
+    }
+
+    pub fn create_text(&mut self, text: &str) -> NodeId {
+        self.create_node(Node::new_text(text.into()))
+    }
+
+    pub fn append_child(&mut self, parent: NodeId, child: NodeId) {
+        self.insert_before(parent, child, None);
+    }
+
+    /// Insert `child` before `reference`, or at the end if `reference` is `None`.
+    /// `child` is removed from its current parent first, and its subtree is styled again
+    /// because selectors can match differently in the new position.
+    /// `child` must not be `parent` or its ancestor, which would make a cycle.
+    pub fn insert_before(&mut self, parent: NodeId, child: NodeId, reference: Option<NodeId>) {
+        let mut ancestor = Some(parent);
+        while let Some(id) = ancestor {
+            assert_ne!(id, child, "child should not be an ancestor of parent");
+            ancestor = self.node(id).parent;
+        }
+        if let Some(reference) = reference {
+            assert_eq!(
+                self.node(reference).parent,
+                Some(parent),
+                "reference should be a child of parent"
+            );
+        }
+        // Inserting a node before itself keeps it in place.
+        if reference == Some(child) {
+            return;
+        }
+        if let Some(old_parent) = self.node(child).parent {
+            self.remove_child(old_parent, child);
+        }
+        // A subtree which has never been styled has no styles in descendants,
+        // because they are cleared whenever inserted.
+        if self.style_data[child.0].styles.is_some() {
+            let subtree: Vec<NodeId> = self.descendants(child).collect();
+            for id in subtree {
+                self.style_data[id.0].clear();
+            }
+        }
+
+        let prev_sibling = match reference {
+            Some(reference) => self.node(reference).prev_sibling,
+            None => self.node(parent).last_child,
+        };
+        {
+            let child = &mut self.nodes[child.0];
+            child.parent = Some(parent);
+            child.prev_sibling = prev_sibling;
+            child.next_sibling = reference;
+        }
+        match prev_sibling {
+            Some(prev_sibling) => self.nodes[prev_sibling.0].next_sibling = Some(child),
+            None => self.nodes[parent.0].first_child = Some(child),
+        }
+        match reference {
+            Some(reference) => self.nodes[reference.0].prev_sibling = Some(child),
+            None => self.nodes[parent.0].last_child = Some(child),
+        }
+        self.style_data[parent.0].note_damage(RestyleDamage::RebuildBox);
+    }
+
+    pub fn remove_child(&mut self, parent: NodeId, child: NodeId) {
+        let node = &mut self.nodes[child.0];
+        assert_eq!(
+            node.parent,
+            Some(parent),
+            "child should be a child of parent"
+        );
+        let (prev_sibling, next_sibling) = (node.prev_sibling, node.next_sibling);
+        node.parent = None;
+        node.prev_sibling = None;
+        node.next_sibling = None;
+
+        match prev_sibling {
+            Some(prev_sibling) => self.nodes[prev_sibling.0].next_sibling = next_sibling,
+            None => self.nodes[parent.0].first_child = next_sibling,
+        }
+        match next_sibling {
+            Some(next_sibling) => self.nodes[next_sibling.0].prev_sibling = prev_sibling,
+            None => self.nodes[parent.0].last_child = prev_sibling,
+        }
+        self.style_data[parent.0].note_damage(RestyleDamage::RebuildBox);
+    }
+
+    pub fn set_attribute(&mut self, id: NodeId, name: &str, value: String) {
+        self.ensure_snapshot(id);
+        if let NodeType::Element(elm) = &mut self.nodes[id.0].node_type {
+            let old_id = elm.id;
+            elm.set_attribute(name, value);
+            self.update_id(id, old_id);
+        }
+        self.note_attribute_damage(id, name);
+    }
+
+    pub fn remove_attribute(&mut self, id: NodeId, name: &str) -> Option<String> {
+        self.ensure_snapshot(id);
+        match &mut self.nodes[id.0].node_type {
+            NodeType::Element(elm) => {
+                let old_id = elm.id;
+                let value = elm.remove_attribute(name);
+                self.update_id(id, old_id);
+                self.note_attribute_damage(id, name);
+                value
+            }
+            NodeType::Text(_) => None,
+        }
+    }
+
+    /// Spans of table cells and columns are read in layout rather than styling.
+    fn note_attribute_damage(&mut self, id: NodeId, name: &str) {
+        if matches!(name, "colspan" | "rowspan" | "span") {
+            self.style_data[id.0].note_damage(RestyleDamage::Reflow);
+        }
+    }
+
+    /// Move the element from the entry of `old_id` to that of its current id.
+    fn update_id(&mut self, id: NodeId, old_id: Option<Atom>) {
+        let new_id = self.node(id).as_element().and_then(|elm| elm.id);
+        if old_id == new_id {
+            return;
+        }
+        if let Some(old_id) = old_id {
+            if let Some(elements) = self.ids.get_mut(&old_id) {
+                elements.retain(|element| *element != id);
+            }
+        }
+        if let Some(new_id) = new_id {
+            self.ids.entry(new_id).or_default().push(id);
+        }
+    }
+
+    /// Return the first element in tree order which has `id` and is in the document.
+    pub fn get_element_by_id(&self, id: &str) -> Option<NodeId> {
+        let elements: Vec<NodeId> = self
+            .ids
+            .get(&Atom::new(id))?
+            .iter()
+            .copied()
+            .filter(|element| self.is_connected(*element))
+            .collect();
+        match elements.as_slice() {
+            [] => None,
+            [element] => Some(*element),
+            _ => self
+                .descendants(self.root)
+                .find(|element| elements.contains(element)),
+        }
+    }
+
+    /// Whether the node is the root or its descendant.
+    pub fn is_connected(&self, mut id: NodeId) -> bool {
+        while let Some(parent) = self.node(id).parent {
+            id = parent;
+        }
+        id == self.root
+    }
+
+    /// `id` and its descendants in tree order.
+    pub fn descendants(&self, id: NodeId) -> Descendants<'_> {
+        Descendants {
+            document: self,
+            scope: id,
+            next: Some(id),
+        }
+    }
+
+    fn ensure_snapshot(&mut self, id: NodeId) {
+        if let NodeType::Element(elm) = &self.nodes[id.0].node_type {
+            self.snapshots.entry(id).or_insert_with(|| elm.snapshot());
+        }
+    }
+
+    pub fn set_text(&mut self, id: NodeId, text: String) {
+        if let NodeType::Text(old_text) = &mut self.nodes[id.0].node_type {
+            *old_text = text;
+            self.style_data[id.0].note_damage(RestyleDamage::Reflow);
+        }
+    }
+
+    /// Split the document for a restyle, taking snapshots of changed elements.
+    pub(crate) fn split_for_restyle(
+        &mut self,
+    ) -> (
+        &[NodeData],
+        &mut [StyleData],
+        HashMap<NodeId, ElementSnapshot>,
+    ) {
+        let snapshots = std::mem::take(&mut self.snapshots);
+        (&self.nodes, &mut self.style_data, snapshots)
+    }
+}
+
+pub struct Children<'a> {
+    document: &'a Document,
+    next: Option<NodeId>,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.next?;
+        self.next = self.document.node(id).next_sibling;
+        Some(id)
+    }
+}
+
+pub struct Descendants<'a> {
+    document: &'a Document,
+    scope: NodeId,
+    next: Option<NodeId>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.next?;
+        let node = self.document.node(id);
+        self.next = node.first_child.or_else(|| {
+            let mut current = id;
+            loop {
+                if current == self.scope {
+                    return None;
+                }
+                let node = self.document.node(current);
+                if node.next_sibling.is_some() {
+                    return node.next_sibling;
+                }
+                current = node.parent?;
+            }
+        });
+        Some(id)
+    }
+}
 
 #[derive(Debug)]
 pub struct ElementData {
     pub tag_name: String,
+    /// Use `Document::set_attribute` to change attributes, so that the change is restyled.
     pub attributes: AttrMap,
+    /// Atoms for selector matching, which are created from `tag_name` and `attributes`.
+    local_name: Atom,
+    id: Option<Atom>,
+    classes: Vec<Atom>,
+}
+
+/// States of an element which selectors and the `style` attribute depend on.
+#[derive(Debug, Clone)]
+pub struct ElementSnapshot {
+    pub id: Option<Atom>,
+    pub classes: Vec<Atom>,
+    pub style_attribute: Option<String>,
 }
 
 impl ElementData {
     fn new(tag_name: String, attributes: AttrMap) -> ElementData {
-        ElementData {
+        let local_name = Atom::new(&tag_name);
+        let mut elm = ElementData {
             tag_name,
             attributes,
-        }
+            local_name,
+            id: None,
+            classes: vec![],
+        };
+        elm.update_atoms();
+        elm
+    }
+
+    fn update_atoms(&mut self) {
+        self.id = self.attributes.get("id").map(|id| Atom::new(id));
+        self.classes = match self.attributes.get("class") {
+            Some(class_list) => class_list.split_whitespace().map(Atom::new).collect(),
+            None => vec![],
+        };
+    }
+
+    pub fn local_name(&self) -> &Atom {
+        &self.local_name
+    }
+
+    pub fn id(&self) -> Option<&Atom> {
+        self.id.as_ref()
+    }
+
+    pub fn classes(&self) -> &[Atom] {
+        &self.classes
+    }
+
+    pub fn has_class(&self, class: &Atom) -> bool {
+        self.classes.contains(class)
     }
 
-    pub fn id(&self) -> Option<&String> {
-        self.attributes.get("id")
+    fn set_attribute(&mut self, name: &str, value: String) {
+        self.attributes.insert(name.to_string(), value);
+        self.update_atoms();
     }
 
-    pub fn classes(&self) -> HashSet<&str> {
-        match self.attributes.get("class") {
-            Some(class_list) => class_list.split(' ').collect(),
-            None => HashSet::new(),
+    fn remove_attribute(&mut self, name: &str) -> Option<String> {
+        let value = self.attributes.remove(name);
+        self.update_atoms();
+        value
+    }
+
+    fn snapshot(&self) -> ElementSnapshot {
+        ElementSnapshot {
+            id: self.id,
+            classes: self.classes.clone(),
+            style_attribute: self.attributes.get("style").cloned(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::html::HTMLParser;
+
+    fn tag_name(document: &Document, id: NodeId) -> &str {
+        &document.node(id).as_element().unwrap().tag_name
+    }
+
+    fn child_names(document: &Document, id: NodeId) -> Vec<&str> {
+        document
+            .children(id)
+            .map(|child| tag_name(document, child))
+            .collect()
+    }
+
+    #[test]
+    fn test_mutation() {
+        let dom = HTMLParser::new("<div><p></p><span></span></div>".into()).run();
+        let mut document = Document::new(dom);
+        let div = document.root();
+        let p = document.node(div).first_child().unwrap();
+        let span = document.node(div).last_child().unwrap();
+        assert_eq!(document.node(p).parent(), Some(div));
+        assert_eq!(document.node(p).next_sibling(), Some(span));
+        assert_eq!(document.node(span).prev_sibling(), Some(p));
+
+        let em = document.create_element("em", AttrMap::new());
+        document.insert_before(div, em, Some(span));
+        assert_eq!(child_names(&document, div), vec!["p", "em", "span"]);
+
+        // A node in the document is moved.
+        document.insert_before(div, span, Some(p));
+        assert_eq!(child_names(&document, div), vec!["span", "p", "em"]);
+
+        document.insert_before(div, p, Some(p));
+        assert_eq!(child_names(&document, div), vec!["span", "p", "em"]);
+        assert_eq!(document.node(p).prev_sibling(), Some(span));
+        assert_eq!(document.node(p).next_sibling(), Some(em));
+
+        document.remove_child(div, p);
+        assert_eq!(child_names(&document, div), vec!["span", "em"]);
+        assert_eq!(document.node(p).parent(), None);
+        assert_eq!(document.node(em).prev_sibling(), Some(span));
+
+        document.append_child(em, p);
+        assert_eq!(child_names(&document, em), vec!["p"]);
+        assert_eq!(document.node(div).last_child(), Some(em));
+
+        let text = document.create_text("a");
+        document.append_child(p, text);
+        document.set_text(text, "b".into());
+        assert!(matches!(&document.node(text).node_type, NodeType::Text(text) if text == "b"));
+
+        document.set_attribute(em, "class", "a b".into());
+        let elm = document.node(em).as_element().unwrap();
+        assert!(elm.has_class(&Atom::new("b")));
+    }
+
+    #[test]
+    #[should_panic(expected = "child should not be an ancestor of parent")]
+    fn test_insert_ancestor() {
+        let dom = HTMLParser::new("<div><p><span></span></p></div>".into()).run();
+        let mut document = Document::new(dom);
+        let div = document.root();
+        let p = document.node(div).first_child().unwrap();
+        let span = document.node(p).first_child().unwrap();
+        document.append_child(span, div);
+    }
+}
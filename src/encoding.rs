@@ -0,0 +1,363 @@
+// refer: https://html.spec.whatwg.org/multipage/parsing.html#determining-the-character-encoding
+// refer: https://drafts.csswg.org/css-syntax/#input-byte-stream
+
+use encoding_rs::{Encoding, EUC_JP, ISO_2022_JP, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8};
+
+/// Number of bytes which the prescan of `<meta>` looks at.
+const PRESCAN_LENGTH: usize = 1024;
+
+/// Decode an HTML document, and return the text and the encoding used.
+/// The encoding is decided by the BOM, then `<meta>` in the head of the document,
+/// and then a guess from the bytes.
+pub fn decode_html(bytes: &[u8]) -> (String, &'static Encoding) {
+    let encoding = match Encoding::for_bom(bytes) {
+        Some((encoding, _)) => encoding,
+        None => prescan(&bytes[..bytes.len().min(PRESCAN_LENGTH)]).unwrap_or_else(|| guess(bytes)),
+    };
+    let (text, encoding, _) = encoding.decode(bytes);
+    (text.into_owned(), encoding)
+}
+
+/// Decode a stylesheet, and return the text and the encoding used.
+/// The encoding is that of the BOM, `@charset`, the referring document or stylesheet,
+/// or UTF-8, in the order.
+pub fn decode_stylesheet(
+    bytes: &[u8],
+    referrer: Option<&'static Encoding>,
+) -> (String, &'static Encoding) {
+    let encoding = charset_rule(bytes).or(referrer).unwrap_or(UTF_8);
+    // `decode` gives priority to the BOM.
+    let (text, encoding, _) = encoding.decode(bytes);
+    (text.into_owned(), encoding)
+}
+
+/// The encoding of `@charset "...";`, which must be at the very start of the stylesheet.
+fn charset_rule(bytes: &[u8]) -> Option<&'static Encoding> {
+    let rest = bytes.strip_prefix(b"@charset \"")?;
+    let end = rest.windows(2).position(|window| window == b"\";")?;
+    let encoding = Encoding::for_label(&rest[..end])?;
+    Some(utf_8_for_utf_16(encoding))
+}
+
+/// A document in ASCII-compatible bytes can not declare UTF-16 in itself.
+fn utf_8_for_utf_16(encoding: &'static Encoding) -> &'static Encoding {
+    if encoding == UTF_16BE || encoding == UTF_16LE {
+        UTF_8
+    } else {
+        encoding
+    }
+}
+
+/// Guess the encoding of a document without declarations.
+/// Japanese encodings are tried because most documents without declarations are Japanese ones,
+/// and Shift_JIS, which is the default of the Japanese locale, is used if all fail.
+fn guess(bytes: &[u8]) -> &'static Encoding {
+    // ISO-2022-JP is 7-bit, which is also valid UTF-8, and switches character sets
+    // with escape sequences.
+    if bytes
+        .windows(3)
+        .any(|window| matches!(window, b"\x1b$B" | b"\x1b$@" | b"\x1b(J"))
+    {
+        return ISO_2022_JP;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    // Most Shift_JIS text has lead bytes which are invalid in EUC-JP, but EUC-JP text is
+    // often valid in Shift_JIS as half-width katakana.
+    if EUC_JP
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .is_some()
+    {
+        return EUC_JP;
+    }
+    SHIFT_JIS
+}
+
+// refer: https://html.spec.whatwg.org/multipage/parsing.html#prescan-a-byte-stream-to-determine-its-encoding
+fn prescan(bytes: &[u8]) -> Option<&'static Encoding> {
+    let mut prescanner = Prescanner { bytes, pos: 0 };
+    prescanner.run()
+}
+
+struct Prescanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Prescanner<'a> {
+    fn run(&mut self) -> Option<&'static Encoding> {
+        while self.pos < self.bytes.len() {
+            let rest = &self.bytes[self.pos..];
+            if rest.starts_with(b"<!--") {
+                // `-->` may overlap `<!--`, as in `<!-->`.
+                match find(&rest[2..], b"-->") {
+                    Some(end) => self.pos += 2 + end + 3,
+                    None => return None,
+                }
+            } else if starts_with_ignore_case(rest, b"<meta")
+                && rest.get(5).is_some_and(|b| is_space(*b) || *b == b'/')
+            {
+                self.pos += 5;
+                if let Some(encoding) = self.meta() {
+                    return Some(encoding);
+                }
+            } else if rest.len() > 2
+                && ((rest[0] == b'<' && rest[1].is_ascii_alphabetic())
+                    || (rest.starts_with(b"</") && rest[2].is_ascii_alphabetic()))
+            {
+                while self.pos < self.bytes.len()
+                    && !is_space(self.bytes[self.pos])
+                    && self.bytes[self.pos] != b'>'
+                {
+                    self.pos += 1;
+                }
+                while self.attribute().is_some() {}
+            } else if rest.starts_with(b"<!") || rest.starts_with(b"</") || rest.starts_with(b"<?")
+            {
+                match find(rest, b">") {
+                    Some(end) => self.pos += end + 1,
+                    None => return None,
+                }
+            } else {
+                self.pos += 1;
+            }
+        }
+        None
+    }
+
+    /// Return the encoding declared by attributes of `<meta>`.
+    fn meta(&mut self) -> Option<&'static Encoding> {
+        let mut names = vec![];
+        let mut got_pragma = false;
+        let mut need_pragma = None;
+        let mut charset = None;
+        while let Some((name, value)) = self.attribute() {
+            if names.contains(&name) {
+                continue;
+            }
+            match name.as_slice() {
+                b"http-equiv" => got_pragma |= value == b"content-type",
+                b"content" if charset.is_none() => {
+                    if let Some(encoding) = extract_charset(&value).and_then(Encoding::for_label) {
+                        charset = Some(encoding);
+                        need_pragma = Some(true);
+                    }
+                }
+                b"charset" => {
+                    charset = Encoding::for_label(&value);
+                    need_pragma = Some(false);
+                }
+                _ => {}
+            }
+            names.push(name);
+        }
+
+        match need_pragma {
+            None => return None,
+            Some(true) if !got_pragma => return None,
+            _ => {}
+        }
+        let encoding = utf_8_for_utf_16(charset?);
+        if encoding.name() == "x-user-defined" {
+            return Encoding::for_label(b"windows-1252");
+        }
+        Some(encoding)
+    }
+
+    /// Return a lowercase attribute name and value, or `None` at the end of a tag.
+    // refer: https://html.spec.whatwg.org/multipage/parsing.html#concept-get-attributes-when-sniffing
+    fn attribute(&mut self) -> Option<(Vec<u8>, Vec<u8>)> {
+        while is_space(self.peek()?) || self.peek()? == b'/' {
+            self.pos += 1;
+        }
+        if self.peek()? == b'>' {
+            return None;
+        }
+
+        let mut name = vec![];
+        loop {
+            let b = self.peek()?;
+            match b {
+                b'=' if !name.is_empty() => {
+                    self.pos += 1;
+                    break;
+                }
+                b if is_space(b) => {
+                    while is_space(self.peek()?) {
+                        self.pos += 1;
+                    }
+                    if self.peek()? != b'=' {
+                        return Some((name, vec![]));
+                    }
+                    self.pos += 1;
+                    break;
+                }
+                b'/' | b'>' => return Some((name, vec![])),
+                b => name.push(b.to_ascii_lowercase()),
+            }
+            self.pos += 1;
+        }
+
+        while is_space(self.peek()?) {
+            self.pos += 1;
+        }
+        let mut value = vec![];
+        match self.peek()? {
+            quote @ (b'"' | b'\'') => {
+                self.pos += 1;
+                loop {
+                    let b = self.peek()?;
+                    self.pos += 1;
+                    if b == quote {
+                        return Some((name, value));
+                    }
+                    value.push(b.to_ascii_lowercase());
+                }
+            }
+            b'>' => return Some((name, value)),
+            _ => {}
+        }
+        loop {
+            let b = self.peek()?;
+            if is_space(b) || b == b'>' {
+                return Some((name, value));
+            }
+            value.push(b.to_ascii_lowercase());
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+}
+
+/// Extract the label from `content` of `<meta http-equiv="content-type">`.
+// refer: https://html.spec.whatwg.org/multipage/urls-and-fetching.html#algorithm-for-extracting-a-character-encoding-from-a-meta-element
+fn extract_charset(content: &[u8]) -> Option<&[u8]> {
+    let mut pos = 0;
+    loop {
+        pos += find_ignore_case(&content[pos..], b"charset")? + "charset".len();
+        while content.get(pos).is_some_and(|b| is_space(*b)) {
+            pos += 1;
+        }
+        if content.get(pos) == Some(&b'=') {
+            pos += 1;
+            break;
+        }
+    }
+    while content.get(pos).is_some_and(|b| is_space(*b)) {
+        pos += 1;
+    }
+    let rest = &content[pos..];
+    match rest.first()? {
+        quote @ (b'"' | b'\'') => {
+            let end = rest[1..].iter().position(|b| b == quote)?;
+            Some(&rest[1..end + 1])
+        }
+        _ => {
+            let end = rest
+                .iter()
+                .position(|b| is_space(*b) || *b == b';')
+                .unwrap_or(rest.len());
+            Some(&rest[..end])
+        }
+    }
+}
+
+fn is_space(b: u8) -> bool {
+    matches!(b, b'\t' | b'\n' | b'\x0c' | b'\r' | b' ')
+}
+
+fn find(bytes: &[u8], pattern: &[u8]) -> Option<usize> {
+    bytes
+        .windows(pattern.len())
+        .position(|window| window == pattern)
+}
+
+fn find_ignore_case(bytes: &[u8], pattern: &[u8]) -> Option<usize> {
+    bytes
+        .windows(pattern.len())
+        .position(|window| window.eq_ignore_ascii_case(pattern))
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+    bytes.len() >= prefix.len() && bytes[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(encoding: &'static Encoding, head: &str, body: &str) -> Vec<u8> {
+        let mut bytes = head.as_bytes().to_vec();
+        bytes.extend(encoding.encode(body).0.iter());
+        bytes
+    }
+
+    #[test]
+    fn test_decode_html() {
+        let text = "<p>日本語のテキスト</p>";
+
+        let bytes = encode(SHIFT_JIS, "<meta charset=\"Shift_JIS\">", text);
+        assert_eq!(
+            decode_html(&bytes),
+            (format!("<meta charset=\"Shift_JIS\">{}", text), SHIFT_JIS)
+        );
+
+        let head = "<!-- <meta charset=utf-8> --><META http-equiv=Content-Type content='text/html; charset=\"euc-jp\"'>";
+        let bytes = encode(EUC_JP, head, text);
+        assert_eq!(decode_html(&bytes).1, EUC_JP);
+
+        // `content` is ignored without `http-equiv`, and the encoding is guessed.
+        let bytes = encode(SHIFT_JIS, "<meta content='charset=euc-jp'>", text);
+        assert_eq!(decode_html(&bytes).1, SHIFT_JIS);
+
+        // The BOM wins over `<meta>`.
+        let bytes = encode(UTF_8, "\u{feff}<meta charset=shift_jis>", text);
+        assert_eq!(
+            decode_html(&bytes),
+            (format!("<meta charset=shift_jis>{}", text), UTF_8)
+        );
+
+        // UTF-16 declared in ASCII bytes is UTF-8.
+        let bytes = encode(UTF_8, "<meta charset=utf-16le>", text);
+        assert_eq!(decode_html(&bytes).1, UTF_8);
+    }
+
+    #[test]
+    fn test_guess_encoding() {
+        let text = "<p>日本語のテキスト</p>";
+        assert_eq!(guess(text.as_bytes()), UTF_8);
+        assert_eq!(guess(&encode(SHIFT_JIS, "", text)), SHIFT_JIS);
+        assert_eq!(guess(&encode(EUC_JP, "", text)), EUC_JP);
+        assert_eq!(guess(&encode(ISO_2022_JP, "", text)), ISO_2022_JP);
+
+        let (decoded, encoding) = decode_html(&encode(EUC_JP, "", text));
+        assert_eq!((decoded.as_str(), encoding), (text, EUC_JP));
+    }
+
+    #[test]
+    fn test_decode_stylesheet() {
+        let css = "p::before { content: \"日本語\"; }";
+        let bytes = encode(SHIFT_JIS, "@charset \"shift_jis\";\n", css);
+        assert_eq!(
+            decode_stylesheet(&bytes, Some(EUC_JP)),
+            (format!("@charset \"shift_jis\";\n{}", css), SHIFT_JIS)
+        );
+
+        let bytes = encode(EUC_JP, "", css);
+        assert_eq!(
+            decode_stylesheet(&bytes, Some(EUC_JP)),
+            (css.into(), EUC_JP)
+        );
+
+        // `@charset` must be exactly in this form.
+        let bytes = encode(UTF_8, "@charset 'shift_jis';\n", css);
+        assert_eq!(
+            decode_stylesheet(&bytes, None),
+            (format!("@charset 'shift_jis';\n{}", css), UTF_8)
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::cssom::GenericFontFamily;
 use std::collections::HashMap;
 use ucd::{Codepoint, UnicodeBlock};
 
@@ -89,27 +90,56 @@ pub fn fallback_font_families(codepoint: Option<char>) -> Vec<String> {
 
 type GenericFont = Vec<String>;
 
-pub fn get_generic_fonts() -> HashMap<String, GenericFont> {
-    fn append(generic_fonts: &mut HashMap<String, Vec<String>>, key: &str, val: GenericFont) {
-        generic_fonts.insert(key.to_string(), val);
+pub fn get_generic_fonts() -> HashMap<GenericFontFamily, GenericFont> {
+    fn append(
+        generic_fonts: &mut HashMap<GenericFontFamily, GenericFont>,
+        key: GenericFontFamily,
+        val: GenericFont,
+    ) {
+        generic_fonts.insert(key, val);
     }
 
-    let mut generic_fonts = HashMap::with_capacity(5);
+    let mut generic_fonts = HashMap::with_capacity(8);
     append(
         &mut generic_fonts,
-        "serif",
+        GenericFontFamily::Serif,
         vec!["Times New Roman".into(), "ヒラギノ明朝 ProN".into()],
     );
-    append(&mut generic_fonts, "sans-serif", vec!["Helvetica".into()]);
-    append(&mut generic_fonts, "cursive", vec!["Apple Chancery".into()]);
-    append(&mut generic_fonts, "fantasy", vec!["Papyrus".into()]);
     append(
         &mut generic_fonts,
-        "monospace",
+        GenericFontFamily::SansSerif,
+        vec!["Helvetica".into()],
+    );
+    append(
+        &mut generic_fonts,
+        GenericFontFamily::Cursive,
+        vec!["Apple Chancery".into()],
+    );
+    append(
+        &mut generic_fonts,
+        GenericFontFamily::Fantasy,
+        vec!["Papyrus".into()],
+    );
+    append(
+        &mut generic_fonts,
+        GenericFontFamily::Monospace,
         vec!["Menlo".into(), "Osaka".into()],
     );
+    append(
+        &mut generic_fonts,
+        GenericFontFamily::SystemUi,
+        vec!["Helvetica Neue".into(), "ヒラギノ角ゴシック".into()],
+    );
+    append(
+        &mut generic_fonts,
+        GenericFontFamily::UiMonospace,
+        vec!["SF Mono".into(), "Menlo".into()],
+    );
+    append(
+        &mut generic_fonts,
+        GenericFontFamily::Emoji,
+        vec!["Apple Color Emoji".into()],
+    );
 
     generic_fonts
 }
-
-pub static DEFAULT_FONT_FAMILY_NAME: &str = "Times New Roman";
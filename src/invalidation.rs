@@ -0,0 +1,123 @@
+// refer: https://github.com/servo/servo/blob/master/components/style/invalidation/element/invalidation_map.rs
+// refer: https://chromium.googlesource.com/chromium/src/+/master/third_party/blink/renderer/core/css/invalidation/README.md
+
+use std::collections::HashMap;
+
+use crate::atom::Atom;
+use crate::cssom::{Combinator, Selector, SimpleSelector, Stylesheet};
+use crate::dom::{ElementData, ElementSnapshot};
+use crate::selector_map::matches_simple_selector;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InvalidationScope {
+    Children,
+    Descendants,
+}
+
+/// Elements in `scope` of the changed element, which match `subject`, need a restyle.
+/// Only the subject compound is tested, so that some elements may be restyled needlessly.
+#[derive(Clone, Copy)]
+pub struct Invalidation<'a> {
+    pub scope: InvalidationScope,
+    pub subject: &'a SimpleSelector,
+}
+
+impl<'a> Invalidation<'a> {
+    pub fn matches(&self, elm: &ElementData) -> bool {
+        matches_simple_selector(elm, self.subject)
+    }
+}
+
+/// Selectors which depend on an id or a class.
+#[derive(Default)]
+struct Dependencies<'a> {
+    /// The feature is in a subject compound, so that the element itself needs a restyle.
+    element: bool,
+    /// The feature is in a compound of an ancestor.
+    descendants: Vec<Invalidation<'a>>,
+}
+
+/// Map from ids and classes to selectors which depend on them.
+/// Tag names are not mapped, because they never change.
+#[derive(Default)]
+pub struct InvalidationMap<'a> {
+    id_dependencies: HashMap<Atom, Dependencies<'a>>,
+    class_dependencies: HashMap<Atom, Dependencies<'a>>,
+}
+
+impl<'a> InvalidationMap<'a> {
+    pub fn new(stylesheet: &'a Stylesheet) -> InvalidationMap<'a> {
+        let mut map = InvalidationMap::default();
+        for selector in stylesheet.rules.iter().flat_map(|rule| &rule.selectors) {
+            map.add_selector(selector);
+        }
+        map
+    }
+
+    fn add_selector(&mut self, selector: &'a Selector) {
+        let subject = selector.subject();
+        self.add_dependencies(subject, |dependencies| dependencies.element = true);
+
+        let complex = match selector {
+            Selector::Simple(_) => return,
+            Selector::Complex(complex) => complex,
+        };
+        for (i, (combinator, simple)) in complex.ancestors.iter().enumerate() {
+            let scope = if i == 0 && *combinator == Combinator::Child {
+                InvalidationScope::Children
+            } else {
+                InvalidationScope::Descendants
+            };
+            self.add_dependencies(simple, |dependencies| {
+                dependencies
+                    .descendants
+                    .push(Invalidation { scope, subject })
+            });
+        }
+    }
+
+    fn add_dependencies<F>(&mut self, simple: &SimpleSelector, f: F)
+    where
+        F: Fn(&mut Dependencies<'a>),
+    {
+        if let Some(id) = simple.id {
+            f(self.id_dependencies.entry(id).or_default());
+        }
+        for class in &simple.class {
+            f(self.class_dependencies.entry(*class).or_default());
+        }
+    }
+
+    /// Return whether the element itself needs a restyle, and invalidations of its descendants,
+    /// from changes since `snapshot`.
+    pub fn invalidate(
+        &self,
+        elm: &ElementData,
+        snapshot: &ElementSnapshot,
+    ) -> (bool, Vec<Invalidation<'a>>) {
+        let mut restyle_self = snapshot.style_attribute.as_ref() != elm.attributes.get("style");
+        let mut invalidations = vec![];
+        let mut collect = |dependencies: Option<&Dependencies<'a>>| {
+            if let Some(dependencies) = dependencies {
+                restyle_self |= dependencies.element;
+                invalidations.extend(dependencies.descendants.iter().copied());
+            }
+        };
+
+        if snapshot.id.as_ref() != elm.id() {
+            for id in snapshot.id.iter().chain(elm.id()) {
+                collect(self.id_dependencies.get(id));
+            }
+        }
+        let old_classes = snapshot.classes.iter().filter(|c| !elm.has_class(c));
+        let new_classes = elm
+            .classes()
+            .iter()
+            .filter(|c| !snapshot.classes.contains(c));
+        for class in old_classes.chain(new_classes) {
+            collect(self.class_dependencies.get(class));
+        }
+
+        (restyle_self, invalidations)
+    }
+}
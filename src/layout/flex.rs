@@ -0,0 +1,751 @@
+// refer: https://drafts.csswg.org/css-flexbox-1/#layout-algorithm
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{clamp_size, BoxType, Dimensions, FloatContext, LayoutBox, Rect};
+use crate::computed_values::{
+    AlignContent, AlignItems, BoxSizing, ComputedValues, FlexBasis, FlexDirection, FlexWrap,
+    JustifyContent, LengthOrAuto,
+};
+
+/// A flex item, where sizes are of the content box unless noted.
+struct FlexItem {
+    /// Index in children of the flex container
+    index: usize,
+    flex_base_size: f32,
+    hypothetical_main_size: f32,
+    /// The main size while lengths are resolved, which is the used main size after that.
+    target_main_size: f32,
+    min_main_size: f32,
+    max_main_size: Option<f32>,
+    /// Sum of margins, borders and paddings in the main axis, where `auto` margins are `0`.
+    main_edges: f32,
+    /// Whether margins in the main axis are `auto`, at the start and the end.
+    main_auto_margins: (bool, bool),
+    /// Whether margins in the cross axis are `auto`, at the start and the end.
+    cross_auto_margins: (bool, bool),
+    flex_grow: f32,
+    flex_shrink: f32,
+    frozen: bool,
+    align_self: AlignItems,
+    /// Whether the size in the cross axis is `auto`.
+    is_cross_size_auto: bool,
+    /// Size of the margin box in the cross axis.
+    outer_cross_size: f32,
+    /// Offset of the baseline from the cross start margin edge.
+    baseline: f32,
+}
+
+impl FlexItem {
+    fn outer_target_main_size(&self) -> f32 {
+        self.target_main_size + self.main_edges
+    }
+}
+
+/// Dimensions of a flex or grid item laid out at the origin, which are cached for the width.
+#[derive(Clone)]
+pub(super) struct Measurement {
+    width: f32,
+    containing_block_height: Option<f32>,
+    pub(super) dimensions: Dimensions,
+    /// Offset of the last baseline from the top of the margin box.
+    pub(super) baseline: Option<f32>,
+}
+
+struct FlexLine {
+    items: Vec<FlexItem>,
+    cross_size: f32,
+}
+
+/// Return the space before the first of `count` subjects and between subjects,
+/// which distributes `free_space`.
+// refer: https://drafts.csswg.org/css-align-3/#distribution-values
+pub(super) fn distribute(free_space: f32, count: usize, content: JustifyContent) -> (f32, f32) {
+    let count = count as f32;
+    match content {
+        JustifyContent::Normal | JustifyContent::FlexStart => (0., 0.),
+        JustifyContent::FlexEnd => (free_space, 0.),
+        JustifyContent::Center => (free_space / 2., 0.),
+        // Negative free space overflows like `flex-start` or `center`.
+        JustifyContent::SpaceBetween if free_space < 0. || count < 2. => (0., 0.),
+        JustifyContent::SpaceAround | JustifyContent::SpaceEvenly if free_space < 0. => {
+            (free_space / 2., 0.)
+        }
+        JustifyContent::SpaceBetween => (0., free_space / (count - 1.)),
+        JustifyContent::SpaceAround => (free_space / count / 2., free_space / count),
+        JustifyContent::SpaceEvenly => (free_space / (count + 1.), free_space / (count + 1.)),
+    }
+}
+
+/// `align-content` as a distribution of free space, where `stretch` is `normal`.
+pub(super) fn content_distribution(align_content: AlignContent) -> JustifyContent {
+    match align_content {
+        AlignContent::Stretch => JustifyContent::Normal,
+        AlignContent::FlexStart => JustifyContent::FlexStart,
+        AlignContent::FlexEnd => JustifyContent::FlexEnd,
+        AlignContent::Center => JustifyContent::Center,
+        AlignContent::SpaceBetween => JustifyContent::SpaceBetween,
+        AlignContent::SpaceAround => JustifyContent::SpaceAround,
+        AlignContent::SpaceEvenly => JustifyContent::SpaceEvenly,
+    }
+}
+
+/// Resolve target main sizes of items in a line, which fill `available_space`.
+// refer: https://drafts.csswg.org/css-flexbox-1/#resolve-flexible-lengths
+fn resolve_flexible_lengths(items: &mut [FlexItem], available_space: f32, gaps: f32) {
+    let hypothetical_outer_size: f32 = items
+        .iter()
+        .map(|item| item.hypothetical_main_size + item.main_edges)
+        .sum();
+    let is_growing = hypothetical_outer_size + gaps < available_space;
+
+    // Size inflexible items.
+    for item in items.iter_mut() {
+        let flex_factor = if is_growing {
+            item.flex_grow
+        } else {
+            item.flex_shrink
+        };
+        item.frozen = flex_factor == 0.
+            || (is_growing && item.flex_base_size > item.hypothetical_main_size)
+            || (!is_growing && item.flex_base_size < item.hypothetical_main_size);
+        item.target_main_size = if item.frozen {
+            item.hypothetical_main_size
+        } else {
+            item.flex_base_size
+        };
+    }
+
+    let free_space = |items: &[FlexItem]| {
+        let used: f32 = items.iter().map(FlexItem::outer_target_main_size).sum();
+        available_space - gaps - used
+    };
+    let initial_free_space = free_space(items);
+
+    while items.iter().any(|item| !item.frozen) {
+        // Targets of unfrozen items are flex base sizes here.
+        let unfrozen = || items.iter().filter(|item| !item.frozen);
+        let mut remaining_free_space = free_space(items);
+        let sum_of_flex_factors: f32 = if is_growing {
+            unfrozen().map(|item| item.flex_grow).sum()
+        } else {
+            unfrozen().map(|item| item.flex_shrink).sum()
+        };
+        if sum_of_flex_factors < 1. {
+            let scaled = initial_free_space * sum_of_flex_factors;
+            if scaled.abs() < remaining_free_space.abs() {
+                remaining_free_space = scaled;
+            }
+        }
+        let sum_of_scaled_shrink_factors: f32 = unfrozen()
+            .map(|item| item.flex_shrink * item.flex_base_size)
+            .sum();
+
+        let mut total_violation = 0.;
+        let mut violations = vec![0.; items.len()];
+        for (item, violation) in items.iter_mut().zip(violations.iter_mut()) {
+            if item.frozen {
+                continue;
+            }
+            let target = if is_growing && sum_of_flex_factors > 0. {
+                item.flex_base_size + remaining_free_space * item.flex_grow / sum_of_flex_factors
+            } else if !is_growing && sum_of_scaled_shrink_factors > 0. {
+                let ratio = item.flex_shrink * item.flex_base_size / sum_of_scaled_shrink_factors;
+                item.flex_base_size - remaining_free_space.abs() * ratio
+            } else {
+                item.flex_base_size
+            };
+            let clamped = clamp_size(target, item.min_main_size, item.max_main_size).max(0.);
+            *violation = clamped - target;
+            total_violation += *violation;
+            item.target_main_size = clamped;
+        }
+
+        // Freeze all items, or items which violate min or max sizes.
+        for (item, violation) in items.iter_mut().zip(violations) {
+            if item.frozen {
+                continue;
+            }
+            item.frozen = total_violation == 0.
+                || (total_violation > 0. && violation > 0.)
+                || (total_violation < 0. && violation < 0.);
+            if !item.frozen {
+                item.target_main_size = item.flex_base_size;
+            }
+        }
+    }
+}
+
+impl<'a> LayoutBox<'a> {
+    /// Style of a box, where an anonymous box such as a flex or grid item has initial values.
+    /// A table wrapper box has the style of its table.
+    pub(super) fn item_style(&self) -> &'a ComputedValues {
+        match &self.box_type {
+            BoxType::AnonymousBlock | BoxType::AnonymousTableBox(_) => ComputedValues::initial(),
+            BoxType::TableWrapper => self.wrapped_table().item_style(),
+            _ => &self.get_style_node().style,
+        }
+    }
+
+    /// Whether the box is an anonymous block, which has only collapsible white space.
+    /// It does not become a flex or grid item.
+    pub(super) fn is_collapsible_whitespace(&self) -> bool {
+        matches!(self.box_type, BoxType::AnonymousBlock)
+            && self.children.iter().all(|child| match &child.box_type {
+                BoxType::TextNode(node) => node.get_text().trim().is_empty(),
+                _ => false,
+            })
+    }
+
+    /// Lay out a flex or grid item in a new formatting context, whose margin box is at the origin.
+    /// `height` is the height of the content box when it is determined by the container.
+    pub(super) fn layout_at_origin(
+        &mut self,
+        width: f32,
+        height: Option<f32>,
+        containing_block_height: Option<f32>,
+    ) {
+        if let BoxType::TableWrapper = self.box_type {
+            let containing_block = Rect {
+                width,
+                ..Default::default()
+            };
+            self.layout_table_wrapper(&containing_block, width, containing_block_height, false);
+            return;
+        }
+        if let BoxType::AnonymousBlock = self.box_type {
+            let containing_block = Dimensions {
+                content: Rect {
+                    width,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            self.layout(
+                Rc::new(RefCell::new(containing_block)),
+                None,
+                &mut FloatContext::new(),
+            );
+            let mut d = self.dimensions.borrow_mut();
+            d.content.width = width;
+            if let Some(height) = height {
+                d.content.height = height;
+            }
+            return;
+        }
+
+        let (style_height, min_height, max_height) =
+            self.block_height_constraints(containing_block_height);
+        {
+            let mut d = self.dimensions.borrow_mut();
+            d.content.width = width;
+            d.content.x = d.margin_left_offset();
+            d.content.y = d.margin.top + d.border_top_offset();
+            d.content.height = 0.;
+        }
+        let definite_height =
+            height.or_else(|| style_height.map(|h| clamp_size(h, min_height, max_height)));
+        let mut floats = FloatContext::new();
+        self.layout_contents(definite_height, false, false, &mut floats);
+        self.include_floats(&floats);
+        match height {
+            Some(height) => self.dimensions.borrow_mut().content.height = height,
+            None => self.calculate_block_height(style_height, min_height, max_height),
+        }
+    }
+
+    /// Measure the box laid out at the origin with `width`, where the content height is `auto`.
+    /// The box is laid out as a copy, since layout is not idempotent.
+    /// Measurements are cached and shared with copies, so that items in nested containers are
+    /// laid out once for each width, not for each measurement of their ancestors.
+    pub(super) fn measure(&self, width: f32, containing_block_height: Option<f32>) -> Measurement {
+        let cached = self
+            .measurements
+            .borrow()
+            .iter()
+            .find(|measurement| {
+                measurement.width == width
+                    && measurement.containing_block_height == containing_block_height
+            })
+            .cloned();
+        if let Some(measurement) = cached {
+            return measurement;
+        }
+        let mut measured = self.clone();
+        measured.layout_at_origin(width, None, containing_block_height);
+        let measurement = Measurement {
+            width,
+            containing_block_height,
+            dimensions: measured.dimensions.borrow().clone(),
+            baseline: measured.last_baseline(),
+        };
+        self.measurements.borrow_mut().push(measurement.clone());
+        measurement
+    }
+
+    /// Lay out children of a flex container, whose content width is determined.
+    /// `definite_height` is the height of the content box when it is definite.
+    pub(super) fn layout_flex_items(&mut self, definite_height: Option<f32>) {
+        let flex = &self.get_style_node().style.flex;
+        let content = self.dimensions.borrow().content.clone();
+        let is_row = matches!(
+            flex.flex_direction,
+            FlexDirection::Row | FlexDirection::RowReverse
+        );
+        let is_reverse = matches!(
+            flex.flex_direction,
+            FlexDirection::RowReverse | FlexDirection::ColumnReverse
+        );
+        let is_single_line = flex.flex_wrap == FlexWrap::Nowrap;
+        let (main_size, cross_size) = if is_row {
+            (Some(content.width), definite_height)
+        } else {
+            (definite_height, Some(content.width))
+        };
+        let row_gap = flex.row_gap.resolve(definite_height).unwrap_or(0.);
+        let column_gap = flex.column_gap.resolve(Some(content.width)).unwrap_or(0.);
+        let (main_gap, cross_gap) = if is_row {
+            (column_gap, row_gap)
+        } else {
+            (row_gap, column_gap)
+        };
+
+        self.children
+            .retain(|child| !child.is_collapsible_whitespace());
+        let mut indices = vec![];
+        for (index, child) in self.children.iter().enumerate() {
+            if child.is_out_of_flow() {
+                // The static position is the content edge of the container.
+                let mut d = child.dimensions.borrow_mut();
+                d.content.x = content.x;
+                d.content.y = content.y;
+            } else {
+                indices.push(index);
+            }
+        }
+        // Items are laid out in `order`, and in the document order for the same `order`.
+        indices.sort_by_key(|&index| self.children[index].item_style().flex.order);
+
+        // Determine the flex base size and hypothetical main size of each item.
+        let mut items = vec![];
+        for index in indices {
+            let child = &mut self.children[index];
+            let style = child.item_style();
+            let box_ = &style.box_;
+            if let BoxType::BlockNode(_) = child.box_type {
+                child.assign_horizontal_margin_box(content.width);
+                child.assign_vertical_margin_box(content.width);
+            }
+            let (horizontal_edges, vertical_edges, horizontal_margins, vertical_margins) = {
+                let d = child.dimensions.borrow();
+                (
+                    d.border.left + d.border.right + d.padding.left + d.padding.right,
+                    d.border.top + d.border.bottom + d.padding.top + d.padding.bottom,
+                    d.margin.left + d.margin.right,
+                    d.margin.top + d.margin.bottom,
+                )
+            };
+            let to_content_size = |size: f32, edges: f32| match box_.box_sizing {
+                BoxSizing::ContentBox => size,
+                BoxSizing::BorderBox => (size - edges).max(0.),
+            };
+            let margin = &style.margin;
+            let (main_edges, main_margins, cross_margins) = if is_row {
+                (
+                    horizontal_edges,
+                    (margin.margin_left, margin.margin_right),
+                    (margin.margin_top, margin.margin_bottom),
+                )
+            } else {
+                (
+                    vertical_edges,
+                    (margin.margin_top, margin.margin_bottom),
+                    (margin.margin_left, margin.margin_right),
+                )
+            };
+            let (main_property, cross_property, min_main, max_main) = if is_row {
+                (box_.width, box_.height, box_.min_width, box_.max_width)
+            } else {
+                (box_.height, box_.width, box_.min_height, box_.max_height)
+            };
+            let align_self = style.flex.align_self.unwrap_or(flex.align_items);
+            let cross_auto_margins = (cross_margins.0.is_auto(), cross_margins.1.is_auto());
+
+            // The width of an item in a column is determined before the main size,
+            // because the content height depends on it.
+            let column_item_width = if is_row {
+                0.
+            } else {
+                let available = content.width - horizontal_margins - horizontal_edges;
+                let width = box_.width.resolve(Some(content.width));
+                let specified =
+                    child.resolve_content_width(width, horizontal_edges, Some(available));
+                let width = match specified {
+                    Some(width) => width,
+                    None if align_self == AlignItems::Stretch
+                        && is_single_line
+                        && cross_auto_margins == (false, false) =>
+                    {
+                        available.max(0.)
+                    }
+                    None => child.shrink_to_fit_width(available),
+                };
+                let min_width = box_
+                    .min_width
+                    .and_then(|min_width| min_width.resolve(Some(content.width)))
+                    .map_or(0., |min_width| to_content_size(min_width, horizontal_edges));
+                let max_width = box_
+                    .max_width
+                    .and_then(|max_width| max_width.resolve(Some(content.width)))
+                    .map(|max_width| to_content_size(max_width, horizontal_edges));
+                clamp_size(width, min_width, max_width)
+            };
+
+            let basis = match style.flex.flex_basis {
+                FlexBasis::Size(LengthOrAuto::Auto) => main_property,
+                FlexBasis::Size(size) => size,
+                FlexBasis::Content => LengthOrAuto::Auto,
+            };
+            let flex_base_size = match basis.resolve(main_size) {
+                LengthOrAuto::Length(size) => to_content_size(size, main_edges),
+                // The content size is the max-content size.
+                basis if is_row => {
+                    let available = content.width - horizontal_margins - main_edges;
+                    child
+                        .resolve_content_width(basis, main_edges, Some(available))
+                        .unwrap_or_else(|| child.intrinsic_widths().1)
+                }
+                _ => {
+                    child
+                        .measure(column_item_width, None)
+                        .dimensions
+                        .content
+                        .height
+                }
+            };
+            let max_main_size = max_main
+                .and_then(|max| max.resolve(main_size))
+                .map(|max| to_content_size(max, main_edges));
+            let min_main_size = match min_main {
+                Some(min) => min
+                    .resolve(main_size)
+                    .map_or(0., |min| to_content_size(min, main_edges)),
+                // `auto` is the content-based minimum size, which is the min-content size
+                // unless the specified size is smaller.
+                // refer: https://drafts.csswg.org/css-flexbox-1/#min-size-auto
+                None => {
+                    let content_size = if is_row {
+                        child.intrinsic_widths().0
+                    } else {
+                        child
+                            .measure(column_item_width, None)
+                            .dimensions
+                            .content
+                            .height
+                    };
+                    let content_size =
+                        max_main_size.map_or(content_size, |max| content_size.min(max));
+                    match main_property.resolve(main_size) {
+                        LengthOrAuto::Length(size) => {
+                            content_size.min(to_content_size(size, main_edges))
+                        }
+                        _ => content_size,
+                    }
+                }
+            };
+            let hypothetical_main_size = clamp_size(flex_base_size, min_main_size, max_main_size);
+
+            items.push(FlexItem {
+                index,
+                flex_base_size,
+                hypothetical_main_size,
+                target_main_size: hypothetical_main_size,
+                min_main_size,
+                max_main_size,
+                main_edges: main_edges
+                    + if is_row {
+                        horizontal_margins
+                    } else {
+                        vertical_margins
+                    },
+                main_auto_margins: (main_margins.0.is_auto(), main_margins.1.is_auto()),
+                cross_auto_margins,
+                flex_grow: style.flex.flex_grow,
+                flex_shrink: style.flex.flex_shrink,
+                frozen: false,
+                align_self,
+                is_cross_size_auto: cross_property.resolve(cross_size).is_auto(),
+                outer_cross_size: if is_row {
+                    0.
+                } else {
+                    column_item_width + horizontal_edges + horizontal_margins
+                },
+                baseline: 0.,
+            });
+        }
+
+        // Collect items into flex lines.
+        // refer: https://drafts.csswg.org/css-flexbox-1/#algo-line-break
+        let mut lines: Vec<FlexLine> = vec![];
+        let mut line_size = 0.;
+        for item in items {
+            let outer_size = item.hypothetical_main_size + item.main_edges;
+            let fits = match (lines.last(), main_size) {
+                (Some(line), Some(main_size)) if !is_single_line => {
+                    line_size + main_gap + outer_size <= main_size || line.items.is_empty()
+                }
+                (Some(_), _) => true,
+                (None, _) => false,
+            };
+            if fits {
+                line_size += main_gap + outer_size;
+                lines.last_mut().unwrap().items.push(item);
+            } else {
+                line_size = outer_size;
+                lines.push(FlexLine {
+                    items: vec![item],
+                    cross_size: 0.,
+                });
+            }
+        }
+
+        // Resolve main sizes, and lay out items to determine cross sizes.
+        for line in &mut lines {
+            let gaps = main_gap * (line.items.len().saturating_sub(1)) as f32;
+            if let Some(main_size) = main_size {
+                resolve_flexible_lengths(&mut line.items, main_size, gaps);
+            }
+            for item in &mut line.items {
+                let child = &mut self.children[item.index];
+                if is_row {
+                    // Items in a row are laid out after they are stretched to the line.
+                    let measurement = child.measure(item.target_main_size, definite_height);
+                    let margin_box = measurement.dimensions.margin_box();
+                    item.outer_cross_size = margin_box.height;
+                    item.baseline = measurement.baseline.unwrap_or(margin_box.height);
+                } else {
+                    // The width is determined before, with the hypothetical main size.
+                    let width = {
+                        let d = child.dimensions.borrow();
+                        item.outer_cross_size - d.margin_left_offset() - d.margin_right_offset()
+                    };
+                    child.layout_at_origin(width, Some(item.target_main_size), definite_height);
+                    item.outer_cross_size = child.dimensions.borrow().margin_box().width;
+                }
+            }
+
+            // refer: https://drafts.csswg.org/css-flexbox-1/#algo-cross-line
+            line.cross_size = match cross_size {
+                Some(cross_size) if is_single_line => cross_size,
+                _ => {
+                    let is_baseline = |item: &&FlexItem| {
+                        is_row
+                            && item.align_self == AlignItems::Baseline
+                            && item.cross_auto_margins == (false, false)
+                    };
+                    let above = line
+                        .items
+                        .iter()
+                        .filter(is_baseline)
+                        .map(|item| item.baseline)
+                        .fold(0., f32::max);
+                    let under = line
+                        .items
+                        .iter()
+                        .filter(is_baseline)
+                        .map(|item| item.outer_cross_size - item.baseline)
+                        .fold(0., f32::max);
+                    line.items
+                        .iter()
+                        .filter(|item| !is_baseline(item))
+                        .map(|item| item.outer_cross_size)
+                        .fold(above + under, f32::max)
+                }
+            };
+        }
+
+        // Distribute free space in the cross axis to lines.
+        // refer: https://drafts.csswg.org/css-flexbox-1/#algo-line-align
+        let cross_gaps = cross_gap * (lines.len().saturating_sub(1)) as f32;
+        let lines_cross_size = lines.iter().map(|line| line.cross_size).sum::<f32>() + cross_gaps;
+        let container_cross_size = cross_size.unwrap_or(lines_cross_size);
+        let (mut cross_position, between_lines) = if is_single_line {
+            (0., 0.)
+        } else {
+            let free_space = container_cross_size - lines_cross_size;
+            if flex.align_content == AlignContent::Stretch && free_space > 0. {
+                let extra = free_space / lines.len() as f32;
+                for line in &mut lines {
+                    line.cross_size += extra;
+                }
+            }
+            let content = content_distribution(flex.align_content);
+            distribute(free_space.max(0.), lines.len(), content)
+        };
+        let is_wrap_reverse = flex.flex_wrap == FlexWrap::WrapReverse;
+
+        let mut container_main_size = main_size.unwrap_or(0.);
+        for line in &mut lines {
+            let gaps = main_gap * (line.items.len().saturating_sub(1)) as f32;
+            let used: f32 = line
+                .items
+                .iter()
+                .map(FlexItem::outer_target_main_size)
+                .sum::<f32>()
+                + gaps;
+            let line_main_size = main_size.unwrap_or(used);
+            container_main_size = container_main_size.max(line_main_size);
+
+            // `auto` margins absorb free space before `justify-content`.
+            // refer: https://drafts.csswg.org/css-flexbox-1/#auto-margins
+            let free_space = line_main_size - used;
+            let auto_margins: usize = line
+                .items
+                .iter()
+                .map(|item| item.main_auto_margins.0 as usize + item.main_auto_margins.1 as usize)
+                .sum();
+            let auto_margin = if free_space > 0. && auto_margins > 0 {
+                free_space / auto_margins as f32
+            } else {
+                0.
+            };
+            let (mut main_position, between) = if auto_margins > 0 && free_space > 0. {
+                (0., 0.)
+            } else {
+                distribute(free_space, line.items.len(), flex.justify_content)
+            };
+
+            let baseline = line
+                .items
+                .iter()
+                .filter(|item| item.align_self == AlignItems::Baseline)
+                .map(|item| item.baseline)
+                .fold(0., f32::max);
+            let line_cross_start = if is_wrap_reverse {
+                container_cross_size - cross_position - line.cross_size
+            } else {
+                cross_position
+            };
+
+            for item in &mut line.items {
+                let child = &mut self.children[item.index];
+
+                // Main axis
+                let (start_margin, end_margin) = (
+                    if item.main_auto_margins.0 {
+                        auto_margin
+                    } else {
+                        0.
+                    },
+                    if item.main_auto_margins.1 {
+                        auto_margin
+                    } else {
+                        0.
+                    },
+                );
+                let outer_main_size = item.outer_target_main_size() + start_margin + end_margin;
+                let item_main_position = if is_reverse {
+                    line_main_size - main_position - outer_main_size
+                } else {
+                    main_position
+                };
+                main_position += outer_main_size + main_gap + between;
+
+                // Cross axis
+                let (cross_start, cross_end) = item.cross_auto_margins;
+                let stretched = item.align_self == AlignItems::Stretch
+                    && item.is_cross_size_auto
+                    && !cross_start
+                    && !cross_end;
+                if is_row {
+                    // A stretched item is laid out with the definite height.
+                    let height = match child.box_type {
+                        BoxType::AnonymousBlock if stretched => Some(line.cross_size),
+                        BoxType::BlockNode(_) if stretched => {
+                            let (_, min_height, max_height) =
+                                child.block_height_constraints(definite_height);
+                            let edges = {
+                                let d = child.dimensions.borrow();
+                                d.margin.top
+                                    + d.margin.bottom
+                                    + d.border.top
+                                    + d.border.bottom
+                                    + d.padding.top
+                                    + d.padding.bottom
+                            };
+                            Some(clamp_size(
+                                (line.cross_size - edges).max(0.),
+                                min_height,
+                                max_height,
+                            ))
+                        }
+                        _ => None,
+                    };
+                    child.layout_at_origin(item.target_main_size, height, definite_height);
+                    item.outer_cross_size = child.dimensions.borrow().margin_box().height;
+                }
+                let cross_free_space = line.cross_size - item.outer_cross_size;
+                let (cross_start_margin, cross_end_margin, offset) = if cross_start || cross_end {
+                    let free_space = cross_free_space.max(0.);
+                    match (cross_start, cross_end) {
+                        (true, true) => (free_space / 2., free_space / 2., free_space / 2.),
+                        (true, false) => (free_space, 0., free_space),
+                        _ => (0., free_space, 0.),
+                    }
+                } else {
+                    let offset = match item.align_self {
+                        AlignItems::Stretch | AlignItems::FlexStart => 0.,
+                        AlignItems::FlexEnd => cross_free_space,
+                        AlignItems::Center => cross_free_space / 2.,
+                        AlignItems::Baseline if is_row => baseline - item.baseline,
+                        AlignItems::Baseline => 0.,
+                    };
+                    // Cross start and end are swapped in `wrap-reverse`.
+                    let offset = if is_wrap_reverse {
+                        cross_free_space - offset
+                    } else {
+                        offset
+                    };
+                    (0., 0., offset)
+                };
+                let item_cross_position = line_cross_start + offset - cross_start_margin;
+
+                // Used `auto` margins are added to the margin box.
+                {
+                    let mut d = child.dimensions.borrow_mut();
+                    let margin = &mut d.margin;
+                    if is_row {
+                        margin.left += start_margin;
+                        margin.right += end_margin;
+                        margin.top += cross_start_margin;
+                        margin.bottom += cross_end_margin;
+                    } else {
+                        margin.top += start_margin;
+                        margin.bottom += end_margin;
+                        margin.left += cross_start_margin;
+                        margin.right += cross_end_margin;
+                    }
+                }
+
+                let (x, y) = if is_row {
+                    (item_main_position, item_cross_position)
+                } else {
+                    (item_cross_position, item_main_position)
+                };
+                let margin_box = child.dimensions.borrow().margin_box();
+                child.translate(content.x + x - margin_box.x, content.y + y - margin_box.y);
+                let (dx, dy) = child.relative_offset(content.width, definite_height);
+                child.translate(dx, dy);
+            }
+            cross_position += line.cross_size + cross_gap + between_lines;
+        }
+
+        let mut d = self.dimensions.borrow_mut();
+        d.content.height = if is_row {
+            container_cross_size
+        } else {
+            container_main_size
+        };
+    }
+}
@@ -0,0 +1,265 @@
+// refer: https://www.w3.org/TR/CSS21/visuren.html#floats
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{BoxType, Dimensions, LayoutBox, Rect};
+use crate::computed_values::{Clear, Float};
+use crate::style::Display;
+
+#[derive(Debug, Clone)]
+struct PlacedFloat {
+    side: Float,
+    /// Margin box
+    rect: Rect,
+}
+
+/// Floats placed in a block formatting context, which are in absolute positions.
+#[derive(Debug, Clone, Default)]
+pub struct FloatContext {
+    floats: Vec<PlacedFloat>,
+    /// A float can not be above the top of earlier floats.
+    ceiling: f32,
+}
+
+impl FloatContext {
+    pub fn new() -> FloatContext {
+        Default::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.floats.is_empty()
+    }
+
+    /// Bottom of the lowest float, or `None` when there is no float.
+    pub fn bottom(&self) -> Option<f32> {
+        self.floats
+            .iter()
+            .map(|float| float.rect.y + float.rect.height)
+            .reduce(f32::max)
+    }
+
+    /// Return the left and right edges of the space in `left..right`, which is not occupied by
+    /// floats in the band from `top` to `top + height`.
+    pub fn available_space(&self, top: f32, height: f32, left: f32, right: f32) -> (f32, f32) {
+        let (mut left, mut right) = (left, right);
+        for float in self.overlapping(top, height) {
+            let rect = &float.rect;
+            match float.side {
+                Float::Left => left = left.max(rect.x + rect.width),
+                Float::Right => right = right.min(rect.x),
+                Float::None => {}
+            }
+        }
+        (left, right)
+    }
+
+    /// Bottom of the highest float in the band from `top` to `top + height`, below which
+    /// the space beside floats may be wider, or `None` when no float is in the band.
+    pub fn next_bottom(&self, top: f32, height: f32) -> Option<f32> {
+        self.overlapping(top, height)
+            .map(|float| float.rect.y + float.rect.height)
+            .reduce(f32::min)
+    }
+
+    /// Position where the top border edge of a box with `clear` is placed,
+    /// which is the bottom of floats on the cleared sides.
+    pub fn clearance(&self, clear: Clear) -> Option<f32> {
+        self.floats
+            .iter()
+            .filter(|float| match clear {
+                Clear::None => false,
+                Clear::Left => float.side == Float::Left,
+                Clear::Right => float.side == Float::Right,
+                Clear::Both => true,
+            })
+            .map(|float| float.rect.y + float.rect.height)
+            .reduce(f32::max)
+    }
+
+    /// Place a float whose margin box has `width` and `height` in `left..right` below `ceiling`,
+    /// and return the position of the margin box.
+    // refer: https://www.w3.org/TR/CSS21/visuren.html#float-rules
+    pub fn place(
+        &mut self,
+        side: Float,
+        width: f32,
+        height: f32,
+        ceiling: f32,
+        (left, right): (f32, f32),
+    ) -> (f32, f32) {
+        let mut y = ceiling.max(self.ceiling);
+        let (x, y) = loop {
+            let (space_left, space_right) = self.available_space(y, height, left, right);
+            let fits = space_right - space_left >= width;
+            // Move down to the bottom of the highest float, which makes the space narrow.
+            match self.next_bottom(y, height) {
+                Some(next_y) if !fits => y = next_y,
+                _ => {
+                    let x = match side {
+                        Float::Right => space_right - width,
+                        _ => space_left,
+                    };
+                    break (x, y);
+                }
+            }
+        };
+
+        self.ceiling = y;
+        self.floats.push(PlacedFloat {
+            side,
+            rect: Rect {
+                x,
+                y,
+                width,
+                height,
+            },
+        });
+        (x, y)
+    }
+
+    fn overlapping(&self, top: f32, height: f32) -> impl Iterator<Item = &PlacedFloat> {
+        // A band which has no height overlaps floats at the top.
+        let bottom = top + height.max(f32::EPSILON);
+        self.floats.iter().filter(move |float| {
+            let rect = &float.rect;
+            rect.height > 0. && rect.y < bottom && top < rect.y + rect.height
+        })
+    }
+}
+
+impl<'a> LayoutBox<'a> {
+    pub fn float(&self) -> Float {
+        match &self.box_type {
+            // The table wrapper box is floated instead of the table box.
+            BoxType::BlockNode(node) if node.display() != Display::Table => node.style.box_.float,
+            BoxType::TableWrapper => self.item_style().box_.float,
+            _ => Float::None,
+        }
+    }
+
+    pub fn is_floating(&self) -> bool {
+        self.float() != Float::None
+    }
+
+    pub fn clear(&self) -> Clear {
+        match &self.box_type {
+            BoxType::BlockNode(node) if node.display() != Display::Table => node.style.box_.clear,
+            BoxType::TableWrapper => self.item_style().box_.clear,
+            _ => Clear::None,
+        }
+    }
+
+    /// Whether the box establishes a new block formatting context for its children.
+    // refer: https://www.w3.org/TR/CSS21/visuren.html#block-formatting
+    pub fn establishes_formatting_context(&self) -> bool {
+        match &self.box_type {
+            BoxType::BlockNode(node) => {
+                matches!(
+                    node.display(),
+                    Display::FlowRoot
+                        | Display::Flex
+                        | Display::Grid
+                        | Display::TableCell
+                        | Display::TableCaption
+                ) || self.is_floating()
+                    || self.is_out_of_flow()
+            }
+            BoxType::InlineBlockNode(_) | BoxType::TableWrapper | BoxType::AnonymousTableBox(_) => {
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Lay out a float below `ceiling`, and place it in `floats`.
+    pub(super) fn layout_float(
+        &mut self,
+        containing_block: Rc<RefCell<Dimensions>>,
+        containing_block_height: Option<f32>,
+        ceiling: f32,
+        floats: &mut FloatContext,
+    ) {
+        let mut containing_block = containing_block.borrow().content.clone();
+        containing_block.y = ceiling;
+        self.layout_shrink_to_fit_block(
+            &containing_block,
+            containing_block.width,
+            containing_block_height,
+        );
+        self.place_float(&containing_block, containing_block_height, ceiling, floats);
+    }
+
+    /// Place a float which is laid out already below `ceiling`, and move it to the position.
+    pub(super) fn place_float(
+        &mut self,
+        containing_block: &Rect,
+        containing_block_height: Option<f32>,
+        ceiling: f32,
+        floats: &mut FloatContext,
+    ) {
+        let margin_box = self.dimensions.borrow().margin_box();
+        let clearance = floats.clearance(self.clear()).unwrap_or(ceiling);
+        let (x, y) = floats.place(
+            self.float(),
+            margin_box.width,
+            margin_box.height,
+            ceiling.max(clearance),
+            (
+                containing_block.x,
+                containing_block.x + containing_block.width,
+            ),
+        );
+        self.translate(x - margin_box.x, y - margin_box.y);
+
+        let (dx, dy) = self.relative_offset(containing_block.width, containing_block_height);
+        self.translate(dx, dy);
+    }
+
+    /// Extend the content height of a block formatting context root to contain its floats.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#root-height
+    pub(super) fn include_floats(&self, floats: &FloatContext) {
+        if let Some(bottom) = floats.bottom() {
+            let mut d = self.dimensions.borrow_mut();
+            d.content.height = d.content.height.max(bottom - d.content.y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place_floats() {
+        let mut floats = FloatContext::new();
+        assert_eq!(
+            floats.place(Float::Left, 100., 50., 0., (0., 300.)),
+            (0., 0.)
+        );
+        assert_eq!(
+            floats.place(Float::Right, 100., 80., 0., (0., 300.)),
+            (200., 0.)
+        );
+        // No space beside the floats, so that it is moved down below the left float.
+        assert_eq!(
+            floats.place(Float::Left, 150., 10., 0., (0., 300.)),
+            (0., 50.)
+        );
+        // A float can not be above earlier floats.
+        assert_eq!(
+            floats.place(Float::Left, 10., 10., 0., (0., 300.)),
+            (150., 50.)
+        );
+
+        assert_eq!(floats.available_space(0., 10., 0., 300.), (100., 200.));
+        assert_eq!(floats.available_space(55., 30., 0., 300.), (160., 200.));
+        assert_eq!(floats.available_space(60., 30., 0., 300.), (0., 200.));
+        assert_eq!(floats.available_space(100., 10., 0., 300.), (0., 300.));
+
+        assert_eq!(floats.clearance(Clear::Left), Some(60.));
+        assert_eq!(floats.clearance(Clear::Right), Some(80.));
+        assert_eq!(floats.clearance(Clear::None), None);
+        assert_eq!(floats.bottom(), Some(80.));
+    }
+}
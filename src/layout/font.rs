@@ -5,6 +5,9 @@ pub use font_kit::properties::{
 };
 pub use glyph_brush::ab_glyph::{Font as GlyphBrushFont, PxScale, ScaleFont};
 
+use super::woff;
+use crate::cssom::{FontFaceRule, FontFaceSource, FontFamily};
+use crate::font_list::{fallback_font_families, get_generic_fonts};
 use crate::style::StyledNode;
 use core_foundation::string::UniChar;
 use core_graphics::font::CGGlyph;
@@ -15,7 +18,9 @@ use glyph_brush::ab_glyph::FontRef;
 use std::cell::RefCell;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::fs;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 pub fn create_font_properties(styled_node: &StyledNode) -> FontProperties {
     FontProperties {
@@ -31,6 +36,8 @@ pub struct FontCacheKey {
     size: f32,
     properties: FontProperties,
     family_name: String,
+    /// Index of a font face registered by `@font-face` in `FontContext`.
+    font_face: Option<usize>,
 }
 
 impl Hash for FontCacheKey {
@@ -44,6 +51,7 @@ impl Hash for FontCacheKey {
         (self.properties.stretch.0 as i32).hash(state);
         (self.properties.weight.0 as i32).hash(state);
         self.family_name.hash(state);
+        self.font_face.hash(state);
     }
 }
 
@@ -59,6 +67,7 @@ impl PartialEq for FontCacheKey {
                     | (FontStyle::Oblique, FontStyle::Oblique)
             )
             && self.family_name == other.family_name
+            && self.font_face == other.font_face
     }
 }
 
@@ -70,22 +79,101 @@ impl FontCacheKey {
             size,
             properties,
             family_name,
+            font_face: None,
         }
     }
+}
 
-    pub fn new_from_style(styled_node: &StyledNode) -> FontCacheKey {
-        FontCacheKey {
-            size: styled_node.font_size(),
-            properties: create_font_properties(styled_node),
-            // TODO: Fix to find appropriate family name
-            family_name: styled_node.font_family().pop().unwrap(),
+/// A font loaded from `@font-face` rule.
+pub struct FontFace {
+    rule: FontFaceRule,
+    font: font::Font,
+}
+
+const SUPPORTED_FONT_FORMATS: [&str; 4] = ["truetype", "opentype", "woff", "woff2"];
+
+impl FontFace {
+    /// Load the first available source of `rule`.
+    pub fn load(rule: &FontFaceRule) -> Option<FontFace> {
+        let font = rule.sources.iter().find_map(|source| match source {
+            FontFaceSource::Url { url, format } => {
+                if let Some(format) = format {
+                    if !SUPPORTED_FONT_FORMATS.contains(&&*format.to_ascii_lowercase()) {
+                        return None;
+                    }
+                }
+                let data = woff::decode(fs::read(url).ok()?).ok()?;
+                font::Font::from_bytes(Arc::new(data), 0).ok()
+            }
+            FontFaceSource::Local(name) => SystemSource::new()
+                .select_by_postscript_name(name)
+                .ok()?
+                .load()
+                .ok(),
+        })?;
+        Some(FontFace {
+            rule: rule.clone(),
+            font,
+        })
+    }
+
+    // refer: https://drafts.csswg.org/css-fonts-4/#font-style-matching
+    fn style_rank(&self, style: FontStyle) -> usize {
+        let order = match style {
+            FontStyle::Italic => [FontStyle::Italic, FontStyle::Oblique, FontStyle::Normal],
+            FontStyle::Oblique => [FontStyle::Oblique, FontStyle::Italic, FontStyle::Normal],
+            FontStyle::Normal => [FontStyle::Normal, FontStyle::Oblique, FontStyle::Italic],
+        };
+        order
+            .iter()
+            .position(|s| *s == self.rule.style)
+            .unwrap_or(order.len())
+    }
+
+    /// Smaller rank is closer to `weight`.
+    // refer: https://drafts.csswg.org/css-fonts-4/#font-weight-matching
+    fn weight_rank(&self, weight: FontWeight) -> f32 {
+        let (min, max) = (self.rule.weight.0 .0, self.rule.weight.1 .0);
+        let desired = weight.0;
+        if min <= desired && desired <= max {
+            return 0.;
+        }
+        let (below, above) = (desired - max, min - desired);
+        // Weights in the preferred direction are ranked before the others.
+        let other_direction = 10000.;
+        if (400. ..=500.).contains(&desired) {
+            if min > desired && min <= 500. {
+                above
+            } else if max < desired {
+                other_direction + below
+            } else {
+                other_direction * 2. + above
+            }
+        } else if desired < 400. {
+            if max < desired {
+                below
+            } else {
+                other_direction + above
+            }
+        } else if min > desired {
+            above
+        } else {
+            other_direction + below
         }
     }
+
+    fn has_same_descriptors(&self, other: &FontFace) -> bool {
+        self.rule.style == other.rule.style && self.rule.weight == other.rule.weight
+    }
 }
 
 pub struct FontContext {
-    font_caches: HashMap<FontCacheKey, Font>,
+    /// `None` is cached for a family which is not available.
+    font_caches: HashMap<FontCacheKey, Option<Font>>,
     font_data_caches: HashMap<FontCacheKey, &'static [u8]>,
+    font_faces: Vec<FontFace>,
+    /// Fonts smaller than this are used at this size, which is `Device::minimum_font_size`.
+    pub minimum_font_size: f32,
 }
 
 impl FontContext {
@@ -93,18 +181,140 @@ impl FontContext {
         FontContext {
             font_caches: HashMap::new(),
             font_data_caches: HashMap::new(),
+            font_faces: vec![],
+            minimum_font_size: 0.,
         }
     }
 
+    /// Size of fonts used for the element, where the minimum font size is applied.
+    pub fn used_font_size(&self, styled_node: &StyledNode) -> f32 {
+        styled_node.font_size().max(self.minimum_font_size)
+    }
+
+    /// Return the font of `cache_key`, which must be available,
+    /// e.g. a key of a font which has been found once.
     pub fn get_or_create_by(&mut self, cache_key: &FontCacheKey) -> Font {
-        let font = self.font_caches.get(&cache_key);
-        if let Some(font) = font {
+        self.find_by(cache_key).unwrap()
+    }
+
+    /// Return `None` if the family of `cache_key` is not available.
+    pub fn find_by(&mut self, cache_key: &FontCacheKey) -> Option<Font> {
+        if let Some(font) = self.font_caches.get(cache_key) {
             return font.clone();
         }
-        let font = Font::new(cache_key);
+        let font = match cache_key.font_face {
+            Some(id) => Some(Font::new_with_font(
+                cache_key,
+                self.font_faces[id].font.clone(),
+            )),
+            None => Font::load(cache_key),
+        };
         self.font_caches.insert(cache_key.clone(), font.clone());
         font
     }
+
+    /// Return the first available font of the element, which is used for its line metrics.
+    /// Return `None` if no font can be loaded, even from fallback families.
+    // refer: https://drafts.csswg.org/css-fonts-4/#first-available-font
+    pub fn first_available_font(&mut self, styled_node: &StyledNode) -> Option<Font> {
+        let size = self.used_font_size(styled_node);
+        let properties = create_font_properties(styled_node);
+        self.font_family_keys(styled_node.font_family(), size, properties, ' ')
+            .into_iter()
+            .chain(
+                fallback_font_families(None)
+                    .into_iter()
+                    .map(|family| FontCacheKey::new(size, properties, family)),
+            )
+            .find_map(|key| self.find_by(&key))
+    }
+
+    /// Register a font face of `@font-face` rule.
+    /// Return `false` if no source of the rule can be loaded.
+    pub fn add_font_face(&mut self, rule: &FontFaceRule) -> bool {
+        match FontFace::load(rule) {
+            Some(font_face) => {
+                self.font_faces.push(font_face);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return cache keys to try for `codepoint`, in order of `families`.
+    /// A named family is looked up in `@font-face` rules before system fonts,
+    /// and a generic family is expanded to its system families in place.
+    pub fn font_family_keys(
+        &self,
+        families: &[FontFamily],
+        size: f32,
+        properties: FontProperties,
+        codepoint: char,
+    ) -> Vec<FontCacheKey> {
+        let generic_fonts = get_generic_fonts();
+        let mut keys = vec![];
+        for family in families {
+            match family {
+                FontFamily::Named(name) => {
+                    keys.extend(self.font_face_keys(name, size, properties, codepoint));
+                    keys.push(FontCacheKey::new(size, properties, name.clone()));
+                }
+                FontFamily::Generic(generic) => {
+                    keys.extend(generic_fonts[generic].iter().map(|family_name| {
+                        FontCacheKey::new(size, properties, family_name.clone())
+                    }));
+                }
+            }
+        }
+        keys
+    }
+
+    /// Return cache keys of font faces of `family_name` which can render `codepoint`.
+    /// Faces which have the closest style and weight are chosen,
+    /// and the last defined face is tried first among them.
+    // refer: https://drafts.csswg.org/css-fonts-4/#font-style-matching
+    fn font_face_keys(
+        &self,
+        family_name: &str,
+        size: f32,
+        properties: FontProperties,
+        codepoint: char,
+    ) -> Vec<FontCacheKey> {
+        let faces: Vec<(usize, &FontFace)> = self
+            .font_faces
+            .iter()
+            .enumerate()
+            .filter(|(_, face)| face.rule.family_name.eq_ignore_ascii_case(family_name))
+            .collect();
+
+        let best = faces.iter().min_by(|(_, a), (_, b)| {
+            let a = (
+                a.style_rank(properties.style),
+                a.weight_rank(properties.weight),
+            );
+            let b = (
+                b.style_rank(properties.style),
+                b.weight_rank(properties.weight),
+            );
+            a.partial_cmp(&b).unwrap()
+        });
+        let best = match best {
+            Some((_, best)) => *best,
+            None => return vec![],
+        };
+
+        faces
+            .iter()
+            .rev()
+            .filter(|(_, face)| face.has_same_descriptors(best) && face.rule.contains(codepoint))
+            .map(|(id, face)| FontCacheKey {
+                size,
+                properties,
+                family_name: face.rule.family_name.clone(),
+                font_face: Some(*id),
+            })
+            .collect()
+    }
 }
 
 thread_local! {
@@ -129,9 +339,9 @@ pub struct Font {
     pub descent: f32,
     pub size: f32,
     pub family_name: String,
+    pub cache_key: FontCacheKey,
     ctfont: CTFont,
     units_per_em: f32,
-    cache_key: FontCacheKey,
 }
 
 fn px_to_pt(px: f64) -> f64 {
@@ -143,11 +353,19 @@ fn pt_to_px(pt: f64) -> f64 {
 }
 
 impl Font {
-    pub fn new(descriptor: &FontCacheKey) -> Font {
-        let size = descriptor.size;
+    /// Load a system font. Return `None` if the family is not installed.
+    pub fn load(descriptor: &FontCacheKey) -> Option<Font> {
         let font_families = &[FamilyName::Title(descriptor.family_name.clone())];
-        let font = load_font_family(Some(font_families), &descriptor.properties);
+        let font = SystemSource::new()
+            .select_best_match(font_families, &descriptor.properties)
+            .ok()?
+            .load()
+            .ok()?;
+        Some(Font::new_with_font(descriptor, font))
+    }
 
+    fn new_with_font(descriptor: &FontCacheKey, font: font::Font) -> Font {
+        let size = descriptor.size;
         let ctfont = font.native_font().clone_with_font_size(size as f64);
 
         let ascent = ctfont.ascent() as f64;
@@ -229,25 +447,3 @@ impl Font {
         Box::leak(hasher.finish().to_string().into_boxed_str())
     }
 }
-
-fn load_font_family(
-    font_families: Option<&[FamilyName]>,
-    properties: &FontProperties,
-) -> font::Font {
-    match font_families {
-        Some(font_families) => SystemSource::new()
-            .select_best_match(font_families, properties)
-            .unwrap()
-            .load()
-            .unwrap(),
-        None => load_default_font_family(),
-    }
-}
-
-fn load_default_font_family() -> font::Font {
-    SystemSource::new()
-        .select_best_match(&[FamilyName::Serif], &FontProperties::new())
-        .unwrap()
-        .load()
-        .unwrap()
-}
@@ -0,0 +1,900 @@
+// refer: https://drafts.csswg.org/css-grid-2/#layout-algorithm
+
+use std::ops::Range;
+
+use super::flex::{content_distribution, distribute};
+use super::{clamp_size, BoxType, LayoutBox, Rect};
+use crate::computed_values::{
+    AlignItems, GridTemplateAreas, JustifyContent, TrackBreadth, TrackListItem, TrackSize,
+};
+use crate::cssom::{GridLine, RepeatCount};
+
+/// A start line, which is `None` when the item is placed automatically, and a span.
+type Placement = (Option<usize>, usize);
+
+/// Index of an item in children of the container, and rows and columns which it spans.
+type Area = (usize, Range<usize>, Range<usize>);
+
+/// Outer sizes which an item contributes to the tracks it spans.
+struct Contribution {
+    tracks: Range<usize>,
+    min_content: f32,
+    max_content: f32,
+}
+
+struct Track {
+    size: TrackSize,
+    base_size: f32,
+    /// `f32::INFINITY` until it is determined.
+    growth_limit: f32,
+}
+
+impl Track {
+    fn flex_factor(&self) -> Option<f32> {
+        match self.size.max {
+            TrackBreadth::Flex(flex) => Some(flex),
+            _ => None,
+        }
+    }
+}
+
+/// Cells occupied by grid items, indexed by the line and the cell in the line,
+/// where cells are filled along the direction of `grid-auto-flow`.
+#[derive(Default)]
+struct Occupancy {
+    lines: Vec<Vec<bool>>,
+}
+
+impl Occupancy {
+    fn is_free(&self, lines: &Range<usize>, cells: &Range<usize>) -> bool {
+        lines.clone().all(|line| {
+            cells.clone().all(|cell| {
+                !self
+                    .lines
+                    .get(line)
+                    .and_then(|line| line.get(cell))
+                    .copied()
+                    .unwrap_or(false)
+            })
+        })
+    }
+
+    fn occupy(&mut self, lines: &Range<usize>, cells: &Range<usize>) {
+        if self.lines.len() < lines.end {
+            self.lines.resize(lines.end, vec![]);
+        }
+        for line in &mut self.lines[lines.clone()] {
+            if line.len() < cells.end {
+                line.resize(cells.end, false);
+            }
+            for cell in &mut line[cells.clone()] {
+                *cell = true;
+            }
+        }
+    }
+}
+
+/// Length of a fixed breadth, or `None` for an intrinsic or flexible breadth.
+/// A percentage is intrinsic when `basis` is indefinite.
+fn fixed_breadth(breadth: TrackBreadth, basis: Option<f32>) -> Option<f32> {
+    match breadth {
+        TrackBreadth::Length(length) => Some(length),
+        TrackBreadth::Percentage(percentage) => basis.map(|basis| percentage * basis),
+        TrackBreadth::Flex(_) | TrackBreadth::Auto => None,
+    }
+}
+
+/// Maximum number of tracks from a track list, which bounds huge `repeat()` counts and `auto-fill`
+/// repetitions of tiny tracks.
+const MAX_TRACKS: usize = 10000;
+
+/// Expand `repeat()` in a track list, where `auto-fill` repeats tracks as many times as they fit
+/// in `available_size`, or once when it is indefinite.
+// refer: https://drafts.csswg.org/css-grid-2/#auto-repeat
+fn expand_track_list(
+    items: &[TrackListItem],
+    available_size: Option<f32>,
+    gap: f32,
+) -> Vec<TrackSize> {
+    // Each track is treated as its fixed maximum, or as its fixed minimum.
+    let fixed_size = |size: &TrackSize| {
+        fixed_breadth(size.max, available_size)
+            .or_else(|| fixed_breadth(size.min, available_size))
+            .unwrap_or(0.)
+    };
+    let mut repetitions = 1;
+    if let Some(available_size) = available_size {
+        let (mut fixed, mut count) = (0., 0);
+        let (mut repeated, mut repeated_count) = (0., 0);
+        for item in items {
+            match item {
+                TrackListItem::Size(size) => {
+                    fixed += fixed_size(size);
+                    count += 1;
+                }
+                TrackListItem::Repeat(RepeatCount::Count(times), sizes) => {
+                    fixed += *times as f32 * sizes.iter().map(fixed_size).sum::<f32>();
+                    count = count.saturating_add(times.saturating_mul(sizes.len()));
+                }
+                TrackListItem::Repeat(RepeatCount::AutoFill, sizes) => {
+                    repeated = sizes.iter().map(fixed_size).sum::<f32>();
+                    repeated_count = sizes.len();
+                }
+            }
+        }
+        // A repetition adds its tracks and a gap after each of them.
+        let step = repeated + gap * repeated_count as f32;
+        if step > 0. {
+            let space = available_size - fixed - gap * (count as f32 - 1.);
+            repetitions = ((space / step).floor() as usize).clamp(1, MAX_TRACKS);
+        }
+    }
+
+    items
+        .iter()
+        .flat_map(|item| match item {
+            TrackListItem::Size(size) => vec![*size],
+            TrackListItem::Repeat(count, sizes) => {
+                let times = match count {
+                    RepeatCount::Count(times) => *times,
+                    RepeatCount::AutoFill => repetitions,
+                };
+                sizes
+                    .iter()
+                    .cycle()
+                    .take(times.saturating_mul(sizes.len()).min(MAX_TRACKS))
+                    .copied()
+                    .collect()
+            }
+        })
+        .take(MAX_TRACKS)
+        .collect()
+}
+
+/// Resolve a line at the start or end side to an index of lines from `0`.
+/// A name which is not found is treated as `auto`.
+fn resolve_line(
+    line: &GridLine,
+    is_start: bool,
+    explicit_tracks: usize,
+    areas: Option<&GridTemplateAreas>,
+    is_row: bool,
+) -> Option<usize> {
+    match line {
+        GridLine::Line(line) if *line > 0 => Some(*line as usize - 1),
+        // Negative lines are counted from the end of the explicit grid.
+        // Lines before the explicit grid are not supported, which are the first line.
+        GridLine::Line(line) => Some((explicit_tracks as i32 + 1 + line).max(0) as usize),
+        GridLine::Name(name) => {
+            // A named area has implicit lines such as `a-start` and `a-end`.
+            let (name, is_start) = match (name.strip_suffix("-start"), name.strip_suffix("-end")) {
+                (Some(name), _) => (name, true),
+                (_, Some(name)) => (name, false),
+                _ => (&**name, is_start),
+            };
+            let area = areas?.areas.iter().find(|area| area.name == name)?;
+            let lines = if is_row { &area.rows } else { &area.columns };
+            Some(if is_start { lines.start } else { lines.end })
+        }
+        GridLine::Auto | GridLine::Span(_) => None,
+    }
+}
+
+/// Resolve the start line and the span of an item in an axis.
+// refer: https://drafts.csswg.org/css-grid-2/#line-placement
+fn resolve_placement(
+    start: &GridLine,
+    end: &GridLine,
+    explicit_tracks: usize,
+    areas: Option<&GridTemplateAreas>,
+    is_row: bool,
+) -> Placement {
+    let span = |line: &GridLine| match line {
+        GridLine::Span(span) => Some(*span),
+        _ => None,
+    };
+    let start_line = resolve_line(start, true, explicit_tracks, areas, is_row);
+    let end_line = resolve_line(end, false, explicit_tracks, areas, is_row);
+    match (start_line, end_line) {
+        // Lines in the reverse order are swapped, and the same lines span a track.
+        (Some(start), Some(end)) if start < end => (Some(start), end - start),
+        (Some(start), Some(end)) if start > end => (Some(end), start - end),
+        (Some(start), _) => (Some(start), span(end).unwrap_or(1)),
+        (None, Some(end)) => {
+            let span = span(start).unwrap_or(1).min(end).max(1);
+            (Some(end.saturating_sub(span)), span)
+        }
+        (None, None) => (None, span(start).or_else(|| span(end)).unwrap_or(1)),
+    }
+}
+
+/// Place items, whose placements are in lines and cells of `grid-auto-flow`,
+/// and return their areas in the same order.
+/// `cells` is the number of explicit tracks in the direction of `grid-auto-flow`.
+// refer: https://drafts.csswg.org/css-grid-2/#auto-placement-algo
+fn place_items(
+    placements: &[(Placement, Placement)],
+    cells: usize,
+    dense: bool,
+) -> Vec<(Range<usize>, Range<usize>)> {
+    let mut cells = placements
+        .iter()
+        .map(|(_, (start, span))| start.unwrap_or(0) + span)
+        .fold(cells, usize::max);
+    let mut occupancy = Occupancy::default();
+    let mut areas = vec![None; placements.len()];
+
+    // Items with definite positions in both axes are placed first.
+    for (area, placement) in areas.iter_mut().zip(placements) {
+        if let ((Some(line), line_span), (Some(cell), cell_span)) = *placement {
+            let placed = (line..line + line_span, cell..cell + cell_span);
+            occupancy.occupy(&placed.0, &placed.1);
+            *area = Some(placed);
+        }
+    }
+
+    // Items locked to a line are placed after items previously placed in the line.
+    let mut line_cursors: Vec<usize> = vec![];
+    for (area, placement) in areas.iter_mut().zip(placements) {
+        if let ((Some(line), line_span), (None, cell_span)) = *placement {
+            if line_cursors.len() <= line {
+                line_cursors.resize(line + 1, 0);
+            }
+            let lines = line..line + line_span;
+            let mut cell = if dense { 0 } else { line_cursors[line] };
+            while !occupancy.is_free(&lines, &(cell..cell + cell_span)) {
+                cell += 1;
+            }
+            line_cursors[line] = cell + cell_span;
+            cells = cells.max(cell + cell_span);
+            let placed = (lines, cell..cell + cell_span);
+            occupancy.occupy(&placed.0, &placed.1);
+            *area = Some(placed);
+        }
+    }
+
+    // Other items are placed at the auto-placement cursor.
+    let (mut line, mut cell) = (0, 0);
+    for (area, placement) in areas.iter_mut().zip(placements) {
+        if area.is_some() {
+            continue;
+        }
+        let ((_, line_span), (start, cell_span)) = *placement;
+        if dense {
+            line = 0;
+            cell = 0;
+        }
+        match start {
+            Some(start) => {
+                if start < cell {
+                    line += 1;
+                }
+                cell = start;
+                while !occupancy.is_free(&(line..line + line_span), &(cell..cell + cell_span)) {
+                    line += 1;
+                }
+            }
+            None => loop {
+                if cell + cell_span > cells {
+                    line += 1;
+                    cell = 0;
+                } else if occupancy.is_free(&(line..line + line_span), &(cell..cell + cell_span)) {
+                    break;
+                } else {
+                    cell += 1;
+                }
+            },
+        }
+        let placed = (line..line + line_span, cell..cell + cell_span);
+        occupancy.occupy(&placed.0, &placed.1);
+        *area = Some(placed);
+        cell += cell_span;
+    }
+
+    areas.into_iter().map(Option::unwrap).collect()
+}
+
+/// Size of `1fr`, where flexible tracks fill `space` with inflexible tracks.
+// refer: https://drafts.csswg.org/css-grid-2/#algo-find-fr-size
+fn find_fr_size(tracks: &[Track], space: f32) -> f32 {
+    // A flexible track whose base size is larger than its flexible size is treated as inflexible.
+    let mut inflexible: Vec<bool> = tracks
+        .iter()
+        .map(|track| track.flex_factor().is_none())
+        .collect();
+    loop {
+        let leftover = space
+            - tracks
+                .iter()
+                .zip(&inflexible)
+                .filter(|(_, inflexible)| **inflexible)
+                .map(|(track, _)| track.base_size)
+                .sum::<f32>();
+        let flex_factors = tracks
+            .iter()
+            .zip(&inflexible)
+            .filter(|(_, inflexible)| !**inflexible)
+            .filter_map(|(track, _)| track.flex_factor())
+            .sum::<f32>()
+            .max(1.);
+        let fr_size = leftover.max(0.) / flex_factors;
+
+        let mut is_changed = false;
+        for (track, inflexible) in tracks.iter().zip(inflexible.iter_mut()) {
+            match track.flex_factor() {
+                Some(flex) if !*inflexible && fr_size * flex < track.base_size => {
+                    *inflexible = true;
+                    is_changed = true;
+                }
+                _ => {}
+            }
+        }
+        if !is_changed {
+            return fr_size;
+        }
+    }
+}
+
+/// Size tracks in an axis, whose size is `available_size` when it is definite,
+/// and return the base sizes.
+// refer: https://drafts.csswg.org/css-grid-2/#algo-track-sizing
+fn size_tracks(
+    sizes: &[TrackSize],
+    contributions: &[Contribution],
+    available_size: Option<f32>,
+    gap: f32,
+    content: JustifyContent,
+) -> Vec<f32> {
+    let resolve = |breadth| fixed_breadth(breadth, available_size);
+    let gaps = |tracks: usize| gap * tracks.saturating_sub(1) as f32;
+
+    // Initialize track sizes.
+    // refer: https://drafts.csswg.org/css-grid-2/#algo-init
+    let mut tracks: Vec<Track> = sizes
+        .iter()
+        .map(|size| {
+            let base_size = resolve(size.min).unwrap_or(0.);
+            let growth_limit = match size.max {
+                TrackBreadth::Flex(_) => base_size,
+                max => resolve(max).unwrap_or(f32::INFINITY).max(base_size),
+            };
+            Track {
+                size: *size,
+                base_size,
+                growth_limit,
+            }
+        })
+        .collect();
+    let is_intrinsic_min = |track: &Track| resolve(track.size.min).is_none();
+    let is_intrinsic_max =
+        |track: &Track| track.flex_factor().is_none() && resolve(track.size.max).is_none();
+
+    // Resolve intrinsic track sizes, with items in the ascending order of spans.
+    // Extra space of an item spanning several tracks is distributed equally.
+    // refer: https://drafts.csswg.org/css-grid-2/#algo-content
+    let mut sorted: Vec<&Contribution> = contributions.iter().collect();
+    sorted.sort_by_key(|contribution| contribution.tracks.len());
+    for contribution in sorted {
+        let spanned = &mut tracks[contribution.tracks.clone()];
+        let gaps = gaps(spanned.len());
+        let spans_flexible_track = spanned.iter().any(|track| track.flex_factor().is_some());
+
+        // Items spanning flexible tracks only increase base sizes of the flexible tracks.
+        let extra = contribution.min_content
+            - gaps
+            - spanned.iter().map(|track| track.base_size).sum::<f32>();
+        let growing: Vec<&mut Track> = spanned
+            .iter_mut()
+            .filter(|track| {
+                is_intrinsic_min(track) && (!spans_flexible_track || track.flex_factor().is_some())
+            })
+            .collect();
+        if extra > 0. && !growing.is_empty() {
+            let extra = extra / growing.len() as f32;
+            for track in growing {
+                track.base_size += extra;
+                track.growth_limit = track.growth_limit.max(track.base_size);
+            }
+        }
+        if spans_flexible_track {
+            continue;
+        }
+
+        // An infinite growth limit is treated as the base size.
+        let extra = contribution.max_content
+            - gaps
+            - spanned
+                .iter()
+                .map(|track| {
+                    if track.growth_limit.is_infinite() {
+                        track.base_size
+                    } else {
+                        track.growth_limit
+                    }
+                })
+                .sum::<f32>();
+        let growing: Vec<&mut Track> = spanned
+            .iter_mut()
+            .filter(|track| is_intrinsic_max(track))
+            .collect();
+        let extra = extra.max(0.) / growing.len().max(1) as f32;
+        for track in growing {
+            if track.growth_limit.is_infinite() {
+                track.growth_limit = track.base_size;
+            }
+            track.growth_limit += extra;
+        }
+    }
+    for track in &mut tracks {
+        if track.growth_limit.is_infinite() {
+            track.growth_limit = track.base_size;
+        }
+    }
+
+    // Maximize tracks, which grow equally up to their growth limits.
+    // refer: https://drafts.csswg.org/css-grid-2/#algo-grow-tracks
+    match available_size {
+        Some(available_size) => {
+            let mut free_space = available_size
+                - gaps(tracks.len())
+                - tracks.iter().map(|track| track.base_size).sum::<f32>();
+            // Each round freezes a track or distributes all free space.
+            for _ in 0..tracks.len() {
+                let growing: Vec<&mut Track> = tracks
+                    .iter_mut()
+                    .filter(|track| track.base_size < track.growth_limit)
+                    .collect();
+                if free_space <= 0. || growing.is_empty() {
+                    break;
+                }
+                let share = free_space / growing.len() as f32;
+                for track in growing {
+                    let grow = share.min(track.growth_limit - track.base_size);
+                    track.base_size += grow;
+                    free_space -= grow;
+                }
+            }
+        }
+        None => {
+            for track in &mut tracks {
+                track.base_size = track.growth_limit;
+            }
+        }
+    }
+
+    // Expand flexible tracks.
+    // refer: https://drafts.csswg.org/css-grid-2/#algo-flex-tracks
+    if tracks.iter().any(|track| track.flex_factor().is_some()) {
+        let fr_size = match available_size {
+            Some(available_size) => find_fr_size(&tracks, available_size - gaps(tracks.len())),
+            // The size of `1fr` is the largest one which fits base sizes of flexible tracks
+            // and contributions of items spanning them.
+            None => {
+                let by_tracks = tracks
+                    .iter()
+                    .filter_map(|track| {
+                        let flex = track.flex_factor()?;
+                        Some(track.base_size / flex.max(1.))
+                    })
+                    .fold(0., f32::max);
+                contributions
+                    .iter()
+                    .filter(|contribution| {
+                        tracks[contribution.tracks.clone()]
+                            .iter()
+                            .any(|track| track.flex_factor().is_some())
+                    })
+                    .map(|contribution| {
+                        let spanned = &tracks[contribution.tracks.clone()];
+                        find_fr_size(spanned, contribution.max_content - gaps(spanned.len()))
+                    })
+                    .fold(by_tracks, f32::max)
+            }
+        };
+        for track in &mut tracks {
+            if let Some(flex) = track.flex_factor() {
+                track.base_size = track.base_size.max(fr_size * flex);
+            }
+        }
+    }
+
+    // Stretch `auto` tracks with the remaining free space.
+    // refer: https://drafts.csswg.org/css-grid-2/#algo-stretch
+    if let (JustifyContent::Normal, Some(available_size)) = (content, available_size) {
+        let free_space = available_size
+            - gaps(tracks.len())
+            - tracks.iter().map(|track| track.base_size).sum::<f32>();
+        let stretched: Vec<&mut Track> = tracks
+            .iter_mut()
+            .filter(|track| track.size.max == TrackBreadth::Auto)
+            .collect();
+        if free_space > 0. && !stretched.is_empty() {
+            let extra = free_space / stretched.len() as f32;
+            for track in stretched {
+                track.base_size += extra;
+            }
+        }
+    }
+
+    tracks.into_iter().map(|track| track.base_size).collect()
+}
+
+/// Return positions of tracks, which are aligned in `available_size` by `content`.
+// refer: https://drafts.csswg.org/css-align-3/#align-justify-content
+fn track_positions(
+    sizes: &[f32],
+    gap: f32,
+    available_size: Option<f32>,
+    content: JustifyContent,
+) -> Vec<f32> {
+    let used = sizes.iter().sum::<f32>() + gap * sizes.len().saturating_sub(1) as f32;
+    let free_space = available_size.map_or(0., |available_size| available_size - used);
+    let (mut position, between) = distribute(free_space, sizes.len(), content);
+    sizes
+        .iter()
+        .map(|size| {
+            let start = position;
+            position += size + gap + between;
+            start
+        })
+        .collect()
+}
+
+/// Start position and size of an area spanning `tracks`.
+pub(super) fn span(positions: &[f32], sizes: &[f32], tracks: &Range<usize>) -> (f32, f32) {
+    let start = positions[tracks.start];
+    let end = positions[tracks.end - 1] + sizes[tracks.end - 1];
+    (start, end - start)
+}
+
+/// Offset of a margin box in an area, which has `free_space`.
+/// `auto` margins absorb the free space before the alignment.
+// refer: https://drafts.csswg.org/css-align-3/#justify-self-property
+fn self_alignment_offset(
+    free_space: f32,
+    alignment: AlignItems,
+    auto_margins: (bool, bool),
+) -> f32 {
+    match auto_margins {
+        (true, true) => free_space.max(0.) / 2.,
+        (true, false) => free_space.max(0.),
+        (false, true) => 0.,
+        (false, false) => match alignment {
+            AlignItems::Stretch | AlignItems::FlexStart | AlignItems::Baseline => 0.,
+            AlignItems::FlexEnd => free_space,
+            AlignItems::Center => free_space / 2.,
+        },
+    }
+}
+
+impl<'a> LayoutBox<'a> {
+    /// Width of the content box of a grid item in an area of `area_width`.
+    fn grid_item_width(&self, area_width: f32, justify_self: AlignItems) -> f32 {
+        if let BoxType::AnonymousBlock = self.box_type {
+            return area_width;
+        }
+        let style = self.item_style();
+        self.assign_horizontal_margin_box(area_width);
+        self.assign_vertical_margin_box(area_width);
+        let (edges, available) = {
+            let d = self.dimensions.borrow();
+            let edges = d.border.left + d.border.right + d.padding.left + d.padding.right;
+            (edges, area_width - d.margin.left - d.margin.right - edges)
+        };
+        let box_ = &style.box_;
+        let has_auto_margin =
+            style.margin.margin_left.is_auto() || style.margin.margin_right.is_auto();
+        let width = box_.width.resolve(Some(area_width));
+        let width = match self.resolve_content_width(width, edges, Some(available)) {
+            Some(width) => width,
+            None if justify_self == AlignItems::Stretch && !has_auto_margin => available.max(0.),
+            None => self.shrink_to_fit_width(available),
+        };
+        let min_width = box_
+            .min_width
+            .and_then(|min_width| min_width.resolve(Some(area_width)))
+            .map_or(0., |min_width| self.to_content_size(min_width, edges));
+        let max_width = box_
+            .max_width
+            .and_then(|max_width| max_width.resolve(Some(area_width)))
+            .map(|max_width| self.to_content_size(max_width, edges));
+        clamp_size(width, min_width, max_width)
+    }
+
+    /// Lay out a grid item in `area`, which is relative to the content box of the container.
+    fn layout_grid_item(&mut self, area: &Rect, justify_self: AlignItems, align_self: AlignItems) {
+        let style = self.item_style();
+        let width = self.grid_item_width(area.width, justify_self);
+        let auto_margins = (
+            style.margin.margin_top.is_auto(),
+            style.margin.margin_bottom.is_auto(),
+        );
+        let height = match self.box_type {
+            BoxType::AnonymousBlock if align_self == AlignItems::Stretch => Some(area.height),
+            BoxType::BlockNode(_) => {
+                let (height, min_height, max_height) =
+                    self.block_height_constraints(Some(area.height));
+                let edges = {
+                    let d = self.dimensions.borrow();
+                    d.margin.top
+                        + d.margin.bottom
+                        + d.border.top
+                        + d.border.bottom
+                        + d.padding.top
+                        + d.padding.bottom
+                };
+                match height {
+                    None if align_self == AlignItems::Stretch && auto_margins == (false, false) => {
+                        Some(clamp_size(
+                            (area.height - edges).max(0.),
+                            min_height,
+                            max_height,
+                        ))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        self.layout_at_origin(width, height, Some(area.height));
+
+        let margin_box = self.dimensions.borrow().margin_box();
+        let x = area.x
+            + self_alignment_offset(
+                area.width - margin_box.width,
+                justify_self,
+                (
+                    style.margin.margin_left.is_auto(),
+                    style.margin.margin_right.is_auto(),
+                ),
+            );
+        let y = area.y
+            + self_alignment_offset(area.height - margin_box.height, align_self, auto_margins);
+        self.translate(x - margin_box.x, y - margin_box.y);
+    }
+
+    /// Place in-flow items of a grid container, whose content box is `width` and `height`
+    /// when they are definite, and return columns and rows with implicit tracks, and areas.
+    fn place_grid_items(
+        &self,
+        width: Option<f32>,
+        height: Option<f32>,
+        column_gap: f32,
+        row_gap: f32,
+    ) -> (Vec<TrackSize>, Vec<TrackSize>, Vec<Area>) {
+        let grid = &self.get_style_node().style.grid;
+        let template_areas = grid.grid_template_areas.as_ref();
+
+        // The explicit grid is defined by templates of tracks and areas.
+        // refer: https://drafts.csswg.org/css-grid-2/#explicit-grids
+        let mut columns = expand_track_list(&grid.grid_template_columns, width, column_gap);
+        let mut rows = expand_track_list(&grid.grid_template_rows, height, row_gap);
+        let (template_columns, template_rows) = (columns.len(), rows.len());
+        let explicit_columns =
+            template_columns.max(template_areas.map_or(0, |areas| areas.columns));
+        let explicit_rows = template_rows.max(template_areas.map_or(0, |areas| areas.rows));
+
+        let mut indices: Vec<usize> = (0..self.children.len())
+            .filter(|&index| {
+                let child = &self.children[index];
+                !child.is_out_of_flow() && !child.is_collapsible_whitespace()
+            })
+            .collect();
+        indices.sort_by_key(|&index| self.children[index].item_style().flex.order);
+
+        // Place items, where lines are rows and cells are columns in `grid-auto-flow: row`.
+        let is_column_flow = grid.grid_auto_flow.column;
+        let placements: Vec<(Placement, Placement)> = indices
+            .iter()
+            .map(|&index| {
+                let item = &self.children[index].item_style().grid;
+                let rows = resolve_placement(
+                    &item.grid_row_start,
+                    &item.grid_row_end,
+                    explicit_rows,
+                    template_areas,
+                    true,
+                );
+                let columns = resolve_placement(
+                    &item.grid_column_start,
+                    &item.grid_column_end,
+                    explicit_columns,
+                    template_areas,
+                    false,
+                );
+                if is_column_flow {
+                    (columns, rows)
+                } else {
+                    (rows, columns)
+                }
+            })
+            .collect();
+        let cells = if is_column_flow {
+            explicit_rows
+        } else {
+            explicit_columns
+        };
+        let areas: Vec<Area> = indices
+            .iter()
+            .zip(place_items(&placements, cells, grid.grid_auto_flow.dense))
+            .map(|(&index, (lines, cells))| {
+                if is_column_flow {
+                    (index, cells, lines)
+                } else {
+                    (index, lines, cells)
+                }
+            })
+            .collect();
+
+        // Tracks out of templates are sized by `grid-auto-rows` and `grid-auto-columns`.
+        // refer: https://drafts.csswg.org/css-grid-2/#implicit-grids
+        let row_count = areas
+            .iter()
+            .map(|(_, rows, _)| rows.end)
+            .fold(explicit_rows, usize::max);
+        let column_count = areas
+            .iter()
+            .map(|(_, _, columns)| columns.end)
+            .fold(explicit_columns, usize::max);
+        while rows.len() < row_count {
+            let auto_rows = &grid.grid_auto_rows;
+            rows.push(auto_rows[(rows.len() - template_rows) % auto_rows.len()]);
+        }
+        while columns.len() < column_count {
+            let auto_columns = &grid.grid_auto_columns;
+            columns.push(auto_columns[(columns.len() - template_columns) % auto_columns.len()]);
+        }
+        (columns, rows, areas)
+    }
+
+    /// Min-content and max-content widths of a grid container, which are the sums of columns
+    /// sized for contributions of items. In the min-content size, items contribute
+    /// their min-content contributions only.
+    // refer: https://drafts.csswg.org/css-grid-2/#intrinsic-sizes
+    pub(super) fn grid_intrinsic_widths(&self) -> (f32, f32) {
+        let column_gap = self
+            .item_style()
+            .flex
+            .column_gap
+            .resolve(None)
+            .unwrap_or(0.);
+        let (columns, _, areas) = self.place_grid_items(None, None, column_gap, 0.);
+        let contributions: Vec<(Range<usize>, (f32, f32))> = areas
+            .into_iter()
+            .map(|(index, _, columns)| {
+                let widths = self.children[index].intrinsic_width_contributions(None);
+                (columns, widths)
+            })
+            .collect();
+        let width = |is_min_content: bool| {
+            let contributions: Vec<Contribution> = contributions
+                .iter()
+                .map(|(tracks, (min_content, max_content))| Contribution {
+                    tracks: tracks.clone(),
+                    min_content: *min_content,
+                    max_content: if is_min_content {
+                        *min_content
+                    } else {
+                        *max_content
+                    },
+                })
+                .collect();
+            let sizes = size_tracks(
+                &columns,
+                &contributions,
+                None,
+                column_gap,
+                JustifyContent::Normal,
+            );
+            sizes.iter().sum::<f32>() + column_gap * sizes.len().saturating_sub(1) as f32
+        };
+        (width(true), width(false))
+    }
+
+    /// Lay out children of a grid container, whose content width is determined.
+    /// `definite_height` is the height of the content box when it is definite.
+    pub(super) fn layout_grid_items(&mut self, definite_height: Option<f32>) {
+        let style = &self.get_style_node().style;
+        let (grid, flex) = (&style.grid, &style.flex);
+        let content = self.dimensions.borrow().content.clone();
+        let row_gap = flex.row_gap.resolve(definite_height).unwrap_or(0.);
+        let column_gap = flex.column_gap.resolve(Some(content.width)).unwrap_or(0.);
+        self.children
+            .retain(|child| !child.is_collapsible_whitespace());
+        for child in &self.children {
+            if child.is_out_of_flow() {
+                // The static position is the content edge of the container.
+                let mut d = child.dimensions.borrow_mut();
+                d.content.x = content.x;
+                d.content.y = content.y;
+            }
+        }
+        let (columns, rows, areas) =
+            self.place_grid_items(Some(content.width), definite_height, column_gap, row_gap);
+
+        // Columns are sized first, because heights of items depend on their widths.
+        let column_contributions: Vec<Contribution> = areas
+            .iter()
+            .map(|(index, _, columns)| {
+                let (min_content, max_content) =
+                    self.children[*index].intrinsic_width_contributions(Some(content.width));
+                Contribution {
+                    tracks: columns.clone(),
+                    min_content,
+                    max_content,
+                }
+            })
+            .collect();
+        let column_sizes = size_tracks(
+            &columns,
+            &column_contributions,
+            Some(content.width),
+            column_gap,
+            flex.justify_content,
+        );
+        let column_positions = track_positions(
+            &column_sizes,
+            column_gap,
+            Some(content.width),
+            flex.justify_content,
+        );
+
+        let justify_self = |child: &LayoutBox| {
+            child
+                .item_style()
+                .grid
+                .justify_self
+                .unwrap_or(grid.justify_items)
+        };
+        let align_self = |child: &LayoutBox| {
+            child
+                .item_style()
+                .flex
+                .align_self
+                .unwrap_or(flex.align_items)
+        };
+        let row_contributions: Vec<Contribution> = areas
+            .iter()
+            .map(|(index, rows, columns)| {
+                let child = &self.children[*index];
+                let (_, area_width) = span(&column_positions, &column_sizes, columns);
+                let width = child.grid_item_width(area_width, justify_self(child));
+                let height = child.measure(width, None).dimensions.margin_box().height;
+                Contribution {
+                    tracks: rows.clone(),
+                    min_content: height,
+                    max_content: height,
+                }
+            })
+            .collect();
+        let row_distribution = content_distribution(flex.align_content);
+        let row_sizes = size_tracks(
+            &rows,
+            &row_contributions,
+            definite_height,
+            row_gap,
+            row_distribution,
+        );
+        let row_positions = track_positions(&row_sizes, row_gap, definite_height, row_distribution);
+
+        for (index, rows, columns) in &areas {
+            let child = &mut self.children[*index];
+            let (x, width) = span(&column_positions, &column_sizes, columns);
+            let (y, height) = span(&row_positions, &row_sizes, rows);
+            let area = Rect {
+                x: content.x + x,
+                y: content.y + y,
+                width,
+                height,
+            };
+            let (justify, align) = (justify_self(child), align_self(child));
+            child.layout_grid_item(&area, justify, align);
+            let (dx, dy) = child.relative_offset(content.width, definite_height);
+            child.translate(dx, dy);
+        }
+
+        let mut d = self.dimensions.borrow_mut();
+        d.content.height =
+            row_sizes.iter().sum::<f32>() + row_gap * row_sizes.len().saturating_sub(1) as f32;
+    }
+}
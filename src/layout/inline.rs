@@ -1,10 +1,12 @@
-use super::font::{with_thread_local_font_context, Font, FontCacheKey, FontContext};
+use super::font::{with_thread_local_font_context, Font, FontContext};
 use super::text::{TextNode};
-use super::{BoxType, Dimensions, LayoutBox, Rect};
+use super::{BoxType, Dimensions, FloatContext, LayoutBox, Rect};
+use crate::style::StyledNode;
 use std::collections::VecDeque;
 use std::iter::Iterator;
 use std::mem;
 use std::ops::Range;
+use std::ptr;
 
 #[derive(Clone)]
 struct Line {
@@ -14,6 +16,10 @@ struct Line {
     metrics: LineMetrics,
     is_line_broken: bool,
     is_suppress_line_break_before: bool,
+    /// Whether some text or atomic inline-level box is placed in the line.
+    has_content: bool,
+    /// Space above the line, which moves the line below floats.
+    offset: f32,
 }
 
 impl Line {
@@ -25,6 +31,8 @@ impl Line {
             metrics: LineMetrics::new(),
             is_line_broken: false,
             is_suppress_line_break_before: false,
+            has_content: false,
+            offset: 0.,
         }
     }
 }
@@ -39,10 +47,14 @@ struct LineBreaker<'a> {
     cur_height: f32,
     metrics: LineMetrics,
     last_known_line_breaking_opportunity: Option<usize>,
+    floats: FloatContext,
+    /// Atomic inline-level boxes and floats before they are laid out, which are placed again
+    /// when they are moved to another line.
+    original_boxes: Vec<(&'a StyledNode<'a>, LayoutBox<'a>)>,
 }
 
 impl<'a> LineBreaker<'a> {
-    fn new() -> LineBreaker<'a> {
+    fn new(floats: FloatContext) -> LineBreaker<'a> {
         LineBreaker {
             work_list: VecDeque::new(),
             new_boxes: vec![],
@@ -52,6 +64,8 @@ impl<'a> LineBreaker<'a> {
             cur_height: 0.0,
             metrics: LineMetrics::new(),
             last_known_line_breaking_opportunity: None,
+            floats,
+            original_boxes: vec![],
         }
     }
 
@@ -183,7 +197,11 @@ impl<'a> LineBreaker<'a> {
         layout_box: &mut LayoutBox<'a>,
         font_context: &mut FontContext,
     ) -> (Option<LayoutBox<'a>>, bool) {
-        if layout_box.is_hidden {
+        // An atomic inline-level box and an out-of-flow box have no splitting position.
+        if layout_box.is_hidden
+            || layout_box.is_block_level()
+            || matches!(layout_box.box_type, BoxType::InlineBlockNode(_))
+        {
             return (None, false);
         }
         if let BoxType::TextNode(node) = &mut layout_box.box_type {
@@ -290,7 +308,11 @@ impl<'a> LineBreaker<'a> {
         match &layout_box.box_type {
             BoxType::InlineNode(_) => self.layout_inline(root, layout_box, font_context),
             BoxType::TextNode(_) => self.layout_text(layout_box, font_context),
-            BoxType::BlockNode(_) => unimplemented!(),
+            BoxType::InlineBlockNode(_) => self.layout_atomic(root, layout_box),
+            // Block-level boxes in lines are floats and absolutely positioned boxes.
+            BoxType::BlockNode(_) | BoxType::TableWrapper => {
+                self.layout_out_of_flow(root, layout_box)
+            }
             _ => unreachable!(),
         }
     }
@@ -302,9 +324,17 @@ impl<'a> LineBreaker<'a> {
         font_context: &mut FontContext,
     ) {
         if !layout_box.is_splitted {
-            layout_box.assign_horizontal_margin_box();
+            layout_box.assign_horizontal_margin_box(root.content.width);
+            // Edges of an inline box split by a block box are only on the outer fragments.
+            let mut d = layout_box.dimensions.borrow_mut();
+            if layout_box.is_continuation {
+                d.reset_edge_left();
+            }
+            if layout_box.has_continuation {
+                d.reset_edge_right();
+            }
         }
-        layout_box.assign_vertical_margin_box();
+        layout_box.assign_vertical_margin_box(root.content.width);
 
         {
             let d = layout_box.dimensions.borrow();
@@ -360,6 +390,24 @@ impl<'a> LineBreaker<'a> {
                 continue;
             }
 
+            if child.is_block_level() {
+                // A float does not take space in the line, and it is in an absolute position.
+                if !child.is_floating() {
+                    child.dimensions.borrow_mut().content.x = total_width;
+                }
+                new_children.push(child.clone());
+                continue;
+            }
+
+            if let BoxType::InlineBlockNode(_) = child.box_type {
+                // Descendants of an atomic inline-level box are in absolute positions.
+                let width = child.dimensions.borrow().margin_horizontal_box().width;
+                child.translate(total_width, 0.);
+                total_width += width;
+                new_children.push(child.clone());
+                continue;
+            }
+
             if let BoxType::TextNode(node) = &child.box_type {
                 let ascent = font_context
                     .get_or_create_by(&node.text_run.cache_key)
@@ -410,12 +458,13 @@ impl<'a> LineBreaker<'a> {
                 let mut containing_block = layout_box.dimensions.borrow_mut();
                 containing_block.content.width = total_width;
                 let styled_node = layout_box.get_style_node();
-                let ascent = font_context
-                    .get_or_create_by(&FontCacheKey::new_from_style(styled_node))
-                    .ascent;
-                if ascent > containing_block.content.height {
-                    containing_block.content.height = ascent;
+                if let Some(font) = font_context.first_available_font(styled_node) {
+                    if font.ascent > containing_block.content.height {
+                        containing_block.content.height = font.ascent;
+                    }
                 }
+                // The content box is above the baseline.
+                containing_block.content.y = -containing_block.content.height;
             }
         }
     }
@@ -487,11 +536,12 @@ impl<'a> LineBreaker<'a> {
                 {
                     let mut d = layout_box.dimensions.borrow_mut();
                     d.content.height = font.ascent + font.descent;
-                    // Maybe, this calculation is specific case for `iced`
-                    d.content.y -= metrics.leading / 2.;
+                    // Text is aligned at the baseline.
+                    d.content.y = -font.ascent;
                     d.content.width = text_width;
                 }
                 self.pending_line.bounds.content.width += text_width;
+                self.pending_line.has_content = true;
             }
 
             if let Some(inline_end) = &inline_end {
@@ -508,22 +558,149 @@ impl<'a> LineBreaker<'a> {
                 self.work_list.push_front(new_layout_box);
             }
         } else {
-            let metrics = self
-                .pending_line
+            self.pending_line
                 .metrics
                 .calc_space(node.styled_node.line_height(), &font);
             {
                 let mut d = layout_box.dimensions.borrow_mut();
                 d.content.height = font.ascent + font.descent;
-                // Maybe, this calculation is specific case for `iced`
-                d.content.y -= metrics.leading / 2.;
+                // Text is aligned at the baseline.
+                d.content.y = -font.ascent;
                 d.content.width = text_width;
             }
             self.pending_line.bounds.content.width += text_width;
+            self.pending_line.has_content = true;
+        }
+    }
+
+    /// Lay out an atomic inline-level box in the space of the line, and place it in the line.
+    /// The box is moved to the next line when it does not fit in the line,
+    /// and the line is moved down below floats when the box does not fit beside them.
+    fn layout_atomic(&mut self, root: &Dimensions, layout_box: &mut LayoutBox<'a>) {
+        let original = match self.original_box(layout_box) {
+            Some(original) => original,
+            None => layout_box.clone(),
+        };
+        let margin_box = loop {
+            *layout_box = original.clone();
+            layout_box.layout_inline_block(root, self.pending_line.green_zone.width);
+            let margin_box = layout_box.dimensions.borrow().margin_box();
+            let remaining_width =
+                self.pending_line.green_zone.width - self.pending_line.bounds.content.width;
+            if margin_box.width <= remaining_width {
+                break margin_box;
+            }
+            if self.pending_line.has_content {
+                self.pending_line.is_line_broken = true;
+                self.work_list.push_front(original);
+                layout_box.is_hidden = true;
+                return;
+            }
+            if !self.lower_pending_line(root, layout_box) {
+                break margin_box;
+            }
+        };
+
+        self.metrics.calc_atomic_space(&margin_box);
+        self.pending_line.metrics.calc_atomic_space(&margin_box);
+        self.pending_line.bounds.content.width += margin_box.width;
+        self.pending_line.has_content = true;
+    }
+
+    /// Place a float or an absolutely positioned box, which does not take space in the line.
+    /// A float is placed at the top of the current line, and the line is shortened around it,
+    /// or it is placed below the line when it does not fit beside the content of the line.
+    /// The static position of an absolutely positioned box is assigned with the line.
+    // refer: https://www.w3.org/TR/CSS21/visuren.html#float-position
+    fn layout_out_of_flow(&mut self, root: &Dimensions, layout_box: &mut LayoutBox<'a>) {
+        // A float is placed once, even if it is moved to another line with the content before it.
+        if layout_box.is_out_of_flow() || self.original_box(layout_box).is_some() {
+            return;
+        }
+
+        let (top, line_height) = self.estimated_line_top(root, layout_box);
+        let containing_block = Rect {
+            y: top,
+            ..root.content.clone()
+        };
+        layout_box.layout_shrink_to_fit_block(&containing_block, root.content.width, None);
+
+        let initial_position = layout_box.dimensions.borrow().margin_box();
+        let mut floats = self.floats.clone();
+        layout_box.place_float(&containing_block, None, top, &mut floats);
+        let (left, right) = floats.available_space(
+            top,
+            line_height,
+            root.content.x,
+            root.content.x + root.content.width,
+        );
+        if right - left >= self.pending_line.bounds.content.width || !self.pending_line.has_content
+        {
+            self.floats = floats;
+            self.pending_line.bounds.content.x = left;
+            self.pending_line.green_zone.width = (right - left).max(0.);
+        } else {
+            let position = layout_box.dimensions.borrow().margin_box();
+            layout_box.translate(
+                initial_position.x - position.x,
+                initial_position.y - position.y,
+            );
+            layout_box.place_float(&containing_block, None, top + line_height, &mut self.floats);
         }
     }
 
-    fn initial_line_placement(&self, root: &Dimensions, _layout_box: &LayoutBox) -> Dimensions {
+    /// Top of the current line and the height of lines.
+    /// All lines have the same height, which is estimated from the lines laid out so far.
+    fn estimated_line_top(&self, root: &Dimensions, layout_box: &LayoutBox) -> (f32, f32) {
+        let line_height = (self.metrics.space_above_baseline + self.metrics.space_under_baseline)
+            .max(layout_box.get_style_node().line_height());
+        let offset = self
+            .lines
+            .iter()
+            .chain(Some(&self.pending_line))
+            .map(|line| line.offset)
+            .sum::<f32>();
+        (
+            root.content.y + self.lines.len() as f32 * line_height + offset,
+            line_height,
+        )
+    }
+
+    /// Move the current line down to the bottom of the highest float beside it,
+    /// and return `false` when no float is beside the line.
+    fn lower_pending_line(&mut self, root: &Dimensions, layout_box: &LayoutBox) -> bool {
+        let (top, line_height) = self.estimated_line_top(root, layout_box);
+        let bottom = match self.floats.next_bottom(top, line_height) {
+            Some(bottom) => bottom,
+            None => return false,
+        };
+        self.pending_line.offset += bottom - top;
+        let line_bounds = self.initial_line_placement(root, layout_box);
+        self.pending_line.bounds.content.x = line_bounds.content.x;
+        self.pending_line.green_zone.width = line_bounds.content.width;
+        true
+    }
+
+    /// The box before it is laid out, when it has been laid out in this formatting context,
+    /// otherwise the box is recorded as the original box.
+    fn original_box(&mut self, layout_box: &LayoutBox<'a>) -> Option<LayoutBox<'a>> {
+        let node = match layout_box.box_type {
+            BoxType::InlineBlockNode(node) | BoxType::BlockNode(node) => node,
+            BoxType::TableWrapper => layout_box.get_style_node(),
+            _ => return None,
+        };
+        let original = self
+            .original_boxes
+            .iter()
+            .find(|(original_node, _)| ptr::eq(*original_node, node));
+        if let Some((_, original)) = original {
+            return Some(original.clone());
+        }
+        self.original_boxes.push((node, layout_box.clone()));
+        None
+    }
+
+    fn initial_line_placement(&self, root: &Dimensions, layout_box: &LayoutBox) -> Dimensions {
         // refer: https://github.com/servo/servo/blob/3f7697690aabd2d8c31bc880fcae21250244219a/components/layout/inline.rs#L500
         // let width = if layout_box.can_split() {
         //   self.minimum_splittable_inline_width(&layout_box)
@@ -532,8 +709,21 @@ impl<'a> LineBreaker<'a> {
         //   unimplemented!();
         // };
 
-        // TODO: calculate float dimensions
-        root.clone()
+        let mut bounds = root.clone();
+        if self.floats.is_empty() {
+            return bounds;
+        }
+
+        let (top, line_height) = self.estimated_line_top(root, layout_box);
+        let (left, right) = self.floats.available_space(
+            top,
+            line_height,
+            root.content.x,
+            root.content.x + root.content.width,
+        );
+        bounds.content.x = left;
+        bounds.content.width = (right - left).max(0.);
+        bounds
     }
 
     fn text_width(&self, node: &TextNode<'a>, font: &Font, font_context: &mut FontContext) -> f32 {
@@ -557,22 +747,33 @@ impl<'a> LineBreaker<'a> {
 pub struct InlineBox<'a> {
     pub root: Dimensions,
     pub boxes: Vec<LayoutBox<'a>>,
+    /// Floats around the lines, which include floats in the lines after `process`.
+    pub floats: FloatContext,
     pub width: f32,
     pub height: f32,
+    /// Offset of the baseline of the last line from the top, or `None` when there is no line.
+    pub baseline: Option<f32>,
 }
 
 impl<'a> InlineBox<'a> {
-    pub fn new(root: Dimensions, boxes: Vec<LayoutBox<'a>>) -> InlineBox<'a> {
+    /// `floats` are placed floats, which line boxes are shortened around.
+    pub fn new(
+        root: Dimensions,
+        boxes: Vec<LayoutBox<'a>>,
+        floats: FloatContext,
+    ) -> InlineBox<'a> {
         InlineBox {
             root,
             boxes,
+            floats,
             width: 0.0,
             height: 0.0,
+            baseline: None,
         }
     }
 
     pub fn process(&mut self) {
-        let mut line_breaker = LineBreaker::new();
+        let mut line_breaker = LineBreaker::new(mem::take(&mut self.floats));
         let old_boxes = mem::replace(&mut self.boxes, Vec::new());
         let mut iter_old_boxes = old_boxes.into_iter();
         line_breaker.scan_for_line(&self.root, &mut iter_old_boxes);
@@ -580,23 +781,29 @@ impl<'a> InlineBox<'a> {
         self.boxes = line_breaker.new_boxes;
         self.width = line_breaker.max_width;
         self.height = line_breaker.cur_height;
+        self.floats = line_breaker.floats;
+        if !line_breaker.lines.is_empty() {
+            self.baseline = Some(self.height - line_breaker.metrics.space_under_baseline);
+        }
     }
 
     /// calculate inline position in line box
     fn assign_position(&self, line_breaker: &mut LineBreaker<'a>) {
         for line in &line_breaker.lines {
             let mut line_box_x = line.bounds.content.x;
+            line_breaker.cur_height += line.offset;
+            let line_top = line_breaker.cur_height + line.bounds.content.y;
             for item in &mut line_breaker.new_boxes[line.range.clone()] {
-                let new_rect_y =
-                    line_breaker.cur_height + line.bounds.content.y + line_breaker.metrics.leading;
-                {
-                    let mut d = item.dimensions.borrow_mut();
-                    d.content.x += line_box_x + d.margin_left_offset();
-                    d.content.y += new_rect_y;
+                // Position of the baseline
+                let new_rect_y = line_top + line_breaker.metrics.space_above_baseline;
+                let new_rect_x = line_box_x + item.dimensions.borrow().margin_left_offset();
+                offset_inline_level_box(item, new_rect_x, new_rect_y, line_top);
+                if item.is_block_level() {
+                    continue;
                 }
                 if let BoxType::InlineNode(_) = item.box_type {
                     let line_box_x = { line_box_x + item.dimensions.borrow().margin_left_offset() };
-                    self.calculate_child_position(item, line_box_x, new_rect_y);
+                    self.calculate_child_position(item, line_box_x, new_rect_y, line_top);
                 }
                 let d = item.dimensions.borrow();
                 let margin_box = d.margin_horizontal_box();
@@ -614,6 +821,7 @@ impl<'a> InlineBox<'a> {
         layout_box: &mut LayoutBox<'a>,
         additional_rect_x: f32,
         additional_rect_y: f32,
+        line_top: f32,
     ) {
         let mut new_rect_x = additional_rect_x;
         for child in &mut layout_box.children {
@@ -622,16 +830,84 @@ impl<'a> InlineBox<'a> {
                     let d = child.dimensions.borrow();
                     new_rect_x + d.margin_left_offset()
                 };
-                self.calculate_child_position(child, new_rect_x, additional_rect_y);
+                self.calculate_child_position(child, new_rect_x, additional_rect_y, line_top);
             }
 
-            let mut d = child.dimensions.borrow_mut();
+            let (width, margin_left) = {
+                let d = child.dimensions.borrow();
+                (d.margin_horizontal_box().width, d.margin_left_offset())
+            };
+            new_rect_x += width;
+
+            offset_inline_level_box(
+                child,
+                additional_rect_x + margin_left,
+                additional_rect_y,
+                line_top,
+            );
+        }
+    }
+}
+
+/// Move an inline-level box in a line. An atomic inline-level box is moved with descendants,
+/// which are in absolute positions, and the other descendants are moved separately.
+/// A float is in an absolute position already, and an absolutely positioned box is moved
+/// to the top of the line at `line_top`, which is its static position.
+fn offset_inline_level_box(layout_box: &mut LayoutBox, dx: f32, dy: f32, line_top: f32) {
+    if layout_box.is_floating() {
+        return;
+    }
+    if let BoxType::InlineBlockNode(_) = layout_box.box_type {
+        layout_box.translate(dx, dy);
+    } else {
+        let mut d = layout_box.dimensions.borrow_mut();
+        d.content.x += dx;
+        d.content.y = if layout_box.is_block_level() {
+            line_top
+        } else {
+            d.content.y + dy
+        };
+    }
+}
 
-            new_rect_x += d.margin_horizontal_box().width;
+impl<'a> LayoutBox<'a> {
+    /// Lay out an inline-block in `root`, which is the containing block of the line boxes,
+    /// where the width is shrink-to-fit in `available_width` of the line.
+    /// The box is placed so that the left of the content box is at `0`,
+    /// and the baseline is at `0`, like other inline-level boxes before they are positioned.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#inlineblock-width
+    fn layout_inline_block(&mut self, root: &Dimensions, available_width: f32) {
+        let containing_block = Rect {
+            width: root.content.width,
+            ..Default::default()
+        };
+        self.layout_shrink_to_fit_block(&containing_block, available_width, None);
+
+        // The baseline is the bottom margin edge when there is no line box.
+        // refer: https://www.w3.org/TR/CSS21/visudet.html#propdef-vertical-align
+        let (margin_left, margin_bottom) = {
+            let d = self.dimensions.borrow();
+            let margin_box = d.margin_box();
+            (d.margin_left_offset(), margin_box.y + margin_box.height)
+        };
+        let baseline = self.last_baseline().unwrap_or(margin_bottom);
+        self.translate(-margin_left, -baseline);
+    }
 
-            d.content.x += additional_rect_x + d.margin_left_offset();
-            d.content.y += additional_rect_y;
+    /// Position of the baseline of the last line box in the normal flow of the box.
+    pub(super) fn last_baseline(&self) -> Option<f32> {
+        if let Some(offset) = self.baseline_offset {
+            return Some(self.dimensions.borrow().content.y + offset);
         }
+        self.children
+            .iter()
+            .rev()
+            .filter(|child| match child.box_type {
+                BoxType::BlockNode(_) => !child.is_out_of_flow() && !child.is_floating(),
+                BoxType::AnonymousBlock => true,
+                _ => false,
+            })
+            .find_map(|child| child.last_baseline())
     }
 }
 
@@ -683,4 +959,12 @@ impl LineMetrics {
 
         LineMetrics::new_from_style(space_above_baseline, space_under_baseline, ascent, leading)
     }
+
+    /// Extend the space for an atomic inline-level box, whose baseline is at `0`.
+    fn calc_atomic_space(&mut self, margin_box: &Rect) {
+        self.space_above_baseline = self.space_above_baseline.max(-margin_box.y);
+        self.space_under_baseline = self
+            .space_under_baseline
+            .max(margin_box.y + margin_box.height);
+    }
 }
@@ -0,0 +1,268 @@
+// refer: https://drafts.csswg.org/css-sizing-3/#intrinsic-sizes
+
+use super::font::with_thread_local_font_context;
+use super::text::{TextFlags, TextNode};
+use super::{clamp_size, BoxType, LayoutBox};
+use crate::computed_values::{Clear, FlexDirection, FlexWrap, LengthOrAuto};
+use crate::style::Display;
+
+/// Widths of inline-level content, which is measured from the start to the end.
+// refer: https://drafts.csswg.org/css-sizing-3/#min-content-inline-size
+#[derive(Default)]
+struct InlineWidths {
+    /// The widest piece of content between soft wrap opportunities.
+    min_content: f32,
+    /// The widest line, where no soft wrap opportunity is taken.
+    max_content: f32,
+    /// Width of the content after the last soft wrap opportunity.
+    piece: f32,
+    /// Width of the current line without trailing white space.
+    line: f32,
+    /// White space at the end of the current line, which hangs when the line ends.
+    trailing_whitespace: f32,
+}
+
+impl InlineWidths {
+    /// Add content which is not broken, and is `min_content` and `max_content` wide.
+    fn push(&mut self, min_content: f32, max_content: f32) {
+        self.piece += min_content;
+        if max_content > 0. {
+            self.line += self.trailing_whitespace + max_content;
+            self.trailing_whitespace = 0.;
+        }
+    }
+
+    /// Add white space, after which a line can be broken.
+    /// White space at the start of a line is collapsed.
+    fn push_whitespace(&mut self, width: f32) {
+        if self.line > 0. {
+            self.trailing_whitespace += width;
+        }
+        self.soft_wrap_opportunity();
+    }
+
+    fn soft_wrap_opportunity(&mut self) {
+        self.min_content = self.min_content.max(self.piece);
+        self.piece = 0.;
+    }
+
+    fn finish(mut self) -> (f32, f32) {
+        self.soft_wrap_opportunity();
+        (self.min_content, self.max_content.max(self.line))
+    }
+}
+
+/// Measure glyphs of a text, which are split at line break opportunities by `TextRun`.
+fn measure_text(node: &TextNode, widths: &mut InlineWidths) {
+    let text_run = &node.text_run;
+    with_thread_local_font_context(|font_context| {
+        let font = font_context.get_or_create_by(&text_run.cache_key);
+        for index in node.range.clone() {
+            let glyph = &text_run.glyphs[index];
+            let width = font.width(&text_run.text[glyph.range.clone()], font_context);
+            if glyph.glyph_store.is_whitespace {
+                widths.push_whitespace(width);
+                continue;
+            }
+            // A text continued from the previous text has no break opportunity at the start.
+            let is_continued =
+                index == 0 && node.flags.contains(&TextFlags::SuppressLineBreakBefore);
+            if !is_continued {
+                widths.soft_wrap_opportunity();
+            }
+            widths.push(width, width);
+        }
+    });
+}
+
+impl<'a> LayoutBox<'a> {
+    /// Min-content and max-content widths of the content box.
+    pub(super) fn intrinsic_widths(&self) -> (f32, f32) {
+        let display = match &self.box_type {
+            BoxType::TextNode(node) => {
+                let mut widths = InlineWidths::default();
+                measure_text(node, &mut widths);
+                return widths.finish();
+            }
+            BoxType::AnonymousBlock | BoxType::InlineNode(_) => {
+                let mut widths = InlineWidths::default();
+                self.measure_inline_content(&mut widths);
+                return widths.finish();
+            }
+            BoxType::TableWrapper => return self.table_wrapper_widths(None),
+            BoxType::BlockNode(node) => node.display(),
+            BoxType::InlineBlockNode(_) => Display::Block,
+            BoxType::AnonymousTableBox(display) => *display,
+        };
+        match display {
+            Display::Flex => self.flex_intrinsic_widths(),
+            Display::Grid => self.grid_intrinsic_widths(),
+            _ => self.block_intrinsic_widths(),
+        }
+    }
+
+    /// Outer min-content and max-content contributions, which include margins, borders and
+    /// paddings, and follow `width`, `min-width` and `max-width`.
+    /// Percentages are resolved against `containing_width`, or treated as `auto` without it.
+    // refer: https://drafts.csswg.org/css-sizing-3/#contributions
+    pub(super) fn intrinsic_width_contributions(
+        &self,
+        containing_width: Option<f32>,
+    ) -> (f32, f32) {
+        if let BoxType::AnonymousBlock | BoxType::TextNode(_) | BoxType::AnonymousTableBox(_) =
+            self.box_type
+        {
+            return self.intrinsic_widths();
+        }
+        let style = self.item_style();
+        let margins = style.margin.margin_left.resolve(containing_width).to_px()
+            + style.margin.margin_right.resolve(containing_width).to_px();
+        if let BoxType::TableWrapper = self.box_type {
+            let (min_content, max_content) = self.table_wrapper_widths(containing_width);
+            return (min_content + margins, max_content + margins);
+        }
+
+        let box_ = &style.box_;
+        let edges = style.border.border_left_width
+            + style.border.border_right_width
+            + style.padding.padding_left
+            + style.padding.padding_right;
+        let min_width = box_
+            .min_width
+            .and_then(|min_width| min_width.resolve(containing_width))
+            .map_or(0., |min_width| self.to_content_size(min_width, edges));
+        let max_width = box_
+            .max_width
+            .and_then(|max_width| max_width.resolve(containing_width))
+            .map(|max_width| self.to_content_size(max_width, edges));
+        let width = box_.width.resolve(containing_width);
+        let (min_content, max_content) = match self.resolve_content_width(width, edges, None) {
+            Some(width) => (width, width),
+            None => self.intrinsic_widths(),
+        };
+        let outer_width = |width: f32| clamp_size(width, min_width, max_width) + edges + margins;
+        (outer_width(min_content), outer_width(max_content))
+    }
+
+    /// Width of the content box for `width` whose percentage is resolved,
+    /// or `None` when it is `auto`, where `edges` is the sum of borders and paddings.
+    /// `fit-content` is shrink-to-fit in `available_width`, and `auto` when it is unknown.
+    pub(super) fn resolve_content_width(
+        &self,
+        width: LengthOrAuto,
+        edges: f32,
+        available_width: Option<f32>,
+    ) -> Option<f32> {
+        match width {
+            LengthOrAuto::Length(width) => Some(self.to_content_size(width, edges)),
+            LengthOrAuto::MinContent => Some(self.intrinsic_widths().0),
+            LengthOrAuto::MaxContent => Some(self.intrinsic_widths().1),
+            LengthOrAuto::FitContent => {
+                available_width.map(|available_width| self.shrink_to_fit_width(available_width))
+            }
+            LengthOrAuto::Percentage(_) | LengthOrAuto::Auto => None,
+        }
+    }
+
+    /// Width of `auto`, which fits the content in `available_width`
+    /// between the min-content and max-content widths.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#shrink-to-fit-float
+    pub(super) fn shrink_to_fit_width(&self, available_width: f32) -> f32 {
+        let (min_content, max_content) = self.intrinsic_widths();
+        available_width.max(min_content).min(max_content)
+    }
+
+    /// Widths of a block container, where block-level children are stacked,
+    /// and floats are placed side by side before the following content.
+    fn block_intrinsic_widths(&self) -> (f32, f32) {
+        let (mut min_content, mut max_content) = (0f32, 0f32);
+        // Width of floats placed in a row, which are cleared by `clear`.
+        let mut floats = 0.;
+        for child in &self.children {
+            if child.is_out_of_flow() {
+                continue;
+            }
+            if child.clear() != Clear::None {
+                floats = 0.;
+            }
+            let (min, max) = child.intrinsic_width_contributions(None);
+            min_content = min_content.max(min);
+            if child.is_floating() {
+                floats += max;
+                max_content = max_content.max(floats);
+            } else {
+                max_content = max_content.max(floats + max);
+            }
+        }
+        (min_content, max_content)
+    }
+
+    /// Widths of a flex container, where items in a row are side by side,
+    /// and an item can be in a line alone when lines wrap.
+    // refer: https://drafts.csswg.org/css-flexbox-1/#intrinsic-main-sizes
+    fn flex_intrinsic_widths(&self) -> (f32, f32) {
+        let flex = &self.item_style().flex;
+        let contributions: Vec<(f32, f32)> = self
+            .children
+            .iter()
+            .filter(|child| !child.is_out_of_flow() && !child.is_collapsible_whitespace())
+            .map(|child| child.intrinsic_width_contributions(None))
+            .collect();
+        let min_content = contributions
+            .iter()
+            .map(|widths| widths.0)
+            .fold(0., f32::max);
+        if let FlexDirection::Column | FlexDirection::ColumnReverse = flex.flex_direction {
+            let max_content = contributions
+                .iter()
+                .map(|widths| widths.1)
+                .fold(0., f32::max);
+            return (min_content, max_content);
+        }
+
+        let gaps = flex.column_gap.resolve(None).unwrap_or(0.)
+            * contributions.len().saturating_sub(1) as f32;
+        let max_content = contributions.iter().map(|widths| widths.1).sum::<f32>() + gaps;
+        match flex.flex_wrap {
+            FlexWrap::Nowrap => (
+                contributions.iter().map(|widths| widths.0).sum::<f32>() + gaps,
+                max_content,
+            ),
+            FlexWrap::Wrap | FlexWrap::WrapReverse => (min_content, max_content),
+        }
+    }
+
+    /// Measure inline-level descendants in order, where inline boxes add their edges
+    /// and atomic inline-level boxes are not broken.
+    fn measure_inline_content(&self, widths: &mut InlineWidths) {
+        for child in &self.children {
+            match &child.box_type {
+                BoxType::TextNode(node) => measure_text(node, widths),
+                BoxType::InlineNode(node) => {
+                    let style = &node.style;
+                    // Edges of an inline box split by a block box are only on the outer fragments.
+                    if !child.is_continuation {
+                        let start = style.margin.margin_left.resolve(None).to_px()
+                            + style.border.border_left_width
+                            + style.padding.padding_left;
+                        widths.push(start, start);
+                    }
+                    child.measure_inline_content(widths);
+                    if !child.has_continuation {
+                        let end = style.margin.margin_right.resolve(None).to_px()
+                            + style.border.border_right_width
+                            + style.padding.padding_right;
+                        widths.push(end, end);
+                    }
+                }
+                _ if child.is_out_of_flow() => {}
+                _ => {
+                    let (min, max) = child.intrinsic_width_contributions(None);
+                    widths.soft_wrap_opportunity();
+                    widths.push(min, max);
+                    widths.soft_wrap_opportunity();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// refer: https://www.w3.org/TR/CSS21/box.html#collapsing-margins
+
+/// Adjoining vertical margins, which collapse into a single margin.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CollapsedMargin {
+    max_positive: f32,
+    min_negative: f32,
+}
+
+impl CollapsedMargin {
+    pub fn zero() -> CollapsedMargin {
+        Default::default()
+    }
+
+    pub fn new(margin: f32) -> CollapsedMargin {
+        CollapsedMargin {
+            max_positive: margin.max(0.),
+            min_negative: margin.min(0.),
+        }
+    }
+
+    pub fn adjoin(&self, other: &CollapsedMargin) -> CollapsedMargin {
+        CollapsedMargin {
+            max_positive: self.max_positive.max(other.max_positive),
+            min_negative: self.min_negative.min(other.min_negative),
+        }
+    }
+
+    pub fn adjoin_assign(&mut self, other: &CollapsedMargin) {
+        *self = self.adjoin(other);
+    }
+
+    /// The width of the collapsed margin is the sum of the largest positive margin
+    /// and the most negative margin.
+    pub fn solve(&self) -> f32 {
+        self.max_positive + self.min_negative
+    }
+}
+
+/// Margins of a block box, which may collapse with margins outside of the box.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CollapsedBlockMargins {
+    /// Whether the top and bottom margins of the box are adjoining,
+    /// which means that the box has no line box and no in-flow content.
+    pub collapsed_through: bool,
+    /// The top margin adjoined with the top margins of the first children.
+    pub start: CollapsedMargin,
+    /// The bottom margin adjoined with the bottom margins of the last children.
+    pub end: CollapsedMargin,
+}
+
+impl CollapsedBlockMargins {
+    pub fn zero() -> CollapsedBlockMargins {
+        Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapsed_margin() {
+        let margin = CollapsedMargin::new(10.).adjoin(&CollapsedMargin::new(20.));
+        assert_eq!(margin.solve(), 20.);
+
+        let margin = margin.adjoin(&CollapsedMargin::new(-5.));
+        assert_eq!(margin.solve(), 15.);
+
+        let margin = CollapsedMargin::new(-5.).adjoin(&CollapsedMargin::new(-15.));
+        assert_eq!(margin.solve(), -15.);
+
+        assert_eq!(CollapsedMargin::zero().solve(), 0.);
+    }
+}
@@ -1,12 +1,23 @@
+mod flex;
+mod float;
+mod grid;
 pub mod font;
 mod inline;
+mod intrinsic;
+mod margin;
+mod positioned;
+mod table;
 pub mod text;
+mod woff;
 
-use crate::cssom::{Unit, Value};
+use crate::computed_values::{BoxSizing, LengthOrAuto};
 use crate::dom::NodeType;
 use crate::style::*;
+use flex::Measurement;
+pub use float::FloatContext;
 use font::{with_thread_local_font_context, FontContext};
 use inline::InlineBox;
+pub use margin::{CollapsedBlockMargins, CollapsedMargin};
 use std::cell::RefCell;
 use std::mem;
 use std::rc::Rc;
@@ -151,6 +162,17 @@ pub struct LayoutBox<'a> {
     /// used for line breaking
     pub is_splitted: bool,
     pub is_hidden: bool,
+    /// The inline box is continued from a fragment before a block box, so that it has no left edge.
+    pub is_continuation: bool,
+    /// The inline box is continued by a fragment after a block box, so that it has no right edge.
+    pub has_continuation: bool,
+    /// Offset of a fixed or sticky box, which is applied for the scroll position.
+    pub scroll_offset: (f32, f32),
+    /// Offset of the baseline of the last line box from the top of the content box,
+    /// in an anonymous block which has line boxes.
+    pub baseline_offset: Option<f32>,
+    /// Measurements as a flex or grid item, which are shared with copies.
+    measurements: Rc<RefCell<Vec<Measurement>>>,
 }
 
 impl<'a> Clone for LayoutBox<'a> {
@@ -159,6 +181,11 @@ impl<'a> Clone for LayoutBox<'a> {
         let d = self.dimensions.borrow();
         layout_box.dimensions = Rc::new(RefCell::new(d.clone()));
         layout_box.children = self.children.clone();
+        layout_box.is_continuation = self.is_continuation;
+        layout_box.has_continuation = self.has_continuation;
+        layout_box.scroll_offset = self.scroll_offset;
+        layout_box.baseline_offset = self.baseline_offset;
+        layout_box.measurements = Rc::clone(&self.measurements);
         layout_box
     }
 }
@@ -171,182 +198,679 @@ impl<'a> LayoutBox<'a> {
             children: vec![],
             is_splitted: false,
             is_hidden: false,
+            is_continuation: false,
+            has_continuation: false,
+            scroll_offset: (0., 0.),
+            baseline_offset: None,
+            measurements: Rc::new(RefCell::new(vec![])),
         }
     }
 
     fn get_style_node(&self) -> &'a StyledNode<'a> {
         match &self.box_type {
-            BoxType::BlockNode(node) | BoxType::InlineNode(node) => node,
+            BoxType::BlockNode(node)
+            | BoxType::InlineNode(node)
+            | BoxType::InlineBlockNode(node) => node,
             BoxType::TextNode(node) => node.styled_node,
+            BoxType::TableWrapper => self.wrapped_table().get_style_node(),
             BoxType::AnonymousBlock => panic!("Anonymous block box has no style node"),
+            BoxType::AnonymousTableBox(_) => panic!("Anonymous table box has no style node"),
         }
     }
 
-    pub fn layout(&mut self, containing_block: Rc<RefCell<Dimensions>>) {
+    /// Return margins of the box, which may collapse with margins of siblings and the parent.
+    /// `containing_block_height` is the height of the containing block when it is definite,
+    /// which percentage heights are resolved against.
+    /// `floats` is the float context of the block formatting context which the box is in.
+    pub fn layout(
+        &mut self,
+        containing_block: Rc<RefCell<Dimensions>>,
+        containing_block_height: Option<f32>,
+        floats: &mut FloatContext,
+    ) -> CollapsedBlockMargins {
+        let space = {
+            let d = containing_block.borrow();
+            (d.content.x, d.content.x + d.content.width)
+        };
+        self.layout_beside_floats(containing_block, containing_block_height, floats, space)
+    }
+
+    /// Lay out the box like `layout`, where the margin box of a block formatting context root
+    /// is placed in `space`, which is the left and right edges of the space beside floats.
+    /// Percentages are resolved against `containing_block` even if the space is narrower.
+    // refer: https://www.w3.org/TR/CSS21/visuren.html#bfc-next-to-float
+    fn layout_beside_floats(
+        &mut self,
+        containing_block: Rc<RefCell<Dimensions>>,
+        containing_block_height: Option<f32>,
+        floats: &mut FloatContext,
+        space: (f32, f32),
+    ) -> CollapsedBlockMargins {
         match self.box_type {
-            BoxType::BlockNode(_) => self.layout_block(containing_block),
+            BoxType::BlockNode(_) if self.establishes_formatting_context() => {
+                self.layout_block(containing_block, containing_block_height, None, space)
+            }
+            BoxType::BlockNode(_) => self.layout_block(
+                containing_block,
+                containing_block_height,
+                Some(floats),
+                space,
+            ),
             BoxType::AnonymousBlock => {
-                let containing_block = containing_block.borrow();
+                // Line boxes are laid out at the current position, so that they avoid floats.
+                let mut root = containing_block.borrow().clone();
+                root.content.y += root.content.height;
                 {
                     let mut d = self.dimensions.borrow_mut();
-                    d.content.x = containing_block.content.x;
-                    d.content.y = containing_block.content.y;
+                    d.content.x = root.content.x;
+                    d.content.y = root.content.y;
                 }
+                let containing_width = root.content.width;
                 // Anonymous block is including only inline box in children
                 let mut inline_box = InlineBox::new(
-                    containing_block.clone(),
+                    root,
                     mem::replace(&mut self.children, Vec::new()),
+                    floats.clone(),
                 );
                 inline_box.process();
-                let mut d = self.dimensions.borrow_mut();
-                d.content.width = inline_box.width;
-                d.content.height = inline_box.height;
+                {
+                    let mut d = self.dimensions.borrow_mut();
+                    d.content.width = inline_box.width;
+                    d.content.height = inline_box.height;
+                }
                 self.children = inline_box.boxes;
+                *floats = inline_box.floats;
+                self.baseline_offset = inline_box.baseline;
+                self.offset_relative_inline_boxes(containing_width);
+
+                // Line boxes separate margins of the preceding and following blocks.
+                CollapsedBlockMargins {
+                    collapsed_through: inline_box.height == 0.,
+                    ..CollapsedBlockMargins::zero()
+                }
             }
-            // All inline node and text node are contained in anonymous box.
-            BoxType::InlineNode(_) | BoxType::TextNode(_) => unreachable!(),
+            BoxType::TableWrapper => {
+                let containing_block = {
+                    let d = containing_block.borrow();
+                    Rect {
+                        x: space.0,
+                        y: d.content.y + d.content.height,
+                        ..d.content.clone()
+                    }
+                };
+                self.layout_table_wrapper(
+                    &containing_block,
+                    space.1 - space.0,
+                    containing_block_height,
+                    true,
+                );
+
+                // The table wrapper box establishes a block formatting context.
+                let d = self.dimensions.borrow();
+                CollapsedBlockMargins {
+                    collapsed_through: false,
+                    start: CollapsedMargin::new(d.margin.top),
+                    end: CollapsedMargin::new(d.margin.bottom),
+                }
+            }
+            // All inline-level boxes are contained in anonymous box,
+            // and parts of tables are laid out by the table.
+            BoxType::InlineNode(_)
+            | BoxType::InlineBlockNode(_)
+            | BoxType::TextNode(_)
+            | BoxType::AnonymousTableBox(_) => unreachable!(),
         }
     }
 
-    fn layout_block(&mut self, containing_block: Rc<RefCell<Dimensions>>) {
+    /// When `floats` is `None`, the box establishes a new block formatting context,
+    /// whose margins do not collapse with margins of its children,
+    /// and whose height contains its floats.
+    /// The margin box is placed in `space`, which is the left and right edges.
+    fn layout_block(
+        &mut self,
+        containing_block: Rc<RefCell<Dimensions>>,
+        containing_block_height: Option<f32>,
+        floats: Option<&mut FloatContext>,
+        (left, right): (f32, f32),
+    ) -> CollapsedBlockMargins {
         // Child width depends on parent width,
         // so parent width need to be calculated before child width.
-        self.calculate_block_width(containing_block.clone());
-
-        self.calculate_block_position(containing_block);
-
-        self.layout_block_children();
+        self.calculate_block_width(containing_block.clone(), right - left);
+
+        self.calculate_block_position(containing_block, left);
+
+        let (height, min_height, max_height) =
+            self.block_height_constraints(containing_block_height);
+        let (no_top_edge, no_bottom_edge) = {
+            let d = self.dimensions.borrow();
+            (
+                d.border.top == 0. && d.padding.top == 0.,
+                d.border.bottom == 0. && d.padding.bottom == 0.,
+            )
+        };
+        let is_formatting_context_root = floats.is_none();
+        let collapsible_start = !is_formatting_context_root && no_top_edge;
+        let collapsible_end = !is_formatting_context_root && no_bottom_edge && height.is_none();
+
+        // Percentage heights of children are resolved only against a definite height.
+        let definite_height = height.map(|height| clamp_size(height, min_height, max_height));
+        let mut own_floats = FloatContext::new();
+        let children_margins = self.layout_contents(
+            definite_height,
+            collapsible_start,
+            collapsible_end,
+            floats.unwrap_or(&mut own_floats),
+        );
+        self.include_floats(&own_floats);
 
         // Parent height is affected by child layout,
         // so parent height need to be calculated after children are laid out.
-        self.calculate_block_height();
+        self.calculate_block_height(height, min_height, max_height);
+
+        let d = self.dimensions.borrow();
+        let mut margins = CollapsedBlockMargins {
+            collapsed_through: false,
+            start: CollapsedMargin::new(d.margin.top),
+            end: CollapsedMargin::new(d.margin.bottom),
+        };
+        if collapsible_start {
+            margins.start.adjoin_assign(&children_margins.start);
+        }
+        if collapsible_end {
+            margins.end.adjoin_assign(&children_margins.end);
+        }
+
+        let zero_height = height.unwrap_or(0.) == 0. && min_height == 0.;
+        if collapsible_start
+            && no_bottom_edge
+            && zero_height
+            && children_margins.collapsed_through
+        {
+            // Top and bottom margins of the box are adjoining, so that the box collapses through.
+            margins.collapsed_through = true;
+            margins.start.adjoin_assign(&margins.end);
+            margins.end = margins.start;
+        }
+        margins
     }
 
-    fn calculate_block_width(&mut self, containing_block: Rc<RefCell<Dimensions>>) {
-        let style = self.get_style_node();
+    /// Lay out a block container which establishes a new block formatting context,
+    /// whose margin box is at the top left of `containing_block`.
+    /// `auto` margins are `0`, and `auto` width is shrink-to-fit in `available_width`.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#float-width
+    fn layout_shrink_to_fit_block(
+        &mut self,
+        containing_block: &Rect,
+        available_width: f32,
+        containing_block_height: Option<f32>,
+    ) {
+        if let BoxType::TableWrapper = self.box_type {
+            self.layout_table_wrapper(
+                containing_block,
+                available_width,
+                containing_block_height,
+                false,
+            );
+            return;
+        }
+        let containing_width = Some(containing_block.width);
+        self.assign_horizontal_margin_box(containing_block.width);
+        self.assign_vertical_margin_box(containing_block.width);
+
+        let box_ = &self.get_style_node().style.box_;
+        let (edges, margins) = {
+            let d = self.dimensions.borrow();
+            (
+                d.border.left + d.border.right + d.padding.left + d.padding.right,
+                d.margin.left + d.margin.right,
+            )
+        };
+        let available = available_width - margins - edges;
+        let width = self
+            .resolve_content_width(box_.width.resolve(containing_width), edges, Some(available))
+            .unwrap_or_else(|| self.shrink_to_fit_width(available));
+        let min_width = box_
+            .min_width
+            .and_then(|min_width| min_width.resolve(containing_width))
+            .map_or(0., |min_width| self.to_content_size(min_width, edges));
+        let max_width = box_
+            .max_width
+            .and_then(|max_width| max_width.resolve(containing_width))
+            .map(|max_width| self.to_content_size(max_width, edges));
+
+        let (height, min_height, max_height) =
+            self.block_height_constraints(containing_block_height);
+        {
+            let mut d = self.dimensions.borrow_mut();
+            d.content.width = clamp_size(width, min_width, max_width);
+            d.content.x = containing_block.x + d.margin.left + d.border.left + d.padding.left;
+            d.content.y = containing_block.y + d.margin.top + d.border.top + d.padding.top;
+            d.content.height = 0.;
+        }
 
-        // `width` has initial value `auto`.
-        let auto = Value::Keyword("auto".into());
-        let mut width = style.value("width").unwrap_or_else(|| auto.clone());
+        let definite_height = height.map(|height| clamp_size(height, min_height, max_height));
+        let mut floats = FloatContext::new();
+        self.layout_contents(definite_height, false, false, &mut floats);
+        self.include_floats(&floats);
+        self.calculate_block_height(height, min_height, max_height);
+    }
 
-        // `margin`, `border`, `padding` has initial value `0`.
-        let zero = Value::Length(0.0, Unit::Px);
+    /// The margin box fills `available_width`, and percentages are resolved against
+    /// the width of `containing_block`.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#min-max-widths
+    fn calculate_block_width(
+        &mut self,
+        containing_block: Rc<RefCell<Dimensions>>,
+        available_width: f32,
+    ) {
+        let style = &self.get_style_node().style;
+        let containing_width = containing_block.borrow().content.width;
+
+        let border_left = style.border.border_left_width;
+        let border_right = style.border.border_right_width;
+
+        let padding_left = style.padding.padding_left;
+        let padding_right = style.padding.padding_right;
+
+        let edges = border_left + border_right + padding_left + padding_right;
+
+        let basis = Some(containing_width);
+        let available = available_width
+            - style.margin.margin_left.resolve(basis).to_px()
+            - style.margin.margin_right.resolve(basis).to_px()
+            - edges;
+        let width = style.box_.width.resolve(basis);
+        let width = match self.resolve_content_width(width, edges, Some(available)) {
+            Some(width) => LengthOrAuto::Length(width),
+            None => LengthOrAuto::Auto,
+        };
+        let mut used = self.solve_block_width(width, containing_width, available_width, edges);
+
+        let max_width = style
+            .box_
+            .max_width
+            .and_then(|max_width| max_width.resolve(Some(containing_width)));
+        if let Some(max_width) = max_width {
+            let max_width = self.to_content_size(max_width, edges);
+            if used.0 > max_width {
+                used = self.solve_block_width(
+                    LengthOrAuto::Length(max_width),
+                    containing_width,
+                    available_width,
+                    edges,
+                );
+            }
+        }
 
-        let mut margin_left = style.lookup("margin-left", "margin", &zero);
-        let mut margin_right = style.lookup("margin-right", "margin", &zero);
+        let min_width = style
+            .box_
+            .min_width
+            .and_then(|min_width| min_width.resolve(Some(containing_width)))
+            .map_or(0., |min_width| self.to_content_size(min_width, edges));
+        if used.0 < min_width {
+            used = self.solve_block_width(
+                LengthOrAuto::Length(min_width),
+                containing_width,
+                available_width,
+                edges,
+            );
+        }
 
-        let border_left = style.lookup("border-left-width", "border", &zero);
-        let border_right = style.lookup("border-right-width", "border", &zero);
+        let (width, margin_left, margin_right) = used;
 
-        let padding_left = style.lookup("padding-left", "padding", &zero);
-        let padding_right = style.lookup("padding-right", "padding", &zero);
+        let mut d = self.dimensions.borrow_mut();
+        d.content.width = width;
 
-        let total: f32 = [
-            margin_left.to_px(),
-            margin_right.to_px(),
-            border_left.to_px(),
-            border_right.to_px(),
-            padding_left.to_px(),
-            padding_right.to_px(),
-            width.to_px(),
-        ]
-        .iter()
-        .sum();
+        d.margin.left = margin_left;
+        d.margin.right = margin_right;
 
-        let containing_block = containing_block.borrow();
+        d.padding.left = padding_left;
+        d.padding.right = padding_right;
 
-        if width != auto && total > containing_block.content.width {
-            if margin_left == auto {
-                margin_left = Value::Length(0.0, Unit::Px);
+        d.border.left = border_left;
+        d.border.right = border_right;
+    }
+
+    /// Return the content width and horizontal margins, which fill `available_width`.
+    /// Percentage margins are resolved against `containing_width`.
+    /// `edges` is the sum of horizontal borders and paddings.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#blockwidth
+    fn solve_block_width(
+        &self,
+        mut width: LengthOrAuto,
+        containing_width: f32,
+        available_width: f32,
+        edges: f32,
+    ) -> (f32, f32, f32) {
+        let style = &self.get_style_node().style;
+
+        let mut margin_left = style.margin.margin_left.resolve(Some(containing_width));
+        let mut margin_right = style.margin.margin_right.resolve(Some(containing_width));
+
+        let total = margin_left.to_px() + margin_right.to_px() + edges + width.to_px();
+
+        if !width.is_auto() && total > available_width {
+            if margin_left.is_auto() {
+                margin_left = LengthOrAuto::Length(0.0);
             }
-            if margin_right == auto {
-                margin_right = Value::Length(0.0, Unit::Px);
+            if margin_right.is_auto() {
+                margin_right = LengthOrAuto::Length(0.0);
             }
         }
 
-        let underflow = containing_block.content.width - total;
+        let underflow = available_width - total;
 
-        match (width == auto, margin_left == auto, margin_right == auto) {
+        match (
+            width.is_auto(),
+            margin_left.is_auto(),
+            margin_right.is_auto(),
+        ) {
             (false, false, false) => {
-                margin_right = Value::Length(margin_right.to_px() + underflow, Unit::Px);
+                margin_right = LengthOrAuto::Length(margin_right.to_px() + underflow);
             }
             (false, false, true) => {
-                margin_right = Value::Length(underflow, Unit::Px);
+                margin_right = LengthOrAuto::Length(underflow);
             }
             (false, true, false) => {
-                margin_left = Value::Length(underflow, Unit::Px);
+                margin_left = LengthOrAuto::Length(underflow);
             }
             (true, _, _) => {
-                if margin_left == auto {
-                    margin_left = Value::Length(0.0, Unit::Px)
+                if margin_left.is_auto() {
+                    margin_left = LengthOrAuto::Length(0.0)
                 }
-                if margin_right == auto {
-                    margin_right = Value::Length(0.0, Unit::Px)
+                if margin_right.is_auto() {
+                    margin_right = LengthOrAuto::Length(0.0)
                 }
 
                 if underflow > 0.0 {
-                    width = Value::Length(underflow, Unit::Px);
+                    width = LengthOrAuto::Length(underflow);
                 } else {
-                    width = Value::Length(0.0, Unit::Px);
-                    margin_right = Value::Length(margin_right.to_px() + underflow, Unit::Px);
+                    width = LengthOrAuto::Length(0.0);
+                    margin_right = LengthOrAuto::Length(margin_right.to_px() + underflow);
                 }
             }
             (false, true, true) => {
-                margin_left = Value::Length(underflow / 2.0, Unit::Px);
-                margin_right = Value::Length(underflow / 2.0, Unit::Px);
+                margin_left = LengthOrAuto::Length(underflow / 2.0);
+                margin_right = LengthOrAuto::Length(underflow / 2.0);
             }
         }
 
-        let mut d = self.dimensions.borrow_mut();
-        d.content.width = width.to_px();
-
-        d.margin.left = margin_left.to_px();
-        d.margin.right = margin_right.to_px();
-
-        d.padding.left = padding_left.to_px();
-        d.padding.right = padding_right.to_px();
+        (width.to_px(), margin_left.to_px(), margin_right.to_px())
+    }
 
-        d.border.left = border_left.to_px();
-        d.border.right = border_right.to_px();
+    /// Convert a size of the box, which is in `box-sizing`, to a size of the content box.
+    /// `edges` is the sum of borders and paddings in the same axis.
+    fn to_content_size(&self, size: f32, edges: f32) -> f32 {
+        match self.item_style().box_.box_sizing {
+            BoxSizing::ContentBox => size,
+            BoxSizing::BorderBox => (size - edges).max(0.),
+        }
     }
 
-    fn calculate_block_position(&mut self, containing_block: Rc<RefCell<Dimensions>>) {
+    /// The left margin edge is placed at `left`.
+    fn calculate_block_position(&mut self, containing_block: Rc<RefCell<Dimensions>>, left: f32) {
         let containing_block = containing_block.borrow();
 
-        self.assign_vertical_margin_box();
+        self.assign_vertical_margin_box(containing_block.content.width);
 
         let mut d = self.dimensions.borrow_mut();
 
-        d.content.x = containing_block.content.x + d.margin.left + d.border.left + d.padding.left;
+        d.content.x = left + d.margin.left + d.border.left + d.padding.left;
 
+        // The top margin is added after margin collapsing.
         d.content.y = containing_block.content.height
             + containing_block.content.y
-            + d.margin.top
             + d.border.top
             + d.padding.top;
     }
 
-    fn layout_block_children(&mut self) {
+    /// Lay out children in the formatting context which the box establishes,
+    /// or in the block formatting context which the box is in.
+    fn layout_contents(
+        &mut self,
+        definite_height: Option<f32>,
+        collapsible_start: bool,
+        collapsible_end: bool,
+        floats: &mut FloatContext,
+    ) -> CollapsedBlockMargins {
+        let display = match &self.box_type {
+            BoxType::BlockNode(node) => node.display(),
+            _ => Display::Block,
+        };
+        match display {
+            Display::Flex => {
+                self.layout_flex_items(definite_height);
+                CollapsedBlockMargins::zero()
+            }
+            Display::Grid => {
+                self.layout_grid_items(definite_height);
+                CollapsedBlockMargins::zero()
+            }
+            _ => self.layout_block_children(
+                definite_height,
+                collapsible_start,
+                collapsible_end,
+                floats,
+            ),
+        }
+    }
+
+    /// Place children vertically with collapsing adjoining margins.
+    /// When `collapsible_start` or `collapsible_end` is `true`, margins of the first or last
+    /// children collapse with the top or bottom margin of this box, and are returned.
+    /// Floats are placed in `floats`, and do not affect the position of following boxes.
+    fn layout_block_children(
+        &mut self,
+        definite_height: Option<f32>,
+        collapsible_start: bool,
+        collapsible_end: bool,
+        floats: &mut FloatContext,
+    ) -> CollapsedBlockMargins {
         let parent_dimensions = &self.dimensions;
+        let mut start = CollapsedMargin::zero();
+        // Margins after the last placed child, which are not resolved yet.
+        let mut pending = CollapsedMargin::zero();
+        let mut collapses_with_start = collapsible_start;
+
         for child in &mut self.children {
-            child.layout(parent_dimensions.clone());
+            if child.is_out_of_flow() {
+                // The static position is used when offsets are `auto`.
+                // Absolutely positioned boxes are laid out after the normal flow.
+                let d = parent_dimensions.borrow();
+                let mut child_d = child.dimensions.borrow_mut();
+                child_d.content.x = d.content.x;
+                child_d.content.y = d.content.y + d.content.height;
+                if !collapses_with_start {
+                    child_d.content.y += pending.solve();
+                }
+                continue;
+            }
+
+            let (cursor, containing_width) = {
+                let d = parent_dimensions.borrow();
+                (d.content.y + d.content.height, d.content.width)
+            };
+
+            if child.is_floating() {
+                let ceiling = if collapses_with_start {
+                    cursor
+                } else {
+                    cursor + pending.solve()
+                };
+                child.layout_float(parent_dimensions.clone(), definite_height, ceiling, floats);
+                continue;
+            }
+
+            // Floats are placed in absolute positions, so that the child is laid out at the
+            // position where it is expected to be after margin collapsing.
+            let expected_offset = if collapses_with_start {
+                0.
+            } else {
+                pending
+                    .adjoin(&child.speculative_start_margin(containing_width))
+                    .solve()
+            };
+            // refer: https://www.w3.org/TR/CSS21/visuren.html#flow-control
+            let clearance = floats
+                .clearance(child.clear())
+                .map(|bottom| bottom - cursor)
+                .filter(|&offset| offset > expected_offset);
+            let offset = clearance.unwrap_or(expected_offset);
+
+            parent_dimensions.borrow_mut().content.height += offset;
+            // The border box of a block formatting context root does not overlap floats.
+            let (left, right) = {
+                let d = parent_dimensions.borrow();
+                (d.content.x, d.content.x + d.content.width)
+            };
+            let space = if child.establishes_formatting_context() {
+                let (left, right) = floats.available_space(cursor + offset, 0., left, right);
+                (left, right.max(left))
+            } else {
+                (left, right)
+            };
+            let margins = child.layout_beside_floats(
+                parent_dimensions.clone(),
+                definite_height,
+                floats,
+                space,
+            );
             let mut d = parent_dimensions.borrow_mut();
-            let child_d = child.dimensions.borrow();
-            d.content.height += child_d.margin_box().height;
+            d.content.height -= offset;
+
+            let offset = if let Some(clearance) = clearance {
+                // Clearance separates margins of the child from margins before it.
+                collapses_with_start = false;
+                clearance
+            } else if collapses_with_start {
+                start.adjoin_assign(&margins.start);
+                0.
+            } else {
+                pending.adjoin_assign(&margins.start);
+                pending.solve()
+            };
+            let child_top = child.dimensions.borrow().border_box().y;
+            child.translate(0., cursor + offset - child_top);
+            // Relative offsets do not affect following boxes.
+            let (dx, dy) = child.relative_offset(d.content.width, definite_height);
+            child.translate(dx, dy);
+
+            if margins.collapsed_through && clearance.is_none() {
+                // The child has no height, and margins go through it.
+                continue;
+            }
+            collapses_with_start = false;
+            d.content.height += offset + child.dimensions.borrow().border_box().height;
+            pending = margins.end;
+        }
+
+        let mut d = parent_dimensions.borrow_mut();
+        let end = if collapsible_end {
+            pending
+        } else {
+            d.content.height += pending.solve();
+            CollapsedMargin::zero()
+        };
+        CollapsedBlockMargins {
+            collapsed_through: collapses_with_start,
+            start,
+            end,
         }
     }
 
-    fn calculate_block_height(&mut self) {
-        if let Some(Value::Length(height, Unit::Px)) = self.get_style_node().value("height") {
-            self.dimensions.borrow_mut().content.height = height;
+    /// Estimate the top margin of the box collapsed with top margins of its first children,
+    /// before the children are laid out. Percentages are resolved against `containing_width`.
+    fn speculative_start_margin(&self, containing_width: f32) -> CollapsedMargin {
+        if !matches!(self.box_type, BoxType::BlockNode(_) | BoxType::TableWrapper) {
+            return CollapsedMargin::zero();
+        }
+        let style = self.item_style();
+        let margin_top = style.margin.margin_top.resolve(Some(containing_width));
+        let mut margin = CollapsedMargin::new(margin_top.to_px());
+        if self.establishes_formatting_context()
+            || style.border.border_top_width != 0.
+            || style.padding.padding_top != 0.
+        {
+            return margin;
+        }
+        let first_child = self
+            .children
+            .iter()
+            .find(|child| !child.is_out_of_flow() && !child.is_floating());
+        if let Some(child) = first_child {
+            margin.adjoin_assign(&child.speculative_start_margin(containing_width));
+        }
+        margin
+    }
+
+    /// Move the box and all descendants.
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        if dx == 0. && dy == 0. {
+            return;
+        }
+        {
+            let mut d = self.dimensions.borrow_mut();
+            d.content.x += dx;
+            d.content.y += dy;
+        }
+        for child in &mut self.children {
+            child.translate(dx, dy);
         }
     }
 
+    /// Return `height`, `min-height` and `max-height` of the content box,
+    /// where `height` is `None` when it is `auto`.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#the-height-property
+    fn block_height_constraints(
+        &self,
+        containing_block_height: Option<f32>,
+    ) -> (Option<f32>, f32, Option<f32>) {
+        let style = self.item_style();
+        let edges = {
+            let d = self.dimensions.borrow();
+            d.border.top + d.border.bottom + d.padding.top + d.padding.bottom
+        };
+
+        // A percentage is treated as `auto` when the containing block height is not definite.
+        let height = match style.box_.height.resolve(containing_block_height) {
+            LengthOrAuto::Length(height) => Some(self.to_content_size(height, edges)),
+            _ => None,
+        };
+        // Percentages of `min-height` and `max-height` are treated as `0` and `none` likewise.
+        let min_height = style
+            .box_
+            .min_height
+            .and_then(|min_height| min_height.resolve(containing_block_height))
+            .map_or(0., |min_height| self.to_content_size(min_height, edges));
+        let max_height = style
+            .box_
+            .max_height
+            .and_then(|max_height| max_height.resolve(containing_block_height))
+            .map(|max_height| self.to_content_size(max_height, edges));
+
+        (height, min_height, max_height)
+    }
+
+    /// `content.height` is the height of laid out children, which is used when `height` is `auto`.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#min-max-heights
+    fn calculate_block_height(
+        &mut self,
+        height: Option<f32>,
+        min_height: f32,
+        max_height: Option<f32>,
+    ) {
+        let mut d = self.dimensions.borrow_mut();
+        d.content.height = clamp_size(height.unwrap_or(d.content.height), min_height, max_height);
+    }
+
     /// When a anonymous box has some node, this node will be placed horizontally.
     fn get_inline_container(&mut self) -> &mut LayoutBox<'a> {
         match self.box_type {
             BoxType::TextNode(_) | BoxType::InlineNode(_) | BoxType::AnonymousBlock => self,
-            BoxType::BlockNode(_) => {
+            BoxType::BlockNode(_)
+            | BoxType::InlineBlockNode(_)
+            | BoxType::TableWrapper
+            | BoxType::AnonymousTableBox(_) => {
                 match self.children.last() {
                     Some(&LayoutBox {
                         box_type: BoxType::AnonymousBlock,
@@ -359,38 +883,61 @@ impl<'a> LayoutBox<'a> {
         }
     }
 
-    fn assign_vertical_margin_box(&self) {
-        let node = self.get_style_node();
+    /// Whether the box is placed in a block formatting context, not in lines.
+    fn is_block_level(&self) -> bool {
+        matches!(self.box_type, BoxType::BlockNode(_) | BoxType::TableWrapper)
+    }
+
+    /// A float after inline content is pushed to the anonymous block which has the content,
+    /// so that it is placed in the line where it occurs.
+    fn push_block_level_box(&mut self, layout_box: LayoutBox<'a>) {
+        match self.children.last_mut() {
+            Some(last)
+                if layout_box.is_floating() && matches!(last.box_type, BoxType::AnonymousBlock) =>
+            {
+                last.children.push(layout_box)
+            }
+            _ => self.children.push(layout_box),
+        }
+    }
+
+    /// Percentage margins are relative to `containing_width` in both axes.
+    fn assign_vertical_margin_box(&self, containing_width: f32) {
+        let style = self.item_style();
         let mut d = self.dimensions.borrow_mut();
-        let zero = Value::Length(0.0, Unit::Px);
 
-        d.margin.top = node.lookup("margin-top", "margin", &zero).to_px();
-        d.margin.bottom = node.lookup("margin-bottom", "margin", &zero).to_px();
+        let containing_width = Some(containing_width);
+        d.margin.top = style.margin.margin_top.resolve(containing_width).to_px();
+        d.margin.bottom = style.margin.margin_bottom.resolve(containing_width).to_px();
 
-        d.border.top = node.lookup("border-top-width", "border", &zero).to_px();
-        d.border.bottom = node.lookup("border-bottom-width", "border", &zero).to_px();
+        d.border.top = style.border.border_top_width;
+        d.border.bottom = style.border.border_bottom_width;
 
-        d.padding.top = node.lookup("padding-top", "padding", &zero).to_px();
-        d.padding.bottom = node.lookup("padding-bottom", "padding", &zero).to_px();
+        d.padding.top = style.padding.padding_top;
+        d.padding.bottom = style.padding.padding_bottom;
     }
 
-    fn assign_horizontal_margin_box(&self) {
-        let node = self.get_style_node();
+    fn assign_horizontal_margin_box(&self, containing_width: f32) {
+        let style = self.item_style();
         let mut d = self.dimensions.borrow_mut();
-        let zero = Value::Length(0.0, Unit::Px);
 
-        d.margin.left = node.lookup("margin-left", "margin", &zero).to_px();
-        d.margin.right = node.lookup("margin-right", "margin", &zero).to_px();
+        let containing_width = Some(containing_width);
+        d.margin.left = style.margin.margin_left.resolve(containing_width).to_px();
+        d.margin.right = style.margin.margin_right.resolve(containing_width).to_px();
 
-        d.border.left = node.lookup("border-left-width", "border", &zero).to_px();
-        d.border.right = node.lookup("border-right-width", "border", &zero).to_px();
+        d.border.left = style.border.border_left_width;
+        d.border.right = style.border.border_right_width;
 
-        d.padding.left = node.lookup("padding-left", "padding", &zero).to_px();
-        d.padding.right = node.lookup("padding-right", "padding", &zero).to_px();
+        d.padding.left = style.padding.padding_left;
+        d.padding.right = style.padding.padding_right;
     }
 
     fn reset_all_edge_left(&mut self) -> f32 {
-        if self.is_splitted {
+        // An atomic inline-level box and an out-of-flow box are not split.
+        if self.is_splitted
+            || self.is_block_level()
+            || matches!(self.box_type, BoxType::InlineBlockNode(_))
+        {
             return 0.;
         }
         self.is_splitted = true;
@@ -410,6 +957,9 @@ impl<'a> LayoutBox<'a> {
     }
 
     fn reset_all_edge_right(&mut self) -> f32 {
+        if self.is_block_level() || matches!(self.box_type, BoxType::InlineBlockNode(_)) {
+            return 0.;
+        }
         let mut d = self.dimensions.borrow_mut();
         let right = d.margin_right_offset();
 
@@ -426,27 +976,50 @@ impl<'a> LayoutBox<'a> {
     }
 }
 
+/// `max` wins over `size`, and `min` wins over `max`.
+fn clamp_size(size: f32, min: f32, max: Option<f32>) -> f32 {
+    let size = max.map_or(size, |max| size.min(max));
+    size.max(min)
+}
+
 #[derive(Debug, Clone)]
 pub enum BoxType<'a> {
     BlockNode(&'a StyledNode<'a>),
     InlineNode(&'a StyledNode<'a>),
+    /// An atomic inline-level box, which is a block container.
+    InlineBlockNode(&'a StyledNode<'a>),
     TextNode(TextNode<'a>),
     AnonymousBlock,
+    /// An anonymous box which contains a table box and its captions,
+    /// and has margins and the position of the table.
+    TableWrapper,
+    /// An anonymous table, row or cell, which is generated for a missing part of a table.
+    AnonymousTableBox(Display),
 }
 
 pub fn layout_tree<'a>(
     node: &'a StyledNode<'a>,
     containing_block: Rc<RefCell<Dimensions>>,
 ) -> LayoutBox<'a> {
+    // The initial containing block is used for percentage heights of the root box,
+    // and absolutely positioned boxes which have no positioned ancestor.
+    let initial_containing_block = containing_block.borrow().content.clone();
+    let initial_height = initial_containing_block.height;
     // The layout algorithm expects the container height to start at 0.
-    // TODO: Save the initial containing block height, for calculating percent heights.
     containing_block.borrow_mut().content.height = 0.0;
 
     let mut root_box = with_thread_local_font_context(|font_context| {
         let mut last_whitespace = false;
         build_layout_tree(node, None, font_context, &mut last_whitespace, &mut None).unwrap()
     });
-    root_box.layout(containing_block);
+    let space = (
+        initial_containing_block.x,
+        initial_containing_block.x + initial_containing_block.width,
+    );
+    let margins = root_box.layout_block(containing_block, Some(initial_height), None, space);
+    root_box.translate(0., margins.start.solve());
+    // Fixed boxes are laid out at the scroll origin.
+    root_box.layout_positioned_descendants(&initial_containing_block, &initial_containing_block);
     root_box
 }
 
@@ -459,12 +1032,30 @@ pub fn build_layout_tree<'a>(
 ) -> Option<LayoutBox<'a>> {
     let mut root = {
         let box_type = match style_node.display() {
-            Display::Block => {
+            Display::Block
+            | Display::FlowRoot
+            | Display::Flex
+            | Display::Grid
+            | Display::Table
+            | Display::TableRowGroup
+            | Display::TableHeaderGroup
+            | Display::TableFooterGroup
+            | Display::TableRow
+            | Display::TableColumnGroup
+            | Display::TableColumn
+            | Display::TableCell
+            | Display::TableCaption => {
                 *last_whitespace = false;
                 // Reset breaker because BlockNode make new line
                 *breaker = None;
                 BoxType::BlockNode(style_node)
             }
+            Display::InlineBlock => {
+                // Contents of the inline-block are in new lines.
+                *last_whitespace = false;
+                *breaker = None;
+                BoxType::InlineBlockNode(style_node)
+            }
             Display::Inline => match &style_node.node.node_type {
                 NodeType::Element(_) => BoxType::InlineNode(style_node),
                 NodeType::Text(_) => {
@@ -493,7 +1084,19 @@ pub fn build_layout_tree<'a>(
     {
         for child in &style_node.children {
             match child.display() {
-                Display::Block => {
+                Display::Block
+                | Display::FlowRoot
+                | Display::Flex
+                | Display::Grid
+                | Display::Table
+                | Display::TableRowGroup
+                | Display::TableHeaderGroup
+                | Display::TableFooterGroup
+                | Display::TableRow
+                | Display::TableColumnGroup
+                | Display::TableColumn
+                | Display::TableCell
+                | Display::TableCaption => {
                     if let Some(layout_box) = build_layout_tree(
                         child,
                         Some(&mut root),
@@ -501,10 +1104,10 @@ pub fn build_layout_tree<'a>(
                         last_whitespace,
                         breaker,
                     ) {
-                        root.children.push(layout_box);
+                        root.push_block_level_box(layout_box);
                     }
                 }
-                Display::Inline => {
+                Display::Inline | Display::InlineBlock => {
                     if let Some(layout_box) = build_layout_tree(
                         child,
                         Some(&mut root),
@@ -512,7 +1115,13 @@ pub fn build_layout_tree<'a>(
                         last_whitespace,
                         breaker,
                     ) {
-                        root.get_inline_container().children.push(layout_box);
+                        for layout_box in split_inline_around_blocks(layout_box) {
+                            if layout_box.is_block_level() {
+                                root.push_block_level_box(layout_box);
+                            } else {
+                                root.get_inline_container().children.push(layout_box);
+                            }
+                        }
                     }
                 }
                 Display::None => {}
@@ -520,16 +1129,69 @@ pub fn build_layout_tree<'a>(
         }
     }
 
-    Some(root)
+    Some(root.fix_table_structure())
+}
+
+/// Split an inline box around block boxes in its children, so that the block boxes become
+/// siblings of fragments of the inline box. Fragments which have no children are kept,
+/// because they have the edges of the inline box.
+/// Inline boxes in the children are already split, so that only the children are searched.
+/// Absolutely positioned boxes and floats do not split the inline box, and are kept in place.
+// refer: https://www.w3.org/TR/CSS21/visuren.html#anonymous-block-level
+fn split_inline_around_blocks<'a>(mut layout_box: LayoutBox<'a>) -> Vec<LayoutBox<'a>> {
+    let is_in_flow_block = |child: &LayoutBox| {
+        child.is_block_level() && !child.is_out_of_flow() && !child.is_floating()
+    };
+    let has_block = layout_box.children.iter().any(is_in_flow_block);
+    if !matches!(layout_box.box_type, BoxType::InlineNode(_)) || !has_block {
+        return vec![layout_box];
+    }
+
+    let mut result = vec![];
+    let mut fragment = LayoutBox::new(layout_box.box_type.clone());
+    fragment.is_continuation = layout_box.is_continuation;
+    for child in mem::take(&mut layout_box.children) {
+        if !is_in_flow_block(&child) {
+            fragment.children.push(child);
+            continue;
+        }
+        let mut next_fragment = LayoutBox::new(layout_box.box_type.clone());
+        next_fragment.is_continuation = true;
+        fragment.has_continuation = true;
+        result.push(mem::replace(&mut fragment, next_fragment));
+        result.push(child);
+    }
+    fragment.has_continuation = layout_box.has_continuation;
+    result.push(fragment);
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cssom::*;
+    use crate::dom::Document;
     use crate::parser::css::*;
     use crate::parser::html::*;
 
+    fn viewport() -> Dimensions {
+        let mut viewport: Dimensions = Default::default();
+        viewport.content.width = 800.0;
+        viewport.content.height = 600.0;
+        viewport
+    }
+
+    /// Lay out `html` with the author stylesheet `css` and the user agent stylesheet `ua_css`.
+    /// The document and styles are leaked, so that they outlive the returned layout tree.
+    fn layout_html(html: &str, ua_css: &str, css: &str) -> LayoutBox<'static> {
+        let dom = Box::leak(Box::new(Document::new(HTMLParser::new(html.into()).run())));
+        let mut rules = CSSParser::new(css.into()).parse_rules(Origin::Author);
+        rules.extend(CSSParser::new(ua_css.into()).parse_rules(Origin::UA));
+        let cssom = Box::leak(Box::new(Stylesheet::new(rules)));
+        let styled_node = Box::leak(Box::new(create_style_tree(dom, cssom)));
+        layout_tree(styled_node, Rc::new(RefCell::new(viewport())))
+    }
+
     #[test]
     fn test_block() {
         let html = "
@@ -565,14 +1227,14 @@ mod tests {
         let mut ua_css_parser = CSSParser::new(ua_css.into());
         let mut css_parser = CSSParser::new(css.into());
 
-        let dom = html_parser.run();
+        let dom = Document::new(html_parser.run());
 
         let ua_rules = ua_css_parser.parse_rules(Origin::UA);
         let mut rules = css_parser.parse_rules(Origin::Author);
         rules.extend(ua_rules);
         let cssom = Stylesheet::new(rules);
 
-        let styled_node = create_style_tree(&dom, &cssom, None);
+        let styled_node = create_style_tree(&dom, &cssom);
 
         let mut viewport: Dimensions = Default::default();
         viewport.content.width = 800.0;
@@ -580,4 +1242,604 @@ mod tests {
 
         layout_tree(&styled_node, Rc::new(RefCell::new(viewport)));
     }
+
+    #[test]
+    fn test_margin_collapse() {
+        let html = "<body><div id='a'><div id='b'></div></div><div id='c'></div><div id='d'></div><div id='e'><div id='f'></div></div></body>";
+        let ua_css = "body, div { display: block; }";
+        let css = "
+    body { margin: 8px; }
+    #a { margin-top: 20px; margin-bottom: 10px; }
+    #b { margin-top: 30px; margin-bottom: 40px; height: 50px; }
+    #c { margin-top: 15px; margin-bottom: -5px; }
+    #d { margin-top: 5px; height: 10px; }
+    #e { margin-top: 10px; padding-top: 1px; }
+    #f { margin-top: 20px; height: 5px; }
+    ";
+
+        let body = layout_html(html, ua_css, css);
+        let border_box = |layout_box: &LayoutBox| layout_box.dimensions.borrow().border_box();
+
+        // Margins of the root box do not collapse.
+        assert_eq!(border_box(&body).y, 8.);
+
+        // The top margin of `#b` collapses with the top margin of its parent,
+        // and the bottom margin collapses with the bottom margin of its parent.
+        let a = &body.children[0];
+        assert_eq!(border_box(a).y, 38.);
+        assert_eq!(border_box(a).height, 50.);
+        assert_eq!(border_box(&a.children[0]).y, 38.);
+
+        // Margins collapse through the empty `#c`, including the negative margin.
+        let c = &body.children[1];
+        let d = &body.children[2];
+        assert_eq!(border_box(c).height, 0.);
+        assert_eq!(border_box(d).y, 123.);
+
+        // Padding separates the margins of the parent and the child.
+        let e = &body.children[3];
+        assert_eq!(border_box(e).y, 143.);
+        assert_eq!(border_box(&e.children[0]).y, 164.);
+        assert_eq!(border_box(e).height, 26.);
+
+        assert_eq!(border_box(&body).height, 161.);
+    }
+
+    #[test]
+    fn test_block_sizing() {
+        let html = "<body><div id='a'><div id='b'></div></div><div id='c'><div id='d'></div></div><div id='e'></div></body>";
+        let ua_css = "body, div { display: block; }";
+        let css = "
+    body { width: 500px; }
+    #a { width: 50%; height: 200px; padding: 10px; border: 5px; box-sizing: border-box; }
+    #b { width: 200%; max-width: 100%; height: 50%; }
+    #c { min-height: 40px; max-width: 100px; margin-left: 10%; }
+    #d { height: 50%; }
+    #e { width: 10px; min-width: 20px; max-width: 15px; }
+    ";
+
+        let body = layout_html(html, ua_css, css);
+        let dimensions = |layout_box: &LayoutBox| layout_box.dimensions.borrow().clone();
+
+        // `border-box` includes paddings and borders.
+        let a = dimensions(&body.children[0]);
+        assert_eq!(a.border_box().width, 250.);
+        assert_eq!(a.border_box().height, 200.);
+        assert_eq!(a.content.width, 220.);
+        assert_eq!(a.content.height, 170.);
+
+        // Percentages are relative to the content box of the containing block.
+        let b = dimensions(&body.children[0].children[0]);
+        assert_eq!(b.content.width, 220.);
+        assert_eq!(b.content.height, 85.);
+
+        // A percentage height is `auto` when the containing block height depends on content.
+        let c = dimensions(&body.children[1]);
+        assert_eq!(c.content.width, 100.);
+        assert_eq!(c.margin.left, 50.);
+        assert_eq!(c.content.height, 40.);
+        assert_eq!(dimensions(&body.children[1].children[0]).content.height, 0.);
+
+        // `min-width` wins over `max-width`.
+        assert_eq!(dimensions(&body.children[2]).content.width, 20.);
+    }
+
+    #[test]
+    fn test_block_in_inline() {
+        let html = "<body><span>a<em>b<div>c</div></em>d</span><div id='empty'><span><div>e</div></span></div><div id='in-place'><span>f<div class='float'></div><div class='abs'></div>g</span></div></body>";
+        let ua_css = "body, div { display: block; }";
+        let css = "
+    span { padding-left: 5px; padding-right: 7px; }
+    .float { float: left; width: 30px; height: 30px; }
+    .abs { position: absolute; }
+    ";
+
+        let body = layout_html(html, ua_css, css);
+
+        // The span is split into two fragments in anonymous blocks around the div.
+        assert_eq!(body.children.len(), 5);
+        assert!(matches!(body.children[0].box_type, BoxType::AnonymousBlock));
+        assert!(matches!(body.children[1].box_type, BoxType::BlockNode(_)));
+        assert!(matches!(body.children[2].box_type, BoxType::AnonymousBlock));
+
+        let first = &body.children[0].children[0];
+        let last = &body.children[2].children[0];
+        assert!(matches!(first.box_type, BoxType::InlineNode(_)));
+        assert!(matches!(last.box_type, BoxType::InlineNode(_)));
+        // The first fragment has the em fragment, which has the text before the div.
+        assert_eq!(first.children.len(), 2);
+        assert!(matches!(first.children[1].box_type, BoxType::InlineNode(_)));
+
+        // Paddings are only on the outer sides of the fragments.
+        let (first, last) = (first.dimensions.borrow(), last.dimensions.borrow());
+        assert_eq!((first.padding.left, first.padding.right), (5., 0.));
+        assert_eq!((last.padding.left, last.padding.right), (0., 7.));
+
+        // The div is placed between the anonymous blocks.
+        let border_box = |layout_box: &LayoutBox| layout_box.dimensions.borrow().border_box();
+        let (before, div, after) = (
+            border_box(&body.children[0]),
+            border_box(&body.children[1]),
+            border_box(&body.children[2]),
+        );
+        assert_eq!(div.y, before.y + before.height);
+        assert_eq!(after.y, div.y + div.height);
+
+        // Fragments which have no children are kept with their edges.
+        let empty = &body.children[3];
+        assert_eq!(empty.children.len(), 3);
+        let (start, end) = (
+            &empty.children[0].children[0],
+            &empty.children[2].children[0],
+        );
+        assert!(start.children.is_empty() && end.children.is_empty());
+        assert_eq!(start.dimensions.borrow().padding.left, 5.);
+        assert_eq!(end.dimensions.borrow().padding.right, 7.);
+
+        // A float and an absolutely positioned box do not split the span.
+        let in_place = &body.children[4];
+        assert_eq!(in_place.children.len(), 1);
+        let span = &in_place.children[0].children[0];
+        assert_eq!(span.children.len(), 4);
+        assert!(span.children[1].is_floating());
+        assert!(span.children[2].is_out_of_flow());
+
+        // The float is placed at the top of the line, and the line is shortened around it.
+        let top = border_box(in_place).y;
+        let float = border_box(&span.children[1]);
+        assert_eq!((float.x, float.y), (0., top));
+        let f = border_box(&span.children[0]);
+        assert_eq!(f.x, 35.);
+        // The static position of the absolutely positioned box is in the line.
+        let abs = border_box(&span.children[2]);
+        assert_eq!((abs.x, abs.y), (f.x + f.width, top));
+    }
+
+    #[test]
+    fn test_positioned() {
+        let html = "<body><div id='rel'></div><div id='container'><div id='static'></div><div id='abs'></div><div id='abs2'></div><div id='table'></div></div><div id='fixed'></div><div id='sticky'></div><div id='tall'></div></body>";
+        let ua_css = "body, div { display: block; }";
+        let css = "
+    #rel { position: relative; top: 10px; left: 5px; height: 20px; }
+    #container { position: relative; height: 200px; padding: 10px; }
+    #static { height: 30px; }
+    #abs { position: absolute; top: 5px; right: 10px; width: 100px; height: 50%; }
+    #abs2 { position: absolute; }
+    #table { display: table; position: absolute; left: 0px; right: 0px; bottom: 5px; width: 100px; height: 30px; margin-left: auto; margin-right: auto; }
+    #fixed { position: fixed; bottom: 0px; left: 0px; width: 50px; height: 40px; }
+    #sticky { position: sticky; top: 0px; height: 10px; }
+    #tall { height: 1000px; }
+    ";
+
+        let scrollport = viewport().content;
+        let mut body = layout_html(html, ua_css, css);
+        let border_box = |layout_box: &LayoutBox| layout_box.dimensions.borrow().border_box();
+
+        // A relative offset does not move following boxes.
+        let rel = border_box(&body.children[0]);
+        assert_eq!((rel.x, rel.y), (5., 10.));
+        let container = &body.children[1];
+        assert_eq!(border_box(container).y, 20.);
+
+        // The containing block is the padding box of the positioned ancestor.
+        let abs = border_box(&container.children[1]);
+        assert_eq!(
+            (abs.x, abs.y, abs.width, abs.height),
+            (690., 25., 100., 110.)
+        );
+
+        // `auto` offsets are the static position.
+        let abs2 = border_box(&container.children[2]);
+        assert_eq!((abs2.x, abs2.y), (10., 60.));
+
+        // The size of an absolutely positioned table is given by the table,
+        // and `auto` margins center it between the offsets.
+        let table = border_box(&container.children[3]);
+        assert_eq!(
+            (table.x, table.y, table.width, table.height),
+            (350., 205., 100., 30.)
+        );
+
+        // Absolutely positioned boxes are out of the flow.
+        let fixed = border_box(&body.children[2]);
+        assert_eq!((fixed.x, fixed.y), (0., 560.));
+        assert_eq!(border_box(&body.children[3]).y, 240.);
+
+        body.scroll_to(&scrollport, 0., 300.);
+        assert_eq!(border_box(&body.children[2]).y, 860.);
+        assert_eq!(border_box(&body.children[3]).y, 300.);
+        assert_eq!(border_box(&body.children[4]).y, 250.);
+
+        body.scroll_to(&scrollport, 0., 0.);
+        assert_eq!(border_box(&body.children[2]).y, 560.);
+        assert_eq!(border_box(&body.children[3]).y, 240.);
+    }
+
+    #[test]
+    fn test_floats() {
+        let html = "<body><div id='left'></div><div id='right'></div><div id='root'></div><div id='half'></div><div id='text'>a</div><div id='clear'></div><div id='para'>a<span id='inner'></span>b</div></body>";
+        let ua_css = "body, div { display: block; }";
+        let css = "
+    #left { float: left; width: 100px; height: 50px; }
+    #right { float: right; width: 200px; height: 80px; margin-top: 10px; }
+    #root { display: flow-root; height: 10px; }
+    #half { display: flow-root; width: 50%; height: 10px; }
+    #clear { clear: both; }
+    #inner { float: left; width: 30px; height: 30px; }
+    ";
+
+        let body = layout_html(html, ua_css, css);
+        let border_box = |layout_box: &LayoutBox| layout_box.dimensions.borrow().border_box();
+
+        let left = border_box(&body.children[0]);
+        assert_eq!(
+            (left.x, left.y, left.width, left.height),
+            (0., 0., 100., 50.)
+        );
+        let right = border_box(&body.children[1]);
+        assert_eq!(
+            (right.x, right.y, right.width, right.height),
+            (600., 10., 200., 80.)
+        );
+
+        // A block formatting context root does not overlap floats.
+        let root = border_box(&body.children[2]);
+        assert_eq!((root.x, root.y, root.width), (100., 0., 500.));
+        // Percentages are resolved against the containing block, not the space beside floats.
+        let half = border_box(&body.children[3]);
+        assert_eq!((half.x, half.y, half.width), (100., 10., 400.));
+
+        // Line boxes are shortened around floats.
+        let text = &body.children[4];
+        assert_eq!(border_box(text).y, 20.);
+        assert_eq!(border_box(&text.children[0].children[0]).x, 100.);
+
+        // Clearance places the border edge below the floats.
+        assert_eq!(border_box(&body.children[5]).y, 90.);
+
+        // A float in inline content stays in the line where it occurs,
+        // and is placed at the top of the line, which is shortened around it.
+        let para = &body.children[6];
+        assert_eq!(para.children.len(), 1);
+        let line = &para.children[0];
+        assert!(line.children[1].is_floating());
+        let inner = border_box(&line.children[1]);
+        assert_eq!((inner.x, inner.y), (0., 90.));
+        assert_eq!(border_box(&line.children[0]).x, 30.);
+        assert!(border_box(&line.children[2]).x > 30.);
+
+        // The root of a block formatting context contains its floats.
+        assert_eq!(border_box(&body).height, 120.);
+    }
+
+    #[test]
+    fn test_inline_block() {
+        let html = "<body><div id='p'>a<span id='block'><div>b</div></span>c</div><div id='q'>a</div><div id='r'>a<span id='wide'></span></div><div id='s'><div class='float'></div><span id='fill'>aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa aaaa</span></div><div id='t'><div class='float'></div><span id='beside'></span></div><div id='u'>a<span id='empty'></span></div></body>";
+        let ua_css = "body, div { display: block; }";
+        let css = "
+    #block { display: inline-block; width: 100px; padding: 5px; margin-left: 3px; }
+    #wide { display: inline-block; width: 795px; height: 20px; }
+    .float { float: left; width: 100px; height: 30px; }
+    #fill { display: inline-block; }
+    #beside { display: inline-block; width: 750px; height: 10px; }
+    #empty { display: inline-block; width: 10px; height: 20px; margin-bottom: 5px; }
+    ";
+
+        let body = layout_html(html, ua_css, css);
+        let dimensions = |layout_box: &LayoutBox| layout_box.dimensions.borrow().clone();
+
+        let line = &body.children[0].children[0];
+        assert_eq!(line.children.len(), 3);
+        let (a, block, c) = (&line.children[0], &line.children[1], &line.children[2]);
+        assert!(matches!(block.box_type, BoxType::InlineBlockNode(_)));
+
+        // The inline-block is placed in the line as an atom.
+        let (a, c) = (dimensions(a), dimensions(c));
+        let inner_text = dimensions(&block.children[0].children[0].children[0]);
+        let block = dimensions(block);
+        assert_eq!(block.content.width, 100.);
+        let approx_eq = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        assert!(approx_eq(
+            block.content.x,
+            a.content.x + a.content.width + 8.
+        ));
+        assert!(approx_eq(
+            c.content.x,
+            block.border_box().x + block.border_box().width
+        ));
+
+        // The baseline of the inline-block is the baseline of its last line box.
+        assert_eq!(inner_text.content.y, a.content.y);
+        assert_eq!(c.content.y, a.content.y);
+        let line_height = dimensions(&body.children[1]).content.height;
+        assert_eq!(
+            dimensions(&body.children[0]).content.height,
+            line_height + 10.
+        );
+
+        // An inline-block which does not fit is moved to the next line.
+        let wide = dimensions(&body.children[2].children[0].children[1]);
+        assert_eq!(wide.border_box().x, 0.);
+        assert!(wide.border_box().y > dimensions(&body.children[2]).content.y);
+
+        // The width shrinks to fit in the line shortened by a float.
+        let fill = dimensions(&body.children[3].children[1].children[0]).border_box();
+        assert_eq!(fill.x, 100.);
+        assert!(fill.x + fill.width <= 800.);
+
+        // An inline-block which does not fit beside a float is moved below it.
+        let top = dimensions(&body.children[4]).content.y;
+        let beside = dimensions(&body.children[4].children[1].children[0]).border_box();
+        assert_eq!((beside.x, beside.y), (0., top + 30.));
+
+        // The baseline of an inline-block without line boxes is its bottom margin edge.
+        let line = &body.children[5].children[0];
+        let baseline = dimensions(line).content.y + line.baseline_offset.unwrap();
+        let empty = dimensions(&line.children[1]).margin_box();
+        assert_eq!(empty.y + empty.height, baseline);
+    }
+
+    #[test]
+    fn test_flex() {
+        let html = "<body><div id='row'><div id='a'></div><div id='b'></div><div id='c'></div></div><div id='wrap'><div id='f'></div><div id='g'></div><div id='h'></div></div><div id='column'><div id='d'></div><div id='e'></div></div><div id='text'>a <div id='i'></div></div><div id='shrink'><div id='j'><div id='k'></div></div><div id='l'></div></div><div id='stretch'><div id='n'><div id='o'></div></div></div></body>";
+        let ua_css = "body, div { display: block; }";
+        let css = "
+    #row { display: flex; height: 100px; column-gap: 10px; }
+    #a { width: 100px; height: 20px; flex-grow: 1; }
+    #b { width: 100px; flex-grow: 3; max-width: 200px; }
+    #c { width: 50px; height: 30px; align-self: center; }
+    #wrap { display: flex; flex-wrap: wrap; row-gap: 5px; justify-content: space-between; align-items: flex-start; }
+    #wrap > div { width: 300px; height: 10px; }
+    #wrap > #g { order: -1; width: 400px; }
+    #column { display: flex; flex-direction: column-reverse; height: 100px; align-items: flex-end; }
+    #d { width: 50px; height: 20px; }
+    #e { width: 60px; flex-grow: 1; }
+    #text { display: flex; }
+    #i { width: 10px; height: 10px; }
+    #shrink { display: flex; width: 100px; }
+    #k { width: 80px; height: 10px; }
+    #l { width: 100px; height: 10px; }
+    #stretch { display: flex; height: 100px; }
+    #o { height: 50%; }
+    ";
+
+        let body = layout_html(html, ua_css, css);
+        let rect = |layout_box: &LayoutBox| {
+            let r = layout_box.dimensions.borrow().border_box();
+            (r.x, r.y, r.width, r.height)
+        };
+
+        // Free space is distributed by `flex-grow`, and an item is frozen at its max size.
+        let row = &body.children[0];
+        assert_eq!(rect(&row.children[0]), (0., 0., 530., 20.));
+        // An item without the cross size is stretched to the line.
+        assert_eq!(rect(&row.children[1]), (540., 0., 200., 100.));
+        assert_eq!(rect(&row.children[2]), (750., 35., 50., 30.));
+
+        // Items are placed in `order`, and wrapped into lines.
+        let wrap = &body.children[1];
+        assert_eq!(rect(&wrap.children[1]), (0., 100., 400., 10.));
+        assert_eq!(rect(&wrap.children[0]), (500., 100., 300., 10.));
+        assert_eq!(rect(&wrap.children[2]), (0., 115., 300., 10.));
+        assert_eq!(rect(wrap).3, 25.);
+
+        let column = &body.children[2];
+        assert_eq!(rect(&column.children[0]), (750., 205., 50., 20.));
+        assert_eq!(rect(&column.children[1]), (740., 125., 60., 80.));
+
+        // Text in a flex container is wrapped in an anonymous flex item.
+        let text = &body.children[3];
+        assert!(matches!(text.children[0].box_type, BoxType::AnonymousBlock));
+        let (x, _, width, _) = rect(&text.children[0]);
+        assert_eq!(x, 0.);
+        assert!(width > 0.);
+        assert_eq!(rect(&text.children[1]).0, width);
+
+        // An item does not shrink below its min-content width with `min-width: auto`.
+        let shrink = &body.children[4];
+        assert_eq!(rect(&shrink.children[0]).2, 80.);
+        assert_eq!(rect(&shrink.children[1]).0, 80.);
+        assert_eq!(rect(&shrink.children[1]).2, 20.);
+
+        // A stretched item is laid out with the definite height, which resolves percentages.
+        let stretch = &body.children[5];
+        assert_eq!(rect(&stretch.children[0]).3, 100.);
+        assert_eq!(rect(&stretch.children[0].children[0]).3, 50.);
+    }
+
+    #[test]
+    fn test_grid() {
+        let html = "<body><div id='tracks'><div></div><div id='b'></div><div id='c'></div><div id='d'></div></div><div id='areas'><div id='h'></div><div id='s'></div><div id='m'></div></div><div id='auto'><div id='w'></div><div id='x'></div><div id='y'></div></div></body>";
+        let ua_css = "body, div { display: block; }";
+        let css = "
+    #tracks { display: grid; grid-template-columns: 100px 1fr minmax(50px, 2fr); grid-template-rows: 40px; grid-auto-rows: 20px; row-gap: 5px; column-gap: 20px; }
+    #b { height: 10px; align-self: end; }
+    #areas { display: grid; grid-template-areas: 'head head' 'side main'; grid-template-columns: 200px auto; grid-template-rows: 50px auto; }
+    #h { grid-area: head; }
+    #s { grid-area: side; height: 30px; }
+    #m { grid-area: main; height: 100px; }
+    #auto { display: grid; grid-template-columns: repeat(auto-fill, 100px); grid-auto-rows: 10px; grid-auto-flow: row dense; justify-items: center; }
+    #w { grid-column: span 7; }
+    #x { grid-column: span 2; }
+    #y { width: 50px; }
+    ";
+
+        let body = layout_html(html, ua_css, css);
+        let rect = |layout_box: &LayoutBox| {
+            let r = layout_box.dimensions.borrow().border_box();
+            (r.x, r.y, r.width, r.height)
+        };
+
+        // Flexible tracks share the space left by fixed tracks and gaps.
+        let tracks = &body.children[0];
+        assert_eq!(rect(&tracks.children[0]), (0., 0., 100., 40.));
+        assert_eq!(rect(&tracks.children[1]), (120., 30., 220., 10.));
+        assert_eq!(rect(&tracks.children[2]), (360., 0., 440., 40.));
+        // An item out of the explicit grid is placed in an implicit row.
+        assert_eq!(rect(&tracks.children[3]), (0., 45., 100., 20.));
+        assert_eq!(rect(tracks).3, 65.);
+
+        // Items are placed in named areas, and an `auto` track fits its items.
+        let areas = &body.children[1];
+        assert_eq!(rect(&areas.children[0]), (0., 65., 800., 50.));
+        assert_eq!(rect(&areas.children[1]), (0., 115., 200., 30.));
+        assert_eq!(rect(&areas.children[2]), (200., 115., 600., 100.));
+        assert_eq!(rect(areas).3, 150.);
+
+        // `auto-fill` repeats tracks in the width, and `dense` fills a hole with a later item.
+        let auto = &body.children[2];
+        assert_eq!(rect(&auto.children[0]), (0., 215., 700., 10.));
+        assert_eq!(rect(&auto.children[1]), (0., 225., 200., 10.));
+        assert_eq!(rect(&auto.children[2]), (725., 215., 50., 10.));
+        assert_eq!(rect(auto).3, 20.);
+    }
+
+    #[test]
+    fn test_table() {
+        let html = "<body><table id='fixed'><caption></caption><tr><td id='a'></td><td></td></tr><tr><td id='c' colspan='2'></td></tr></table><table id='auto'><tr><td id='r' rowspan='2'></td><td><div id='w'></div></td><td id='middle'><div></div></td></tr><tr><td id='m'></td><td id='bottom'><div></div></td></tr></table><table id='collapse'><caption></caption><tr><td id='x'></td><td id='y'></td></tr></table><div id='grid'><div class='cell'></div></div><div id='flex'><div class='cell'></div><div id='next'></div></div></body>";
+        let ua_css = "body, div { display: block; } table { display: table; } caption { display: table-caption; } tr { display: table-row; } td { display: table-cell; }";
+        let css = "
+    #fixed { table-layout: fixed; width: 300px; border-spacing: 10px 5px; }
+    caption { height: 15px; }
+    #a { width: 100px; height: 20px; }
+    #c { height: 30px; }
+    #r { width: 50px; height: 60px; }
+    #w { width: 100px; height: 10px; }
+    #middle { vertical-align: middle; }
+    #m { height: 20px; }
+    #bottom { vertical-align: bottom; }
+    #middle > div, #bottom > div { width: 30px; height: 5px; }
+    #collapse { border-collapse: collapse; border-top-width: 4px; border-right-width: 4px; border-bottom-width: 4px; border-left-width: 4px; caption-side: bottom; }
+    #x { width: 20px; height: 10px; border-right-width: 6px; }
+    #y { width: 30px; height: 10px; }
+    #grid { display: grid; }
+    #flex { display: flex; }
+    .cell { display: table-cell; width: 40px; height: 10px; }
+    #next { width: 10px; height: 10px; }
+    ";
+
+        let body = layout_html(html, ua_css, css);
+        let rect = |layout_box: &LayoutBox| {
+            let r = layout_box.dimensions.borrow().border_box();
+            (r.x, r.y, r.width, r.height)
+        };
+
+        // The table is wrapped with its caption, which is placed above the table.
+        let wrapper = &body.children[0];
+        assert_eq!(rect(wrapper), (0., 0., 300., 80.));
+        assert_eq!(rect(&wrapper.children[0]), (0., 0., 300., 15.));
+        let table = &wrapper.children[1];
+        assert_eq!(rect(table), (0., 15., 300., 65.));
+
+        // The first row fixes widths of columns, and the auto column takes the rest.
+        let first_row = &table.children[0];
+        assert_eq!(rect(first_row), (10., 20., 280., 20.));
+        assert_eq!(rect(&first_row.children[0]), (10., 20., 100., 20.));
+        assert_eq!(rect(&first_row.children[1]), (120., 20., 170., 20.));
+
+        // A cell spanning columns covers them and the spacing between them.
+        let second_row = &table.children[1];
+        assert_eq!(rect(&second_row.children[0]), (10., 45., 280., 30.));
+
+        // Columns fit contents of cells, and a cell spanning rows grows them equally.
+        let auto = &body.children[1].children[0];
+        assert_eq!(rect(auto), (0., 80., 180., 60.));
+        let (first_row, second_row) = (&auto.children[0], &auto.children[1]);
+        assert_eq!(rect(&first_row.children[0]), (0., 80., 50., 60.));
+        assert_eq!(rect(&first_row.children[1]), (50., 80., 100., 25.));
+        assert_eq!(rect(&second_row.children[0]), (50., 105., 100., 35.));
+        // Contents of cells are aligned by `vertical-align` in their rows.
+        let middle = &first_row.children[2];
+        assert_eq!(rect(middle), (150., 80., 30., 25.));
+        assert_eq!(rect(&middle.children[0]), (150., 90., 30., 5.));
+        let bottom = &second_row.children[1];
+        assert_eq!(rect(bottom), (150., 105., 30., 35.));
+        assert_eq!(rect(&bottom.children[0]), (150., 135., 30., 5.));
+
+        // Cells and the table have halves of collapsed borders,
+        // and the outer halves are in the margin of the table.
+        let wrapper = &body.children[2];
+        assert_eq!(rect(wrapper), (0., 140., 68., 37.));
+        let table = &wrapper.children[0];
+        assert_eq!(rect(table), (2., 142., 64., 18.));
+        assert_eq!(table.dimensions.borrow().margin_box().height, 22.);
+        let row = &table.children[0];
+        assert_eq!(rect(&row.children[0]), (4., 144., 25., 14.));
+        assert_eq!(rect(&row.children[1]), (29., 144., 35., 14.));
+        // A caption with `caption-side: bottom` is placed below the table.
+        assert_eq!(rect(&wrapper.children[1]), (0., 162., 68., 15.));
+
+        // Parts of tables in grid and flex containers are wrapped in anonymous tables.
+        let grid = &body.children[3];
+        assert!(matches!(grid.children[0].box_type, BoxType::TableWrapper));
+        let cell = &grid.children[0].children[0].children[0].children[0];
+        assert_eq!(rect(cell), (0., 177., 40., 10.));
+        let flex = &body.children[4];
+        assert!(matches!(flex.children[0].box_type, BoxType::TableWrapper));
+        assert_eq!(rect(&flex.children[0]), (0., 187., 40., 10.));
+        assert_eq!(rect(&flex.children[1]), (40., 187., 10., 10.));
+    }
+
+    #[test]
+    fn test_intrinsic_sizes() {
+        let html = "<body><div id='floats'><div id='float'><span class='a'></span><span class='b'></span></div></div><div id='min'><span class='a'></span><span class='b'></span></div><div id='max'><span class='a'></span><span class='b'></span></div><div id='narrow'><div id='fit'><span class='a'></span><span class='b'></span></div></div><table><tr><td><span class='a'></span></td><td><span class='b'></span></td></tr></table><div id='keywords'><div class='float min'><span class='a'></span><span class='b'></span></div><div class='float max'><span class='a'></span><span class='b'></span></div><div class='float fit'><span class='a'></span><span class='b'></span></div></div><div class='box'><div class='inline min'><span class='a'></span><span class='b'></span></div></div><div class='box'><div class='inline max'><span class='a'></span><span class='b'></span></div></div><div class='box'><div class='inline fit'><span class='a'></span><span class='b'></span></div></div><div id='flex'><div class='min'><span class='a'></span><span class='b'></span></div><div class='max'><span class='a'></span><span class='b'></span></div><div class='fit'><span class='a'></span><span class='b'></span></div></div><div id='grids'><div id='grid-float' class='grid'><div><span class='a'></span><span class='b'></span></div><div><span class='a'></span><span class='b'></span></div></div></div><div id='grid-min' class='grid'><div><span class='a'></span><span class='b'></span></div><div><span class='a'></span><span class='b'></span></div></div></body>";
+        let ua_css = "body, div { display: block; } table { display: table; } tr { display: table-row; } td { display: table-cell; }";
+        let css = "
+    .a { display: inline-block; width: 100px; height: 10px; }
+    .b { display: inline-block; width: 60px; height: 10px; }
+    #floats { display: flow-root; }
+    #float { float: left; }
+    #min { width: min-content; }
+    #max { width: max-content; }
+    #narrow { width: 120px; }
+    #fit { width: fit-content; }
+    .min { width: min-content; }
+    .max { width: max-content; }
+    .fit { width: fit-content; }
+    #keywords { display: flow-root; width: 120px; }
+    .float { float: left; clear: left; }
+    .box { width: 120px; }
+    .inline { display: inline-block; }
+    #flex { display: flex; }
+    #grids { display: flow-root; }
+    .grid { display: grid; grid-template-columns: auto auto; column-gap: 10px; }
+    #grid-float { float: left; }
+    #grid-min { width: min-content; }
+    ";
+
+        let body = layout_html(html, ua_css, css);
+        let width = |layout_box: &LayoutBox| layout_box.dimensions.borrow().border_box().width;
+
+        // A float shrinks to the max-content width.
+        assert_eq!(width(&body.children[0].children[0]), 160.);
+
+        // Atomic inline-level boxes can be broken into lines one by one.
+        assert_eq!(width(&body.children[1]), 100.);
+        assert_eq!(width(&body.children[2]), 160.);
+        assert_eq!(width(&body.children[3].children[0]), 120.);
+
+        // Columns of a table fit contents of cells.
+        let table = &body.children[4].children[0];
+        assert_eq!(width(table), 160.);
+        assert_eq!(width(&table.children[0].children[0]), 100.);
+        assert_eq!(width(&table.children[0].children[1]), 60.);
+
+        // Floats, inline-blocks and flex items follow `min-content`, `max-content`
+        // and `fit-content`, which fits in the available width.
+        let floats = &body.children[5];
+        assert_eq!(width(&floats.children[0]), 100.);
+        assert_eq!(width(&floats.children[1]), 160.);
+        assert_eq!(width(&floats.children[2]), 120.);
+        assert_eq!(width(&body.children[6].children[0].children[0]), 100.);
+        assert_eq!(width(&body.children[7].children[0].children[0]), 160.);
+        assert_eq!(width(&body.children[8].children[0].children[0]), 120.);
+        let flex = &body.children[9];
+        assert_eq!(width(&flex.children[0]), 100.);
+        assert_eq!(width(&flex.children[1]), 160.);
+        assert_eq!(width(&flex.children[2]), 160.);
+
+        // A grid container is as wide as the sum of its columns and gaps.
+        assert_eq!(width(&body.children[10].children[0]), 330.);
+        assert_eq!(width(&body.children[11]), 210.);
+    }
 }
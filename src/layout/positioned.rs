@@ -0,0 +1,478 @@
+// refer: https://www.w3.org/TR/CSS21/visuren.html#positioning-scheme
+
+use super::{clamp_size, BoxType, EdgeSizes, FloatContext, LayoutBox, Rect};
+use crate::computed_values::{LengthOrAuto, Position};
+use crate::style::Display;
+
+/// Used values of an axis of an absolutely positioned box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AxisSizes {
+    /// Offset of the margin edge from the containing block.
+    start: f32,
+    size: f32,
+    margin_start: f32,
+    margin_end: f32,
+}
+
+/// Values of an axis of an absolutely positioned box, where `None` is `auto`.
+struct AxisConstraints {
+    start: Option<f32>,
+    end: Option<f32>,
+    size: Option<f32>,
+    margin_start: Option<f32>,
+    margin_end: Option<f32>,
+    /// Sum of borders and paddings.
+    edges: f32,
+    containing_size: f32,
+    /// Offset of the margin edge in the normal flow, which is used when `start` and `end` are `auto`.
+    static_start: f32,
+}
+
+impl AxisConstraints {
+    /// Solve `start + margin_start + edges + size + margin_end + end = containing_size`.
+    /// `auto_size` returns the size for `auto` from the available space.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#abs-non-replaced-width
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#abs-non-replaced-height
+    fn solve(&self, size: Option<f32>, auto_size: impl Fn(f32) -> f32) -> AxisSizes {
+        let containing_size = self.containing_size;
+        let margin_start = self.margin_start.unwrap_or(0.);
+        let margin_end = self.margin_end.unwrap_or(0.);
+        let others = margin_start + margin_end + self.edges;
+        let (start, size) = match (self.start, size, self.end) {
+            (Some(start), Some(size), Some(end)) => return self.solve_margins(start, size, end),
+            (None, None, None) => {
+                let start = self.static_start;
+                (start, auto_size(containing_size - start - others))
+            }
+            (None, None, Some(end)) => {
+                let size = auto_size(containing_size - end - others);
+                (containing_size - end - others - size, size)
+            }
+            (None, Some(size), None) => (self.static_start, size),
+            (Some(start), None, None) => (start, auto_size(containing_size - start - others)),
+            (None, Some(size), Some(end)) => (containing_size - end - others - size, size),
+            (Some(start), None, Some(end)) => {
+                (start, (containing_size - start - end - others).max(0.))
+            }
+            (Some(start), Some(size), None) => (start, size),
+        };
+        AxisSizes {
+            start,
+            size,
+            margin_start,
+            margin_end,
+        }
+    }
+
+    /// Solve margins when `start`, `size` and `end` are not `auto`,
+    /// where `auto` margins take the free space.
+    fn solve_margins(&self, start: f32, size: f32, end: f32) -> AxisSizes {
+        let free = self.containing_size - start - end - size - self.edges;
+        let (margin_start, margin_end) = match (self.margin_start, self.margin_end) {
+            (None, None) if free < 0. => (0., free),
+            (None, None) => (free / 2., free / 2.),
+            (None, Some(margin_end)) => (free - margin_end, margin_end),
+            (Some(margin_start), None) => (margin_start, free - margin_start),
+            // Over-constrained, so that `end` is ignored.
+            (Some(margin_start), Some(margin_end)) => (margin_start, margin_end),
+        };
+        AxisSizes {
+            start,
+            size,
+            margin_start,
+            margin_end,
+        }
+    }
+
+    /// Solve with `min` and `max` sizes of the content box.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#min-max-widths
+    fn solve_with_limits(
+        &self,
+        min: f32,
+        max: Option<f32>,
+        auto_size: impl Fn(f32) -> f32,
+    ) -> AxisSizes {
+        let mut sizes = self.solve(self.size, &auto_size);
+        if let Some(max) = max {
+            if sizes.size > max {
+                sizes = self.solve(Some(max), &auto_size);
+            }
+        }
+        if sizes.size < min {
+            sizes = self.solve(Some(min), &auto_size);
+        }
+        sizes
+    }
+}
+
+impl<'a> LayoutBox<'a> {
+    /// `position` of the box, where boxes without a style are `static`.
+    pub fn position(&self) -> Position {
+        match &self.box_type {
+            // The table wrapper box is positioned instead of the table box.
+            BoxType::BlockNode(node) if node.display() == Display::Table => Position::Static,
+            BoxType::BlockNode(node)
+            | BoxType::InlineNode(node)
+            | BoxType::InlineBlockNode(node) => node.style.box_.position,
+            BoxType::TableWrapper => self.item_style().box_.position,
+            BoxType::TextNode(_) | BoxType::AnonymousBlock | BoxType::AnonymousTableBox(_) => {
+                Position::Static
+            }
+        }
+    }
+
+    pub fn is_positioned(&self) -> bool {
+        self.position() != Position::Static
+    }
+
+    /// Whether the box is absolutely positioned, which is taken out of the normal flow.
+    pub fn is_out_of_flow(&self) -> bool {
+        matches!(self.position(), Position::Absolute | Position::Fixed)
+    }
+
+    /// `z-index` of a positioned box, where `auto` is treated as `0`.
+    pub fn z_index(&self) -> i32 {
+        self.specified_z_index().unwrap_or(0)
+    }
+
+    /// Whether the box makes a stacking context, which is a positioned box
+    /// whose `z-index` is not `auto`.
+    pub fn is_stacking_context(&self) -> bool {
+        self.is_positioned() && self.specified_z_index().is_some()
+    }
+
+    /// `z-index` of the box, which is `None` when it is `auto`.
+    fn specified_z_index(&self) -> Option<i32> {
+        match &self.box_type {
+            BoxType::BlockNode(node)
+            | BoxType::InlineNode(node)
+            | BoxType::InlineBlockNode(node) => node.style.box_.z_index,
+            BoxType::TableWrapper => self.item_style().box_.z_index,
+            BoxType::TextNode(_) | BoxType::AnonymousBlock | BoxType::AnonymousTableBox(_) => None,
+        }
+    }
+
+    /// Offset of a relatively positioned box from the position in the normal flow.
+    /// `containing_height` is `None` when the height of the containing block is not definite.
+    // refer: https://www.w3.org/TR/CSS21/visuren.html#relative-positioning
+    pub(super) fn relative_offset(
+        &self,
+        containing_width: f32,
+        containing_height: Option<f32>,
+    ) -> (f32, f32) {
+        if self.position() != Position::Relative {
+            return (0., 0.);
+        }
+        let box_ = &self.item_style().box_;
+        let offset = |start: LengthOrAuto, end: LengthOrAuto, basis: Option<f32>| {
+            match (
+                start.resolve(basis).non_auto(),
+                end.resolve(basis).non_auto(),
+            ) {
+                // `start` wins over `end`.
+                (Some(start), _) => start,
+                (None, Some(end)) => -end,
+                (None, None) => 0.,
+            }
+        };
+        (
+            offset(box_.left, box_.right, Some(containing_width)),
+            offset(box_.top, box_.bottom, containing_height),
+        )
+    }
+
+    /// Apply relative offsets to inline boxes, after lines are laid out.
+    pub(super) fn offset_relative_inline_boxes(&mut self, containing_width: f32) {
+        for child in &mut self.children {
+            // Floats are offset in their own layout, and absolutely positioned boxes are laid out
+            // after the normal flow.
+            if child.is_block_level() {
+                continue;
+            }
+            let (dx, dy) = child.relative_offset(containing_width, None);
+            child.translate(dx, dy);
+            // Descendants of an inline-block are offset in its own layout.
+            if !matches!(child.box_type, BoxType::InlineBlockNode(_)) {
+                child.offset_relative_inline_boxes(containing_width);
+            }
+        }
+    }
+
+    /// Lay out absolutely positioned descendants, after the normal flow is laid out.
+    /// `absolute_containing_block` is the padding box of the nearest positioned ancestor,
+    /// and `fixed_containing_block` is the viewport.
+    pub(super) fn layout_positioned_descendants(
+        &mut self,
+        absolute_containing_block: &Rect,
+        fixed_containing_block: &Rect,
+    ) {
+        let containing_block = if self.is_positioned() {
+            self.dimensions.borrow().padding_box()
+        } else {
+            absolute_containing_block.clone()
+        };
+        for child in &mut self.children {
+            match child.position() {
+                Position::Absolute => child.layout_absolute(&containing_block),
+                Position::Fixed => child.layout_absolute(fixed_containing_block),
+                _ => {}
+            }
+            child.layout_positioned_descendants(&containing_block, fixed_containing_block);
+        }
+    }
+
+    /// Lay out an absolutely positioned box, whose `content` has the static position,
+    /// which is the position of the margin edge in the normal flow.
+    fn layout_absolute(&mut self, containing_block: &Rect) {
+        let (static_x, static_y) = {
+            let d = self.dimensions.borrow();
+            (d.content.x, d.content.y)
+        };
+        if let BoxType::TableWrapper = self.box_type {
+            self.layout_absolute_table(containing_block, (static_x, static_y));
+            return;
+        }
+        self.assign_horizontal_margin_box(containing_block.width);
+        self.assign_vertical_margin_box(containing_block.width);
+
+        let style = self.item_style();
+        let box_ = &style.box_;
+        let margin = &style.margin;
+        let containing_width = Some(containing_block.width);
+        let containing_height = Some(containing_block.height);
+
+        // Horizontal
+        let edges = {
+            let d = self.dimensions.borrow();
+            d.border.left + d.border.right + d.padding.left + d.padding.right
+        };
+        let horizontal = AxisConstraints {
+            start: box_.left.resolve(containing_width).non_auto(),
+            end: box_.right.resolve(containing_width).non_auto(),
+            size: self.resolve_content_width(box_.width.resolve(containing_width), edges, None),
+            margin_start: margin.margin_left.resolve(containing_width).non_auto(),
+            margin_end: margin.margin_right.resolve(containing_width).non_auto(),
+            edges,
+            containing_size: containing_block.width,
+            static_start: static_x - containing_block.x,
+        };
+        let min_width = box_
+            .min_width
+            .and_then(|min_width| min_width.resolve(containing_width))
+            .map_or(0., |min_width| self.to_content_size(min_width, edges));
+        let max_width = box_
+            .max_width
+            .and_then(|max_width| max_width.resolve(containing_width))
+            .map(|max_width| self.to_content_size(max_width, edges));
+        let horizontal = horizontal.solve_with_limits(min_width, max_width, |available| {
+            self.shrink_to_fit_width(available)
+        });
+
+        let (height, min_height, max_height) = self.block_height_constraints(containing_height);
+        {
+            let mut d = self.dimensions.borrow_mut();
+            d.margin.left = horizontal.margin_start;
+            d.margin.right = horizontal.margin_end;
+            d.content.width = horizontal.size;
+            d.content.x = containing_block.x
+                + horizontal.start
+                + horizontal.margin_start
+                + d.border.left
+                + d.padding.left;
+            // Children are laid out from the top of the containing block, and moved later.
+            d.content.y = containing_block.y;
+            d.content.height = 0.;
+        }
+
+        let definite_height = height.map(|height| clamp_size(height, min_height, max_height));
+        let mut floats = FloatContext::new();
+        self.layout_contents(definite_height, false, false, &mut floats);
+        self.include_floats(&floats);
+
+        // Vertical
+        let (edges, content_height) = {
+            let d = self.dimensions.borrow();
+            (
+                d.border.top + d.border.bottom + d.padding.top + d.padding.bottom,
+                d.content.height,
+            )
+        };
+        let vertical = AxisConstraints {
+            start: box_.top.resolve(containing_height).non_auto(),
+            end: box_.bottom.resolve(containing_height).non_auto(),
+            size: height,
+            margin_start: margin.margin_top.resolve(containing_width).non_auto(),
+            margin_end: margin.margin_bottom.resolve(containing_width).non_auto(),
+            edges,
+            containing_size: containing_block.height,
+            static_start: static_y - containing_block.y,
+        };
+        let vertical = vertical.solve_with_limits(min_height, max_height, |_| content_height);
+
+        let dy = {
+            let mut d = self.dimensions.borrow_mut();
+            d.margin.top = vertical.margin_start;
+            d.margin.bottom = vertical.margin_end;
+            d.content.height = vertical.size;
+            containing_block.y
+                + vertical.start
+                + vertical.margin_start
+                + d.border.top
+                + d.padding.top
+                - d.content.y
+        };
+        self.translate(0., dy);
+    }
+
+    /// Lay out an absolutely positioned table wrapper box, whose size is the size of the table
+    /// laid out in the available space, like a replaced element.
+    // refer: https://www.w3.org/TR/CSS21/visudet.html#abs-replaced-width
+    fn layout_absolute_table(&mut self, containing_block: &Rect, (static_x, static_y): (f32, f32)) {
+        let style = self.item_style();
+        let (box_, margin) = (&style.box_, &style.margin);
+        let containing_width = Some(containing_block.width);
+        let containing_height = Some(containing_block.height);
+        let left = box_.left.resolve(containing_width).non_auto();
+        let right = box_.right.resolve(containing_width).non_auto();
+        let start = left.unwrap_or(static_x - containing_block.x);
+        let available_width = containing_block.width - start - right.unwrap_or(0.);
+        self.layout_table_wrapper(containing_block, available_width, containing_height, false);
+
+        let (width, height) = {
+            let d = self.dimensions.borrow();
+            (d.content.width, d.content.height)
+        };
+        let horizontal = AxisConstraints {
+            start: left,
+            end: right,
+            size: Some(width),
+            margin_start: margin.margin_left.resolve(containing_width).non_auto(),
+            margin_end: margin.margin_right.resolve(containing_width).non_auto(),
+            edges: 0.,
+            containing_size: containing_block.width,
+            static_start: static_x - containing_block.x,
+        }
+        .solve(Some(width), |_| width);
+        let vertical = AxisConstraints {
+            start: box_.top.resolve(containing_height).non_auto(),
+            end: box_.bottom.resolve(containing_height).non_auto(),
+            size: Some(height),
+            margin_start: margin.margin_top.resolve(containing_width).non_auto(),
+            margin_end: margin.margin_bottom.resolve(containing_width).non_auto(),
+            edges: 0.,
+            containing_size: containing_block.height,
+            static_start: static_y - containing_block.y,
+        }
+        .solve(Some(height), |_| height);
+
+        let (dx, dy) = {
+            let mut d = self.dimensions.borrow_mut();
+            d.margin = EdgeSizes {
+                left: horizontal.margin_start,
+                right: horizontal.margin_end,
+                top: vertical.margin_start,
+                bottom: vertical.margin_end,
+            };
+            (
+                containing_block.x + horizontal.start + horizontal.margin_start - d.content.x,
+                containing_block.y + vertical.start + vertical.margin_start - d.content.y,
+            )
+        };
+        self.translate(dx, dy);
+    }
+
+    /// Move fixed and sticky boxes for the scroll position of `viewport`,
+    /// which is the scrollport at the scroll origin.
+    /// Offsets applied for the previous scroll position are replaced.
+    pub fn scroll_to(&mut self, viewport: &Rect, scroll_x: f32, scroll_y: f32) {
+        let containing_block = self.dimensions.borrow().content.clone();
+        for child in &mut self.children {
+            let offset = match child.position() {
+                Position::Fixed => (scroll_x, scroll_y),
+                Position::Sticky => {
+                    child.sticky_offset(&containing_block, viewport, scroll_x, scroll_y)
+                }
+                _ => {
+                    child.scroll_to(viewport, scroll_x, scroll_y);
+                    continue;
+                }
+            };
+            child.translate(
+                offset.0 - child.scroll_offset.0,
+                offset.1 - child.scroll_offset.1,
+            );
+            child.scroll_offset = offset;
+            // Fixed descendants of a fixed box are moved together.
+            if child.position() == Position::Sticky {
+                child.scroll_to(viewport, scroll_x, scroll_y);
+            }
+        }
+    }
+
+    /// Offset of a sticky box, which keeps the box in the scrollport within `containing_block`.
+    // refer: https://drafts.csswg.org/css-position-3/#stickypos-insets
+    fn sticky_offset(
+        &self,
+        containing_block: &Rect,
+        viewport: &Rect,
+        scroll_x: f32,
+        scroll_y: f32,
+    ) -> (f32, f32) {
+        let box_ = &self.item_style().box_;
+        let (mut border_box, mut margin_box) = {
+            let d = self.dimensions.borrow();
+            (d.border_box(), d.margin_box())
+        };
+        // Position in the normal flow.
+        border_box.x -= self.scroll_offset.0;
+        border_box.y -= self.scroll_offset.1;
+        margin_box.x -= self.scroll_offset.0;
+        margin_box.y -= self.scroll_offset.1;
+
+        (
+            sticky_axis_offset(
+                (box_.left, box_.right),
+                (viewport.x + scroll_x, viewport.width),
+                (border_box.x, border_box.width),
+                (margin_box.x, margin_box.width),
+                (containing_block.x, containing_block.width),
+            ),
+            sticky_axis_offset(
+                (box_.top, box_.bottom),
+                (viewport.y + scroll_y, viewport.height),
+                (border_box.y, border_box.height),
+                (margin_box.y, margin_box.height),
+                (containing_block.y, containing_block.height),
+            ),
+        )
+    }
+}
+
+/// Offset of a sticky box in an axis, where each pair is the start position and the size.
+/// The border box is kept within the insets from the scrollport,
+/// and the margin box is kept within the containing block.
+fn sticky_axis_offset(
+    (start, end): (LengthOrAuto, LengthOrAuto),
+    (scrollport_start, scrollport_size): (f32, f32),
+    (position, size): (f32, f32),
+    (margin_position, margin_size): (f32, f32),
+    (containing_start, containing_size): (f32, f32),
+) -> f32 {
+    let basis = Some(scrollport_size);
+    // `start` wins over `end`.
+    if let Some(start) = start.resolve(basis).non_auto() {
+        let limit = scrollport_start + start;
+        if position < limit {
+            let max = containing_start + containing_size - (margin_position + margin_size);
+            return (limit - position).min(max).max(0.);
+        }
+    }
+    if let Some(end) = end.resolve(basis).non_auto() {
+        let limit = scrollport_start + scrollport_size - end;
+        if position + size > limit {
+            let min = containing_start - margin_position;
+            return (limit - position - size).max(min).min(0.);
+        }
+    }
+    0.
+}
@@ -0,0 +1,999 @@
+// refer: https://www.w3.org/TR/CSS21/tables.html
+
+use std::cell::RefCell;
+use std::iter;
+use std::mem;
+use std::ops::Range;
+use std::rc::Rc;
+
+use super::grid::span;
+use super::{clamp_size, BoxType, Dimensions, EdgeSizes, FloatContext, LayoutBox, Rect};
+use crate::computed_values::{
+    BorderCollapse, BorderStruct, BoxSizing, CaptionSide, LengthOrAuto, PaddingStruct, TableLayout,
+    VerticalAlign,
+};
+use crate::style::Display;
+
+/// Indices of a row group in the table and a row in the group,
+/// where a row directly in the table has no group.
+type RowPath = (Option<usize>, usize);
+
+/// A cell placed in slots of the table grid.
+struct PlacedCell {
+    row: RowPath,
+    /// Index in children of the row
+    index: usize,
+    rows: Range<usize>,
+    columns: Range<usize>,
+    /// Used borders, which are halves of collapsed borders in the collapsing border model.
+    border: EdgeSizes,
+    /// Sum of borders and paddings in the horizontal axis.
+    horizontal_edges: f32,
+    /// Height of the border box which fits the content.
+    height: f32,
+    /// Offset of the baseline from the top of the border box.
+    baseline: f32,
+    vertical_align: VerticalAlign,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Column {
+    min: f32,
+    max: f32,
+    /// Width specified by a column element, or by a cell in the first row in the fixed layout.
+    width: Option<f32>,
+}
+
+fn border_edges(border: &BorderStruct) -> EdgeSizes {
+    EdgeSizes {
+        left: border.border_left_width,
+        right: border.border_right_width,
+        top: border.border_top_width,
+        bottom: border.border_bottom_width,
+    }
+}
+
+fn padding_edges(padding: &PaddingStruct) -> EdgeSizes {
+    EdgeSizes {
+        left: padding.padding_left,
+        right: padding.padding_right,
+        top: padding.padding_top,
+        bottom: padding.padding_bottom,
+    }
+}
+
+fn half_of_widest(edges: impl Iterator<Item = f32>) -> f32 {
+    edges.fold(0., f32::max) / 2.
+}
+
+/// Spacing around `count` columns or rows, which is also before the first and after the last.
+fn total_spacing(count: usize, spacing: f32) -> f32 {
+    match count {
+        0 => 0.,
+        count => spacing * (count + 1) as f32,
+    }
+}
+
+fn has_table_display<'a>(display: Display) -> impl Fn(&LayoutBox<'a>) -> bool {
+    move |layout_box| layout_box.table_display() == Some(display)
+}
+
+/// Generate an anonymous table, row or cell which contains `children`.
+fn anonymous_table_box(display: Display, children: Vec<LayoutBox>) -> LayoutBox {
+    let mut layout_box = LayoutBox::new(BoxType::AnonymousTableBox(display));
+    layout_box.children = children;
+    layout_box.fix_table_structure()
+}
+
+/// Wrap each run of consecutive children which are not `is_proper` in a box made by `wrap`.
+/// A run which has only collapsible white space is dropped.
+fn wrap_improper_runs<'a>(
+    children: Vec<LayoutBox<'a>>,
+    is_proper: impl Fn(&LayoutBox<'a>) -> bool,
+    wrap: impl Fn(Vec<LayoutBox<'a>>) -> LayoutBox<'a>,
+) -> Vec<LayoutBox<'a>> {
+    let mut result = vec![];
+    let mut run = vec![];
+    let flush = |run: Vec<LayoutBox<'a>>, result: &mut Vec<LayoutBox<'a>>| {
+        if !run.iter().all(LayoutBox::is_collapsible_whitespace) {
+            result.push(wrap(run));
+        }
+    };
+    for child in children {
+        if is_proper(&child) {
+            flush(mem::take(&mut run), &mut result);
+            result.push(child);
+        } else {
+            run.push(child);
+        }
+    }
+    flush(run, &mut result);
+    result
+}
+
+/// Wrap each run of consecutive parts of tables out of tables in an anonymous table,
+/// where collapsible white space between them is dropped.
+fn wrap_table_parts(children: Vec<LayoutBox>) -> Vec<LayoutBox> {
+    let mut result = vec![];
+    let mut run = vec![];
+    // Collapsible white space after the run, which is kept when the run ends.
+    let mut whitespace = vec![];
+    for child in children {
+        if child.table_display().is_some() {
+            whitespace.clear();
+            run.push(child);
+        } else if !run.is_empty() && child.is_collapsible_whitespace() {
+            whitespace.push(child);
+        } else {
+            if !run.is_empty() {
+                result.push(anonymous_table_box(Display::Table, mem::take(&mut run)));
+            }
+            result.append(&mut whitespace);
+            result.push(child);
+        }
+    }
+    if !run.is_empty() {
+        result.push(anonymous_table_box(Display::Table, run));
+    }
+    result.append(&mut whitespace);
+    result
+}
+
+/// Widths of columns which fill `width`, between their min and max widths when possible.
+/// Width over the max widths goes to columns without specified widths in proportion to
+/// their max widths, or to all columns when every column has a specified width.
+// refer: https://www.w3.org/TR/CSS21/tables.html#auto-table-layout
+fn distribute_column_widths(columns: &[Column], width: f32) -> Vec<f32> {
+    let min: f32 = columns.iter().map(|column| column.min).sum();
+    let max: f32 = columns.iter().map(|column| column.max).sum();
+    if width <= min {
+        return columns.iter().map(|column| column.min).collect();
+    }
+    if width <= max {
+        let ratio = (width - min) / (max - min);
+        return columns
+            .iter()
+            .map(|column| column.min + (column.max - column.min) * ratio)
+            .collect();
+    }
+
+    let has_auto_column = columns.iter().any(|column| column.width.is_none());
+    let is_growing = |column: &Column| !has_auto_column || column.width.is_none();
+    let growing_max: f32 = columns
+        .iter()
+        .filter(|column| is_growing(column))
+        .map(|column| column.max)
+        .sum();
+    let growing_count = columns.iter().filter(|column| is_growing(column)).count() as f32;
+    let extra = width - max;
+    columns
+        .iter()
+        .map(|column| {
+            if !is_growing(column) {
+                column.max
+            } else if growing_max > 0. {
+                column.max + extra * column.max / growing_max
+            } else {
+                column.max + extra / growing_count
+            }
+        })
+        .collect()
+}
+
+impl<'a> LayoutBox<'a> {
+    /// `display` of a table or a part of a table, or `None` for other boxes.
+    pub(super) fn table_display(&self) -> Option<Display> {
+        let display = match &self.box_type {
+            BoxType::BlockNode(node) => node.display(),
+            BoxType::AnonymousTableBox(display) => *display,
+            _ => return None,
+        };
+        if display == Display::Table || display.is_table_internal() {
+            Some(display)
+        } else {
+            None
+        }
+    }
+
+    /// The table box in a table wrapper box.
+    pub(super) fn wrapped_table(&self) -> &LayoutBox<'a> {
+        self.children
+            .iter()
+            .find(|child| child.table_display() == Some(Display::Table))
+            .expect("Table wrapper box has no table box")
+    }
+
+    /// Generate anonymous boxes for missing parts of tables in children of the box,
+    /// and return the box, which is wrapped in a table wrapper box with its captions
+    /// when it is a table.
+    // refer: https://www.w3.org/TR/CSS21/tables.html#anonymous-boxes
+    pub(super) fn fix_table_structure(mut self) -> LayoutBox<'a> {
+        let children = mem::take(&mut self.children);
+        let display = self.table_display();
+        let anonymous_row = |run| anonymous_table_box(Display::TableRow, run);
+        self.children = match display {
+            Some(Display::Table) => wrap_improper_runs(
+                children,
+                |child| matches!(child.table_display(), Some(part) if part != Display::TableCell),
+                anonymous_row,
+            ),
+            Some(display) if display.is_table_row_group() => wrap_improper_runs(
+                children,
+                has_table_display(Display::TableRow),
+                anonymous_row,
+            ),
+            Some(Display::TableRow) => {
+                wrap_improper_runs(children, has_table_display(Display::TableCell), |run| {
+                    anonymous_table_box(Display::TableCell, run)
+                })
+            }
+            Some(Display::TableColumnGroup) => children
+                .into_iter()
+                .filter(has_table_display(Display::TableColumn))
+                .collect(),
+            Some(Display::TableColumn) => vec![],
+            _ => wrap_table_parts(children),
+        };
+        if display != Some(Display::Table) {
+            return self;
+        }
+
+        // Captions are placed before or after the table in the wrapper box.
+        // refer: https://www.w3.org/TR/CSS21/tables.html#caption-position
+        let (captions, children): (Vec<_>, Vec<_>) = mem::take(&mut self.children)
+            .into_iter()
+            .partition(has_table_display(Display::TableCaption));
+        self.children = children;
+        let (top, bottom): (Vec<_>, Vec<_>) = captions.into_iter().partition(|caption| {
+            caption.item_style().inherited_table.caption_side == CaptionSide::Top
+        });
+        let mut wrapper = LayoutBox::new(BoxType::TableWrapper);
+        wrapper.children = top;
+        wrapper.children.push(self);
+        wrapper.children.extend(bottom);
+        wrapper
+    }
+
+    /// Value of an attribute such as `colspan`, which is `None` when it is missing or invalid.
+    fn span_attribute(&self, name: &str) -> Option<usize> {
+        match &self.box_type {
+            BoxType::BlockNode(node) => node
+                .node
+                .as_element()?
+                .attributes
+                .get(name)?
+                .trim()
+                .parse()
+                .ok(),
+            _ => None,
+        }
+    }
+
+    /// Rows of a table in the visual order, where the first header group is placed before
+    /// other rows, and the first footer group is placed after them.
+    fn table_rows(&self) -> Vec<RowPath> {
+        let position = |display| {
+            self.children
+                .iter()
+                .position(|child| child.table_display() == Some(display))
+        };
+        let header = position(Display::TableHeaderGroup);
+        let footer = position(Display::TableFooterGroup);
+        let body = (0..self.children.len())
+            .filter(|&index| Some(index) != header && Some(index) != footer);
+
+        header
+            .into_iter()
+            .chain(body)
+            .chain(footer)
+            .flat_map(|index| {
+                let child = &self.children[index];
+                match child.table_display() {
+                    Some(Display::TableRow) => vec![(None, index)],
+                    Some(display) if display.is_table_row_group() => (0..child.children.len())
+                        .map(|row| (Some(index), row))
+                        .collect(),
+                    _ => vec![],
+                }
+            })
+            .collect()
+    }
+
+    fn table_row(&self, (group, row): RowPath) -> &LayoutBox<'a> {
+        match group {
+            Some(group) => &self.children[group].children[row],
+            None => &self.children[row],
+        }
+    }
+
+    fn table_row_mut(&mut self, (group, row): RowPath) -> &mut LayoutBox<'a> {
+        match group {
+            Some(group) => &mut self.children[group].children[row],
+            None => &mut self.children[row],
+        }
+    }
+
+    fn table_cell(&self, cell: &PlacedCell) -> &LayoutBox<'a> {
+        &self.table_row(cell.row).children[cell.index]
+    }
+
+    fn table_cell_mut(&mut self, cell: &PlacedCell) -> &mut LayoutBox<'a> {
+        &mut self.table_row_mut(cell.row).children[cell.index]
+    }
+
+    /// Place cells in slots of the table grid, where a cell is placed in the first free slot
+    /// of its row, and `rowspan` does not go over the row group.
+    // refer: https://html.spec.whatwg.org/multipage/tables.html#forming-a-table
+    fn place_cells(&self, rows: &[RowPath]) -> Vec<PlacedCell> {
+        let mut occupied: Vec<Vec<bool>> = vec![vec![]; rows.len()];
+        let mut cells = vec![];
+        for (row_index, &row) in rows.iter().enumerate() {
+            let group_end = row_index
+                + rows[row_index..]
+                    .iter()
+                    .take_while(|other| other.0 == row.0)
+                    .count();
+            let mut column = 0;
+            for (index, cell) in self.table_row(row).children.iter().enumerate() {
+                while occupied[row_index].get(column).copied().unwrap_or(false) {
+                    column += 1;
+                }
+                let colspan = cell
+                    .span_attribute("colspan")
+                    .filter(|&colspan| colspan > 0)
+                    .unwrap_or(1)
+                    .min(1000);
+                // `rowspan="0"` spans the rest of the row group.
+                let rowspan = match cell.span_attribute("rowspan").unwrap_or(1) {
+                    0 => group_end - row_index,
+                    rowspan => rowspan.min(group_end - row_index),
+                };
+                let rows = row_index..row_index + rowspan;
+                let columns = column..column + colspan;
+                for slots in &mut occupied[rows.clone()] {
+                    if slots.len() < columns.end {
+                        slots.resize(columns.end, false);
+                    }
+                    for slot in &mut slots[columns.clone()] {
+                        *slot = true;
+                    }
+                }
+                cells.push(PlacedCell {
+                    row,
+                    index,
+                    rows,
+                    columns,
+                    border: EdgeSizes::default(),
+                    horizontal_edges: 0.,
+                    height: 0.,
+                    baseline: 0.,
+                    vertical_align: cell.item_style().box_.vertical_align,
+                });
+                column += colspan;
+            }
+        }
+        cells
+    }
+
+    /// Widths of columns specified by column and column group elements,
+    /// where `span` repeats a column, and percentages are treated as `auto`.
+    fn column_element_widths(&self) -> Vec<Option<f32>> {
+        let width = |column: &LayoutBox| column.item_style().box_.width.non_auto();
+        let repeat = |column: &LayoutBox, width: Option<f32>| {
+            let span = column
+                .span_attribute("span")
+                .filter(|&span| span > 0)
+                .unwrap_or(1)
+                .min(1000);
+            iter::repeat(width).take(span)
+        };
+        let mut widths = vec![];
+        for child in &self.children {
+            match child.table_display() {
+                Some(Display::TableColumn) => widths.extend(repeat(child, width(child))),
+                Some(Display::TableColumnGroup) if child.children.is_empty() => {
+                    widths.extend(repeat(child, width(child)))
+                }
+                Some(Display::TableColumnGroup) => {
+                    for column in &child.children {
+                        widths.extend(repeat(column, width(column).or_else(|| width(child))));
+                    }
+                }
+                _ => {}
+            }
+        }
+        widths
+    }
+
+    /// Resolve borders of cells in the collapsing border model, where the widest border
+    /// of the table and cells wins at each edge, and return borders of the table.
+    /// Cells and the table have halves of the collapsed borders,
+    /// and the other halves of the table borders are in its margin.
+    // refer: https://www.w3.org/TR/CSS21/tables.html#collapsing-borders
+    fn collapse_borders(
+        &self,
+        cells: &mut [PlacedCell],
+        row_count: usize,
+        column_count: usize,
+    ) -> EdgeSizes {
+        let table = border_edges(&self.item_style().border);
+        // Widths of edges above each row and before each column, in each slot.
+        let mut horizontal = vec![vec![0f32; column_count]; row_count + 1];
+        let mut vertical = vec![vec![0f32; column_count + 1]; row_count];
+        for column in 0..column_count {
+            horizontal[0][column] = table.top;
+            horizontal[row_count][column] = table.bottom;
+        }
+        for edges in &mut vertical {
+            edges[0] = table.left;
+            edges[column_count] = table.right;
+        }
+        for cell in cells.iter() {
+            let border = border_edges(&self.table_cell(cell).item_style().border);
+            for column in cell.columns.clone() {
+                let top = &mut horizontal[cell.rows.start][column];
+                *top = top.max(border.top);
+                let bottom = &mut horizontal[cell.rows.end][column];
+                *bottom = bottom.max(border.bottom);
+            }
+            for edges in &mut vertical[cell.rows.clone()] {
+                edges[cell.columns.start] = edges[cell.columns.start].max(border.left);
+                edges[cell.columns.end] = edges[cell.columns.end].max(border.right);
+            }
+        }
+
+        for cell in cells.iter_mut() {
+            let (rows, columns) = (cell.rows.clone(), cell.columns.clone());
+            cell.border = EdgeSizes {
+                top: half_of_widest(horizontal[rows.start][columns.clone()].iter().copied()),
+                bottom: half_of_widest(horizontal[rows.end][columns.clone()].iter().copied()),
+                left: half_of_widest(vertical[rows.clone()].iter().map(|e| e[columns.start])),
+                right: half_of_widest(vertical[rows].iter().map(|e| e[columns.end])),
+            };
+        }
+        EdgeSizes {
+            top: half_of_widest(horizontal[0].iter().copied()),
+            bottom: half_of_widest(horizontal[row_count].iter().copied()),
+            left: half_of_widest(vertical.iter().map(|edges| edges[0])),
+            right: half_of_widest(vertical.iter().map(|edges| edges[column_count])),
+        }
+    }
+
+    /// Outer width of a table cell specified by `width`, whose borders and paddings are `edges`.
+    /// Percentages of cells are treated as `auto`.
+    fn table_cell_outer_width(&self, edges: f32) -> Option<f32> {
+        let box_ = &self.item_style().box_;
+        box_.width.non_auto().map(|width| match box_.box_sizing {
+            BoxSizing::ContentBox => width + edges,
+            BoxSizing::BorderBox => width.max(edges),
+        })
+    }
+
+    /// Outer min-content and max-content widths of a table cell,
+    /// whose borders and paddings are `edges`. A specified width is both widths at least.
+    fn table_cell_width_contributions(&self, edges: f32) -> (f32, f32) {
+        let (min_content, max_content) = self.intrinsic_widths();
+        let (min, max) = match self.item_style().box_.width {
+            LengthOrAuto::MinContent => (min_content, min_content),
+            LengthOrAuto::MaxContent => (max_content, max_content),
+            _ => (min_content, max_content),
+        };
+        let (min, max) = (min + edges, max + edges);
+        match self.table_cell_outer_width(edges) {
+            Some(width) => (width.max(min), width.max(min)),
+            None => (min, max),
+        }
+    }
+
+    /// Lay out a table cell whose content width is `width`, and whose border box is at the origin.
+    fn layout_table_cell(&mut self, width: f32, border: EdgeSizes) {
+        let padding = padding_edges(&self.item_style().padding);
+        {
+            let mut d = self.dimensions.borrow_mut();
+            d.margin = EdgeSizes::default();
+            d.border = border;
+            d.padding = padding;
+            let (x, y) = (d.border_left_offset(), d.border_top_offset());
+            d.content = Rect {
+                x,
+                y,
+                width,
+                height: 0.,
+            };
+        }
+        let mut floats = FloatContext::new();
+        self.layout_contents(None, false, false, &mut floats);
+        self.include_floats(&floats);
+    }
+
+    /// Spacing between borders of adjacent cells, which is `0` in the collapsing border model.
+    fn border_spacing(&self) -> (f32, f32) {
+        let inherited_table = &self.item_style().inherited_table;
+        match inherited_table.border_collapse {
+            BorderCollapse::Collapse => (0., 0.),
+            BorderCollapse::Separate => inherited_table.border_spacing,
+        }
+    }
+
+    /// Place cells in `rows` with their used borders, and return them with columns
+    /// and the border and the padding of the table.
+    fn prepare_table(
+        &self,
+        rows: &[RowPath],
+    ) -> (Vec<PlacedCell>, Vec<Column>, EdgeSizes, EdgeSizes) {
+        let style = self.item_style();
+        let mut cells = self.place_cells(rows);
+        let column_widths = self.column_element_widths();
+        let column_count = cells
+            .iter()
+            .map(|cell| cell.columns.end)
+            .fold(column_widths.len(), usize::max);
+
+        // The table has no padding in the collapsing border model.
+        let (border, padding) = match style.inherited_table.border_collapse {
+            BorderCollapse::Collapse => {
+                let border = self.collapse_borders(&mut cells, rows.len(), column_count);
+                (border, EdgeSizes::default())
+            }
+            BorderCollapse::Separate => {
+                for cell in &mut cells {
+                    cell.border = border_edges(&self.table_cell(cell).item_style().border);
+                }
+                (border_edges(&style.border), padding_edges(&style.padding))
+            }
+        };
+        for cell in &mut cells {
+            let padding = &self.table_cell(cell).item_style().padding;
+            cell.horizontal_edges =
+                cell.border.left + cell.border.right + padding.padding_left + padding.padding_right;
+        }
+
+        let columns = (0..column_count)
+            .map(|index| {
+                let width = column_widths.get(index).copied().flatten();
+                Column {
+                    min: width.unwrap_or(0.),
+                    max: width.unwrap_or(0.),
+                    width,
+                }
+            })
+            .collect();
+        (cells, columns, border, padding)
+    }
+
+    /// Margin of a table box, which has the outer halves of the table borders
+    /// in the collapsing border model.
+    fn table_margin(&self, border: &EdgeSizes) -> EdgeSizes {
+        match self.item_style().inherited_table.border_collapse {
+            BorderCollapse::Collapse => border.clone(),
+            BorderCollapse::Separate => EdgeSizes::default(),
+        }
+    }
+
+    /// Fit min and max widths of `columns` to contents of cells in the automatic table layout,
+    /// where cells spanning fewer columns are first.
+    /// Width over the widths of spanned columns is distributed equally.
+    // refer: https://www.w3.org/TR/CSS21/tables.html#auto-table-layout
+    fn fit_columns_to_cells(&self, cells: &[PlacedCell], columns: &mut [Column], spacing_x: f32) {
+        let mut sorted: Vec<&PlacedCell> = cells.iter().collect();
+        sorted.sort_by_key(|cell| cell.columns.len());
+        for cell in sorted {
+            let (min, max) = self
+                .table_cell(cell)
+                .table_cell_width_contributions(cell.horizontal_edges);
+            let spanned = &mut columns[cell.columns.clone()];
+            let gaps = spacing_x * (spanned.len() - 1) as f32;
+            let count = spanned.len() as f32;
+            let extra_min = min - gaps - spanned.iter().map(|c| c.min).sum::<f32>();
+            let extra_max = max - gaps - spanned.iter().map(|c| c.max).sum::<f32>();
+            for column in spanned {
+                column.min += extra_min.max(0.) / count;
+                column.max += extra_max.max(0.) / count;
+                column.max = column.max.max(column.min);
+            }
+        }
+        // A column element fixes the max width of the column.
+        for column in columns {
+            if let Some(width) = column.width {
+                column.max = width.max(column.min);
+            }
+        }
+    }
+
+    /// Min-content and max-content contributions of the margin box of a table,
+    /// where percentages are resolved against `containing_width` when it is known.
+    fn table_width_contributions(&self, containing_width: Option<f32>) -> (f32, f32) {
+        let rows = self.table_rows();
+        let (cells, mut columns, border, padding) = self.prepare_table(&rows);
+        let (spacing_x, _) = self.border_spacing();
+        self.fit_columns_to_cells(&cells, &mut columns, spacing_x);
+        let spacings = total_spacing(columns.len(), spacing_x);
+        let min = columns.iter().map(|column| column.min).sum::<f32>() + spacings;
+        let max = columns.iter().map(|column| column.max).sum::<f32>() + spacings;
+
+        let box_ = &self.item_style().box_;
+        let edges = border.left + border.right + padding.left + padding.right;
+        let to_content_size = |size: f32| match box_.box_sizing {
+            BoxSizing::ContentBox => size,
+            BoxSizing::BorderBox => (size - edges).max(0.),
+        };
+        let min_width = box_
+            .min_width
+            .and_then(|min_width| min_width.resolve(containing_width))
+            .map_or(0., to_content_size);
+        let max_width = box_
+            .max_width
+            .and_then(|max_width| max_width.resolve(containing_width))
+            .map(to_content_size);
+        let (min_content, max_content) = match box_.width.resolve(containing_width) {
+            LengthOrAuto::Length(width) => (to_content_size(width), to_content_size(width)),
+            LengthOrAuto::MinContent => (min, min),
+            LengthOrAuto::MaxContent => (max, max),
+            _ => (min, max),
+        };
+        let margin = self.table_margin(&border);
+        let margins = margin.left + margin.right;
+        let outer = |width: f32| clamp_size(width, min_width, max_width).max(min) + edges + margins;
+        (outer(min_content), outer(max_content))
+    }
+
+    /// Min-content and max-content widths of a table wrapper box, which are the widest
+    /// contributions of the table and captions.
+    pub(super) fn table_wrapper_widths(&self, containing_width: Option<f32>) -> (f32, f32) {
+        let (mut min, mut max) = (0f32, 0f32);
+        for child in &self.children {
+            let (child_min, child_max) = if child.table_display() == Some(Display::Table) {
+                child.table_width_contributions(containing_width)
+            } else {
+                child.intrinsic_width_contributions(containing_width)
+            };
+            min = min.max(child_min);
+            max = max.max(child_max);
+        }
+        (min, max.max(min))
+    }
+
+    /// Lay out a table box whose margin box is at the origin, where `containing_width` is
+    /// the width of the containing block, and `available_width` is the width for the margin box.
+    fn layout_table(
+        &mut self,
+        containing_width: f32,
+        available_width: f32,
+        containing_block_height: Option<f32>,
+    ) {
+        let style = self.item_style();
+        let box_ = &style.box_;
+        let (spacing_x, spacing_y) = self.border_spacing();
+        let rows = self.table_rows();
+        let (mut cells, mut columns, border, padding) = self.prepare_table(&rows);
+        let margin = self.table_margin(&border);
+        let horizontal_edges = border.left + border.right + padding.left + padding.right;
+        let vertical_edges = border.top + border.bottom + padding.top + padding.bottom;
+        let available = (available_width - margin.left - margin.right - horizontal_edges).max(0.);
+        {
+            let mut d = self.dimensions.borrow_mut();
+            d.margin = margin;
+            d.border = border;
+            d.padding = padding;
+        }
+        let to_content_size = |size: f32, edges: f32| match box_.box_sizing {
+            BoxSizing::ContentBox => size,
+            BoxSizing::BorderBox => (size - edges).max(0.),
+        };
+
+        // Determine widths of columns and the table.
+        // refer: https://www.w3.org/TR/CSS21/tables.html#width-layout
+        let basis = Some(containing_width);
+        let specified_width = match box_.width.resolve(basis) {
+            LengthOrAuto::Length(width) => Some(to_content_size(width, horizontal_edges)),
+            _ => None,
+        };
+        let min_width = box_
+            .min_width
+            .and_then(|min_width| min_width.resolve(basis))
+            .map_or(0., |min_width| to_content_size(min_width, horizontal_edges));
+        let max_width = box_
+            .max_width
+            .and_then(|max_width| max_width.resolve(basis))
+            .map(|max_width| to_content_size(max_width, horizontal_edges));
+        let spacings = total_spacing(columns.len(), spacing_x);
+
+        let width = match specified_width {
+            // Widths of columns are determined by column elements and the first row.
+            // refer: https://www.w3.org/TR/CSS21/tables.html#fixed-table-layout
+            Some(specified_width) if style.table.table_layout == TableLayout::Fixed => {
+                for cell in cells.iter().filter(|cell| cell.rows.start == 0) {
+                    let width = match self
+                        .table_cell(cell)
+                        .table_cell_outer_width(cell.horizontal_edges)
+                    {
+                        Some(width) => width,
+                        None => continue,
+                    };
+                    let spanned = &mut columns[cell.columns.clone()];
+                    let gaps = spacing_x * (spanned.len() - 1) as f32;
+                    let width = (width - gaps).max(0.) / spanned.len() as f32;
+                    for column in spanned.iter_mut().filter(|column| column.width.is_none()) {
+                        column.width = Some(width);
+                        column.min = width;
+                        column.max = width;
+                    }
+                }
+                let fixed: f32 = columns.iter().filter_map(|column| column.width).sum();
+                clamp_size(specified_width, min_width, max_width).max(fixed + spacings)
+            }
+            // Columns fit contents of cells, and the table fits the columns.
+            _ => {
+                self.fit_columns_to_cells(&cells, &mut columns, spacing_x);
+                let min = columns.iter().map(|column| column.min).sum::<f32>() + spacings;
+                let max = columns.iter().map(|column| column.max).sum::<f32>() + spacings;
+                let width = match box_.width {
+                    LengthOrAuto::MinContent => min,
+                    LengthOrAuto::MaxContent => max,
+                    _ => specified_width.unwrap_or_else(|| max.min(available)),
+                };
+                clamp_size(width, min_width, max_width).max(min)
+            }
+        };
+        let column_sizes = distribute_column_widths(&columns, width - spacings);
+        let column_positions: Vec<f32> = column_sizes
+            .iter()
+            .scan(spacing_x, |position, size| {
+                let start = *position;
+                *position += size + spacing_x;
+                Some(start)
+            })
+            .collect();
+
+        // Lay out cells in their columns to determine heights of rows.
+        // refer: https://www.w3.org/TR/CSS21/tables.html#height-layout
+        for cell in &mut cells {
+            let (_, width) = span(&column_positions, &column_sizes, &cell.columns);
+            let (horizontal_edges, border) = (cell.horizontal_edges, cell.border.clone());
+            let cell_box = self.table_cell_mut(cell);
+            cell_box.layout_table_cell((width - horizontal_edges).max(0.), border);
+            let box_ = &cell_box.item_style().box_;
+            let (content_height, edges, top) = {
+                let d = cell_box.dimensions.borrow();
+                let edges = d.border.top + d.border.bottom + d.padding.top + d.padding.bottom;
+                (d.content.height, edges, d.border_box().y)
+            };
+            // Percentages of cells are treated as `auto`.
+            let height = match box_.height.non_auto() {
+                Some(height) => match box_.box_sizing {
+                    BoxSizing::ContentBox => height,
+                    BoxSizing::BorderBox => (height - edges).max(0.),
+                },
+                None => 0.,
+            };
+            cell.height = content_height.max(height) + edges;
+            // The baseline is the bottom of the content box when there is no line box.
+            cell.baseline = match cell_box.last_baseline() {
+                Some(baseline) => baseline - top,
+                None => cell.height - edges + cell_box.dimensions.borrow().border_top_offset(),
+            };
+        }
+
+        // Heights of rows are their specified heights at least.
+        let mut row_heights: Vec<f32> = rows
+            .iter()
+            .map(|&row| {
+                let height = self.table_row(row).item_style().box_.height;
+                height.resolve(None).non_auto().unwrap_or(0.)
+            })
+            .collect();
+        // Space above and below the baseline of each row.
+        let mut above_baselines = vec![0f32; rows.len()];
+        let mut below_baselines = vec![0f32; rows.len()];
+        for cell in cells.iter().filter(|cell| cell.rows.len() == 1) {
+            let row = cell.rows.start;
+            row_heights[row] = row_heights[row].max(cell.height);
+            if cell.vertical_align == VerticalAlign::Baseline {
+                above_baselines[row] = above_baselines[row].max(cell.baseline);
+                below_baselines[row] = below_baselines[row].max(cell.height - cell.baseline);
+            }
+        }
+        for (row, height) in row_heights.iter_mut().enumerate() {
+            *height = height.max(above_baselines[row] + below_baselines[row]);
+        }
+        // Cells spanning several rows grow the rows equally.
+        for cell in cells.iter().filter(|cell| cell.rows.len() > 1) {
+            let spanned = &mut row_heights[cell.rows.clone()];
+            let gaps = spacing_y * (spanned.len() - 1) as f32;
+            let extra = cell.height - gaps - spanned.iter().sum::<f32>();
+            if extra > 0. {
+                let count = spanned.len() as f32;
+                for height in spanned {
+                    *height += extra / count;
+                }
+            }
+        }
+
+        // The table is as high as its rows at least, and rows share the extra height.
+        let row_spacings = total_spacing(rows.len(), spacing_y);
+        let rows_height = row_heights.iter().sum::<f32>() + row_spacings;
+        let specified_height = match box_.height.resolve(containing_block_height) {
+            LengthOrAuto::Length(height) => to_content_size(height, vertical_edges),
+            _ => 0.,
+        };
+        let min_height = box_
+            .min_height
+            .and_then(|min_height| min_height.resolve(containing_block_height))
+            .map_or(0., |min_height| to_content_size(min_height, vertical_edges));
+        let max_height = box_
+            .max_height
+            .and_then(|max_height| max_height.resolve(containing_block_height))
+            .map(|max_height| to_content_size(max_height, vertical_edges));
+        let height = clamp_size(specified_height, min_height, max_height).max(rows_height);
+        if height > rows_height && !rows.is_empty() {
+            let extra = (height - rows_height) / rows.len() as f32;
+            for row_height in &mut row_heights {
+                *row_height += extra;
+            }
+        }
+        let row_positions: Vec<f32> = row_heights
+            .iter()
+            .scan(spacing_y, |position, size| {
+                let start = *position;
+                *position += size + spacing_y;
+                Some(start)
+            })
+            .collect();
+
+        let content = {
+            let mut d = self.dimensions.borrow_mut();
+            let (x, y) = (d.margin_left_offset(), d.margin.top + d.border_top_offset());
+            d.content = Rect {
+                x,
+                y,
+                width,
+                height,
+            };
+            d.content.clone()
+        };
+
+        // Rows span all columns, and row groups cover their rows.
+        for (index, &row) in rows.iter().enumerate() {
+            self.table_row(row).dimensions.borrow_mut().content = Rect {
+                x: content.x + spacing_x,
+                y: content.y + row_positions[index],
+                width: (width - spacing_x * 2.).max(0.),
+                height: row_heights[index],
+            };
+        }
+        for (index, group) in self.children.iter().enumerate() {
+            let is_row_group =
+                matches!(group.table_display(), Some(display) if display.is_table_row_group());
+            let group_rows: Vec<usize> = (0..rows.len())
+                .filter(|&row| rows[row].0 == Some(index))
+                .collect();
+            if let (true, Some(&first), Some(&last)) =
+                (is_row_group, group_rows.first(), group_rows.last())
+            {
+                let (y, height) = span(&row_positions, &row_heights, &(first..last + 1));
+                group.dimensions.borrow_mut().content = Rect {
+                    x: content.x + spacing_x,
+                    y: content.y + y,
+                    width: (width - spacing_x * 2.).max(0.),
+                    height,
+                };
+            }
+        }
+
+        // Cells are stretched to their rows, and contents are aligned in them.
+        for cell in &cells {
+            let (x, _) = span(&column_positions, &column_sizes, &cell.columns);
+            let (y, height) = span(&row_positions, &row_heights, &cell.rows);
+            let baseline = above_baselines[cell.rows.start];
+            let cell_box = self.table_cell_mut(cell);
+            let free_space = {
+                let mut d = cell_box.dimensions.borrow_mut();
+                let border_box = d.border_box();
+                let free_space = height - border_box.height;
+                d.content.height += free_space;
+                free_space
+            };
+            let offset = match cell.vertical_align {
+                VerticalAlign::Top => 0.,
+                VerticalAlign::Middle => free_space / 2.,
+                VerticalAlign::Bottom => free_space,
+                VerticalAlign::Baseline if cell.rows.len() == 1 => baseline - cell.baseline,
+                VerticalAlign::Baseline => 0.,
+            };
+            for child in &mut cell_box.children {
+                child.translate(0., offset);
+            }
+            cell_box.translate(content.x + x, content.y + y);
+        }
+    }
+
+    /// Lay out a table wrapper box, whose margin box is at the top left of `containing_block`,
+    /// and fits in `available_width`. Percentages are resolved against `containing_block`.
+    /// The wrapper box is as wide as the table, and has margins of the table.
+    /// When `centers` is `true`, `auto` margins center the table, otherwise they are `0`.
+    // refer: https://www.w3.org/TR/CSS21/tables.html#model
+    pub(super) fn layout_table_wrapper(
+        &mut self,
+        containing_block: &Rect,
+        available_width: f32,
+        containing_block_height: Option<f32>,
+        centers: bool,
+    ) {
+        let margin = &self.item_style().margin;
+        let containing_width = Some(containing_block.width);
+        let margin_left = margin.margin_left.resolve(containing_width);
+        let margin_right = margin.margin_right.resolve(containing_width);
+        let margin_top = margin.margin_top.resolve(containing_width).to_px();
+        let margin_bottom = margin.margin_bottom.resolve(containing_width).to_px();
+        let available_width = available_width - margin_left.to_px() - margin_right.to_px();
+
+        let table_index = self
+            .children
+            .iter()
+            .position(|child| child.table_display() == Some(Display::Table))
+            .expect("Table wrapper box has no table box");
+        let table = &mut self.children[table_index];
+        table.layout_table(
+            containing_block.width,
+            available_width,
+            containing_block_height,
+        );
+        let width = table.dimensions.borrow().margin_box().width;
+
+        // refer: https://www.w3.org/TR/CSS21/visudet.html#blockwidth
+        let underflow = (available_width - width).max(0.);
+        let (margin_left, margin_right) = match (margin_left.non_auto(), margin_right.non_auto()) {
+            (None, None) if centers => (underflow / 2., underflow / 2.),
+            (None, Some(margin_right)) if centers => (underflow, margin_right),
+            (margin_left, margin_right) => (margin_left.unwrap_or(0.), margin_right.unwrap_or(0.)),
+        };
+
+        // Captions are laid out as blocks in the width of the table.
+        let mut height = 0.;
+        for child in &mut self.children {
+            if child.table_display() == Some(Display::Table) {
+                child.translate(0., height);
+                height += child.dimensions.borrow().margin_box().height;
+                continue;
+            }
+            let containing_block = Dimensions {
+                content: Rect {
+                    width,
+                    height,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let margins = child.layout(
+                Rc::new(RefCell::new(containing_block)),
+                None,
+                &mut FloatContext::new(),
+            );
+            let (start, end) = (margins.start.solve(), margins.end.solve());
+            child.translate(0., start);
+            height += start + child.dimensions.borrow().border_box().height + end;
+        }
+
+        {
+            let mut d = self.dimensions.borrow_mut();
+            d.margin = EdgeSizes {
+                left: margin_left,
+                right: margin_right,
+                top: margin_top,
+                bottom: margin_bottom,
+            };
+            d.border = EdgeSizes::default();
+            d.padding = EdgeSizes::default();
+            d.content = Rect {
+                x: 0.,
+                y: 0.,
+                width,
+                height,
+            };
+        }
+        self.translate(
+            containing_block.x + margin_left,
+            containing_block.y + margin_top,
+        );
+    }
+}
@@ -211,7 +211,7 @@ impl TextRun {
         let (glyphs, break_at_zero) = TextRun::split_with_line_break_opportunity(&text, breaker);
         (
             TextRun {
-                cache_key: FontCacheKey::new(size, descriptor, font.family_name.clone()),
+                cache_key: font.cache_key.clone(),
                 text,
                 size,
                 descriptor,
@@ -295,7 +295,7 @@ impl TextRun {
             _ => unreachable!(),
         };
         let descriptor = create_font_properties(styled_node);
-        let size = styled_node.font_size();
+        let size = font_context.used_font_size(styled_node);
         let families = styled_node.font_family();
 
         let mut script = Script::Common;
@@ -314,28 +314,19 @@ impl TextRun {
                     script = new_script;
                 }
 
-                let new_font = families
-                    .iter()
-                    .map(|family| {
-                        let key = FontCacheKey::new(size, descriptor, family.clone());
-                        font_context.get_or_create_by(&key)
-                    })
-                    .find(has_glyph);
-                let new_font = if new_font.is_some() {
-                    new_font
-                } else {
-                    fallback_font_families(Some(ch))
-                        .into_iter()
-                        .map(|family| {
-                            let key = FontCacheKey::new(size, descriptor, family);
-                            font_context.get_or_create_by(&key)
-                        })
-                        .find(has_glyph)
-                };
+                let new_font = font_context
+                    .font_family_keys(families, size, descriptor, ch)
+                    .into_iter()
+                    .chain(
+                        fallback_font_families(Some(ch))
+                            .into_iter()
+                            .map(|family| FontCacheKey::new(size, descriptor, family)),
+                    )
+                    .find_map(|key| font_context.find_by(&key).filter(has_glyph));
 
                 let has_font = match &font {
                     Some(font) => match &new_font {
-                        Some(new_font) => font.family_name == new_font.family_name,
+                        Some(new_font) => font.cache_key == new_font.cache_key,
                         None => false,
                     },
                     None => false,
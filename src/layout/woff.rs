@@ -0,0 +1,850 @@
+// Decode WOFF and WOFF2 into plain sfnt (TrueType/OpenType) data.
+// refer: https://www.w3.org/TR/WOFF/
+// refer: https://www.w3.org/TR/WOFF2/
+
+use brotli_decompressor::Decompressor;
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+
+const WOFF_SIGNATURE: u32 = 0x774F_4646;
+const WOFF2_SIGNATURE: u32 = 0x774F_4632;
+const TTC_FLAVOR: u32 = 0x7474_6366;
+
+const GLYF: u32 = 0x676C_7966;
+const LOCA: u32 = 0x6C6F_6361;
+const HMTX: u32 = 0x686D_7478;
+const HHEA: u32 = 0x6868_6561;
+const MAXP: u32 = 0x6D61_7870;
+const HEAD: u32 = 0x6865_6164;
+
+/// The most tables whose table directory fits in 16-bit fields,
+/// since `searchRange` and `rangeShift` are up to 16 times the number of tables.
+const MAX_TABLES: u16 = 0xFFFF / 16;
+
+/// Tags which are referred by index in the WOFF2 table directory.
+const KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+// Flags of a component in a composite glyph.
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+// Flags of a point in a simple glyph.
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const X_IS_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+#[derive(Debug, PartialEq)]
+pub enum WoffError {
+    Truncated,
+    InvalidData(&'static str),
+    Unsupported(&'static str),
+}
+
+/// Return sfnt data of `data`. TrueType and OpenType data are returned as they are.
+pub fn decode(data: Vec<u8>) -> Result<Vec<u8>, WoffError> {
+    let signature = Reader::new(&data).u32()?;
+    match signature {
+        WOFF_SIGNATURE => decode_woff(&data),
+        WOFF2_SIGNATURE => decode_woff2(&data),
+        _ => Ok(data),
+    }
+}
+
+fn read_num_tables(reader: &mut Reader) -> Result<u16, WoffError> {
+    let num_tables = reader.u16()?;
+    if num_tables > MAX_TABLES {
+        return Err(WoffError::InvalidData("too many tables"));
+    }
+    Ok(num_tables)
+}
+
+struct Table {
+    tag: u32,
+    data: Vec<u8>,
+}
+
+fn decode_woff(data: &[u8]) -> Result<Vec<u8>, WoffError> {
+    let mut reader = Reader::new(data);
+    reader.skip(4)?;
+    let flavor = reader.u32()?;
+    // length
+    reader.skip(4)?;
+    let num_tables = read_num_tables(&mut reader)?;
+    // reserved, totalSfntSize, version, metadata and private data block
+    reader.skip(2 + 4 + 2 + 2 + 4 * 5)?;
+
+    let mut tables = vec![];
+    for _ in 0..num_tables {
+        let tag = reader.u32()?;
+        let offset = reader.u32()? as usize;
+        let comp_length = reader.u32()? as usize;
+        let orig_length = reader.u32()? as usize;
+        // origChecksum
+        reader.skip(4)?;
+
+        let end = offset
+            .checked_add(comp_length)
+            .ok_or(WoffError::Truncated)?;
+        let compressed = data.get(offset..end).ok_or(WoffError::Truncated)?;
+        let table = if comp_length < orig_length {
+            // `origLength` is not trusted, so a longer stream is read one byte past it to fail the
+            // length check below without inflating the rest.
+            let mut table = vec![];
+            ZlibDecoder::new(compressed)
+                .take(orig_length as u64 + 1)
+                .read_to_end(&mut table)
+                .map_err(|_| WoffError::InvalidData("invalid zlib stream"))?;
+            table
+        } else {
+            compressed.to_vec()
+        };
+        if table.len() != orig_length {
+            return Err(WoffError::InvalidData("table length mismatch"));
+        }
+        tables.push(Table { tag, data: table });
+    }
+
+    Ok(build_sfnt(flavor, tables))
+}
+
+struct TableEntry {
+    tag: u32,
+    transform_length: usize,
+    is_transformed: bool,
+}
+
+fn decode_woff2(data: &[u8]) -> Result<Vec<u8>, WoffError> {
+    let mut reader = Reader::new(data);
+    reader.skip(4)?;
+    let flavor = reader.u32()?;
+    if flavor == TTC_FLAVOR {
+        return Err(WoffError::Unsupported("font collection"));
+    }
+    // length
+    reader.skip(4)?;
+    let num_tables = read_num_tables(&mut reader)?;
+    // reserved, totalSfntSize
+    reader.skip(2 + 4)?;
+    let total_compressed_size = reader.u32()? as usize;
+    // version, metadata and private data block
+    reader.skip(2 + 2 + 4 * 5)?;
+
+    let mut entries = vec![];
+    for _ in 0..num_tables {
+        let flags = reader.u8()?;
+        let tag = match flags & 0x3f {
+            63 => reader.u32()?,
+            index => u32::from_be_bytes(*KNOWN_TAGS[index as usize]),
+        };
+        let transform_version = flags >> 6;
+        let orig_length = reader.uint_base128()? as usize;
+        // The null transform of `glyf` and `loca` is version 3, and of the others is version 0.
+        let is_transformed = if tag == GLYF || tag == LOCA {
+            transform_version != 3
+        } else {
+            transform_version != 0
+        };
+        let transform_length = if is_transformed {
+            reader.uint_base128()? as usize
+        } else {
+            orig_length
+        };
+        entries.push(TableEntry {
+            tag,
+            transform_length,
+            is_transformed,
+        });
+    }
+
+    let mut stream = vec![];
+    Decompressor::new(reader.bytes(total_compressed_size)?, 4096)
+        .read_to_end(&mut stream)
+        .map_err(|_| WoffError::InvalidData("invalid brotli stream"))?;
+
+    let mut offset = 0;
+    let mut raw_tables = vec![];
+    for entry in &entries {
+        let end = offset + entry.transform_length;
+        raw_tables.push(stream.get(offset..end).ok_or(WoffError::Truncated)?);
+        offset = end;
+    }
+
+    let find_raw_table = |tag: u32| {
+        entries
+            .iter()
+            .position(|entry| entry.tag == tag)
+            .map(|i| raw_tables[i])
+    };
+
+    let mut glyf = None;
+    if let Some(i) = entries
+        .iter()
+        .position(|e| e.tag == GLYF && e.is_transformed)
+    {
+        glyf = Some(reconstruct_glyf(raw_tables[i])?);
+    }
+
+    let mut tables = vec![];
+    for (entry, raw_table) in entries.iter().zip(raw_tables.iter()) {
+        let data = match (entry.tag, entry.is_transformed, &glyf) {
+            (_, false, _) => raw_table.to_vec(),
+            (GLYF, true, Some(glyf)) => glyf.glyf.clone(),
+            (LOCA, true, Some(glyf)) => {
+                if !raw_table.is_empty() {
+                    return Err(WoffError::InvalidData("transformed loca has data"));
+                }
+                glyf.loca.clone()
+            }
+            (HMTX, true, Some(glyf)) => {
+                let hhea = find_raw_table(HHEA).ok_or(WoffError::InvalidData("missing hhea"))?;
+                let num_h_metrics = Reader::new(hhea).at(34).u16()? as usize;
+                reconstruct_hmtx(raw_table, num_h_metrics, &glyf.x_mins)?
+            }
+            _ => return Err(WoffError::Unsupported("table transform")),
+        };
+        tables.push(Table {
+            tag: entry.tag,
+            data,
+        });
+    }
+
+    if let (Some(maxp), Some(glyf)) = (find_raw_table(MAXP), &glyf) {
+        if Reader::new(maxp).at(4).u16()? as usize != glyf.x_mins.len() {
+            return Err(WoffError::InvalidData("number of glyphs mismatch"));
+        }
+    }
+
+    Ok(build_sfnt(flavor, tables))
+}
+
+struct Glyf {
+    glyf: Vec<u8>,
+    loca: Vec<u8>,
+    /// `xMin` of each glyph, which is used to reconstruct `hmtx`.
+    x_mins: Vec<i16>,
+}
+
+fn reconstruct_glyf(data: &[u8]) -> Result<Glyf, WoffError> {
+    let mut reader = Reader::new(data);
+    // reserved
+    reader.skip(2)?;
+    let option_flags = reader.u16()?;
+    let num_glyphs = reader.u16()? as usize;
+    let index_format = reader.u16()?;
+
+    let mut stream_sizes = [0; 7];
+    for size in stream_sizes.iter_mut() {
+        *size = reader.u32()? as usize;
+    }
+    let mut streams = vec![];
+    for size in stream_sizes.iter() {
+        streams.push(Reader::new(reader.bytes(*size)?));
+    }
+    let overlap_bitmap = if option_flags & 1 != 0 {
+        Some(reader.bytes(num_glyphs.div_ceil(8))?)
+    } else {
+        None
+    };
+    let mut streams = streams.into_iter();
+    let mut n_contour_stream = streams.next().unwrap();
+    let mut n_points_stream = streams.next().unwrap();
+    let mut flag_stream = streams.next().unwrap();
+    let mut glyph_stream = streams.next().unwrap();
+    let mut composite_stream = streams.next().unwrap();
+    let mut bbox_stream = streams.next().unwrap();
+    let mut instruction_stream = streams.next().unwrap();
+
+    let bbox_bitmap = bbox_stream.bytes(num_glyphs.div_ceil(32) * 4)?;
+    let has_bit = |bitmap: &[u8], i: usize| bitmap[i >> 3] & (0x80 >> (i & 7)) != 0;
+
+    let mut glyf = vec![];
+    let mut loca_offsets = Vec::with_capacity(num_glyphs + 1);
+    let mut x_mins = vec![0; num_glyphs];
+
+    for (i, x_min) in x_mins.iter_mut().enumerate() {
+        loca_offsets.push(glyf.len());
+
+        let has_bbox = has_bit(bbox_bitmap, i);
+        let n_contours = n_contour_stream.i16()?;
+
+        if n_contours == 0 {
+            if has_bbox {
+                return Err(WoffError::InvalidData("empty glyph has bbox"));
+            }
+            continue;
+        }
+
+        if n_contours < 0 {
+            if !has_bbox {
+                return Err(WoffError::InvalidData("composite glyph has no bbox"));
+            }
+            let (components, has_instructions) = read_composite_glyph(&mut composite_stream)?;
+            let bbox = bbox_stream.bytes(8)?;
+            *x_min = Reader::new(bbox).i16()?;
+
+            write_u16(&mut glyf, n_contours as u16);
+            glyf.extend_from_slice(bbox);
+            glyf.extend_from_slice(components);
+            if has_instructions {
+                let instruction_length = glyph_stream.uint_255()?;
+                write_u16(&mut glyf, instruction_length);
+                glyf.extend_from_slice(instruction_stream.bytes(instruction_length as usize)?);
+            }
+        } else {
+            let mut end_points = vec![];
+            let mut total_points = 0;
+            for _ in 0..n_contours {
+                total_points += n_points_stream.uint_255()? as usize;
+                if total_points == 0 {
+                    return Err(WoffError::InvalidData("contour has no point"));
+                }
+                end_points.push(total_points - 1);
+            }
+
+            let mut points = Vec::with_capacity(total_points);
+            let (mut x, mut y) = (0, 0);
+            for &flag in flag_stream.bytes(total_points)? {
+                let (dx, dy) = decode_triplet(flag & 0x7f, &mut glyph_stream)?;
+                x += dx;
+                y += dy;
+                points.push((x, y, flag & 0x80 == 0));
+            }
+
+            let instruction_length = glyph_stream.uint_255()?;
+
+            let bbox = if has_bbox {
+                let mut bbox = Reader::new(bbox_stream.bytes(8)?);
+                [bbox.i16()?, bbox.i16()?, bbox.i16()?, bbox.i16()?]
+            } else {
+                compute_bbox(&points)
+            };
+            *x_min = bbox[0];
+
+            write_u16(&mut glyf, n_contours as u16);
+            for v in bbox.iter() {
+                write_u16(&mut glyf, *v as u16);
+            }
+            for end_point in end_points {
+                write_u16(&mut glyf, end_point as u16);
+            }
+            write_u16(&mut glyf, instruction_length);
+            glyf.extend_from_slice(instruction_stream.bytes(instruction_length as usize)?);
+
+            let has_overlap = match overlap_bitmap {
+                Some(bitmap) => has_bit(bitmap, i),
+                None => false,
+            };
+            encode_points(&mut glyf, &points, has_overlap);
+        }
+
+        while glyf.len() % 4 != 0 {
+            glyf.push(0);
+        }
+    }
+    loca_offsets.push(glyf.len());
+
+    let mut loca = vec![];
+    for offset in loca_offsets {
+        if index_format == 0 {
+            if offset / 2 > u16::MAX as usize {
+                return Err(WoffError::InvalidData("glyf is too large for short loca"));
+            }
+            write_u16(&mut loca, (offset / 2) as u16);
+        } else {
+            loca.extend_from_slice(&(offset as u32).to_be_bytes());
+        }
+    }
+
+    Ok(Glyf { glyf, loca, x_mins })
+}
+
+/// Return component records of a composite glyph, and whether it has instructions.
+fn read_composite_glyph<'a>(reader: &mut Reader<'a>) -> Result<(&'a [u8], bool), WoffError> {
+    let start = reader.pos;
+    let mut has_instructions = false;
+    loop {
+        let flags = reader.u16()?;
+        // glyphIndex
+        reader.skip(2)?;
+        let mut args_size = if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            args_size += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            args_size += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            args_size += 8;
+        }
+        reader.skip(args_size)?;
+        has_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    Ok((&reader.data[start..reader.pos], has_instructions))
+}
+
+/// Decode a coordinate delta from a triplet encoding.
+/// refer: https://www.w3.org/TR/WOFF2/#triplet_decoding
+fn decode_triplet(flag: u8, reader: &mut Reader) -> Result<(i32, i32), WoffError> {
+    let flag = flag as i32;
+    let with_sign = |flag: i32, value: i32| if flag & 1 != 0 { value } else { -value };
+    let len = match flag {
+        0..=83 => 1,
+        84..=119 => 2,
+        120..=123 => 3,
+        _ => 4,
+    };
+    let b: Vec<i32> = reader.bytes(len)?.iter().map(|b| *b as i32).collect();
+
+    let delta = match flag {
+        0..=9 => (0, with_sign(flag, ((flag & 14) << 7) + b[0])),
+        10..=19 => (with_sign(flag, (((flag - 10) & 14) << 7) + b[0]), 0),
+        20..=83 => {
+            let b0 = flag - 20;
+            (
+                with_sign(flag, 1 + (b0 & 0x30) + (b[0] >> 4)),
+                with_sign(flag >> 1, 1 + ((b0 & 0x0c) << 2) + (b[0] & 0x0f)),
+            )
+        }
+        84..=119 => {
+            let b0 = flag - 84;
+            (
+                with_sign(flag, 1 + ((b0 / 12) << 8) + b[0]),
+                with_sign(flag >> 1, 1 + (((b0 % 12) >> 2) << 8) + b[1]),
+            )
+        }
+        120..=123 => (
+            with_sign(flag, (b[0] << 4) + (b[1] >> 4)),
+            with_sign(flag >> 1, ((b[1] & 0x0f) << 8) + b[2]),
+        ),
+        _ => (
+            with_sign(flag, (b[0] << 8) + b[1]),
+            with_sign(flag >> 1, (b[2] << 8) + b[3]),
+        ),
+    };
+    Ok(delta)
+}
+
+fn compute_bbox(points: &[(i32, i32, bool)]) -> [i16; 4] {
+    if points.is_empty() {
+        return [0; 4];
+    }
+    let mut bbox = [i32::MAX, i32::MAX, i32::MIN, i32::MIN];
+    for (x, y, _) in points {
+        bbox[0] = bbox[0].min(*x);
+        bbox[1] = bbox[1].min(*y);
+        bbox[2] = bbox[2].max(*x);
+        bbox[3] = bbox[3].max(*y);
+    }
+    [
+        bbox[0] as i16,
+        bbox[1] as i16,
+        bbox[2] as i16,
+        bbox[3] as i16,
+    ]
+}
+
+/// Write flags and coordinates of a simple glyph without repeating flags.
+fn encode_points(glyf: &mut Vec<u8>, points: &[(i32, i32, bool)], has_overlap: bool) {
+    let mut flags = vec![];
+    let mut xs = vec![];
+    let mut ys = vec![];
+    let (mut last_x, mut last_y) = (0, 0);
+
+    for (i, (x, y, is_on_curve)) in points.iter().enumerate() {
+        let mut flag = if *is_on_curve { ON_CURVE_POINT } else { 0 };
+        if has_overlap && i == 0 {
+            flag |= OVERLAP_SIMPLE;
+        }
+
+        let dx = x - last_x;
+        if dx == 0 {
+            flag |= X_IS_SAME_OR_POSITIVE;
+        } else if dx.abs() < 256 {
+            flag |= X_SHORT_VECTOR;
+            if dx > 0 {
+                flag |= X_IS_SAME_OR_POSITIVE;
+            }
+            xs.push(dx.unsigned_abs() as u8);
+        } else {
+            write_u16(&mut xs, dx as u16);
+        }
+
+        let dy = y - last_y;
+        if dy == 0 {
+            flag |= Y_IS_SAME_OR_POSITIVE;
+        } else if dy.abs() < 256 {
+            flag |= Y_SHORT_VECTOR;
+            if dy > 0 {
+                flag |= Y_IS_SAME_OR_POSITIVE;
+            }
+            ys.push(dy.unsigned_abs() as u8);
+        } else {
+            write_u16(&mut ys, dy as u16);
+        }
+
+        flags.push(flag);
+        last_x = *x;
+        last_y = *y;
+    }
+
+    glyf.append(&mut flags);
+    glyf.append(&mut xs);
+    glyf.append(&mut ys);
+}
+
+fn reconstruct_hmtx(
+    data: &[u8],
+    num_h_metrics: usize,
+    x_mins: &[i16],
+) -> Result<Vec<u8>, WoffError> {
+    let num_glyphs = x_mins.len();
+    if num_h_metrics == 0 || num_h_metrics > num_glyphs {
+        return Err(WoffError::InvalidData("invalid numberOfHMetrics"));
+    }
+
+    let mut reader = Reader::new(data);
+    let flags = reader.u8()?;
+    let has_proportional_lsbs = flags & 1 == 0;
+    let has_monospace_lsbs = flags & 2 == 0;
+
+    let mut advances = vec![];
+    for _ in 0..num_h_metrics {
+        advances.push(reader.u16()?);
+    }
+
+    let mut hmtx = vec![];
+    for (i, x_min) in x_mins.iter().enumerate() {
+        let has_lsb = if i < num_h_metrics {
+            has_proportional_lsbs
+        } else {
+            has_monospace_lsbs
+        };
+        let lsb = if has_lsb { reader.i16()? } else { *x_min };
+        if i < num_h_metrics {
+            write_u16(&mut hmtx, advances[i]);
+        }
+        write_u16(&mut hmtx, lsb as u16);
+    }
+
+    Ok(hmtx)
+}
+
+/// Assemble sfnt data from tables, with the table directory sorted by tag.
+fn build_sfnt(flavor: u32, mut tables: Vec<Table>) -> Vec<u8> {
+    tables.sort_by_key(|table| table.tag);
+
+    // The number of tables is limited by `MAX_TABLES` in parsing, so that the fields fit in u16.
+    let num_tables = tables.len() as u32;
+    let entry_selector = if num_tables == 0 {
+        0
+    } else {
+        31 - num_tables.leading_zeros()
+    };
+    let search_range = (1 << entry_selector) * 16;
+
+    let mut sfnt = vec![];
+    sfnt.extend_from_slice(&flavor.to_be_bytes());
+    write_u16(&mut sfnt, num_tables as u16);
+    write_u16(&mut sfnt, search_range as u16);
+    write_u16(&mut sfnt, entry_selector as u16);
+    write_u16(&mut sfnt, (num_tables * 16 - search_range) as u16);
+
+    let mut offset = 12 + 16 * tables.len();
+    let mut head_offset = None;
+    for table in &mut tables {
+        if table.tag == HEAD && table.data.len() >= 12 {
+            // checkSumAdjustment is calculated after the whole font is assembled.
+            table.data[8..12].copy_from_slice(&[0; 4]);
+            head_offset = Some(offset);
+        }
+        sfnt.extend_from_slice(&table.tag.to_be_bytes());
+        sfnt.extend_from_slice(&checksum(&table.data).to_be_bytes());
+        sfnt.extend_from_slice(&(offset as u32).to_be_bytes());
+        sfnt.extend_from_slice(&(table.data.len() as u32).to_be_bytes());
+        offset += (table.data.len() + 3) & !3;
+    }
+
+    for table in &tables {
+        sfnt.extend_from_slice(&table.data);
+        while sfnt.len() % 4 != 0 {
+            sfnt.push(0);
+        }
+    }
+
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0xB1B0_AFBA_u32.wrapping_sub(checksum(&sfnt));
+        sfnt[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+
+    sfnt
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn write_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend_from_slice(&value.to_be_bytes());
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data, pos: 0 }
+    }
+
+    fn at(mut self, pos: usize) -> Reader<'a> {
+        self.pos = pos;
+        self
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], WoffError> {
+        let end = self.pos.checked_add(len).ok_or(WoffError::Truncated)?;
+        let bytes = self.data.get(self.pos..end).ok_or(WoffError::Truncated)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), WoffError> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, WoffError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, WoffError> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, WoffError> {
+        Ok(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, WoffError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// refer: https://www.w3.org/TR/WOFF2/#DataTypes
+    fn uint_base128(&mut self) -> Result<u32, WoffError> {
+        let mut value: u32 = 0;
+        for i in 0..5 {
+            let b = self.u8()?;
+            if i == 0 && b == 0x80 {
+                return Err(WoffError::InvalidData("UIntBase128 has leading zeros"));
+            }
+            if value & 0xFE00_0000 != 0 {
+                return Err(WoffError::InvalidData("UIntBase128 overflows"));
+            }
+            value = (value << 7) | (b & 0x7f) as u32;
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(WoffError::InvalidData("UIntBase128 is too long"))
+    }
+
+    /// refer: https://www.w3.org/TR/WOFF2/#255UInt16
+    fn uint_255(&mut self) -> Result<u16, WoffError> {
+        const LOWEST_U_CODE: u16 = 253;
+        match self.u8()? {
+            253 => self.u16(),
+            254 => Ok(self.u8()? as u16 + LOWEST_U_CODE * 2),
+            255 => Ok(self.u8()? as u16 + LOWEST_U_CODE),
+            code => Ok(code as u16),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn tag(name: &[u8; 4]) -> u32 {
+        u32::from_be_bytes(*name)
+    }
+
+    /// Return data of `tag` in sfnt.
+    fn find_table(sfnt: &[u8], tag: u32) -> Option<&[u8]> {
+        let mut reader = Reader::new(sfnt).at(4);
+        let num_tables = reader.u16().unwrap();
+        reader.skip(6).unwrap();
+        for _ in 0..num_tables {
+            let table_tag = reader.u32().unwrap();
+            reader.skip(4).unwrap();
+            let offset = reader.u32().unwrap() as usize;
+            let length = reader.u32().unwrap() as usize;
+            if table_tag == tag {
+                return Some(&sfnt[offset..offset + length]);
+            }
+        }
+        None
+    }
+
+    /// Store `data` in uncompressed meta-blocks of brotli format.
+    fn brotli_store(data: &[u8]) -> Vec<u8> {
+        // WBITS = 16, ISLAST = 0, MNIBBLES = 4, MLEN - 1, ISUNCOMPRESSED = 1
+        let header = (((data.len() - 1) as u32) << 4) | (1 << 20);
+        let mut stream = header.to_le_bytes()[..3].to_vec();
+        stream.extend_from_slice(data);
+        // ISLAST = 1, ISLASTEMPTY = 1
+        stream.push(0x03);
+        stream
+    }
+
+    #[test]
+    fn test_decode_woff() {
+        let name: Vec<u8> = (0..100).map(|i| (i % 7) as u8).collect();
+        let post = vec![1, 2, 3];
+
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(&name).unwrap();
+        let compressed_name = encoder.finish().unwrap();
+
+        let mut woff = vec![];
+        woff.extend_from_slice(&WOFF_SIGNATURE.to_be_bytes());
+        woff.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        woff.extend_from_slice(&[0; 4]);
+        write_u16(&mut woff, 2);
+        woff.extend_from_slice(&[0; 2 + 4 + 2 + 2 + 4 * 5]);
+
+        let data_offset = 44 + 20 * 2;
+        for (table_tag, data, orig_length) in &[
+            (tag(b"post"), post.clone(), post.len()),
+            (tag(b"name"), compressed_name.clone(), name.len()),
+        ] {
+            woff.extend_from_slice(&table_tag.to_be_bytes());
+            let offset = if *table_tag == tag(b"post") {
+                data_offset
+            } else {
+                data_offset + 4
+            };
+            woff.extend_from_slice(&(offset as u32).to_be_bytes());
+            woff.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            woff.extend_from_slice(&(*orig_length as u32).to_be_bytes());
+            woff.extend_from_slice(&[0; 4]);
+        }
+        woff.extend_from_slice(&post);
+        woff.push(0);
+        woff.extend_from_slice(&compressed_name);
+
+        let sfnt = decode(woff).unwrap();
+        assert_eq!(&sfnt[..4], &[0, 1, 0, 0]);
+        assert_eq!(find_table(&sfnt, tag(b"name")).unwrap(), &name[..]);
+        assert_eq!(find_table(&sfnt, tag(b"post")).unwrap(), &post[..]);
+
+        // A stream longer than `origLength` is rejected.
+        let mut short = woff.clone();
+        short[44 + 20 + 12..44 + 20 + 16].copy_from_slice(&50u32.to_be_bytes());
+        assert_eq!(
+            decode(short),
+            Err(WoffError::InvalidData("table length mismatch"))
+        );
+
+        // The table directory of sfnt cannot have so many tables.
+        let mut woff = woff[..12].to_vec();
+        write_u16(&mut woff, 0xFFFF);
+        woff.resize(44, 0);
+        assert_eq!(decode(woff), Err(WoffError::InvalidData("too many tables")));
+    }
+
+    #[test]
+    fn test_decode_woff2_with_transformed_glyf() {
+        // Glyph 0 is a triangle of (0, 100), (100, 100), (100, 0), and glyph 1 is empty.
+        let mut glyf = vec![];
+        write_u16(&mut glyf, 0);
+        write_u16(&mut glyf, 0);
+        write_u16(&mut glyf, 2);
+        write_u16(&mut glyf, 0);
+        let n_contour_stream = [0, 1, 0, 0];
+        let n_points_stream = [3];
+        let flag_stream = [1, 11, 0];
+        let glyph_stream = [100, 100, 100, 0];
+        let bbox_stream = [0; 4];
+        for size in &[
+            n_contour_stream.len(),
+            n_points_stream.len(),
+            flag_stream.len(),
+            glyph_stream.len(),
+            0,
+            bbox_stream.len(),
+            0,
+        ] {
+            glyf.extend_from_slice(&(*size as u32).to_be_bytes());
+        }
+        glyf.extend_from_slice(&n_contour_stream);
+        glyf.extend_from_slice(&n_points_stream);
+        glyf.extend_from_slice(&flag_stream);
+        glyf.extend_from_slice(&glyph_stream);
+        glyf.extend_from_slice(&bbox_stream);
+
+        let head = [0; 54];
+
+        let mut woff2 = vec![];
+        woff2.extend_from_slice(&WOFF2_SIGNATURE.to_be_bytes());
+        woff2.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        woff2.extend_from_slice(&[0; 4]);
+        write_u16(&mut woff2, 3);
+        woff2.extend_from_slice(&[0; 2 + 4]);
+        let stream = brotli_store(&[&glyf[..], &head[..]].concat());
+        woff2.extend_from_slice(&(stream.len() as u32).to_be_bytes());
+        woff2.extend_from_slice(&[0; 2 + 2 + 4 * 5]);
+        // glyf with transform version 0, origLength 20, transformLength
+        woff2.extend_from_slice(&[10, 20, glyf.len() as u8]);
+        // loca with transform version 0, origLength 6, transformLength 0
+        woff2.extend_from_slice(&[11, 6, 0]);
+        // head with null transform, origLength 54
+        woff2.extend_from_slice(&[1, 54]);
+        woff2.extend_from_slice(&stream);
+
+        let sfnt = decode(woff2).unwrap();
+
+        assert_eq!(
+            find_table(&sfnt, GLYF).unwrap(),
+            &[
+                0, 1, // numberOfContours
+                0, 0, 0, 0, 0, 100, 0, 100, // bbox
+                0, 2, // endPtsOfContours
+                0, 0, // instructionLength
+                0x35, 0x33, 0x15, // flags
+                100,  // xCoordinates
+                100, 100, // yCoordinates
+            ][..]
+        );
+        assert_eq!(find_table(&sfnt, LOCA).unwrap(), &[0, 0, 0, 10, 0, 10][..]);
+        assert_eq!(checksum(&sfnt), 0xB1B0_AFBA);
+    }
+}
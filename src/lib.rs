@@ -1,10 +1,24 @@
+pub mod atom;
+pub mod bloom;
+pub mod computed_values;
 pub mod cssom;
+pub mod custom_properties;
 pub mod dom;
+pub mod encoding;
 pub mod error;
+pub mod invalidation;
 pub mod font_list;
 pub mod layout;
+pub mod media_queries;
 pub mod painter;
 pub mod parser;
+pub mod properties;
+pub mod query;
+pub mod restyle;
+pub mod selector_map;
+pub mod serializer;
 pub mod str;
 pub mod style;
+pub mod style_sharing;
+pub mod stylesheet_loader;
 pub mod window;
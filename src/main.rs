@@ -1,5 +1,11 @@
+use std::env;
+
 use kamaitachi::window;
 
 fn main() -> iced::Result {
+    if env::args().any(|arg| arg == "--dump-dom") {
+        window::dump_dom();
+        return Ok(());
+    }
     window::main()
 }
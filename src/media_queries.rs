@@ -0,0 +1,149 @@
+// refer: https://drafts.csswg.org/mediaqueries/
+
+/// The output device which media queries are evaluated against.
+#[derive(Debug, Clone)]
+pub struct Device {
+    pub viewport_width: f32,
+    pub viewport_height: f32,
+    /// Font sizes smaller than this are rendered at this size, which is configured by user
+    /// with `--minimum-font-size`.
+    /// It applies to used font sizes, so computed font sizes and `em` are not affected.
+    pub minimum_font_size: f32,
+}
+
+impl Device {
+    pub fn new(viewport_width: f32, viewport_height: f32) -> Device {
+        Device {
+            viewport_width,
+            viewport_height,
+            minimum_font_size: 0.,
+        }
+    }
+}
+
+/// A comma separated list of media queries.
+/// An empty list matches every device.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaList {
+    pub queries: Vec<MediaQuery>,
+}
+
+impl MediaList {
+    pub fn new(queries: Vec<MediaQuery>) -> MediaList {
+        MediaList { queries }
+    }
+
+    pub fn evaluate(&self, device: &Device) -> bool {
+        self.queries.is_empty() || self.queries.iter().any(|query| query.matches(device))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Qualifier {
+    Only,
+    Not,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaType {
+    All,
+    Screen,
+    Print,
+    Unknown(String),
+}
+
+impl MediaType {
+    pub fn from_ident(ident: &str) -> MediaType {
+        match ident {
+            "all" => MediaType::All,
+            "screen" => MediaType::Screen,
+            "print" => MediaType::Print,
+            _ => MediaType::Unknown(ident.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaQuery {
+    pub qualifier: Option<Qualifier>,
+    pub media_type: MediaType,
+    pub features: Vec<MediaFeature>,
+}
+
+impl MediaQuery {
+    pub fn new(
+        qualifier: Option<Qualifier>,
+        media_type: MediaType,
+        features: Vec<MediaFeature>,
+    ) -> MediaQuery {
+        MediaQuery {
+            qualifier,
+            media_type,
+            features,
+        }
+    }
+
+    /// A malformed media query is replaced with `not all`.
+    /// https://drafts.csswg.org/mediaqueries/#error-handling
+    pub fn not_all() -> MediaQuery {
+        MediaQuery::new(Some(Qualifier::Not), MediaType::All, vec![])
+    }
+
+    pub fn matches(&self, device: &Device) -> bool {
+        // kamaitachi is rendered only on screen.
+        let media_type_matches = matches!(self.media_type, MediaType::All | MediaType::Screen);
+        let result =
+            media_type_matches && self.features.iter().all(|feature| feature.matches(device));
+        match self.qualifier {
+            Some(Qualifier::Not) => !result,
+            _ => result,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaFeature {
+    Width(Range),
+    Height(Range),
+    Orientation(Orientation),
+}
+
+/// Comparison of a media feature, from `min-`/`max-` prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Range {
+    Eq(f32),
+    Min(f32),
+    Max(f32),
+}
+
+impl Range {
+    fn contains(&self, value: f32) -> bool {
+        match *self {
+            Range::Eq(v) => value == v,
+            Range::Min(v) => value >= v,
+            Range::Max(v) => value <= v,
+        }
+    }
+}
+
+impl MediaFeature {
+    pub fn matches(&self, device: &Device) -> bool {
+        match self {
+            MediaFeature::Width(range) => range.contains(device.viewport_width),
+            MediaFeature::Height(range) => range.contains(device.viewport_height),
+            MediaFeature::Orientation(orientation) => {
+                let is_portrait = device.viewport_height >= device.viewport_width;
+                match orientation {
+                    Orientation::Portrait => is_portrait,
+                    Orientation::Landscape => !is_portrait,
+                }
+            }
+        }
+    }
+}
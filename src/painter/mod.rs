@@ -5,7 +5,8 @@ pub mod wrapper;
 pub use block::create_block;
 pub use text::create_text;
 
-use crate::cssom::{Color, Value};
+use crate::computed_values::ComputedValues;
+use crate::cssom::Color;
 use crate::layout::{font, BoxType, LayoutBox, Rect};
 use font::{with_thread_local_font_context, FontContext};
 
@@ -20,28 +21,98 @@ pub enum DisplayCommand {
 pub fn build_display_list(layout_root: &LayoutBox) -> (DisplayList, f32, f32) {
     let mut list = vec![];
     with_thread_local_font_context(|font_context| {
-        render_layout_box(&mut list, layout_root, font_context)
+        render_stacking_context(&mut list, layout_root, font_context)
     });
     let margin_box = layout_root.dimensions.borrow().margin_box();
     (list, margin_box.height, margin_box.width)
 }
 
-fn render_layout_box(
+/// A layer which is painted in `z-index` order in a stacking context.
+enum Layer<'a, 'b> {
+    /// A positioned box whose `z-index` is not `auto`, which makes a stacking context.
+    StackingContext(&'b LayoutBox<'a>),
+    /// A positioned box whose `z-index` is `auto`, which is painted atomically at `z-index: 0`.
+    /// Its positioned descendants are layers of the parent stacking context.
+    Positioned(DisplayList),
+}
+
+/// Render a box and descendants, where positioned descendants are rendered in `z-index` order
+/// after the normal flow, or before it when `z-index` is negative.
+/// The root box and positioned boxes whose `z-index` is not `auto` make stacking contexts.
+// refer: https://www.w3.org/TR/CSS21/zindex.html
+fn render_stacking_context(
     list: &mut DisplayList,
     layout_box: &LayoutBox,
     font_context: &mut FontContext,
 ) {
-    render_background(list, layout_box);
-    render_borders(list, layout_box);
-    render_text(list, layout_box, font_context);
+    render_box(list, layout_box, font_context);
 
+    let mut flow = vec![];
+    let mut layers = vec![];
     for child in &layout_box.children {
-        render_layout_box(list, child, font_context);
+        render_layout_box(&mut flow, child, font_context, &mut layers);
+    }
+
+    // The sort is stable, so that layers with the same `z-index` are in tree order.
+    layers.sort_by_key(|(z_index, _)| *z_index);
+    let negative = layers
+        .iter()
+        .position(|(z_index, _)| *z_index >= 0)
+        .unwrap_or(layers.len());
+    let mut render_layer = |list: &mut DisplayList, layer: Layer| match layer {
+        Layer::StackingContext(child) => render_stacking_context(list, child, font_context),
+        Layer::Positioned(mut child_list) => list.append(&mut child_list),
+    };
+    let mut layers = layers.into_iter().map(|(_, layer)| layer);
+    for layer in layers.by_ref().take(negative) {
+        render_layer(list, layer);
+    }
+    list.append(&mut flow);
+    for layer in layers {
+        render_layer(list, layer);
     }
 }
 
+/// Positioned descendants are not rendered in `list`, but pushed to `layers` in tree order.
+fn render_layout_box<'a, 'b>(
+    list: &mut DisplayList,
+    layout_box: &'b LayoutBox<'a>,
+    font_context: &mut FontContext,
+    layers: &mut Vec<(i32, Layer<'a, 'b>)>,
+) {
+    if layout_box.is_stacking_context() {
+        layers.push((layout_box.z_index(), Layer::StackingContext(layout_box)));
+        return;
+    }
+    if layout_box.is_positioned() {
+        // The layer is before layers of descendants in tree order.
+        let index = layers.len();
+        layers.push((0, Layer::Positioned(vec![])));
+        let mut positioned_list = vec![];
+        render_box(&mut positioned_list, layout_box, font_context);
+        for child in &layout_box.children {
+            render_layout_box(&mut positioned_list, child, font_context, layers);
+        }
+        layers[index].1 = Layer::Positioned(positioned_list);
+        return;
+    }
+
+    render_box(list, layout_box, font_context);
+
+    for child in &layout_box.children {
+        render_layout_box(list, child, font_context, layers);
+    }
+}
+
+fn render_box(list: &mut DisplayList, layout_box: &LayoutBox, font_context: &mut FontContext) {
+    render_background(list, layout_box);
+    render_borders(list, layout_box);
+    render_text(list, layout_box, font_context);
+}
+
 fn render_background(list: &mut DisplayList, layout_box: &LayoutBox) {
-    if let Some(color) = get_color(layout_box, "background") {
+    let color = get_style(layout_box).and_then(|style| style.background.background_color.clone());
+    if let Some(color) = color {
         list.push(DisplayCommand::SolidColor(
             color,
             layout_box.dimensions.borrow().border_box(),
@@ -50,7 +121,7 @@ fn render_background(list: &mut DisplayList, layout_box: &LayoutBox) {
 }
 
 fn render_borders(list: &mut DisplayList, layout_box: &LayoutBox) {
-    let color = match get_color(layout_box, "border-color") {
+    let color = match get_style(layout_box).and_then(|style| style.border.border_color.clone()) {
         Some(color) => color,
         None => return, // render nothing
     };
@@ -119,7 +190,7 @@ fn render_text(list: &mut DisplayList, layout_box: &LayoutBox, font_context: &mu
 
     let text = node.get_text();
 
-    let color = get_color(layout_box, "color").unwrap_or_else(|| Color::new(0, 0, 0, 1.0));
+    let color = node.styled_node.style.inherited_text.color.clone();
 
     let font = font_context.get_or_create_by(&node.text_run.cache_key);
     list.push(DisplayCommand::Text(
@@ -130,16 +201,71 @@ fn render_text(list: &mut DisplayList, layout_box: &LayoutBox, font_context: &mu
     ))
 }
 
-fn get_color(layout_box: &LayoutBox, name: &str) -> Option<Color> {
+fn get_style<'a>(layout_box: &LayoutBox<'a>) -> Option<&'a ComputedValues> {
     match &layout_box.box_type {
-        BoxType::BlockNode(node) | BoxType::InlineNode(node) => match node.value(name) {
-            Some(Value::ColorValue(color)) => Some(color),
-            _ => None,
-        },
-        BoxType::TextNode(node) => match node.styled_node.value(name) {
-            Some(Value::ColorValue(color)) => Some(color),
-            _ => None,
-        },
-        BoxType::AnonymousBlock => None,
+        BoxType::BlockNode(node) | BoxType::InlineNode(node) | BoxType::InlineBlockNode(node) => {
+            Some(&node.style)
+        }
+        BoxType::TextNode(node) => Some(&node.styled_node.style),
+        BoxType::AnonymousBlock | BoxType::TableWrapper | BoxType::AnonymousTableBox(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cssom::{Origin, Stylesheet};
+    use crate::dom::Document;
+    use crate::layout::{layout_tree, Dimensions};
+    use crate::parser::css::CSSParser;
+    use crate::parser::html::HTMLParser;
+    use crate::style::create_style_tree;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_stacking_order() {
+        let html = "<body><div id='a'></div><div id='b'><div id='c'></div></div><div id='d'><div id='e'></div><div id='f'></div></div></body>";
+        let css = "
+body, div { display: block; }
+div { height: 10px; }
+#a { position: relative; z-index: 1; background: #ff0000; }
+#b { background: #00ff00; }
+#c { position: absolute; z-index: -1; background: #0000ff; }
+#d { position: relative; background: #ffff00; }
+#e { position: absolute; z-index: -1; background: #00ffff; }
+#f { background: #ff00ff; }
+";
+        let dom = Document::new(HTMLParser::new(html.into()).run());
+        let cssom = Stylesheet::new(CSSParser::new(css.into()).parse_rules(Origin::Author));
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        let mut viewport: Dimensions = Default::default();
+        viewport.content.width = 800.0;
+        viewport.content.height = 600.0;
+        let layout_root = layout_tree(&styled_node, Rc::new(RefCell::new(viewport)));
+
+        let (list, _, _) = build_display_list(&layout_root);
+        let colors: Vec<(u8, u8, u8)> = list
+            .iter()
+            .map(|command| match command {
+                DisplayCommand::SolidColor(color, _) => (color.r, color.g, color.b),
+                DisplayCommand::Text(..) => unreachable!(),
+            })
+            .collect();
+        // Negative `z-index`, the normal flow, and then positioned boxes in `z-index` order.
+        // A positioned box whose `z-index` is `auto` is painted with its normal flow at `0`,
+        // and its descendant whose `z-index` is negative is below the normal flow of the root.
+        assert_eq!(
+            colors,
+            vec![
+                (0, 0, 255),
+                (0, 255, 255),
+                (0, 255, 0),
+                (255, 255, 0),
+                (255, 0, 255),
+                (255, 0, 0),
+            ]
+        );
     }
 }
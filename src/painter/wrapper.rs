@@ -1,28 +1,40 @@
 use iced_graphics::{Backend, Defaults, Primitive, Renderer};
-use iced_native::{layout, mouse, Element, Hasher, Layout, Length, Point, Rectangle, Size, Widget};
+use iced_native::{
+    event, layout, mouse, Clipboard, Element, Event, Hasher, Layout, Length, Point, Rectangle,
+    Size, Widget,
+};
 
-pub struct Wrapper {
+pub struct Wrapper<Message> {
     pub items: Vec<Primitive>,
     size: Size,
+    on_scroll: Option<Message>,
 }
 
-impl Wrapper {
-    pub fn new(height: f32, width: f32) -> Wrapper {
+impl<Message> Wrapper<Message> {
+    pub fn new(height: f32, width: f32) -> Wrapper<Message> {
         Wrapper {
             items: vec![],
             size: Size::new(width, height),
+            on_scroll: None,
         }
     }
+
+    /// Produce `message` when the scroll position may be changed by the scrollable parent.
+    pub fn on_scroll(mut self, message: Message) -> Wrapper<Message> {
+        self.on_scroll = Some(message);
+        self
+    }
 }
 
-impl Default for Wrapper {
-    fn default() -> Wrapper {
+impl<Message> Default for Wrapper<Message> {
+    fn default() -> Wrapper<Message> {
         Wrapper::new(0., 0.)
     }
 }
 
-impl<Message, B> Widget<Message, Renderer<B>> for Wrapper
+impl<Message, B> Widget<Message, Renderer<B>> for Wrapper<Message>
 where
+    Message: Clone,
     B: Backend,
 {
     fn width(&self) -> Length {
@@ -39,6 +51,27 @@ where
 
     fn hash_layout(&self, _state: &mut Hasher) {}
 
+    /// The event is not captured, so that the scrollable parent scrolls after the message.
+    fn on_event(
+        &mut self,
+        event: Event,
+        _layout: Layout<'_>,
+        _cursor_position: Point,
+        messages: &mut Vec<Message>,
+        _renderer: &Renderer<B>,
+        _clipboard: Option<&dyn Clipboard>,
+    ) -> event::Status {
+        let may_scroll = matches!(
+            event,
+            Event::Mouse(mouse::Event::WheelScrolled { .. })
+                | Event::Mouse(mouse::Event::ButtonReleased(_))
+        );
+        if let (true, Some(message)) = (may_scroll, &self.on_scroll) {
+            messages.push(message.clone());
+        }
+        event::Status::Ignored
+    }
+
     fn draw(
         &self,
         _renderer: &mut Renderer<B>,
@@ -56,8 +89,9 @@ where
     }
 }
 
-impl<'a, Message, B> Into<Element<'a, Message, Renderer<B>>> for Wrapper
+impl<'a, Message, B> Into<Element<'a, Message, Renderer<B>>> for Wrapper<Message>
 where
+    Message: 'a + Clone,
     B: Backend,
 {
     fn into(self) -> Element<'a, Message, Renderer<B>> {
@@ -1,5 +1,11 @@
+use std::ops::RangeInclusive;
+
 use super::Parser;
+use crate::atom::Atom;
 use crate::cssom::*;
+use crate::layout::font::{FontStyle, FontWeight};
+use crate::media_queries::*;
+use crate::properties::DEFAULT_FONT_SIZE;
 
 pub struct CSSParser {
     pos: usize,
@@ -12,27 +18,332 @@ impl CSSParser {
     }
 
     pub fn parse_rules(&mut self, level: Origin) -> Vec<Rule> {
-        let mut rules = vec![];
+        self.parse_stylesheet(level).rules
+    }
+
+    pub fn parse_stylesheet(&mut self, level: Origin) -> Stylesheet {
+        let mut stylesheet = Stylesheet::new(vec![]);
+        // `@import` is valid only before any other rules except `@charset`.
+        let mut accepts_imports = true;
         loop {
             self.consume_whitespace();
             if self.eof() {
                 break;
             }
-            rules.push(self.parse_rule(level.clone()));
+            if self.next_char() == '@' {
+                accepts_imports = self.parse_at_rule(&mut stylesheet, accepts_imports);
+                continue;
+            }
+            accepts_imports = false;
+            stylesheet.rules.push(self.parse_rule(level.clone()));
+        }
+        stylesheet
+    }
+
+    /// Parse an at-rule, where `@import` is ignored unless `accepts_imports` is `true`.
+    /// Return whether `@import` is still accepted after the rule.
+    fn parse_at_rule(&mut self, stylesheet: &mut Stylesheet, accepts_imports: bool) -> bool {
+        // Consumed character should be '@' in here.
+        self.consume_char();
+
+        let name = self.parse_identifier().to_ascii_lowercase();
+        self.consume_whitespace();
+
+        match &*name {
+            "import" if accepts_imports => {
+                match self.parse_import_rule() {
+                    Some(import) => stylesheet.imports.push(import),
+                    None => self.skip_at_rule(),
+                }
+                true
+            }
+            "charset" => {
+                self.skip_at_rule();
+                accepts_imports
+            }
+            "font-face" => {
+                if let Some(font_face) = self.parse_font_face_rule() {
+                    stylesheet.font_faces.push(font_face);
+                }
+                false
+            }
+            _ => {
+                self.skip_at_rule();
+                false
+            }
+        }
+    }
+
+    /// Parse descriptors in `@font-face { ... }`.
+    /// A rule without `font-family` or `src` is invalid.
+    fn parse_font_face_rule(&mut self) -> Option<FontFaceRule> {
+        if self.eof() || self.next_char() != '{' {
+            self.skip_at_rule();
+            return None;
+        }
+        self.consume_char();
+
+        let mut family_name = None;
+        let mut sources = vec![];
+        let mut weight = None;
+        let mut style = None;
+        let mut unicode_range = vec![];
+
+        loop {
+            self.consume_whitespace();
+            if self.eof() {
+                break;
+            }
+            if self.next_char() == '}' {
+                self.consume_char();
+                break;
+            }
+
+            let name = self.parse_identifier().to_ascii_lowercase();
+            self.consume_whitespace();
+            if !self.eof() && self.next_char() == ':' {
+                self.consume_char();
+            }
+            let value = self.parse_raw_value();
+            if !self.eof() && self.next_char() == ';' {
+                self.consume_char();
+            }
+
+            match &*name {
+                "font-family" => family_name = Some(unquote(&value)),
+                "src" => sources = parse_font_face_sources(&value),
+                "font-weight" => weight = parse_font_weight_range(&value),
+                "font-style" => style = parse_font_face_style(&value),
+                "unicode-range" => unicode_range = parse_unicode_range(&value),
+                _ => {}
+            }
+        }
+
+        let family_name = family_name.filter(|name| !name.is_empty())?;
+        if sources.is_empty() {
+            return None;
+        }
+
+        let mut font_face = FontFaceRule::new(family_name, sources);
+        if let Some(weight) = weight {
+            font_face.weight = weight;
+        }
+        if let Some(style) = style {
+            font_face.style = style;
         }
-        rules
+        font_face.unicode_range = unicode_range;
+        Some(font_face)
+    }
+
+    /// Consume a declaration value as it is, until `;` or `}` which is not quoted.
+    fn parse_raw_value(&mut self) -> String {
+        let mut value = String::new();
+        let mut quote = None;
+        while !self.eof() {
+            let c = self.next_char();
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == ';' || c == '}' => break,
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None => {}
+            }
+            value.push(self.consume_char());
+        }
+        value.trim().to_string()
+    }
+
+    /// Parse `@import [ <url> | <string> ] <media-query-list>? ;`.
+    fn parse_import_rule(&mut self) -> Option<ImportRule> {
+        let url = self.parse_url()?;
+
+        let media = self.parse_media_query_list();
+
+        if !self.eof() && self.next_char() == ';' {
+            self.consume_char();
+        }
+
+        Some(ImportRule::new(url, media))
+    }
+
+    fn parse_url(&mut self) -> Option<String> {
+        if self.eof() {
+            return None;
+        }
+        if matches!(self.next_char(), '"' | '\'') {
+            return Some(self.parse_string());
+        }
+        if !self.starts_with("url(") {
+            return None;
+        }
+        self.consume_while(|c| c != '(');
+        self.consume_char();
+        self.consume_whitespace();
+
+        if self.eof() {
+            return None;
+        }
+        let url = if matches!(self.next_char(), '"' | '\'') {
+            self.parse_string()
+        } else {
+            self.consume_while(|c| c != ')' && !c.is_whitespace())
+        };
+
+        self.consume_whitespace();
+        if self.eof() || self.next_char() != ')' {
+            return None;
+        }
+        self.consume_char();
+        Some(url)
+    }
+
+    fn parse_string(&mut self) -> String {
+        let quote = self.consume_char();
+        let s = self.consume_while(|c| c != quote);
+        if !self.eof() {
+            self.consume_char();
+        }
+        s
+    }
+
+    /// Skip an unsupported at-rule, which is ended by `;` or by a `{}` block.
+    fn skip_at_rule(&mut self) {
+        let mut depth = 0;
+        while !self.eof() {
+            match self.consume_char() {
+                ';' if depth == 0 => break,
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth <= 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn parse_media_query_list(&mut self) -> MediaList {
+        let mut queries = vec![];
+        loop {
+            self.consume_whitespace();
+            if self.eof() || matches!(self.next_char(), ';' | '{') {
+                break;
+            }
+            if self.next_char() == ',' {
+                self.consume_char();
+                continue;
+            }
+            queries.push(self.parse_media_query().unwrap_or_else(MediaQuery::not_all));
+        }
+        MediaList::new(queries)
+    }
+
+    /// Parse one media query, such as `only screen and (min-width: 600px)`.
+    fn parse_media_query(&mut self) -> Option<MediaQuery> {
+        let mut qualifier = None;
+        let mut media_type = MediaType::All;
+        let mut features = vec![];
+        let mut is_valid = true;
+
+        loop {
+            self.consume_whitespace();
+            if self.eof() {
+                break;
+            }
+            match self.next_char() {
+                ',' | ';' | '{' => break,
+                '(' => match self.parse_media_feature() {
+                    Some(feature) => features.push(feature),
+                    None => is_valid = false,
+                },
+                c if valid_identifier_char(c) => {
+                    let ident = self.parse_identifier().to_ascii_lowercase();
+                    match &*ident {
+                        "and" => {}
+                        "not" => qualifier = Some(Qualifier::Not),
+                        "only" => qualifier = Some(Qualifier::Only),
+                        _ => media_type = MediaType::from_ident(&ident),
+                    }
+                }
+                _ => {
+                    self.consume_char();
+                    is_valid = false;
+                }
+            }
+        }
+
+        if !is_valid {
+            return None;
+        }
+        Some(MediaQuery::new(qualifier, media_type, features))
+    }
+
+    /// Parse `(<feature>: <value>)`.
+    fn parse_media_feature(&mut self) -> Option<MediaFeature> {
+        // Consumed character should be '(' in here.
+        self.consume_char();
+        self.consume_whitespace();
+
+        let name = self.parse_identifier().to_ascii_lowercase();
+        self.consume_whitespace();
+
+        let mut length = None;
+        let mut keyword = None;
+        if !self.eof() && self.next_char() == ':' {
+            self.consume_char();
+            self.consume_whitespace();
+            if !self.eof() && matches!(self.next_char(), '0'..='9' | '.') {
+                let value = self.parse_float();
+                length = match &*self.parse_identifier().to_ascii_lowercase() {
+                    "px" => Some(value),
+                    // Relative units in media queries are based on the initial font size.
+                    "em" | "rem" => Some(value * DEFAULT_FONT_SIZE),
+                    _ => None,
+                };
+            } else {
+                keyword = Some(self.parse_identifier().to_ascii_lowercase());
+            }
+            self.consume_whitespace();
+        }
+
+        let is_closed = !self.eof() && self.next_char() == ')';
+        self.consume_while(|c| c != ')');
+        if !self.eof() {
+            self.consume_char();
+        }
+        if !is_closed {
+            return None;
+        }
+
+        let feature = match (&*name, length, keyword.as_deref()) {
+            ("width", Some(v), _) => MediaFeature::Width(Range::Eq(v)),
+            ("min-width", Some(v), _) => MediaFeature::Width(Range::Min(v)),
+            ("max-width", Some(v), _) => MediaFeature::Width(Range::Max(v)),
+            ("height", Some(v), _) => MediaFeature::Height(Range::Eq(v)),
+            ("min-height", Some(v), _) => MediaFeature::Height(Range::Min(v)),
+            ("max-height", Some(v), _) => MediaFeature::Height(Range::Max(v)),
+            ("orientation", _, Some("portrait")) => {
+                MediaFeature::Orientation(Orientation::Portrait)
+            }
+            ("orientation", _, Some("landscape")) => {
+                MediaFeature::Orientation(Orientation::Landscape)
+            }
+            _ => return None,
+        };
+        Some(feature)
     }
 
     fn parse_rule(&mut self, level: Origin) -> Rule {
         Rule::new(self.parse_selectors(), self.parse_declarations(), level)
     }
 
-    // TODO: Support chaining selector
     // TODO: Comply specificity with specification
     fn parse_selectors(&mut self) -> Vec<Selector> {
         let mut selectors = vec![];
         loop {
-            selectors.push(Selector::Simple(self.parse_simple_selector()));
+            selectors.push(self.parse_selector());
             self.consume_whitespace();
             match self.next_char() {
                 ',' => {
@@ -52,25 +363,83 @@ impl CSSParser {
         selectors
     }
 
-    fn parse_simple_selector(&mut self) -> SimpleSelector {
-        let mut selector = SimpleSelector::new(None, None, vec![]);
+    /// Parse a comma-separated list of selectors which fills the input, e.g. for `querySelector`.
+    /// Return `None` if some selector is invalid.
+    pub fn parse_selector_list(&mut self) -> Option<Vec<Selector>> {
+        let mut selectors = vec![];
+        loop {
+            self.consume_whitespace();
+            if self.eof() {
+                return None;
+            }
+            let start = self.pos;
+            let selector = self.parse_selector();
+            if self.pos == start {
+                return None;
+            }
+            selectors.push(selector);
+            self.consume_whitespace();
+            if self.eof() {
+                break;
+            }
+            if self.next_char() != ',' {
+                return None;
+            }
+            self.consume_char();
+        }
+        Some(selectors)
+    }
+
+    fn parse_selector(&mut self) -> Selector {
+        let mut subject = self.parse_simple_selector();
+        let mut ancestors = vec![];
         loop {
+            let start = self.pos;
             self.consume_whitespace();
+            if self.eof() {
+                break;
+            }
+            let combinator = match self.next_char() {
+                '>' => {
+                    self.consume_char();
+                    Combinator::Child
+                }
+                c if self.pos != start
+                    && (valid_identifier_char(c) || matches!(c, '#' | '.' | '*')) =>
+                {
+                    Combinator::Descendant
+                }
+                _ => break,
+            };
+            let simple = self.parse_simple_selector();
+            ancestors.insert(0, (combinator, std::mem::replace(&mut subject, simple)));
+        }
+        if ancestors.is_empty() {
+            Selector::Simple(subject)
+        } else {
+            Selector::Complex(ComplexSelector { subject, ancestors })
+        }
+    }
+
+    fn parse_simple_selector(&mut self) -> SimpleSelector {
+        let mut selector = SimpleSelector::new(None, None, vec![]);
+        self.consume_whitespace();
+        while !self.eof() {
             match self.next_char() {
                 '#' => {
                     self.consume_char();
-                    selector.id = Some(self.parse_identifier())
+                    selector.id = Some(Atom::new(&self.parse_identifier()))
                 }
                 '.' => {
                     self.consume_char();
-                    selector.class.push(self.parse_identifier());
+                    selector.class.push(Atom::new(&self.parse_identifier()));
                 }
                 '*' => {
                     // universal selector
                     self.consume_char();
                 }
                 c if valid_identifier_char(c) => {
-                    selector.tag_name = Some(self.parse_identifier());
+                    selector.tag_name = Some(Atom::new(&self.parse_identifier()));
                 }
                 _ => break,
             }
@@ -113,9 +482,18 @@ impl CSSParser {
             return Declaration::new(name, Value::None);
         }
 
-        let value = self.parse_value();
+        let mut value = if name.starts_with("--") {
+            Value::Unparsed(self.parse_raw_value())
+        } else {
+            self.parse_value_with_var(&name)
+        };
 
         self.consume_whitespace();
+        // The declaration is invalid if the value is followed by unexpected tokens.
+        if !self.eof() && !matches!(self.next_char(), ';' | '}') {
+            self.consume_while(|c| c != ';' && c != '}');
+            value = Value::None;
+        }
         if !self.eof() && self.next_char() == ';' {
             self.consume_char();
         }
@@ -123,11 +501,115 @@ impl CSSParser {
         Declaration::new(name, value)
     }
 
+    /// A value containing `var()` is kept unparsed until it is substituted at computed-value time.
+    // refer: https://drafts.csswg.org/css-variables/#using-variables
+    fn parse_value_with_var(&mut self, name: &str) -> Value {
+        let start = self.pos;
+        let raw = self.parse_raw_value();
+        if raw.contains("var(") {
+            return Value::Unparsed(raw);
+        }
+        self.pos = start;
+        self.parse_property_value(name)
+    }
+
+    /// Parse a value of `name` property which is substituted from `var()`.
+    pub fn parse_substituted_value(&mut self, name: &str) -> Value {
+        self.consume_whitespace();
+        if self.eof() {
+            return Value::None;
+        }
+        // Terminate the input, because the value parsers look ahead for `;`.
+        self.input.push(';');
+        self.parse_property_value(name)
+    }
+
+    fn parse_property_value(&mut self, name: &str) -> Value {
+        match name {
+            "font-family" => self.parse_font_family(),
+            "grid-template-columns" | "grid-template-rows" => {
+                self.parse_components(parse_track_list)
+            }
+            "grid-auto-columns" | "grid-auto-rows" => self.parse_components(parse_track_sizes),
+            "grid-template-areas" => self.parse_components(parse_grid_template_areas),
+            "border-spacing" => self.parse_components(parse_lengths),
+            "grid-auto-flow" => self.parse_components(parse_keyword_list),
+            "grid-row-start" | "grid-row-end" | "grid-column-start" | "grid-column-end"
+            | "grid-row" | "grid-column" | "grid-area" => {
+                self.parse_components(parse_grid_placement)
+            }
+            _ => self.parse_value(),
+        }
+    }
+
+    /// Parse a value which has several components, e.g. `grid-template-columns`.
+    /// The declaration is invalid if `parse` fails.
+    fn parse_components(&mut self, parse: fn(&str) -> Option<Value>) -> Value {
+        let value = self.parse_raw_value();
+        if let Some(keyword) = CssWideKeyword::from_ident(&value) {
+            return Value::CssWide(keyword);
+        }
+        parse(&value).unwrap_or(Value::None)
+    }
+
+    /// Parse a comma-separated list of family names.
+    /// The declaration is invalid if an item is neither a string nor a sequence of identifiers.
+    // refer: https://drafts.csswg.org/css-fonts-4/#family-name-syntax
+    fn parse_font_family(&mut self) -> Value {
+        let mut families = vec![];
+        loop {
+            self.consume_whitespace();
+            if self.eof() {
+                return Value::None;
+            }
+            let family = match self.next_char() {
+                '"' | '\'' => FontFamily::Named(self.parse_string()),
+                c if valid_identifier_char(c) => {
+                    let mut idents = vec![self.parse_identifier()];
+                    self.consume_whitespace();
+                    while !self.eof() && valid_identifier_char(self.next_char()) {
+                        idents.push(self.parse_identifier());
+                        self.consume_whitespace();
+                    }
+                    if idents.len() > 1 {
+                        FontFamily::Named(idents.join(" "))
+                    } else if let Some(keyword) = CssWideKeyword::from_ident(&idents[0]) {
+                        // A CSS-wide keyword is valid only as the whole value.
+                        if !families.is_empty() || self.eof() || self.next_char() == ',' {
+                            break;
+                        }
+                        return Value::CssWide(keyword);
+                    } else if let Some(generic) = GenericFontFamily::from_ident(&idents[0]) {
+                        FontFamily::Generic(generic)
+                    } else {
+                        FontFamily::Named(idents.remove(0))
+                    }
+                }
+                _ => break,
+            };
+            families.push(family);
+
+            self.consume_whitespace();
+            if self.eof() || self.next_char() != ',' {
+                return Value::FontFamily(families);
+            }
+            self.consume_char();
+            self.consume_whitespace();
+            // Allow a trailing comma.
+            if self.eof() || matches!(self.next_char(), ';' | '}') {
+                return Value::FontFamily(families);
+            }
+        }
+        self.consume_while(|c| c != ';' && c != '}');
+        Value::None
+    }
+
     fn parse_value(&mut self) -> Value {
         match self.next_char() {
             '0'..='9' => self.parse_length(),
+            '-' | '+' if self.starts_with_signed_number() => self.parse_length(),
             '#' => self.parse_color(),
-            c if valid_identifier_char(c) => self.parse_keyword(),
+            c if valid_identifier_char(c) || c == '"' || c == '\'' => self.parse_keyword(),
             _ => {
                 self.consume_while(|c| c != '}');
                 Value::None
@@ -136,9 +618,14 @@ impl CSSParser {
     }
 
     fn parse_keyword(&mut self) -> Value {
-        let keyword = self.parse_identifier();
+        let is_string = matches!(self.next_char(), '"' | '\'');
+        let keyword = self.parse_keyword_item();
         self.consume_whitespace();
         if self.next_char() != ',' {
+            match CssWideKeyword::from_ident(&keyword) {
+                Some(keyword) if !is_string => return Value::CssWide(keyword),
+                _ => {}
+            }
             return Value::Keyword(keyword);
         }
         let mut keyword_array = vec![keyword];
@@ -148,31 +635,63 @@ impl CSSParser {
             if self.next_char() == ';' {
                 break;
             }
-            let keyword = self.parse_identifier();
+            let keyword = self.parse_keyword_item();
             keyword_array.push(keyword);
         }
         Value::KeywordArray(keyword_array)
     }
 
+    /// Quoted strings are allowed as a keyword, e.g. a family name in `font-family`.
+    fn parse_keyword_item(&mut self) -> String {
+        match self.next_char() {
+            '"' | '\'' => self.parse_string(),
+            _ => self.parse_identifier(),
+        }
+    }
+
+    /// Parse a number with a unit, or without it. The value is invalid if the unit is unknown.
     fn parse_length(&mut self) -> Value {
         let float = self.parse_float();
-        self.consume_whitespace();
-        if self.next_char() == ';' {
-            self.consume_char();
+        if self.eof() || !(self.next_char() == '%' || valid_identifier_char(self.next_char())) {
             return Value::Number(float);
         }
-        Value::Length(float, self.parse_unit())
+        match self.parse_unit() {
+            Some(unit) => Value::Length(float, unit),
+            None => Value::None,
+        }
     }
 
     fn parse_float(&mut self) -> f32 {
+        let sign = match self.next_char() {
+            '-' => {
+                self.consume_char();
+                -1.0
+            }
+            '+' => {
+                self.consume_char();
+                1.0
+            }
+            _ => 1.0,
+        };
         let s = self.consume_while(|c| matches!(c, '0'..='9' | '.'));
-        s.parse().unwrap_or(0.0)
+        sign * s.parse().unwrap_or(0.0)
+    }
+
+    /// Whether a number with a sign follows, e.g. `-5px`, which is not an identifier.
+    fn starts_with_signed_number(&self) -> bool {
+        let mut chars = self.input[self.pos..].chars();
+        matches!(chars.next(), Some('-' | '+')) && matches!(chars.next(), Some('0'..='9' | '.'))
     }
 
-    fn parse_unit(&mut self) -> Unit {
+    fn parse_unit(&mut self) -> Option<Unit> {
+        if self.next_char() == '%' {
+            self.consume_char();
+            return Some(Unit::Percent);
+        }
         match &*self.parse_identifier().to_ascii_lowercase() {
-            "px" => Unit::Px,
-            _ => panic!("unrecognized unit"),
+            "px" => Some(Unit::Px),
+            "em" => Some(Unit::Em),
+            _ => None,
         }
     }
 
@@ -213,6 +732,375 @@ impl Parser for CSSParser {
     }
 }
 
+/// Split `value` by `,` which is not quoted or parenthesized.
+fn split_top_level_commas(value: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    items.push(value[start..i].trim());
+                    start = i + 1;
+                }
+                _ => {}
+            },
+        }
+    }
+    items.push(value[start..].trim());
+    items.into_iter().filter(|item| !item.is_empty()).collect()
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    let is_quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+    if is_quoted {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Return the argument of a functional notation such as `url(...)`, and the rest of `value`.
+fn parse_function<'a>(value: &'a str, name: &str) -> Option<(String, &'a str)> {
+    let prefix = value.get(..name.len() + 1)?;
+    if !prefix.eq_ignore_ascii_case(&format!("{}(", name)) {
+        return None;
+    }
+    // Find the parenthesis which closes the function, skipping nested parentheses.
+    let mut depth = 0;
+    let end = value.char_indices().find_map(|(i, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        None
+    })?;
+    Some((
+        unquote(&value[name.len() + 1..end]),
+        value[end + 1..].trim(),
+    ))
+}
+
+/// Split `value` by white space which is not parenthesized.
+fn split_top_level_whitespace(value: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut start = None;
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = start.take() {
+                    items.push(&value[start..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(start) = start {
+        items.push(&value[start..]);
+    }
+    items
+}
+
+/// Return the arguments of a functional notation which is the whole `value`,
+/// such as `minmax(...)`. The arguments may contain nested functions.
+fn function_arguments<'a>(value: &'a str, name: &str) -> Option<&'a str> {
+    let prefix = value.get(..name.len() + 1)?;
+    if !prefix.eq_ignore_ascii_case(&format!("{}(", name)) || !value.ends_with(')') {
+        return None;
+    }
+    Some(&value[name.len() + 1..value.len() - 1])
+}
+
+/// Split a dimension such as `1.5fr` into the number and the unit.
+fn split_dimension(value: &str) -> Option<(f32, &str)> {
+    let end = value
+        .find(|c: char| !matches!(c, '0'..='9' | '.' | '-' | '+'))
+        .unwrap_or(value.len());
+    Some((value[..end].parse().ok()?, &value[end..]))
+}
+
+// refer: https://drafts.csswg.org/css-grid-2/#typedef-track-breadth
+fn parse_track_breadth(value: &str) -> Option<TrackBreadth> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Some(TrackBreadth::Auto);
+    }
+    let (number, unit) = split_dimension(value)?;
+    if number < 0. {
+        return None;
+    }
+    match &*unit.to_ascii_lowercase() {
+        "fr" => Some(TrackBreadth::Flex(number)),
+        "px" => Some(TrackBreadth::Length(number, Unit::Px)),
+        "em" => Some(TrackBreadth::Length(number, Unit::Em)),
+        "%" => Some(TrackBreadth::Length(number, Unit::Percent)),
+        // Only `0` is allowed without a unit.
+        "" if number == 0. => Some(TrackBreadth::Length(0., Unit::Px)),
+        _ => None,
+    }
+}
+
+// refer: https://drafts.csswg.org/css-grid-2/#typedef-track-size
+fn parse_track_size(value: &str) -> Option<TrackSize> {
+    let arguments = match function_arguments(value, "minmax") {
+        Some(arguments) => split_top_level_commas(arguments),
+        None => return parse_track_breadth(value).map(TrackSize::Breadth),
+    };
+    if arguments.len() != 2 {
+        return None;
+    }
+    match parse_track_breadth(arguments[0])? {
+        // A flexible length is not allowed as the minimum.
+        TrackBreadth::Flex(_) => None,
+        min => Some(TrackSize::MinMax(min, parse_track_breadth(arguments[1])?)),
+    }
+}
+
+// refer: https://drafts.csswg.org/css-grid-2/#typedef-fixed-size
+fn is_fixed_track_size(size: &TrackSize) -> bool {
+    let is_fixed = |breadth: &TrackBreadth| matches!(breadth, TrackBreadth::Length(..));
+    match size {
+        TrackSize::Breadth(breadth) => is_fixed(breadth),
+        TrackSize::MinMax(min, max) => is_fixed(min) || is_fixed(max),
+    }
+}
+
+/// Parse `grid-template-columns` or `grid-template-rows`.
+// refer: https://drafts.csswg.org/css-grid-2/#track-sizing
+fn parse_track_list(value: &str) -> Option<Value> {
+    if value.eq_ignore_ascii_case("none") {
+        return Some(Value::TrackList(vec![]));
+    }
+    let mut items = vec![];
+    for item in split_top_level_whitespace(value) {
+        let arguments = match function_arguments(item, "repeat") {
+            Some(arguments) => split_top_level_commas(arguments),
+            None => {
+                items.push(TrackListItem::Size(parse_track_size(item)?));
+                continue;
+            }
+        };
+        if arguments.len() != 2 {
+            return None;
+        }
+        let count = if arguments[0].eq_ignore_ascii_case("auto-fill") {
+            RepeatCount::AutoFill
+        } else {
+            match arguments[0].parse() {
+                Ok(count) if count > 0 => RepeatCount::Count(count),
+                _ => return None,
+            }
+        };
+        let sizes = split_top_level_whitespace(arguments[1])
+            .into_iter()
+            .map(parse_track_size)
+            .collect::<Option<Vec<_>>>()?;
+        // `auto-fill` only repeats sizes which have a fixed minimum or maximum.
+        if count == RepeatCount::AutoFill && !sizes.iter().all(is_fixed_track_size) {
+            return None;
+        }
+        items.push(TrackListItem::Repeat(count, sizes));
+    }
+    // Only one `repeat(auto-fill, ...)` is allowed.
+    let auto_repeats = items
+        .iter()
+        .filter(|item| matches!(item, TrackListItem::Repeat(RepeatCount::AutoFill, _)))
+        .count();
+    if items.is_empty() || auto_repeats > 1 {
+        return None;
+    }
+    Some(Value::TrackList(items))
+}
+
+/// Parse `grid-auto-columns` or `grid-auto-rows`, which do not allow `repeat()`.
+fn parse_track_sizes(value: &str) -> Option<Value> {
+    let sizes = split_top_level_whitespace(value)
+        .into_iter()
+        .map(|size| parse_track_size(size).map(TrackListItem::Size))
+        .collect::<Option<Vec<_>>>()?;
+    if sizes.is_empty() {
+        return None;
+    }
+    Some(Value::TrackList(sizes))
+}
+
+/// Parse quoted rows of `grid-template-areas`.
+// refer: https://drafts.csswg.org/css-grid-2/#grid-template-areas-property
+fn parse_grid_template_areas(value: &str) -> Option<Value> {
+    if value.eq_ignore_ascii_case("none") {
+        return Some(Value::GridTemplateAreas(vec![]));
+    }
+    let mut rows = vec![];
+    let mut rest = value;
+    while !rest.is_empty() {
+        let quote = rest.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        let end = rest[1..].find(quote)? + 1;
+        rows.push(rest[1..end].to_string());
+        rest = rest[end + 1..].trim_start();
+    }
+    Some(Value::GridTemplateAreas(rows))
+}
+
+/// Parse non-negative lengths separated by white space, e.g. `border-spacing: 2px 4px`.
+fn parse_lengths(value: &str) -> Option<Value> {
+    let lengths = value
+        .split_whitespace()
+        .map(|length| {
+            let (number, unit) = split_dimension(length)?;
+            if number < 0. {
+                return None;
+            }
+            match &*unit.to_ascii_lowercase() {
+                "px" => Some((number, Unit::Px)),
+                "em" => Some((number, Unit::Em)),
+                // Only `0` is allowed without a unit.
+                "" if number == 0. => Some((0., Unit::Px)),
+                _ => None,
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if lengths.is_empty() {
+        return None;
+    }
+    Some(Value::Lengths(lengths))
+}
+
+/// Parse keywords separated by white space, e.g. `grid-auto-flow: row dense`.
+fn parse_keyword_list(value: &str) -> Option<Value> {
+    let keywords = value
+        .split_whitespace()
+        .map(|keyword| keyword.to_ascii_lowercase())
+        .collect();
+    Some(Value::KeywordArray(keywords))
+}
+
+/// Parse grid lines separated by `/`.
+// refer: https://drafts.csswg.org/css-grid-2/#line-placement
+fn parse_grid_placement(value: &str) -> Option<Value> {
+    let lines = value
+        .split('/')
+        .map(|line| parse_grid_line(line.trim()))
+        .collect::<Option<Vec<_>>>()?;
+    if lines.len() > 4 {
+        return None;
+    }
+    Some(Value::GridPlacement(lines))
+}
+
+fn parse_grid_line(value: &str) -> Option<GridLine> {
+    let words: Vec<&str> = value.split_whitespace().collect();
+    match words[..] {
+        [word] if word.eq_ignore_ascii_case("auto") => Some(GridLine::Auto),
+        [span, count] if span.eq_ignore_ascii_case("span") => match count.parse() {
+            Ok(count) if count > 0 => Some(GridLine::Span(count)),
+            _ => None,
+        },
+        [word] if word.eq_ignore_ascii_case("span") => None,
+        [word] => match word.parse() {
+            // There is no line `0`.
+            Ok(0) => None,
+            Ok(line) => Some(GridLine::Line(line)),
+            Err(_) if word.chars().all(valid_identifier_char) => {
+                Some(GridLine::Name(word.to_string()))
+            }
+            Err(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Parse `src: url(...) format(...), local(...)`.
+fn parse_font_face_sources(value: &str) -> Vec<FontFaceSource> {
+    split_top_level_commas(value)
+        .into_iter()
+        .filter_map(|item| {
+            if let Some((url, rest)) = parse_function(item, "url") {
+                let format = parse_function(rest, "format").map(|(format, _)| format);
+                return Some(FontFaceSource::Url { url, format });
+            }
+            parse_function(item, "local").map(|(name, _)| FontFaceSource::Local(name))
+        })
+        .collect()
+}
+
+fn parse_font_weight_range(value: &str) -> Option<(FontWeight, FontWeight)> {
+    let weights = value
+        .split_whitespace()
+        .map(|w| match &*w.to_ascii_lowercase() {
+            "normal" => Some(400.),
+            "bold" => Some(700.),
+            w => w.parse::<f32>().ok().filter(|w| (1.0..=1000.0).contains(w)),
+        })
+        .collect::<Option<Vec<f32>>>()?;
+    match weights[..] {
+        [w] => Some((FontWeight(w), FontWeight(w))),
+        [min, max] => Some((FontWeight(min.min(max)), FontWeight(min.max(max)))),
+        _ => None,
+    }
+}
+
+fn parse_font_face_style(value: &str) -> Option<FontStyle> {
+    // An angle after `oblique` is ignored.
+    match &*value.split_whitespace().next()?.to_ascii_lowercase() {
+        "normal" => Some(FontStyle::Normal),
+        "italic" => Some(FontStyle::Italic),
+        "oblique" => Some(FontStyle::Oblique),
+        _ => None,
+    }
+}
+
+/// Parse `unicode-range: U+0025-00FF, U+4??`.
+fn parse_unicode_range(value: &str) -> Vec<RangeInclusive<u32>> {
+    value
+        .split(',')
+        .filter_map(|item| {
+            let item = item.trim();
+            let item = item
+                .strip_prefix("U+")
+                .or_else(|| item.strip_prefix("u+"))?;
+            let (start, end) = match item.find('-') {
+                Some(i) => (
+                    u32::from_str_radix(&item[..i], 16).ok()?,
+                    u32::from_str_radix(&item[i + 1..], 16).ok()?,
+                ),
+                // `?` is a wildcard of a hex digit.
+                None => (
+                    u32::from_str_radix(&item.replace('?', "0"), 16).ok()?,
+                    u32::from_str_radix(&item.replace('?', "F"), 16).ok()?,
+                ),
+            };
+            let end = end.min(0x10FFFF);
+            if start > end {
+                return None;
+            }
+            Some(start..=end)
+        })
+        .collect()
+}
+
 fn valid_identifier_char(c: char) -> bool {
     // TODO: Include U+00A0 and higher.
     matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_')
@@ -239,17 +1127,23 @@ h3 {
         let stylesheet = Stylesheet::new(rules);
 
         for rule in stylesheet.rules {
-            let Selector::Simple(selector) = &rule.selectors[0];
+            let Selector::Simple(selector) = &rule.selectors[0] else {
+                panic!("selector should be Simple");
+            };
             assert_eq!(selector.tag_name.as_ref().unwrap(), &"h1");
             assert_eq!(selector.id, None);
             assert_eq!(&selector.class.len(), &0);
 
-            let Selector::Simple(selector) = &rule.selectors[1];
+            let Selector::Simple(selector) = &rule.selectors[1] else {
+                panic!("selector should be Simple");
+            };
             assert_eq!(&selector.tag_name.as_ref().unwrap(), &"h2");
             assert_eq!(selector.id, None);
             assert_eq!(&selector.class.len(), &0);
 
-            let Selector::Simple(selector) = &rule.selectors[2];
+            let Selector::Simple(selector) = &rule.selectors[2] else {
+                panic!("selector should be Simple");
+            };
             assert_eq!(selector.tag_name.as_ref().unwrap(), &"h3");
             assert_eq!(selector.id, None);
             assert_eq!(&selector.class.len(), &0);
@@ -282,7 +1176,9 @@ h3 {
         let stylesheet = Stylesheet::new(rules);
 
         for rule in stylesheet.rules {
-            let Selector::Simple(selector) = &rule.selectors[0];
+            let Selector::Simple(selector) = &rule.selectors[0] else {
+                panic!("selector should be Simple");
+            };
             assert_eq!(selector.tag_name.as_ref().unwrap(), &"div");
             assert_eq!(selector.id, None);
             assert_eq!(&selector.class[0], &"note");
@@ -307,6 +1203,45 @@ h3 {
         }
     }
 
+    #[test]
+    fn test_parse_negative_length() {
+        let input = "p { margin-top: -5px; margin-bottom: +.5em; margin-left: -moz-auto; }";
+
+        let mut p = CSSParser::new(input.into());
+
+        let rules = p.parse_rules(Origin::Author);
+        let values: Vec<&Value> = rules[0].declarations.iter().map(|d| &d.value).collect();
+        assert_eq!(values[0], &Value::Length(-5.0, Unit::Px));
+        assert_eq!(values[1], &Value::Length(0.5, Unit::Em));
+        assert_eq!(values[2], &Value::Keyword("-moz-auto".into()));
+    }
+
+    #[test]
+    fn test_parse_invalid_declarations() {
+        let input = "
+.a { flex-grow: 1 }
+.b { width: 10vw; height: 5rem; gap: 10px 20px; margin-top: 1px; }
+";
+
+        let mut p = CSSParser::new(input.into());
+
+        let rules = p.parse_rules(Origin::Author);
+        assert_eq!(rules[0].declarations[0].value, Value::Number(1.0));
+
+        // Unknown units and unexpected tokens make declarations invalid, and the next ones follow.
+        let values: Vec<&Value> = rules[1].declarations.iter().map(|d| &d.value).collect();
+        assert_eq!(
+            values,
+            vec![
+                &Value::None,
+                &Value::None,
+                &Value::None,
+                &Value::Length(1.0, Unit::Px)
+            ]
+        );
+        assert_eq!(rules[1].declarations[3].name, "margin-top");
+    }
+
     #[test]
     fn test_parse_id() {
         let input = "#answer { display: none; }";
@@ -317,7 +1252,9 @@ h3 {
         let stylesheet = Stylesheet::new(rules);
 
         for rule in stylesheet.rules {
-            let Selector::Simple(selector) = &rule.selectors[0];
+            let Selector::Simple(selector) = &rule.selectors[0] else {
+                panic!("selector should be Simple");
+            };
             assert_eq!(selector.tag_name, None);
             assert_eq!(selector.id.as_ref().unwrap(), "answer");
             assert_eq!(&selector.class.len(), &0);
@@ -348,7 +1285,9 @@ h3 {
         let stylesheet = Stylesheet::new(rules);
 
         for rule in stylesheet.rules {
-            let Selector::Simple(selector) = &rule.selectors[0];
+            let Selector::Simple(selector) = &rule.selectors[0] else {
+                panic!("selector should be Simple");
+            };
             assert_eq!(selector.tag_name, None);
             assert_eq!(selector.id, None);
             assert_eq!(&selector.class.len(), &1);
@@ -379,7 +1318,9 @@ h1 {}
 
         assert_eq!(&stylesheet.rules.len(), &1);
         for rule in stylesheet.rules {
-            let Selector::Simple(selector) = &rule.selectors[0];
+            let Selector::Simple(selector) = &rule.selectors[0] else {
+                panic!("selector should be Simple");
+            };
             assert_eq!(selector.tag_name, None);
             assert_eq!(selector.id, None);
             assert_eq!(&selector.class.len(), &1);
@@ -412,7 +1353,9 @@ h1 {}
 
         assert_eq!(&stylesheet.rules.len(), &1);
         for rule in stylesheet.rules {
-            let Selector::Simple(selector) = &rule.selectors[0];
+            let Selector::Simple(selector) = &rule.selectors[0] else {
+                panic!("selector should be Simple");
+            };
             assert_eq!(selector.tag_name, None);
             assert_eq!(selector.id, None);
             assert_eq!(&selector.class.len(), &1);
@@ -445,37 +1388,259 @@ h1 {}
     }
 
     #[test]
-    fn test_parse_keyword_array() {
+    fn test_parse_font_family() {
         let input = "
-.class {
+.generic {
     font-family: serif,  sans-serif,cursive,;
 }
+.named {
+    font-family: \"Noto Sans JP\", Hiragino  Kaku Gothic, 'serif', system-ui, ui-monospace, emoji;
+}
+.inherit {
+    font-family: inherit;
+}
+.invalid {
+    font-family: Foo, inherit;
+}
 ";
 
         let mut p = CSSParser::new(input.into());
 
         let rules = p.parse_rules(Origin::Author);
-        let stylesheet = Stylesheet::new(rules);
+        let values: Vec<&Value> = rules.iter().map(|r| &r.declarations[0].value).collect();
+
+        assert_eq!(
+            values[0],
+            &Value::FontFamily(vec![
+                FontFamily::Generic(GenericFontFamily::Serif),
+                FontFamily::Generic(GenericFontFamily::SansSerif),
+                FontFamily::Generic(GenericFontFamily::Cursive),
+            ])
+        );
+        // A quoted generic name is a family name.
+        assert_eq!(
+            values[1],
+            &Value::FontFamily(vec![
+                FontFamily::Named("Noto Sans JP".into()),
+                FontFamily::Named("Hiragino Kaku Gothic".into()),
+                FontFamily::Named("serif".into()),
+                FontFamily::Generic(GenericFontFamily::SystemUi),
+                FontFamily::Generic(GenericFontFamily::UiMonospace),
+                FontFamily::Generic(GenericFontFamily::Emoji),
+            ])
+        );
+        assert_eq!(values[2], &Value::CssWide(CssWideKeyword::Inherit));
+        assert_eq!(values[3], &Value::None);
+    }
 
-        assert_eq!(&stylesheet.rules.len(), &1);
-        for rule in stylesheet.rules {
-            let Selector::Simple(selector) = &rule.selectors[0];
-            assert_eq!(&selector.class[0], &"class");
+    #[test]
+    fn test_parse_grid() {
+        let input = "
+.a {
+    grid-template-columns: 100px repeat(auto-fill, minmax(10%, 1fr)) 2em;
+}
+.b {
+    grid-template-rows: repeat(2, 1fr auto);
+}
+.c {
+    grid-template-areas: \"head head\" 'side .';
+}
+.d {
+    grid-area: 2 / span 3 / head;
+}
+.e {
+    grid-template-columns: minmax(1fr, 100px);
+}
+.f {
+    grid-row: span;
+}
+.g {
+    grid-template-columns: repeat(auto-fill, 1fr);
+}
+.h {
+    grid-template-columns: repeat(auto-fill, minmax(auto, 20px) auto);
+}
+.i {
+    grid-template-columns: repeat(auto-fill, minmax(auto, 20px));
+}
+";
 
-            let declaration = &rule.declarations[0];
-            assert_eq!(&declaration.name, "font-family");
-            if let Value::KeywordArray(arr) = &declaration.value {
-                assert_eq!(
-                    arr,
-                    &[
-                        "serif".to_string(),
-                        "sans-serif".to_string(),
-                        "cursive".to_string()
-                    ]
-                );
-            } else {
-                panic!("declaration.value should has KeywordArray");
-            };
-        }
+        let mut p = CSSParser::new(input.into());
+
+        let rules = p.parse_rules(Origin::Author);
+        let values: Vec<&Value> = rules.iter().map(|r| &r.declarations[0].value).collect();
+
+        assert_eq!(
+            values[0],
+            &Value::TrackList(vec![
+                TrackListItem::Size(TrackSize::Breadth(TrackBreadth::Length(100., Unit::Px))),
+                TrackListItem::Repeat(
+                    RepeatCount::AutoFill,
+                    vec![TrackSize::MinMax(
+                        TrackBreadth::Length(10., Unit::Percent),
+                        TrackBreadth::Flex(1.)
+                    )]
+                ),
+                TrackListItem::Size(TrackSize::Breadth(TrackBreadth::Length(2., Unit::Em))),
+            ])
+        );
+        assert_eq!(
+            values[1],
+            &Value::TrackList(vec![TrackListItem::Repeat(
+                RepeatCount::Count(2),
+                vec![
+                    TrackSize::Breadth(TrackBreadth::Flex(1.)),
+                    TrackSize::Breadth(TrackBreadth::Auto)
+                ]
+            )])
+        );
+        assert_eq!(
+            values[2],
+            &Value::GridTemplateAreas(vec!["head head".into(), "side .".into()])
+        );
+        assert_eq!(
+            values[3],
+            &Value::GridPlacement(vec![
+                GridLine::Line(2),
+                GridLine::Span(3),
+                GridLine::Name("head".into())
+            ])
+        );
+        assert_eq!(
+            values[3].component(1),
+            Value::GridPlacement(vec![GridLine::Span(3)])
+        );
+        // A missing line of a shorthand is the name at the opposite side, or `auto`.
+        assert_eq!(
+            values[3].component(3),
+            Value::GridPlacement(vec![GridLine::Auto])
+        );
+        let area = Value::GridPlacement(vec![GridLine::Name("head".into())]);
+        assert_eq!(
+            area.component(3),
+            Value::GridPlacement(vec![GridLine::Name("head".into())])
+        );
+
+        // Invalid declarations are ignored.
+        assert_eq!(values[4], &Value::None);
+        assert_eq!(values[5], &Value::None);
+        // `auto-fill` does not repeat flexible or intrinsic sizes.
+        assert_eq!(values[6], &Value::None);
+        assert_eq!(values[7], &Value::None);
+        assert_eq!(
+            values[8],
+            &Value::TrackList(vec![TrackListItem::Repeat(
+                RepeatCount::AutoFill,
+                vec![TrackSize::MinMax(
+                    TrackBreadth::Auto,
+                    TrackBreadth::Length(20., Unit::Px)
+                )]
+            )])
+        );
+    }
+
+    #[test]
+    fn test_parse_import() {
+        let input = "
+@import url(base.css);
+@import url(\"theme.css\") screen and (min-width: 600px), print;
+@import 'print.css' not print;
+@unknown foo { .a { color: red; } }
+@import url(ignored-after-at-rule.css);
+.class {
+    color: red;
+}
+@import url(ignored.css);
+";
+
+        let mut p = CSSParser::new(input.into());
+
+        let stylesheet = p.parse_stylesheet(Origin::Author);
+
+        assert_eq!(stylesheet.imports.len(), 3);
+
+        let import = &stylesheet.imports[0];
+        assert_eq!(import.url, "base.css");
+        assert!(import.media.queries.is_empty());
+
+        let import = &stylesheet.imports[1];
+        assert_eq!(import.url, "theme.css");
+        assert_eq!(
+            import.media.queries,
+            vec![
+                MediaQuery::new(
+                    None,
+                    MediaType::Screen,
+                    vec![MediaFeature::Width(Range::Min(600.))]
+                ),
+                MediaQuery::new(None, MediaType::Print, vec![]),
+            ]
+        );
+        assert!(import.media.evaluate(&Device::new(800., 600.)));
+        assert!(!import.media.evaluate(&Device::new(400., 600.)));
+
+        let import = &stylesheet.imports[2];
+        assert_eq!(import.url, "print.css");
+        assert!(import.media.evaluate(&Device::new(400., 600.)));
+
+        assert_eq!(stylesheet.rules.len(), 1);
+        let Selector::Simple(selector) = &stylesheet.rules[0].selectors[0] else {
+            panic!("selector should be Simple");
+        };
+        assert_eq!(&selector.class[0], &"class");
+    }
+
+    #[test]
+    fn test_parse_font_face() {
+        let input = "
+@font-face {
+    font-family: \"My Font\";
+    src: local(MyFont-Bold), url(fonts/my-font.woff2) format(\"woff2\"), url(my-font(1).ttf);
+    font-weight: 600 800;
+    font-style: italic;
+    unicode-range: U+0000-00FF, U+4??;
+}
+@font-face { font-family: Broken; }
+.class {
+    font-family: 'My Font', serif;
+}
+";
+
+        let mut p = CSSParser::new(input.into());
+
+        let stylesheet = p.parse_stylesheet(Origin::Author);
+        assert_eq!(stylesheet.font_faces.len(), 1);
+
+        let font_face = &stylesheet.font_faces[0];
+        assert_eq!(font_face.family_name, "My Font");
+        assert_eq!(
+            font_face.sources,
+            vec![
+                FontFaceSource::Local("MyFont-Bold".into()),
+                FontFaceSource::Url {
+                    url: "fonts/my-font.woff2".into(),
+                    format: Some("woff2".into()),
+                },
+                FontFaceSource::Url {
+                    url: "my-font(1).ttf".into(),
+                    format: None,
+                },
+            ]
+        );
+        assert_eq!(font_face.weight, (FontWeight(600.), FontWeight(800.)));
+        assert_eq!(font_face.style, FontStyle::Italic);
+        assert_eq!(font_face.unicode_range, vec![0x0..=0xFF, 0x400..=0x4FF]);
+        assert!(font_face.contains('a'));
+        assert!(font_face.contains('Ѐ'));
+        assert!(!font_face.contains('あ'));
+
+        let declaration = &stylesheet.rules[0].declarations[0];
+        assert_eq!(
+            declaration.value,
+            Value::FontFamily(vec![
+                FontFamily::Named("My Font".into()),
+                FontFamily::Generic(GenericFontFamily::Serif)
+            ])
+        );
     }
 }
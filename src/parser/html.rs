@@ -1,8 +1,9 @@
-use std::collections::HashMap;
-
 use super::Parser;
 use crate::dom::{AttrMap, Node};
 
+/// Elements whose first newline in the content is ignored.
+pub(crate) const LEADING_NEWLINE_ELEMENTS: [&str; 3] = ["pre", "textarea", "listing"];
+
 pub struct HTMLParser {
     pos: usize,
     input: String,
@@ -19,7 +20,7 @@ impl HTMLParser {
         if nodes.len() == 1 {
             nodes.swap_remove(0)
         } else {
-            Node::new_element("html".into(), HashMap::new(), nodes)
+            Node::new_element("html".into(), AttrMap::new(), nodes)
         }
     }
 
@@ -65,6 +66,15 @@ impl HTMLParser {
             self.consume_char();
         }
 
+        // A newline just after the start tag is ignored, so that content can start on a new line.
+        // refer: https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody
+        if LEADING_NEWLINE_ELEMENTS.contains(&tag_name.to_ascii_lowercase().as_str())
+            && !self.eof()
+            && self.next_char() == '\n'
+        {
+            self.consume_char();
+        }
+
         let children = self.parse_nodes();
 
         if self.eof() {
@@ -106,7 +116,7 @@ impl HTMLParser {
     }
 
     fn parse_attributes(&mut self) -> AttrMap {
-        let mut attrs = HashMap::new();
+        let mut attrs = AttrMap::new();
         loop {
             if self.eof() {
                 break;
@@ -188,6 +198,7 @@ impl Parser for HTMLParser {
 mod test {
     use super::*;
     use crate::dom::*;
+    use crate::serializer::SerializeMode;
 
     #[test]
     fn test_parse_node() {
@@ -385,4 +396,23 @@ mod test {
             assert_eq!(&elm.attributes.get("id").unwrap(), &"></body>");
         }
     }
+
+    #[test]
+    fn test_round_trip() {
+        let input = "<div class=\"a\"><p>text <em>em</em> more</p><p id=\"b\">b</p></div>";
+
+        let mut p = HTMLParser::new(input.into());
+        let div = p.run();
+        assert_eq!(div.outer_html(SerializeMode::Compact), input);
+
+        // Attributes keep the source order, and a leading newline in `pre` is kept.
+        let input =
+            "<div title=\"t\" id=\"a\" class=\"c\"><pre>\n\ncode</pre><pre>text</pre></div>";
+
+        let mut p = HTMLParser::new(input.into());
+        let div = p.run();
+        let text = &div.children[0].children[0].node_type;
+        assert!(matches!(text, NodeType::Text(text) if text == "\ncode"));
+        assert_eq!(div.outer_html(SerializeMode::Compact), input);
+    }
 }
@@ -0,0 +1,1333 @@
+// refer: https://drafts.csswg.org/css-cascade/#defaulting
+
+use std::sync::Arc;
+
+use crate::computed_values::{
+    AlignContent, AlignItems, BorderCollapse, BoxSizing, CaptionSide, Clear, ComputedValues,
+    FlexBasis, FlexDirection, FlexWrap, Float, GridAutoFlow, GridTemplateAreas, JustifyContent,
+    LengthOrAuto, LengthPercentage, LineHeight, NamedArea, Position, TableLayout, TrackBreadth,
+    TrackListItem, TrackSize, VerticalAlign,
+};
+use crate::cssom::{self, Color, CssWideKeyword, GridLine, Unit, Value};
+use crate::layout::font::{FontStyle, FontWeight};
+use crate::style::{Display, WordBreak};
+
+/// Font size of `medium`, which absolute-size keywords are relative to.
+pub const DEFAULT_FONT_SIZE: f32 = 16.;
+
+/// Scaling factor of `larger` and `smaller`.
+const FONT_SIZE_RATIO: f32 = 1.2;
+
+/// Supported longhand properties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LonghandId {
+    FontSize,
+    FontFamily,
+    FontWeight,
+    FontStyle,
+    Color,
+    LineHeight,
+    WordBreak,
+    Display,
+    Width,
+    Height,
+    MinWidth,
+    MinHeight,
+    MaxWidth,
+    MaxHeight,
+    BoxSizing,
+    Position,
+    Top,
+    Right,
+    Bottom,
+    Left,
+    ZIndex,
+    Float,
+    Clear,
+    VerticalAlign,
+    MarginTop,
+    MarginRight,
+    MarginBottom,
+    MarginLeft,
+    BorderTopWidth,
+    BorderRightWidth,
+    BorderBottomWidth,
+    BorderLeftWidth,
+    BorderColor,
+    PaddingTop,
+    PaddingRight,
+    PaddingBottom,
+    PaddingLeft,
+    FlexDirection,
+    FlexWrap,
+    FlexGrow,
+    FlexShrink,
+    FlexBasis,
+    Order,
+    JustifyContent,
+    AlignItems,
+    AlignSelf,
+    AlignContent,
+    RowGap,
+    ColumnGap,
+    GridTemplateColumns,
+    GridTemplateRows,
+    GridTemplateAreas,
+    GridAutoColumns,
+    GridAutoRows,
+    GridAutoFlow,
+    GridRowStart,
+    GridRowEnd,
+    GridColumnStart,
+    GridColumnEnd,
+    JustifyItems,
+    JustifySelf,
+    TableLayout,
+    BorderCollapse,
+    BorderSpacing,
+    CaptionSide,
+    BackgroundColor,
+}
+
+const LONGHANDS: [(&str, LonghandId); 66] = [
+    ("font-size", LonghandId::FontSize),
+    ("font-family", LonghandId::FontFamily),
+    ("font-weight", LonghandId::FontWeight),
+    ("font-style", LonghandId::FontStyle),
+    ("color", LonghandId::Color),
+    ("line-height", LonghandId::LineHeight),
+    ("word-break", LonghandId::WordBreak),
+    ("display", LonghandId::Display),
+    ("width", LonghandId::Width),
+    ("height", LonghandId::Height),
+    ("min-width", LonghandId::MinWidth),
+    ("min-height", LonghandId::MinHeight),
+    ("max-width", LonghandId::MaxWidth),
+    ("max-height", LonghandId::MaxHeight),
+    ("box-sizing", LonghandId::BoxSizing),
+    ("position", LonghandId::Position),
+    ("top", LonghandId::Top),
+    ("right", LonghandId::Right),
+    ("bottom", LonghandId::Bottom),
+    ("left", LonghandId::Left),
+    ("z-index", LonghandId::ZIndex),
+    ("float", LonghandId::Float),
+    ("clear", LonghandId::Clear),
+    ("vertical-align", LonghandId::VerticalAlign),
+    ("margin-top", LonghandId::MarginTop),
+    ("margin-right", LonghandId::MarginRight),
+    ("margin-bottom", LonghandId::MarginBottom),
+    ("margin-left", LonghandId::MarginLeft),
+    ("border-top-width", LonghandId::BorderTopWidth),
+    ("border-right-width", LonghandId::BorderRightWidth),
+    ("border-bottom-width", LonghandId::BorderBottomWidth),
+    ("border-left-width", LonghandId::BorderLeftWidth),
+    ("border-color", LonghandId::BorderColor),
+    ("padding-top", LonghandId::PaddingTop),
+    ("padding-right", LonghandId::PaddingRight),
+    ("padding-bottom", LonghandId::PaddingBottom),
+    ("padding-left", LonghandId::PaddingLeft),
+    ("flex-direction", LonghandId::FlexDirection),
+    ("flex-wrap", LonghandId::FlexWrap),
+    ("flex-grow", LonghandId::FlexGrow),
+    ("flex-shrink", LonghandId::FlexShrink),
+    ("flex-basis", LonghandId::FlexBasis),
+    ("order", LonghandId::Order),
+    ("justify-content", LonghandId::JustifyContent),
+    ("align-items", LonghandId::AlignItems),
+    ("align-self", LonghandId::AlignSelf),
+    ("align-content", LonghandId::AlignContent),
+    ("row-gap", LonghandId::RowGap),
+    ("column-gap", LonghandId::ColumnGap),
+    ("grid-template-columns", LonghandId::GridTemplateColumns),
+    ("grid-template-rows", LonghandId::GridTemplateRows),
+    ("grid-template-areas", LonghandId::GridTemplateAreas),
+    ("grid-auto-columns", LonghandId::GridAutoColumns),
+    ("grid-auto-rows", LonghandId::GridAutoRows),
+    ("grid-auto-flow", LonghandId::GridAutoFlow),
+    ("grid-row-start", LonghandId::GridRowStart),
+    ("grid-row-end", LonghandId::GridRowEnd),
+    ("grid-column-start", LonghandId::GridColumnStart),
+    ("grid-column-end", LonghandId::GridColumnEnd),
+    ("justify-items", LonghandId::JustifyItems),
+    ("justify-self", LonghandId::JustifySelf),
+    ("table-layout", LonghandId::TableLayout),
+    ("border-collapse", LonghandId::BorderCollapse),
+    ("border-spacing", LonghandId::BorderSpacing),
+    ("caption-side", LonghandId::CaptionSide),
+    ("background-color", LonghandId::BackgroundColor),
+];
+
+/// Shorthands, which set every longhand to a single value,
+/// or each longhand to a component of the value in order.
+const SHORTHANDS: [(&str, &[LonghandId]); 8] = [
+    (
+        "margin",
+        &[
+            LonghandId::MarginTop,
+            LonghandId::MarginRight,
+            LonghandId::MarginBottom,
+            LonghandId::MarginLeft,
+        ],
+    ),
+    (
+        "border",
+        &[
+            LonghandId::BorderTopWidth,
+            LonghandId::BorderRightWidth,
+            LonghandId::BorderBottomWidth,
+            LonghandId::BorderLeftWidth,
+        ],
+    ),
+    (
+        "padding",
+        &[
+            LonghandId::PaddingTop,
+            LonghandId::PaddingRight,
+            LonghandId::PaddingBottom,
+            LonghandId::PaddingLeft,
+        ],
+    ),
+    ("gap", &[LonghandId::RowGap, LonghandId::ColumnGap]),
+    (
+        "grid-row",
+        &[LonghandId::GridRowStart, LonghandId::GridRowEnd],
+    ),
+    (
+        "grid-column",
+        &[LonghandId::GridColumnStart, LonghandId::GridColumnEnd],
+    ),
+    (
+        "grid-area",
+        &[
+            LonghandId::GridRowStart,
+            LonghandId::GridColumnStart,
+            LonghandId::GridRowEnd,
+            LonghandId::GridColumnEnd,
+        ],
+    ),
+    ("background", &[LonghandId::BackgroundColor]),
+];
+
+impl LonghandId {
+    pub fn from_name(name: &str) -> Option<LonghandId> {
+        LONGHANDS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, id)| *id)
+    }
+
+    pub fn name(self) -> &'static str {
+        LONGHANDS.iter().find(|(_, id)| *id == self).unwrap().0
+    }
+
+    pub fn is_inherited(self) -> bool {
+        matches!(
+            self,
+            LonghandId::FontSize
+                | LonghandId::FontFamily
+                | LonghandId::FontWeight
+                | LonghandId::FontStyle
+                | LonghandId::Color
+                | LonghandId::LineHeight
+                | LonghandId::WordBreak
+                | LonghandId::BorderCollapse
+                | LonghandId::BorderSpacing
+                | LonghandId::CaptionSide
+        )
+    }
+
+    /// Longhands of a shorthand property.
+    pub fn from_shorthand(name: &str) -> Option<&'static [LonghandId]> {
+        SHORTHANDS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, longhands)| *longhands)
+    }
+
+    /// Shorthand property which sets this longhand.
+    pub fn shorthand(self) -> Option<&'static str> {
+        SHORTHANDS
+            .iter()
+            .find(|(_, longhands)| longhands.contains(&self))
+            .map(|(name, _)| *name)
+    }
+
+    /// Set a declared value to `style`.
+    /// `parent` is used for CSS-wide keywords. `revert` in author origin must be rolled back
+    /// to user agent origin in cascade, so `revert` here is declared in user agent origin.
+    pub fn cascade(self, value: &Value, parent: &ComputedValues, style: &mut ComputedValues) {
+        let keyword = match value {
+            Value::CssWide(keyword) => keyword,
+            value => return self.set(value, parent, style),
+        };
+        let initial = ComputedValues::initial();
+        let from = match keyword {
+            CssWideKeyword::Initial => initial,
+            CssWideKeyword::Inherit => parent,
+            // `revert` in user agent origin rolls back to no declaration, which is `unset`.
+            CssWideKeyword::Unset | CssWideKeyword::Revert | CssWideKeyword::RevertLayer => {
+                if self.is_inherited() {
+                    parent
+                } else {
+                    initial
+                }
+            }
+        };
+        self.copy(from, style);
+    }
+
+    /// Convert a declared value to the computed value. An invalid value is ignored.
+    /// `font-size` must be set before other properties, which can be relative to it.
+    fn set(self, value: &Value, parent: &ComputedValues, style: &mut ComputedValues) {
+        let font_size = style.font.font_size;
+        match self {
+            LonghandId::FontSize => {
+                if let Some(size) = to_font_size(value, parent.font.font_size) {
+                    Arc::make_mut(&mut style.font).font_size = size;
+                }
+            }
+            LonghandId::FontFamily => {
+                if let Value::FontFamily(families) = value {
+                    Arc::make_mut(&mut style.font).font_family = families.clone();
+                }
+            }
+            LonghandId::FontWeight => {
+                if let Some(weight) = to_font_weight(value) {
+                    Arc::make_mut(&mut style.font).font_weight = weight;
+                }
+            }
+            LonghandId::FontStyle => {
+                if let Some(font_style) = to_font_style(value) {
+                    Arc::make_mut(&mut style.font).font_style = font_style;
+                }
+            }
+            LonghandId::Color => {
+                if let Some(color) = to_color(value) {
+                    Arc::make_mut(&mut style.inherited_text).color = color;
+                }
+            }
+            LonghandId::LineHeight => {
+                if let Some(line_height) = to_line_height(value, font_size) {
+                    Arc::make_mut(&mut style.inherited_text).line_height = line_height;
+                }
+            }
+            LonghandId::WordBreak => {
+                if let Some(word_break) = to_word_break(value) {
+                    Arc::make_mut(&mut style.inherited_text).word_break = word_break;
+                }
+            }
+            LonghandId::Display => {
+                if let Some(display) = to_display(value) {
+                    Arc::make_mut(&mut style.box_).display = display;
+                }
+            }
+            LonghandId::Width => {
+                if let Some(width) = to_width(value, font_size) {
+                    Arc::make_mut(&mut style.box_).width = width;
+                }
+            }
+            LonghandId::Height => {
+                if let Some(height) = to_length_or_auto(value, font_size) {
+                    Arc::make_mut(&mut style.box_).height = height;
+                }
+            }
+            LonghandId::MinWidth => {
+                if let Some(width) = to_min_size(value, font_size) {
+                    Arc::make_mut(&mut style.box_).min_width = width;
+                }
+            }
+            LonghandId::MinHeight => {
+                if let Some(height) = to_min_size(value, font_size) {
+                    Arc::make_mut(&mut style.box_).min_height = height;
+                }
+            }
+            LonghandId::MaxWidth => {
+                if let Some(width) = to_max_size(value, font_size) {
+                    Arc::make_mut(&mut style.box_).max_width = width;
+                }
+            }
+            LonghandId::MaxHeight => {
+                if let Some(height) = to_max_size(value, font_size) {
+                    Arc::make_mut(&mut style.box_).max_height = height;
+                }
+            }
+            LonghandId::BoxSizing => {
+                if let Some(box_sizing) = to_box_sizing(value) {
+                    Arc::make_mut(&mut style.box_).box_sizing = box_sizing;
+                }
+            }
+            LonghandId::Position => {
+                if let Some(position) = to_position(value) {
+                    Arc::make_mut(&mut style.box_).position = position;
+                }
+            }
+            LonghandId::Top => {
+                if let Some(top) = to_length_or_auto(value, font_size) {
+                    Arc::make_mut(&mut style.box_).top = top;
+                }
+            }
+            LonghandId::Right => {
+                if let Some(right) = to_length_or_auto(value, font_size) {
+                    Arc::make_mut(&mut style.box_).right = right;
+                }
+            }
+            LonghandId::Bottom => {
+                if let Some(bottom) = to_length_or_auto(value, font_size) {
+                    Arc::make_mut(&mut style.box_).bottom = bottom;
+                }
+            }
+            LonghandId::Left => {
+                if let Some(left) = to_length_or_auto(value, font_size) {
+                    Arc::make_mut(&mut style.box_).left = left;
+                }
+            }
+            LonghandId::ZIndex => {
+                if let Some(z_index) = to_z_index(value) {
+                    Arc::make_mut(&mut style.box_).z_index = z_index;
+                }
+            }
+            LonghandId::Float => {
+                if let Some(float) = to_float(value) {
+                    Arc::make_mut(&mut style.box_).float = float;
+                }
+            }
+            LonghandId::Clear => {
+                if let Some(clear) = to_clear(value) {
+                    Arc::make_mut(&mut style.box_).clear = clear;
+                }
+            }
+            LonghandId::VerticalAlign => {
+                if let Some(vertical_align) = to_vertical_align(value) {
+                    Arc::make_mut(&mut style.box_).vertical_align = vertical_align;
+                }
+            }
+            LonghandId::MarginTop => {
+                if let Some(margin) = to_length_or_auto(value, font_size) {
+                    Arc::make_mut(&mut style.margin).margin_top = margin;
+                }
+            }
+            LonghandId::MarginRight => {
+                if let Some(margin) = to_length_or_auto(value, font_size) {
+                    Arc::make_mut(&mut style.margin).margin_right = margin;
+                }
+            }
+            LonghandId::MarginBottom => {
+                if let Some(margin) = to_length_or_auto(value, font_size) {
+                    Arc::make_mut(&mut style.margin).margin_bottom = margin;
+                }
+            }
+            LonghandId::MarginLeft => {
+                if let Some(margin) = to_length_or_auto(value, font_size) {
+                    Arc::make_mut(&mut style.margin).margin_left = margin;
+                }
+            }
+            LonghandId::BorderTopWidth => {
+                if let Some(width) = to_length(value, font_size) {
+                    Arc::make_mut(&mut style.border).border_top_width = width;
+                }
+            }
+            LonghandId::BorderRightWidth => {
+                if let Some(width) = to_length(value, font_size) {
+                    Arc::make_mut(&mut style.border).border_right_width = width;
+                }
+            }
+            LonghandId::BorderBottomWidth => {
+                if let Some(width) = to_length(value, font_size) {
+                    Arc::make_mut(&mut style.border).border_bottom_width = width;
+                }
+            }
+            LonghandId::BorderLeftWidth => {
+                if let Some(width) = to_length(value, font_size) {
+                    Arc::make_mut(&mut style.border).border_left_width = width;
+                }
+            }
+            LonghandId::BorderColor => {
+                if let Some(color) = to_color(value) {
+                    Arc::make_mut(&mut style.border).border_color = to_visible(color);
+                }
+            }
+            LonghandId::PaddingTop => {
+                if let Some(padding) = to_length(value, font_size) {
+                    Arc::make_mut(&mut style.padding).padding_top = padding;
+                }
+            }
+            LonghandId::PaddingRight => {
+                if let Some(padding) = to_length(value, font_size) {
+                    Arc::make_mut(&mut style.padding).padding_right = padding;
+                }
+            }
+            LonghandId::PaddingBottom => {
+                if let Some(padding) = to_length(value, font_size) {
+                    Arc::make_mut(&mut style.padding).padding_bottom = padding;
+                }
+            }
+            LonghandId::PaddingLeft => {
+                if let Some(padding) = to_length(value, font_size) {
+                    Arc::make_mut(&mut style.padding).padding_left = padding;
+                }
+            }
+            LonghandId::FlexDirection => {
+                if let Some(direction) = to_flex_direction(value) {
+                    Arc::make_mut(&mut style.flex).flex_direction = direction;
+                }
+            }
+            LonghandId::FlexWrap => {
+                if let Some(wrap) = to_flex_wrap(value) {
+                    Arc::make_mut(&mut style.flex).flex_wrap = wrap;
+                }
+            }
+            LonghandId::FlexGrow => {
+                if let Some(grow) = to_non_negative_number(value) {
+                    Arc::make_mut(&mut style.flex).flex_grow = grow;
+                }
+            }
+            LonghandId::FlexShrink => {
+                if let Some(shrink) = to_non_negative_number(value) {
+                    Arc::make_mut(&mut style.flex).flex_shrink = shrink;
+                }
+            }
+            LonghandId::FlexBasis => {
+                if let Some(basis) = to_flex_basis(value, font_size) {
+                    Arc::make_mut(&mut style.flex).flex_basis = basis;
+                }
+            }
+            LonghandId::Order => {
+                if let Some(order) = to_integer(value) {
+                    Arc::make_mut(&mut style.flex).order = order;
+                }
+            }
+            LonghandId::JustifyContent => {
+                if let Some(justify_content) = to_justify_content(value) {
+                    Arc::make_mut(&mut style.flex).justify_content = justify_content;
+                }
+            }
+            LonghandId::AlignItems => {
+                if let Some(align_items) = to_align_items(value) {
+                    Arc::make_mut(&mut style.flex).align_items = align_items;
+                }
+            }
+            LonghandId::AlignSelf => {
+                if let Some(align_self) = to_align_self(value) {
+                    Arc::make_mut(&mut style.flex).align_self = align_self;
+                }
+            }
+            LonghandId::AlignContent => {
+                if let Some(align_content) = to_align_content(value) {
+                    Arc::make_mut(&mut style.flex).align_content = align_content;
+                }
+            }
+            LonghandId::RowGap => {
+                if let Some(gap) = to_gap(value, font_size) {
+                    Arc::make_mut(&mut style.flex).row_gap = gap;
+                }
+            }
+            LonghandId::ColumnGap => {
+                if let Some(gap) = to_gap(value, font_size) {
+                    Arc::make_mut(&mut style.flex).column_gap = gap;
+                }
+            }
+            LonghandId::GridTemplateColumns => {
+                if let Some(tracks) = to_track_list(value, font_size) {
+                    Arc::make_mut(&mut style.grid).grid_template_columns = tracks;
+                }
+            }
+            LonghandId::GridTemplateRows => {
+                if let Some(tracks) = to_track_list(value, font_size) {
+                    Arc::make_mut(&mut style.grid).grid_template_rows = tracks;
+                }
+            }
+            LonghandId::GridTemplateAreas => {
+                if let Some(areas) = to_grid_template_areas(value) {
+                    Arc::make_mut(&mut style.grid).grid_template_areas = areas;
+                }
+            }
+            LonghandId::GridAutoColumns => {
+                if let Some(sizes) = to_track_sizes(value, font_size) {
+                    Arc::make_mut(&mut style.grid).grid_auto_columns = sizes;
+                }
+            }
+            LonghandId::GridAutoRows => {
+                if let Some(sizes) = to_track_sizes(value, font_size) {
+                    Arc::make_mut(&mut style.grid).grid_auto_rows = sizes;
+                }
+            }
+            LonghandId::GridAutoFlow => {
+                if let Some(auto_flow) = to_grid_auto_flow(value) {
+                    Arc::make_mut(&mut style.grid).grid_auto_flow = auto_flow;
+                }
+            }
+            LonghandId::GridRowStart => {
+                if let Some(line) = to_grid_line(value) {
+                    Arc::make_mut(&mut style.grid).grid_row_start = line;
+                }
+            }
+            LonghandId::GridRowEnd => {
+                if let Some(line) = to_grid_line(value) {
+                    Arc::make_mut(&mut style.grid).grid_row_end = line;
+                }
+            }
+            LonghandId::GridColumnStart => {
+                if let Some(line) = to_grid_line(value) {
+                    Arc::make_mut(&mut style.grid).grid_column_start = line;
+                }
+            }
+            LonghandId::GridColumnEnd => {
+                if let Some(line) = to_grid_line(value) {
+                    Arc::make_mut(&mut style.grid).grid_column_end = line;
+                }
+            }
+            LonghandId::JustifyItems => {
+                if let Some(justify_items) = to_align_items(value) {
+                    Arc::make_mut(&mut style.grid).justify_items = justify_items;
+                }
+            }
+            LonghandId::JustifySelf => {
+                if let Some(justify_self) = to_align_self(value) {
+                    Arc::make_mut(&mut style.grid).justify_self = justify_self;
+                }
+            }
+            LonghandId::TableLayout => {
+                if let Some(table_layout) = to_table_layout(value) {
+                    Arc::make_mut(&mut style.table).table_layout = table_layout;
+                }
+            }
+            LonghandId::BorderCollapse => {
+                if let Some(border_collapse) = to_border_collapse(value) {
+                    Arc::make_mut(&mut style.inherited_table).border_collapse = border_collapse;
+                }
+            }
+            LonghandId::BorderSpacing => {
+                if let Some(border_spacing) = to_border_spacing(value, font_size) {
+                    Arc::make_mut(&mut style.inherited_table).border_spacing = border_spacing;
+                }
+            }
+            LonghandId::CaptionSide => {
+                if let Some(caption_side) = to_caption_side(value) {
+                    Arc::make_mut(&mut style.inherited_table).caption_side = caption_side;
+                }
+            }
+            LonghandId::BackgroundColor => {
+                if let Some(color) = to_color(value) {
+                    Arc::make_mut(&mut style.background).background_color = to_visible(color);
+                }
+            }
+        }
+    }
+
+    /// Copy the computed value from `from`, which is the parent or initial values.
+    fn copy(self, from: &ComputedValues, style: &mut ComputedValues) {
+        match self {
+            LonghandId::FontSize => Arc::make_mut(&mut style.font).font_size = from.font.font_size,
+            LonghandId::FontFamily => {
+                Arc::make_mut(&mut style.font).font_family = from.font.font_family.clone()
+            }
+            LonghandId::FontWeight => {
+                Arc::make_mut(&mut style.font).font_weight = from.font.font_weight
+            }
+            LonghandId::FontStyle => {
+                Arc::make_mut(&mut style.font).font_style = from.font.font_style
+            }
+            LonghandId::Color => {
+                Arc::make_mut(&mut style.inherited_text).color = from.inherited_text.color.clone()
+            }
+            LonghandId::LineHeight => {
+                Arc::make_mut(&mut style.inherited_text).line_height =
+                    from.inherited_text.line_height
+            }
+            LonghandId::WordBreak => {
+                Arc::make_mut(&mut style.inherited_text).word_break = from.inherited_text.word_break
+            }
+            LonghandId::Display => Arc::make_mut(&mut style.box_).display = from.box_.display,
+            LonghandId::Width => Arc::make_mut(&mut style.box_).width = from.box_.width,
+            LonghandId::Height => Arc::make_mut(&mut style.box_).height = from.box_.height,
+            LonghandId::MinWidth => Arc::make_mut(&mut style.box_).min_width = from.box_.min_width,
+            LonghandId::MinHeight => {
+                Arc::make_mut(&mut style.box_).min_height = from.box_.min_height
+            }
+            LonghandId::MaxWidth => Arc::make_mut(&mut style.box_).max_width = from.box_.max_width,
+            LonghandId::MaxHeight => {
+                Arc::make_mut(&mut style.box_).max_height = from.box_.max_height
+            }
+            LonghandId::BoxSizing => {
+                Arc::make_mut(&mut style.box_).box_sizing = from.box_.box_sizing
+            }
+            LonghandId::Position => Arc::make_mut(&mut style.box_).position = from.box_.position,
+            LonghandId::Top => Arc::make_mut(&mut style.box_).top = from.box_.top,
+            LonghandId::Right => Arc::make_mut(&mut style.box_).right = from.box_.right,
+            LonghandId::Bottom => Arc::make_mut(&mut style.box_).bottom = from.box_.bottom,
+            LonghandId::Left => Arc::make_mut(&mut style.box_).left = from.box_.left,
+            LonghandId::ZIndex => Arc::make_mut(&mut style.box_).z_index = from.box_.z_index,
+            LonghandId::Float => Arc::make_mut(&mut style.box_).float = from.box_.float,
+            LonghandId::Clear => Arc::make_mut(&mut style.box_).clear = from.box_.clear,
+            LonghandId::VerticalAlign => {
+                Arc::make_mut(&mut style.box_).vertical_align = from.box_.vertical_align
+            }
+            LonghandId::MarginTop => {
+                Arc::make_mut(&mut style.margin).margin_top = from.margin.margin_top
+            }
+            LonghandId::MarginRight => {
+                Arc::make_mut(&mut style.margin).margin_right = from.margin.margin_right
+            }
+            LonghandId::MarginBottom => {
+                Arc::make_mut(&mut style.margin).margin_bottom = from.margin.margin_bottom
+            }
+            LonghandId::MarginLeft => {
+                Arc::make_mut(&mut style.margin).margin_left = from.margin.margin_left
+            }
+            LonghandId::BorderTopWidth => {
+                Arc::make_mut(&mut style.border).border_top_width = from.border.border_top_width
+            }
+            LonghandId::BorderRightWidth => {
+                Arc::make_mut(&mut style.border).border_right_width = from.border.border_right_width
+            }
+            LonghandId::BorderBottomWidth => {
+                Arc::make_mut(&mut style.border).border_bottom_width =
+                    from.border.border_bottom_width
+            }
+            LonghandId::BorderLeftWidth => {
+                Arc::make_mut(&mut style.border).border_left_width = from.border.border_left_width
+            }
+            LonghandId::BorderColor => {
+                Arc::make_mut(&mut style.border).border_color = from.border.border_color.clone()
+            }
+            LonghandId::PaddingTop => {
+                Arc::make_mut(&mut style.padding).padding_top = from.padding.padding_top
+            }
+            LonghandId::PaddingRight => {
+                Arc::make_mut(&mut style.padding).padding_right = from.padding.padding_right
+            }
+            LonghandId::PaddingBottom => {
+                Arc::make_mut(&mut style.padding).padding_bottom = from.padding.padding_bottom
+            }
+            LonghandId::PaddingLeft => {
+                Arc::make_mut(&mut style.padding).padding_left = from.padding.padding_left
+            }
+            LonghandId::FlexDirection => {
+                Arc::make_mut(&mut style.flex).flex_direction = from.flex.flex_direction
+            }
+            LonghandId::FlexWrap => Arc::make_mut(&mut style.flex).flex_wrap = from.flex.flex_wrap,
+            LonghandId::FlexGrow => Arc::make_mut(&mut style.flex).flex_grow = from.flex.flex_grow,
+            LonghandId::FlexShrink => {
+                Arc::make_mut(&mut style.flex).flex_shrink = from.flex.flex_shrink
+            }
+            LonghandId::FlexBasis => {
+                Arc::make_mut(&mut style.flex).flex_basis = from.flex.flex_basis
+            }
+            LonghandId::Order => Arc::make_mut(&mut style.flex).order = from.flex.order,
+            LonghandId::JustifyContent => {
+                Arc::make_mut(&mut style.flex).justify_content = from.flex.justify_content
+            }
+            LonghandId::AlignItems => {
+                Arc::make_mut(&mut style.flex).align_items = from.flex.align_items
+            }
+            LonghandId::AlignSelf => {
+                Arc::make_mut(&mut style.flex).align_self = from.flex.align_self
+            }
+            LonghandId::AlignContent => {
+                Arc::make_mut(&mut style.flex).align_content = from.flex.align_content
+            }
+            LonghandId::RowGap => Arc::make_mut(&mut style.flex).row_gap = from.flex.row_gap,
+            LonghandId::ColumnGap => {
+                Arc::make_mut(&mut style.flex).column_gap = from.flex.column_gap
+            }
+            LonghandId::GridTemplateColumns => {
+                Arc::make_mut(&mut style.grid).grid_template_columns =
+                    from.grid.grid_template_columns.clone()
+            }
+            LonghandId::GridTemplateRows => {
+                Arc::make_mut(&mut style.grid).grid_template_rows =
+                    from.grid.grid_template_rows.clone()
+            }
+            LonghandId::GridTemplateAreas => {
+                Arc::make_mut(&mut style.grid).grid_template_areas =
+                    from.grid.grid_template_areas.clone()
+            }
+            LonghandId::GridAutoColumns => {
+                Arc::make_mut(&mut style.grid).grid_auto_columns =
+                    from.grid.grid_auto_columns.clone()
+            }
+            LonghandId::GridAutoRows => {
+                Arc::make_mut(&mut style.grid).grid_auto_rows = from.grid.grid_auto_rows.clone()
+            }
+            LonghandId::GridAutoFlow => {
+                Arc::make_mut(&mut style.grid).grid_auto_flow = from.grid.grid_auto_flow
+            }
+            LonghandId::GridRowStart => {
+                Arc::make_mut(&mut style.grid).grid_row_start = from.grid.grid_row_start.clone()
+            }
+            LonghandId::GridRowEnd => {
+                Arc::make_mut(&mut style.grid).grid_row_end = from.grid.grid_row_end.clone()
+            }
+            LonghandId::GridColumnStart => {
+                Arc::make_mut(&mut style.grid).grid_column_start =
+                    from.grid.grid_column_start.clone()
+            }
+            LonghandId::GridColumnEnd => {
+                Arc::make_mut(&mut style.grid).grid_column_end = from.grid.grid_column_end.clone()
+            }
+            LonghandId::JustifyItems => {
+                Arc::make_mut(&mut style.grid).justify_items = from.grid.justify_items
+            }
+            LonghandId::JustifySelf => {
+                Arc::make_mut(&mut style.grid).justify_self = from.grid.justify_self
+            }
+            LonghandId::TableLayout => {
+                Arc::make_mut(&mut style.table).table_layout = from.table.table_layout
+            }
+            LonghandId::BorderCollapse => {
+                Arc::make_mut(&mut style.inherited_table).border_collapse =
+                    from.inherited_table.border_collapse
+            }
+            LonghandId::BorderSpacing => {
+                Arc::make_mut(&mut style.inherited_table).border_spacing =
+                    from.inherited_table.border_spacing
+            }
+            LonghandId::CaptionSide => {
+                Arc::make_mut(&mut style.inherited_table).caption_side =
+                    from.inherited_table.caption_side
+            }
+            LonghandId::BackgroundColor => {
+                Arc::make_mut(&mut style.background).background_color =
+                    from.background.background_color.clone()
+            }
+        }
+    }
+}
+
+/// `em` is relative to `font_size` of the element.
+fn to_length(value: &Value, font_size: f32) -> Option<f32> {
+    match *value {
+        Value::Length(length, Unit::Em) => Some(length * font_size),
+        // TODO: Support percentages.
+        Value::Length(_, Unit::Percent) => None,
+        Value::Length(..) | Value::Number(_) => Some(value.to_px()),
+        _ => None,
+    }
+}
+
+fn to_length_or_auto(value: &Value, font_size: f32) -> Option<LengthOrAuto> {
+    match value {
+        Value::Keyword(keyword) if keyword == "auto" => Some(LengthOrAuto::Auto),
+        Value::Length(percentage, Unit::Percent) => {
+            Some(LengthOrAuto::Percentage(percentage / 100.))
+        }
+        _ => to_length(value, font_size).map(LengthOrAuto::Length),
+    }
+}
+
+/// `width` also takes intrinsic size keywords.
+fn to_width(value: &Value, font_size: f32) -> Option<LengthOrAuto> {
+    match value {
+        Value::Keyword(keyword) => match &**keyword {
+            "min-content" => Some(LengthOrAuto::MinContent),
+            "max-content" => Some(LengthOrAuto::MaxContent),
+            "fit-content" => Some(LengthOrAuto::FitContent),
+            _ => to_length_or_auto(value, font_size),
+        },
+        _ => to_length_or_auto(value, font_size),
+    }
+}
+
+fn to_length_percentage(value: &Value, font_size: f32) -> Option<LengthPercentage> {
+    match value {
+        Value::Length(percentage, Unit::Percent) => {
+            Some(LengthPercentage::Percentage(percentage / 100.))
+        }
+        _ => to_length(value, font_size).map(LengthPercentage::Length),
+    }
+}
+
+/// `auto` is `Some(None)`.
+fn to_min_size(value: &Value, font_size: f32) -> Option<Option<LengthPercentage>> {
+    match value {
+        Value::Keyword(keyword) if keyword == "auto" => Some(None),
+        _ => to_length_percentage(value, font_size).map(Some),
+    }
+}
+
+/// `none` is `Some(None)`.
+fn to_max_size(value: &Value, font_size: f32) -> Option<Option<LengthPercentage>> {
+    match value {
+        Value::Keyword(keyword) if keyword == "none" => Some(None),
+        _ => to_length_percentage(value, font_size).map(Some),
+    }
+}
+
+/// Relative sizes are relative to `parent_size`, which is the computed font size of the parent.
+// refer: https://drafts.csswg.org/css-fonts-4/#font-size-prop
+fn to_font_size(value: &Value, parent_size: f32) -> Option<f32> {
+    match value {
+        Value::Length(size, Unit::Percent) => Some(parent_size * size / 100.),
+        Value::Keyword(keyword) => match &**keyword {
+            "larger" => Some(parent_size * FONT_SIZE_RATIO),
+            "smaller" => Some(parent_size / FONT_SIZE_RATIO),
+            keyword => absolute_font_size(keyword),
+        },
+        _ => to_length(value, parent_size),
+    }
+}
+
+// refer: https://drafts.csswg.org/css-fonts-4/#absolute-size-mapping
+fn absolute_font_size(keyword: &str) -> Option<f32> {
+    let scale = match keyword {
+        "xx-small" => 3. / 5.,
+        "x-small" => 3. / 4.,
+        "small" => 8. / 9.,
+        "medium" => 1.,
+        "large" => 6. / 5.,
+        "x-large" => 3. / 2.,
+        "xx-large" => 2.,
+        "xxx-large" => 3.,
+        _ => return None,
+    };
+    Some(DEFAULT_FONT_SIZE * scale)
+}
+
+fn to_keyword(value: &Value) -> Option<&str> {
+    match value {
+        Value::Keyword(keyword) => Some(keyword),
+        _ => None,
+    }
+}
+
+fn to_color(value: &Value) -> Option<Color> {
+    match value {
+        Value::ColorValue(color) => Some(color.clone()),
+        Value::Keyword(keyword) => Color::from_keyword(keyword),
+        _ => None,
+    }
+}
+
+fn to_visible(color: Color) -> Option<Color> {
+    if color.a == 0. {
+        None
+    } else {
+        Some(color)
+    }
+}
+
+fn to_font_weight(value: &Value) -> Option<FontWeight> {
+    match value {
+        Value::Number(weight) => Some(FontWeight(*weight)),
+        Value::Keyword(keyword) => match &**keyword {
+            "normal" => Some(FontWeight::NORMAL),
+            "bold" => Some(FontWeight::BOLD),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn to_font_style(value: &Value) -> Option<FontStyle> {
+    match to_keyword(value)? {
+        "normal" => Some(FontStyle::Normal),
+        "italic" => Some(FontStyle::Italic),
+        "oblique" => Some(FontStyle::Oblique),
+        _ => None,
+    }
+}
+
+/// A percentage is resolved against the font size of the element like a number.
+fn to_line_height(value: &Value, font_size: f32) -> Option<LineHeight> {
+    match value {
+        Value::Number(number) => Some(LineHeight::Number(*number)),
+        Value::Length(percentage, Unit::Percent) => Some(LineHeight::Number(percentage / 100.)),
+        Value::Length(..) => to_length(value, font_size).map(LineHeight::Length),
+        Value::Keyword(keyword) if keyword == "normal" => Some(LineHeight::Number(1.2)),
+        _ => None,
+    }
+}
+
+fn to_word_break(value: &Value) -> Option<WordBreak> {
+    match to_keyword(value)? {
+        "normal" => Some(WordBreak::Normal),
+        "break-all" => Some(WordBreak::BreakAll),
+        "keep-all" => Some(WordBreak::KeepAll),
+        _ => None,
+    }
+}
+
+fn to_box_sizing(value: &Value) -> Option<BoxSizing> {
+    match to_keyword(value)? {
+        "content-box" => Some(BoxSizing::ContentBox),
+        "border-box" => Some(BoxSizing::BorderBox),
+        _ => None,
+    }
+}
+
+fn to_position(value: &Value) -> Option<Position> {
+    match to_keyword(value)? {
+        "static" => Some(Position::Static),
+        "relative" => Some(Position::Relative),
+        "absolute" => Some(Position::Absolute),
+        "fixed" => Some(Position::Fixed),
+        "sticky" => Some(Position::Sticky),
+        _ => None,
+    }
+}
+
+fn to_float(value: &Value) -> Option<Float> {
+    match to_keyword(value)? {
+        "none" => Some(Float::None),
+        "left" => Some(Float::Left),
+        "right" => Some(Float::Right),
+        _ => None,
+    }
+}
+
+fn to_clear(value: &Value) -> Option<Clear> {
+    match to_keyword(value)? {
+        "none" => Some(Clear::None),
+        "left" => Some(Clear::Left),
+        "right" => Some(Clear::Right),
+        "both" => Some(Clear::Both),
+        _ => None,
+    }
+}
+
+// TODO: Support other values for inline-level boxes.
+fn to_vertical_align(value: &Value) -> Option<VerticalAlign> {
+    match to_keyword(value)? {
+        "baseline" => Some(VerticalAlign::Baseline),
+        "top" => Some(VerticalAlign::Top),
+        "middle" => Some(VerticalAlign::Middle),
+        "bottom" => Some(VerticalAlign::Bottom),
+        _ => None,
+    }
+}
+
+/// `auto` is `Some(None)`.
+fn to_z_index(value: &Value) -> Option<Option<i32>> {
+    match value {
+        Value::Keyword(keyword) if keyword == "auto" => Some(None),
+        _ => to_integer(value).map(Some),
+    }
+}
+
+fn to_non_negative_number(value: &Value) -> Option<f32> {
+    match value {
+        Value::Number(number) if *number >= 0. => Some(*number),
+        _ => None,
+    }
+}
+
+fn to_integer(value: &Value) -> Option<i32> {
+    match value {
+        Value::Number(number) if number.fract() == 0. => Some(*number as i32),
+        _ => None,
+    }
+}
+
+fn to_flex_direction(value: &Value) -> Option<FlexDirection> {
+    match to_keyword(value)? {
+        "row" => Some(FlexDirection::Row),
+        "row-reverse" => Some(FlexDirection::RowReverse),
+        "column" => Some(FlexDirection::Column),
+        "column-reverse" => Some(FlexDirection::ColumnReverse),
+        _ => None,
+    }
+}
+
+fn to_flex_wrap(value: &Value) -> Option<FlexWrap> {
+    match to_keyword(value)? {
+        "nowrap" => Some(FlexWrap::Nowrap),
+        "wrap" => Some(FlexWrap::Wrap),
+        "wrap-reverse" => Some(FlexWrap::WrapReverse),
+        _ => None,
+    }
+}
+
+fn to_flex_basis(value: &Value, font_size: f32) -> Option<FlexBasis> {
+    match value {
+        Value::Keyword(keyword) if keyword == "content" => Some(FlexBasis::Content),
+        _ => to_length_or_auto(value, font_size).map(FlexBasis::Size),
+    }
+}
+
+fn to_justify_content(value: &Value) -> Option<JustifyContent> {
+    match to_keyword(value)? {
+        "normal" | "stretch" => Some(JustifyContent::Normal),
+        "flex-start" | "start" => Some(JustifyContent::FlexStart),
+        "flex-end" | "end" => Some(JustifyContent::FlexEnd),
+        "center" => Some(JustifyContent::Center),
+        "space-between" => Some(JustifyContent::SpaceBetween),
+        "space-around" => Some(JustifyContent::SpaceAround),
+        "space-evenly" => Some(JustifyContent::SpaceEvenly),
+        _ => None,
+    }
+}
+
+fn to_align_items(value: &Value) -> Option<AlignItems> {
+    match to_keyword(value)? {
+        "normal" | "stretch" => Some(AlignItems::Stretch),
+        "flex-start" | "start" | "self-start" => Some(AlignItems::FlexStart),
+        "flex-end" | "end" | "self-end" => Some(AlignItems::FlexEnd),
+        "center" => Some(AlignItems::Center),
+        "baseline" => Some(AlignItems::Baseline),
+        _ => None,
+    }
+}
+
+/// `auto` is `Some(None)`.
+fn to_align_self(value: &Value) -> Option<Option<AlignItems>> {
+    match value {
+        Value::Keyword(keyword) if keyword == "auto" => Some(None),
+        _ => to_align_items(value).map(Some),
+    }
+}
+
+fn to_align_content(value: &Value) -> Option<AlignContent> {
+    match to_keyword(value)? {
+        "normal" | "stretch" => Some(AlignContent::Stretch),
+        "flex-start" | "start" => Some(AlignContent::FlexStart),
+        "flex-end" | "end" => Some(AlignContent::FlexEnd),
+        "center" => Some(AlignContent::Center),
+        "space-between" => Some(AlignContent::SpaceBetween),
+        "space-around" => Some(AlignContent::SpaceAround),
+        "space-evenly" => Some(AlignContent::SpaceEvenly),
+        _ => None,
+    }
+}
+
+/// `normal` is `0`.
+fn to_gap(value: &Value, font_size: f32) -> Option<LengthPercentage> {
+    match value {
+        Value::Keyword(keyword) if keyword == "normal" => Some(LengthPercentage::Length(0.)),
+        _ => to_length_percentage(value, font_size),
+    }
+}
+
+fn to_display(value: &Value) -> Option<Display> {
+    match to_keyword(value)? {
+        "block" => Some(Display::Block),
+        "flow-root" => Some(Display::FlowRoot),
+        "flex" => Some(Display::Flex),
+        "grid" => Some(Display::Grid),
+        "inline-block" => Some(Display::InlineBlock),
+        "table" => Some(Display::Table),
+        "table-row-group" => Some(Display::TableRowGroup),
+        "table-header-group" => Some(Display::TableHeaderGroup),
+        "table-footer-group" => Some(Display::TableFooterGroup),
+        "table-row" => Some(Display::TableRow),
+        "table-column-group" => Some(Display::TableColumnGroup),
+        "table-column" => Some(Display::TableColumn),
+        "table-cell" => Some(Display::TableCell),
+        "table-caption" => Some(Display::TableCaption),
+        "none" => Some(Display::None),
+        _ => Some(Display::Inline),
+    }
+}
+
+fn to_table_layout(value: &Value) -> Option<TableLayout> {
+    match to_keyword(value)? {
+        "auto" => Some(TableLayout::Auto),
+        "fixed" => Some(TableLayout::Fixed),
+        _ => None,
+    }
+}
+
+fn to_border_collapse(value: &Value) -> Option<BorderCollapse> {
+    match to_keyword(value)? {
+        "separate" => Some(BorderCollapse::Separate),
+        "collapse" => Some(BorderCollapse::Collapse),
+        _ => None,
+    }
+}
+
+/// One length is used in both directions.
+fn to_border_spacing(value: &Value, font_size: f32) -> Option<(f32, f32)> {
+    let lengths = match value {
+        Value::Lengths(lengths) => lengths,
+        _ => return None,
+    };
+    let to_px = |&(length, unit): &(f32, Unit)| match unit {
+        Unit::Em => length * font_size,
+        _ => length,
+    };
+    match lengths.as_slice() {
+        [length] => Some((to_px(length), to_px(length))),
+        [horizontal, vertical] => Some((to_px(horizontal), to_px(vertical))),
+        _ => None,
+    }
+}
+
+fn to_caption_side(value: &Value) -> Option<CaptionSide> {
+    match to_keyword(value)? {
+        "top" => Some(CaptionSide::Top),
+        "bottom" => Some(CaptionSide::Bottom),
+        _ => None,
+    }
+}
+
+fn to_track_breadth(breadth: &cssom::TrackBreadth, font_size: f32) -> TrackBreadth {
+    match *breadth {
+        cssom::TrackBreadth::Length(percentage, Unit::Percent) => {
+            TrackBreadth::Percentage(percentage / 100.)
+        }
+        cssom::TrackBreadth::Length(length, Unit::Em) => TrackBreadth::Length(length * font_size),
+        cssom::TrackBreadth::Length(length, Unit::Px) => TrackBreadth::Length(length),
+        cssom::TrackBreadth::Flex(flex) => TrackBreadth::Flex(flex),
+        cssom::TrackBreadth::Auto => TrackBreadth::Auto,
+    }
+}
+
+fn to_track_size(size: &cssom::TrackSize, font_size: f32) -> TrackSize {
+    match size {
+        // The minimum of a flexible breadth is `auto`.
+        // refer: https://drafts.csswg.org/css-grid-2/#valdef-grid-template-columns-flex
+        cssom::TrackSize::Breadth(cssom::TrackBreadth::Flex(flex)) => TrackSize {
+            min: TrackBreadth::Auto,
+            max: TrackBreadth::Flex(*flex),
+        },
+        cssom::TrackSize::Breadth(breadth) => {
+            let breadth = to_track_breadth(breadth, font_size);
+            TrackSize {
+                min: breadth,
+                max: breadth,
+            }
+        }
+        cssom::TrackSize::MinMax(min, max) => TrackSize {
+            min: to_track_breadth(min, font_size),
+            max: to_track_breadth(max, font_size),
+        },
+    }
+}
+
+fn to_track_list(value: &Value, font_size: f32) -> Option<Vec<TrackListItem>> {
+    let items = match value {
+        Value::TrackList(items) => items,
+        _ => return None,
+    };
+    let items = items
+        .iter()
+        .map(|item| match item {
+            cssom::TrackListItem::Size(size) => TrackListItem::Size(to_track_size(size, font_size)),
+            cssom::TrackListItem::Repeat(count, sizes) => TrackListItem::Repeat(
+                *count,
+                sizes
+                    .iter()
+                    .map(|size| to_track_size(size, font_size))
+                    .collect(),
+            ),
+        })
+        .collect();
+    Some(items)
+}
+
+fn to_track_sizes(value: &Value, font_size: f32) -> Option<Vec<TrackSize>> {
+    to_track_list(value, font_size)?
+        .into_iter()
+        .map(|item| match item {
+            TrackListItem::Size(size) => Some(size),
+            TrackListItem::Repeat(..) => None,
+        })
+        .collect()
+}
+
+/// Rows must have the same number of columns, and each named area must be a rectangle.
+/// `none` is `Some(None)`.
+// refer: https://drafts.csswg.org/css-grid-2/#grid-template-areas-property
+fn to_grid_template_areas(value: &Value) -> Option<Option<GridTemplateAreas>> {
+    let rows = match value {
+        Value::GridTemplateAreas(rows) if rows.is_empty() => return Some(None),
+        Value::GridTemplateAreas(rows) => rows,
+        _ => return None,
+    };
+    // A sequence of `.` is a null cell token.
+    let rows: Vec<Vec<&str>> = rows
+        .iter()
+        .map(|row| {
+            row.split_whitespace()
+                .map(|cell| {
+                    if cell.chars().all(|c| c == '.') {
+                        "."
+                    } else {
+                        cell
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let columns = rows[0].len();
+    if columns == 0 || rows.iter().any(|row| row.len() != columns) {
+        return None;
+    }
+
+    let mut areas: Vec<NamedArea> = vec![];
+    for (y, row) in rows.iter().enumerate() {
+        for (x, name) in row.iter().enumerate() {
+            if *name == "." {
+                continue;
+            }
+            match areas.iter_mut().find(|area| area.name == *name) {
+                Some(area) => {
+                    area.rows.end = area.rows.end.max(y + 1);
+                    area.columns.start = area.columns.start.min(x);
+                    area.columns.end = area.columns.end.max(x + 1);
+                }
+                None => areas.push(NamedArea {
+                    name: name.to_string(),
+                    rows: y..y + 1,
+                    columns: x..x + 1,
+                }),
+            }
+        }
+    }
+    // Every cell in the bounding box of an area must have the name.
+    let is_rectangle = |area: &NamedArea| {
+        area.rows
+            .clone()
+            .all(|y| area.columns.clone().all(|x| rows[y][x] == area.name))
+    };
+    let cells = areas
+        .iter()
+        .map(|area| area.rows.len() * area.columns.len())
+        .sum::<usize>();
+    let named_cells = rows.iter().flatten().filter(|name| **name != ".").count();
+    if cells != named_cells || !areas.iter().all(is_rectangle) {
+        return None;
+    }
+
+    Some(Some(GridTemplateAreas {
+        rows: rows.len(),
+        columns,
+        areas,
+    }))
+}
+
+fn to_grid_auto_flow(value: &Value) -> Option<GridAutoFlow> {
+    let keywords = match value {
+        Value::KeywordArray(keywords) => keywords,
+        _ => return None,
+    };
+    let mut auto_flow = GridAutoFlow {
+        column: false,
+        dense: false,
+    };
+    let (mut has_direction, mut has_dense) = (false, false);
+    for keyword in keywords {
+        match &**keyword {
+            "row" | "column" if !has_direction => {
+                auto_flow.column = keyword == "column";
+                has_direction = true;
+            }
+            "dense" if !has_dense => {
+                auto_flow.dense = true;
+                has_dense = true;
+            }
+            _ => return None,
+        }
+    }
+    if keywords.is_empty() {
+        return None;
+    }
+    Some(auto_flow)
+}
+
+fn to_grid_line(value: &Value) -> Option<GridLine> {
+    match value {
+        Value::GridPlacement(lines) => lines.first().cloned(),
+        _ => None,
+    }
+}
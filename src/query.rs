@@ -0,0 +1,189 @@
+// refer: https://dom.spec.whatwg.org/#scope-match-a-selectors-string
+
+use std::fmt;
+
+use crate::atom::Atom;
+use crate::cssom::Selector;
+use crate::dom::{Document, ElementData, NodeId};
+use crate::parser::css::CSSParser;
+use crate::selector_map::matches;
+
+/// A selector string which can not be parsed, which is `SyntaxError` in the DOM.
+#[derive(Debug, PartialEq)]
+pub struct InvalidSelector(pub String);
+
+impl fmt::Display for InvalidSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' is not a valid selector", self.0)
+    }
+}
+
+impl Document {
+    /// Return the first descendant of `scope` in tree order which matches `selectors`.
+    pub fn query_selector(
+        &self,
+        scope: NodeId,
+        selectors: &str,
+    ) -> Result<Option<NodeId>, InvalidSelector> {
+        let selectors = parse_selector_list(selectors)?;
+        let mut result = vec![];
+        self.collect_matches(scope, &selectors, true, &mut result);
+        Ok(result.pop())
+    }
+
+    /// Return descendants of `scope` in tree order which match `selectors`.
+    pub fn query_selector_all(
+        &self,
+        scope: NodeId,
+        selectors: &str,
+    ) -> Result<Vec<NodeId>, InvalidSelector> {
+        let selectors = parse_selector_list(selectors)?;
+        let mut result = vec![];
+        self.collect_matches(scope, &selectors, false, &mut result);
+        Ok(result)
+    }
+
+    /// Return descendants of `scope` in tree order which have all classes in `class_names`,
+    /// which are separated by whitespace.
+    pub fn get_elements_by_class_name(&self, scope: NodeId, class_names: &str) -> Vec<NodeId> {
+        let classes: Vec<Atom> = class_names.split_whitespace().map(Atom::new).collect();
+        if classes.is_empty() {
+            return vec![];
+        }
+        self.descendants(scope)
+            .skip(1)
+            .filter(|id| {
+                self.node(*id)
+                    .as_element()
+                    .is_some_and(|elm| classes.iter().all(|class| elm.has_class(class)))
+            })
+            .collect()
+    }
+
+    fn collect_matches(
+        &self,
+        scope: NodeId,
+        selectors: &[Selector],
+        first_only: bool,
+        result: &mut Vec<NodeId>,
+    ) {
+        // Selectors are matched in the whole document, so that ancestors of `scope` are included.
+        let mut ancestors: Vec<&ElementData> = vec![];
+        let mut parent = Some(scope);
+        while let Some(id) = parent {
+            ancestors.extend(self.node(id).as_element());
+            parent = self.node(id).parent();
+        }
+        ancestors.reverse();
+        self.collect_matches_in_children(scope, selectors, first_only, &mut ancestors, result);
+    }
+
+    /// Return `true` when the search ends.
+    fn collect_matches_in_children<'a>(
+        &'a self,
+        id: NodeId,
+        selectors: &[Selector],
+        first_only: bool,
+        ancestors: &mut Vec<&'a ElementData>,
+        result: &mut Vec<NodeId>,
+    ) -> bool {
+        for child in self.children(id) {
+            let elm = match self.node(child).as_element() {
+                Some(elm) => elm,
+                None => continue,
+            };
+            if selectors
+                .iter()
+                .any(|selector| matches(elm, selector, ancestors))
+            {
+                result.push(child);
+                if first_only {
+                    return true;
+                }
+            }
+            ancestors.push(elm);
+            let done =
+                self.collect_matches_in_children(child, selectors, first_only, ancestors, result);
+            ancestors.pop();
+            if done {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+fn parse_selector_list(selectors: &str) -> Result<Vec<Selector>, InvalidSelector> {
+    CSSParser::new(selectors.into())
+        .parse_selector_list()
+        .ok_or_else(|| InvalidSelector(selectors.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::html::HTMLParser;
+
+    fn tag_names<'a>(document: &'a Document, ids: &[NodeId]) -> Vec<&'a str> {
+        ids.iter()
+            .map(|id| document.node(*id).as_element().unwrap().tag_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_query_selector() {
+        let html = "
+<body class='a'>
+  <div id='main'><p class='b'>1</p><section><p class='a b'>2</p></section></div>
+  <span class='b'>3</span>
+</body>";
+        let document = Document::new(HTMLParser::new(html.into()).run());
+        let body = document.root();
+
+        let all = document.query_selector_all(body, "div > p, span").unwrap();
+        assert_eq!(tag_names(&document, &all), vec!["p", "span"]);
+
+        // Ancestors outside of the scope are matched.
+        let main = document.get_element_by_id("main").unwrap();
+        let all = document.query_selector_all(main, ".a .b").unwrap();
+        assert_eq!(tag_names(&document, &all), vec!["p", "p"]);
+        let all = document.query_selector_all(main, "div").unwrap();
+        assert!(all.is_empty());
+
+        let first = document.query_selector(body, ".b").unwrap();
+        let all = document.get_elements_by_class_name(body, "b");
+        assert_eq!(all.len(), 3);
+        assert_eq!(first, Some(all[0]));
+        assert_eq!(document.query_selector(body, "em").unwrap(), None);
+
+        assert_eq!(
+            document.query_selector(body, "p[title]"),
+            Err(InvalidSelector("p[title]".into()))
+        );
+        assert!(document.query_selector_all(body, "p,").is_err());
+    }
+
+    #[test]
+    fn test_element_lookup() {
+        let html = "<body><div id='a'><p class='x y'>1</p></div><p id='b' class='y'>2</p></body>";
+        let mut document = Document::new(HTMLParser::new(html.into()).run());
+        let body = document.root();
+
+        let a = document.get_element_by_id("a").unwrap();
+        let b = document.get_element_by_id("b").unwrap();
+        assert_eq!(document.get_elements_by_class_name(body, "y").len(), 2);
+        assert_eq!(document.get_elements_by_class_name(body, " y  x ").len(), 1);
+        assert!(document.get_elements_by_class_name(body, "").is_empty());
+
+        // The first element in tree order wins.
+        document.set_attribute(b, "id", "a".into());
+        assert_eq!(document.get_element_by_id("a"), Some(a));
+        assert_eq!(document.get_element_by_id("b"), None);
+
+        // A removed element is not found.
+        document.remove_child(body, a);
+        assert_eq!(document.get_element_by_id("a"), Some(b));
+        document.remove_attribute(b, "id");
+        assert_eq!(document.get_element_by_id("a"), None);
+    }
+}
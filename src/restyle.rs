@@ -0,0 +1,355 @@
+// refer: https://github.com/servo/servo/blob/master/components/style/traversal.rs
+// refer: https://github.com/servo/servo/blob/master/components/style/servo/restyle_damage.rs
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+
+use crate::bloom::StyleBloom;
+use crate::computed_values::ComputedValues;
+use crate::cssom::Stylesheet;
+use crate::custom_properties::CustomPropertyMap;
+use crate::dom::{Document, ElementData, ElementSnapshot, NodeData, NodeId, NodeType};
+use crate::invalidation::{Invalidation, InvalidationMap, InvalidationScope};
+use crate::style::{style_element, SharedStyleContext, StyledNode, Traversal};
+use crate::style_sharing::StyleSharingCache;
+
+/// Work needed to render a change of styles, where each damage includes the smaller ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum RestyleDamage {
+    #[default]
+    None,
+    Repaint,
+    /// Sizes or positions of boxes may change.
+    Reflow,
+    /// Boxes of the node must be created again, e.g. for a change of `display`.
+    RebuildBox,
+}
+
+impl RestyleDamage {
+    pub fn compute(old: &ComputedValues, new: &ComputedValues) -> RestyleDamage {
+        if old.ptr_eq(new) || old == new {
+            return RestyleDamage::None;
+        }
+        if old.box_.display != new.box_.display {
+            return RestyleDamage::RebuildBox;
+        }
+        let (old_border, new_border) = (&old.border, &new.border);
+        let border_width_changed = old_border.border_top_width != new_border.border_top_width
+            || old_border.border_right_width != new_border.border_right_width
+            || old_border.border_bottom_width != new_border.border_bottom_width
+            || old_border.border_left_width != new_border.border_left_width;
+        if old.font != new.font
+            || old.box_ != new.box_
+            || old.margin != new.margin
+            || old.padding != new.padding
+            || border_width_changed
+            || old.flex != new.flex
+            || old.grid != new.grid
+            || old.table != new.table
+            || old.inherited_table != new.inherited_table
+            || old.inherited_text.line_height != new.inherited_text.line_height
+            || old.inherited_text.word_break != new.inherited_text.word_break
+        {
+            return RestyleDamage::Reflow;
+        }
+        RestyleDamage::Repaint
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ElementStyles {
+    pub style: ComputedValues,
+    pub custom_properties: Arc<CustomPropertyMap>,
+}
+
+impl ElementStyles {
+    /// Whether children, which inherit from these styles, need a restyle.
+    fn inherited_differs(&self, other: &ElementStyles) -> bool {
+        let (old, new) = (&self.style, &other.style);
+        (!Arc::ptr_eq(&old.font, &new.font) && old.font != new.font)
+            || (!Arc::ptr_eq(&old.inherited_text, &new.inherited_text)
+                && old.inherited_text != new.inherited_text)
+            || (!Arc::ptr_eq(&old.inherited_table, &new.inherited_table)
+                && old.inherited_table != new.inherited_table)
+            || (!Arc::ptr_eq(&self.custom_properties, &other.custom_properties)
+                && self.custom_properties != other.custom_properties)
+    }
+}
+
+/// Style data stored in a node between restyles.
+#[derive(Debug, Default)]
+pub struct StyleData {
+    /// Styles of the last restyle, or `None` if the node has never been styled.
+    pub styles: Option<ElementStyles>,
+    /// Damage of the node in the last restyle.
+    pub damage: RestyleDamage,
+    /// Damage by DOM mutations since the last restyle.
+    pending_damage: RestyleDamage,
+}
+
+impl StyleData {
+    pub fn note_damage(&mut self, damage: RestyleDamage) {
+        self.pending_damage = self.pending_damage.max(damage);
+    }
+
+    /// Forget styles, so that the node is styled again as a new node.
+    pub(crate) fn clear(&mut self) {
+        self.styles = None;
+    }
+
+    /// Store new styles, and return whether values inherited by children are changed.
+    fn update(&mut self, styles: ElementStyles) -> bool {
+        let (damage, inherited_changed) = match &self.styles {
+            Some(old) => (
+                RestyleDamage::compute(&old.style, &styles.style),
+                old.inherited_differs(&styles),
+            ),
+            None => (RestyleDamage::RebuildBox, true),
+        };
+        self.damage = self.damage.max(damage);
+        self.styles = Some(styles);
+        inherited_changed
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RestyleResult {
+    /// The largest damage of all nodes.
+    pub damage: RestyleDamage,
+    /// Number of elements whose rules are matched again.
+    pub restyled: usize,
+}
+
+struct RestyleContext<'a, 'n> {
+    shared: SharedStyleContext<'a>,
+    invalidation_map: InvalidationMap<'a>,
+    bloom: StyleBloom,
+    sharing_cache: StyleSharingCache,
+    nodes: &'n [NodeData],
+    style_data: &'n mut [StyleData],
+    snapshots: HashMap<NodeId, ElementSnapshot>,
+    result: RestyleResult,
+}
+
+/// Style nodes which have never been styled, and elements which may be affected by changes
+/// since the last restyle, keeping styles of others.
+/// Changes of attributes are found from snapshots of elements, and invalidate only elements
+/// which selectors depending on the changed ids or classes can match.
+pub fn restyle_document(document: &mut Document, stylesheet: &Stylesheet) -> RestyleResult {
+    let root = document.root();
+    let (nodes, style_data, snapshots) = document.split_for_restyle();
+    let mut context = RestyleContext {
+        shared: SharedStyleContext::new(stylesheet, Traversal::Sequential),
+        invalidation_map: InvalidationMap::new(stylesheet),
+        bloom: StyleBloom::new(),
+        sharing_cache: StyleSharingCache::new(),
+        nodes,
+        style_data,
+        snapshots,
+        result: RestyleResult::default(),
+    };
+    restyle_node(&mut context, root, &[], None, false, &[]);
+    context.result
+}
+
+/// `ancestors` are elements from the root to the parent of the node, which are in the Bloom filter.
+/// `invalidations` are from changes of ancestors.
+fn restyle_node<'a, 'n>(
+    context: &mut RestyleContext<'a, 'n>,
+    id: NodeId,
+    ancestors: &[&'n ElementData],
+    parent: Option<&ElementStyles>,
+    parent_changed: bool,
+    invalidations: &[Invalidation<'a>],
+) {
+    let node = &context.nodes[id.index()];
+    let style_data = &mut context.style_data[id.index()];
+    style_data.damage = mem::take(&mut style_data.pending_damage);
+    let parent_style = parent.map(|parent| &parent.style);
+    let inherited_custom_properties = match parent {
+        Some(parent) => parent.custom_properties.clone(),
+        None => Arc::new(CustomPropertyMap::new()),
+    };
+
+    let elm = match &node.node_type {
+        NodeType::Element(elm) => elm,
+        NodeType::Text(_) => {
+            if style_data.styles.is_none() || parent_changed {
+                let style =
+                    ComputedValues::inherit_from(parent_style.unwrap_or(ComputedValues::initial()));
+                style_data.update(ElementStyles {
+                    style,
+                    custom_properties: inherited_custom_properties,
+                });
+            }
+            context.result.damage = context.result.damage.max(style_data.damage);
+            return;
+        }
+    };
+
+    let mut needs_restyle = style_data.styles.is_none()
+        || parent_changed
+        || invalidations
+            .iter()
+            .any(|invalidation| invalidation.matches(elm));
+    // Invalidations scoped to children of an ancestor end at this element.
+    let mut child_invalidations: Vec<Invalidation<'a>> = invalidations
+        .iter()
+        .filter(|invalidation| invalidation.scope == InvalidationScope::Descendants)
+        .copied()
+        .collect();
+    if let Some(snapshot) = context.snapshots.get(&id) {
+        let (restyle_self, invalidations) = context.invalidation_map.invalidate(elm, snapshot);
+        needs_restyle |= restyle_self;
+        child_invalidations.extend(invalidations);
+    }
+
+    let mut inherited_changed = false;
+    if needs_restyle {
+        let (style, custom_properties) = style_element(
+            elm,
+            &context.shared,
+            &context.bloom,
+            ancestors,
+            &mut context.sharing_cache,
+            parent_style,
+            &inherited_custom_properties,
+        );
+        context.result.restyled += 1;
+        inherited_changed = context.style_data[id.index()].update(ElementStyles {
+            style,
+            custom_properties,
+        });
+    }
+    let style_data = &context.style_data[id.index()];
+    context.result.damage = context.result.damage.max(style_data.damage);
+
+    // Styles are cloned, because styles of children are stored while they are borrowed.
+    let styles = style_data.styles.clone();
+    let mut ancestors = ancestors.to_vec();
+    ancestors.push(elm);
+    context.bloom.push(elm);
+    let mut child = node.first_child();
+    while let Some(child_id) = child {
+        restyle_node(
+            context,
+            child_id,
+            &ancestors,
+            styles.as_ref(),
+            inherited_changed,
+            &child_invalidations,
+        );
+        child = context.nodes[child_id.index()].next_sibling();
+    }
+    context.bloom.pop();
+}
+
+/// Create a style tree from styles stored by `restyle_document`.
+pub fn styled_tree(document: &Document) -> StyledNode<'_> {
+    build_styled_tree(document, document.root())
+}
+
+fn build_styled_tree(document: &Document, id: NodeId) -> StyledNode<'_> {
+    let styles = document
+        .style_data(id)
+        .styles
+        .as_ref()
+        .expect("node should be restyled");
+    let children = document
+        .children(id)
+        .map(|child| build_styled_tree(document, child))
+        .collect();
+    StyledNode::new(
+        document.node(id),
+        styles.style.clone(),
+        styles.custom_properties.clone(),
+        children,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cssom::{Color, Origin};
+    use crate::parser::css::CSSParser;
+    use crate::parser::html::HTMLParser;
+    use crate::style::create_style_tree;
+
+    fn child(document: &Document, id: NodeId, index: usize) -> NodeId {
+        document.children(id).nth(index).unwrap()
+    }
+
+    fn color(document: &Document, id: NodeId) -> Color {
+        let styles = document.style_data(id).styles.as_ref().unwrap();
+        styles.style.inherited_text.color.clone()
+    }
+
+    #[test]
+    fn test_incremental_restyle() {
+        let html = "<body><div><p class='item'>a</p><p>b</p></div><p class='item'>c</p></body>";
+        let css = "
+.list .item { color: red; }
+.hidden { display: none; }
+";
+        let stylesheet = Stylesheet::new(CSSParser::new(css.into()).parse_rules(Origin::Author));
+        let mut dom = Document::new(HTMLParser::new(html.into()).run());
+        let body = dom.root();
+        let div = child(&dom, body, 0);
+        let p1 = child(&dom, div, 0);
+        let p2 = child(&dom, div, 1);
+        let p3 = child(&dom, body, 1);
+
+        let result = restyle_document(&mut dom, &stylesheet);
+        assert_eq!(result.restyled, 5);
+        assert_eq!(result.damage, RestyleDamage::RebuildBox);
+
+        let result = restyle_document(&mut dom, &stylesheet);
+        assert_eq!(result.restyled, 0);
+        assert_eq!(result.damage, RestyleDamage::None);
+
+        // Only descendants matching `.item` are restyled, and the div itself is not.
+        dom.set_attribute(div, "class", "list".into());
+        let result = restyle_document(&mut dom, &stylesheet);
+        assert_eq!(result.restyled, 1);
+        assert_eq!(result.damage, RestyleDamage::Repaint);
+        let red = Color::new(255, 0, 0, 1.0);
+        assert_eq!(color(&dom, p1), red);
+        assert_eq!(color(&dom, child(&dom, p1, 0)), red);
+        assert_ne!(color(&dom, p3), red);
+
+        // A change of inherited values restyles all descendants.
+        dom.set_attribute(body, "style", "font-size: 20px".into());
+        let result = restyle_document(&mut dom, &stylesheet);
+        assert_eq!(result.restyled, 5);
+        assert_eq!(result.damage, RestyleDamage::Reflow);
+
+        dom.set_attribute(p3, "class", "item hidden".into());
+        let result = restyle_document(&mut dom, &stylesheet);
+        assert_eq!(result.restyled, 1);
+        assert_eq!(result.damage, RestyleDamage::RebuildBox);
+
+        dom.remove_child(div, p2);
+        let result = restyle_document(&mut dom, &stylesheet);
+        assert_eq!(result.restyled, 0);
+        assert_eq!(dom.style_data(div).damage, RestyleDamage::RebuildBox);
+
+        // Moved nodes are restyled in their new positions without other mutations.
+        dom.append_child(div, p3);
+        dom.append_child(body, p2);
+        let result = restyle_document(&mut dom, &stylesheet);
+        assert_eq!(result.restyled, 2);
+        assert_eq!(color(&dom, p3), red);
+        assert_eq!(color(&dom, child(&dom, p3, 0)), red);
+        assert_ne!(color(&dom, p2), red);
+
+        // Stored styles are the same as styles of a full restyle.
+        let full = create_style_tree(&dom, &stylesheet);
+        let incremental = styled_tree(&dom);
+        assert_eq!(
+            full.children[0].children[1].style,
+            incremental.children[0].children[1].style
+        );
+        assert_eq!(full.children[1].style, incremental.children[1].style);
+    }
+}
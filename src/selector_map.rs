@@ -0,0 +1,282 @@
+// refer: https://github.com/servo/servo/blob/master/components/style/selector_map.rs
+
+use std::collections::HashMap;
+
+use crate::atom::Atom;
+use crate::bloom::StyleBloom;
+use crate::cssom::{Combinator, Rule, Selector, SimpleSelector, Specificity, Stylesheet};
+use crate::dom::ElementData;
+
+/// A selector of a rule, indexed in `SelectorMap`.
+struct RuleEntry<'a> {
+    rule: &'a Rule,
+    selector: &'a Selector,
+    /// Position of the rule in the stylesheet, which decides the order in the cascade.
+    source_order: usize,
+    /// Hashes of names which some ancestor must have, checked with `StyleBloom` before matching.
+    ancestor_hashes: Vec<u32>,
+}
+
+pub type MatchedRule<'a> = (Specificity, &'a Rule);
+
+/// Rules indexed by the rightmost id, class or tag name of their selectors,
+/// so that only rules which can match an element are tested.
+/// A selector is stored in only one bucket, which is the most specific one.
+#[derive(Default)]
+pub struct SelectorMap<'a> {
+    id_hash: HashMap<Atom, Vec<RuleEntry<'a>>>,
+    class_hash: HashMap<Atom, Vec<RuleEntry<'a>>>,
+    tag_hash: HashMap<Atom, Vec<RuleEntry<'a>>>,
+    universal: Vec<RuleEntry<'a>>,
+}
+
+impl<'a> SelectorMap<'a> {
+    pub fn new(stylesheet: &'a Stylesheet) -> SelectorMap<'a> {
+        let mut map = SelectorMap::default();
+        for (source_order, rule) in stylesheet.rules.iter().enumerate() {
+            for selector in &rule.selectors {
+                map.insert(RuleEntry {
+                    rule,
+                    selector,
+                    source_order,
+                    ancestor_hashes: ancestor_hashes(selector),
+                });
+            }
+        }
+        map
+    }
+
+    fn insert(&mut self, entry: RuleEntry<'a>) {
+        let subject = entry.selector.subject();
+        let bucket = if let Some(id) = subject.id {
+            self.id_hash.entry(id).or_default()
+        } else if let Some(class) = subject.class.first() {
+            self.class_hash.entry(*class).or_default()
+        } else if let Some(tag_name) = subject.tag_name {
+            self.tag_hash.entry(tag_name).or_default()
+        } else {
+            &mut self.universal
+        };
+        bucket.push(entry);
+    }
+
+    /// Entries in buckets which `elm` can match.
+    fn candidates<'b>(&'b self, elm: &'b ElementData) -> impl Iterator<Item = &'b RuleEntry<'a>> {
+        let id = elm.id().and_then(|id| self.id_hash.get(id));
+        let classes = elm
+            .classes()
+            .iter()
+            .filter_map(move |class| self.class_hash.get(class));
+        let tag = self.tag_hash.get(elm.local_name());
+        id.into_iter()
+            .chain(classes)
+            .chain(tag)
+            .chain(Some(&self.universal))
+            .flatten()
+    }
+
+    /// Return rules which match `elm`, in order of the stylesheet.
+    /// `ancestors` are elements from the root to the parent of `elm`, which are in `bloom`.
+    pub fn get_matching_rules(
+        &self,
+        elm: &ElementData,
+        ancestors: &[&ElementData],
+        bloom: &StyleBloom,
+    ) -> Vec<MatchedRule<'a>> {
+        let mut matched: Vec<(usize, Specificity, &'a Rule)> = self
+            .candidates(elm)
+            .filter(|entry| {
+                bloom.might_contain_all(&entry.ancestor_hashes)
+                    && matches(elm, entry.selector, ancestors)
+            })
+            .map(|entry| (entry.source_order, entry.selector.specificity(), entry.rule))
+            .collect();
+
+        // A rule matched by some selectors has the highest specificity of them.
+        matched.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        matched.dedup_by_key(|(source_order, _, _)| *source_order);
+        matched
+            .into_iter()
+            .map(|(_, specificity, rule)| (specificity, rule))
+            .collect()
+    }
+
+    /// Return positions of rules whose selectors depend on ancestors and match `elm`.
+    /// Elements which have the same results, tag name, id and classes match the same rules.
+    pub fn get_revalidation_results(
+        &self,
+        elm: &ElementData,
+        ancestors: &[&ElementData],
+        bloom: &StyleBloom,
+    ) -> Vec<usize> {
+        let mut results: Vec<usize> = self
+            .candidates(elm)
+            .filter(|entry| {
+                matches!(entry.selector, Selector::Complex(_))
+                    && bloom.might_contain_all(&entry.ancestor_hashes)
+                    && matches(elm, entry.selector, ancestors)
+            })
+            .map(|entry| entry.source_order)
+            .collect();
+        results.sort_unstable();
+        results.dedup();
+        results
+    }
+}
+
+/// Hashes of ids, classes and tag names in compound selectors on the left of the subject.
+fn ancestor_hashes(selector: &Selector) -> Vec<u32> {
+    let complex = match selector {
+        Selector::Simple(_) => return vec![],
+        Selector::Complex(complex) => complex,
+    };
+    complex
+        .ancestors
+        .iter()
+        .flat_map(|(_, simple)| {
+            simple
+                .tag_name
+                .iter()
+                .chain(simple.id.iter())
+                .chain(simple.class.iter())
+        })
+        .map(Atom::get_hash)
+        .collect()
+}
+
+pub fn matches(elm: &ElementData, selector: &Selector, ancestors: &[&ElementData]) -> bool {
+    match selector {
+        Selector::Simple(simple) => matches_simple_selector(elm, simple),
+        Selector::Complex(complex) => {
+            matches_simple_selector(elm, &complex.subject)
+                && matches_ancestors(&complex.ancestors, ancestors)
+        }
+    }
+}
+
+/// `compounds` are from right to left, and `ancestors` are from the root to the parent.
+fn matches_ancestors(
+    compounds: &[(Combinator, SimpleSelector)],
+    ancestors: &[&ElementData],
+) -> bool {
+    let ((combinator, simple), rest) = match compounds.split_first() {
+        Some(first) => first,
+        None => return true,
+    };
+    match combinator {
+        Combinator::Child => match ancestors.split_last() {
+            Some((parent, ancestors)) => {
+                matches_simple_selector(parent, simple) && matches_ancestors(rest, ancestors)
+            }
+            None => false,
+        },
+        Combinator::Descendant => (0..ancestors.len()).rev().any(|i| {
+            matches_simple_selector(ancestors[i], simple)
+                && matches_ancestors(rest, &ancestors[..i])
+        }),
+    }
+}
+
+pub fn matches_simple_selector(elm: &ElementData, selector: &SimpleSelector) -> bool {
+    if selector
+        .tag_name
+        .iter()
+        .any(|name| name != elm.local_name())
+    {
+        return false;
+    }
+
+    if selector.id.iter().any(|id| Some(id) != elm.id()) {
+        return false;
+    }
+
+    if selector.class.iter().any(|class| !elm.has_class(class)) {
+        return false;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cssom::Origin;
+    use crate::dom::{Node, NodeType};
+    use crate::parser::css::CSSParser;
+    use crate::parser::html::HTMLParser;
+
+    fn element(node: &Node) -> &ElementData {
+        match &node.node_type {
+            NodeType::Element(elm) => elm,
+            NodeType::Text(_) => panic!("node should be Element"),
+        }
+    }
+
+    #[test]
+    fn test_selector_map_buckets() {
+        let css = "
+#main .note { color: red; }
+.note { color: blue; }
+p { color: green; }
+* { color: black; }
+.other { color: white; }
+p.note, .note { color: gray; }
+";
+        let stylesheet = Stylesheet::new(CSSParser::new(css.into()).parse_rules(Origin::Author));
+        let map = SelectorMap::new(&stylesheet);
+        assert_eq!(map.id_hash.len(), 0);
+        assert_eq!(map.class_hash[&Atom::new("note")].len(), 4);
+        assert_eq!(map.class_hash[&Atom::new("other")].len(), 1);
+        assert_eq!(map.tag_hash[&Atom::new("p")].len(), 1);
+        assert_eq!(map.universal.len(), 1);
+
+        let dom = HTMLParser::new("<div id='main'><p class='note'>a</p></div>".into()).run();
+        let div = element(&dom);
+        let p = element(&dom.children[0]);
+
+        let mut bloom = StyleBloom::new();
+        bloom.push(div);
+        let matched = map.get_matching_rules(p, &[div], &bloom);
+        let specificities: Vec<Specificity> = matched
+            .iter()
+            .map(|(specificity, _)| *specificity)
+            .collect();
+        // Rules are in order of the stylesheet, and a rule has its highest specificity.
+        assert_eq!(
+            specificities,
+            vec![(1, 1, 0), (0, 1, 0), (0, 0, 1), (0, 0, 0), (0, 1, 1)]
+        );
+
+        // The Bloom filter rejects the descendant selector without ancestors.
+        bloom.pop();
+        let matched = map.get_matching_rules(p, &[], &bloom);
+        assert_eq!(matched.len(), 4);
+    }
+
+    #[test]
+    fn test_combinators() {
+        let css = "
+div > p { color: red; }
+div p { color: blue; }
+section > p { color: green; }
+section span { color: black; }
+";
+        let stylesheet = Stylesheet::new(CSSParser::new(css.into()).parse_rules(Origin::Author));
+
+        let dom =
+            HTMLParser::new("<section><div><p><span>a</span></p></div></section>".into()).run();
+        let section = element(&dom);
+        let div = element(&dom.children[0]);
+        let p = element(&dom.children[0].children[0]);
+        let span = element(&dom.children[0].children[0].children[0]);
+
+        let matches_rule = |i: usize, elm: &ElementData, ancestors: &[&ElementData]| {
+            matches(elm, &stylesheet.rules[i].selectors[0], ancestors)
+        };
+        assert!(matches_rule(0, p, &[section, div]));
+        assert!(matches_rule(1, p, &[section, div]));
+        assert!(!matches_rule(2, p, &[section, div]));
+        assert!(matches_rule(3, span, &[section, div, p]));
+        assert!(!matches_rule(0, span, &[section, div, p]));
+    }
+}
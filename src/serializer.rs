@@ -0,0 +1,272 @@
+// refer: https://html.spec.whatwg.org/multipage/parsing.html#serialising-html-fragments
+
+use crate::dom::{Document, Node, NodeId, NodeType};
+use crate::parser::html::LEADING_NEWLINE_ELEMENTS;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SerializeMode {
+    /// Output which is parsed back to the same tree.
+    Compact,
+    /// Each element and text on its own line, indented by depth.
+    /// Whitespace in text is trimmed, so that the tree may change by a round trip.
+    Pretty,
+}
+
+// refer: https://html.spec.whatwg.org/multipage/syntax.html#void-elements
+const VOID_ELEMENTS: [&str; 18] = [
+    "area", "base", "basefont", "bgsound", "br", "col", "embed", "frame", "hr", "img", "input",
+    "keygen", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// Elements whose text children are serialized without escaping.
+const RAW_TEXT_ELEMENTS: [&str; 8] = [
+    "style",
+    "script",
+    "xmp",
+    "iframe",
+    "noembed",
+    "noframes",
+    "plaintext",
+    "noscript",
+];
+
+const INDENT: &str = "  ";
+
+/// A tree to serialize, which is either nodes built by the parser or a `Document`.
+trait Tree<'a> {
+    type Id: Copy;
+
+    fn node_type(&self, id: Self::Id) -> &'a NodeType;
+
+    fn children(&self, id: Self::Id) -> Vec<Self::Id>;
+}
+
+struct NodeTree;
+
+impl<'a> Tree<'a> for NodeTree {
+    type Id = &'a Node;
+
+    fn node_type(&self, node: &'a Node) -> &'a NodeType {
+        &node.node_type
+    }
+
+    fn children(&self, node: &'a Node) -> Vec<&'a Node> {
+        node.children.iter().collect()
+    }
+}
+
+impl<'a> Tree<'a> for &'a Document {
+    type Id = NodeId;
+
+    fn node_type(&self, id: NodeId) -> &'a NodeType {
+        &self.node(id).node_type
+    }
+
+    fn children(&self, id: NodeId) -> Vec<NodeId> {
+        Document::children(self, id).collect()
+    }
+}
+
+impl Node {
+    pub fn outer_html(&self, mode: SerializeMode) -> String {
+        outer_html(&NodeTree, self, mode)
+    }
+
+    pub fn inner_html(&self, mode: SerializeMode) -> String {
+        inner_html(&NodeTree, self, mode)
+    }
+}
+
+impl Document {
+    pub fn outer_html(&self, id: NodeId, mode: SerializeMode) -> String {
+        outer_html(&self, id, mode)
+    }
+
+    pub fn inner_html(&self, id: NodeId, mode: SerializeMode) -> String {
+        inner_html(&self, id, mode)
+    }
+}
+
+fn outer_html<'a, T: Tree<'a>>(tree: &T, id: T::Id, mode: SerializeMode) -> String {
+    let mut serializer = Serializer::new(mode);
+    serializer.serialize_node(tree, id, false, 0);
+    serializer.finish()
+}
+
+fn inner_html<'a, T: Tree<'a>>(tree: &T, id: T::Id, mode: SerializeMode) -> String {
+    let mut serializer = Serializer::new(mode);
+    serializer.serialize_children(tree, id, 0);
+    serializer.finish()
+}
+
+struct Serializer {
+    mode: SerializeMode,
+    output: String,
+}
+
+impl Serializer {
+    fn new(mode: SerializeMode) -> Serializer {
+        Serializer {
+            mode,
+            output: String::new(),
+        }
+    }
+
+    fn finish(self) -> String {
+        self.output
+    }
+
+    /// `raw_text` is whether the parent is a raw text element.
+    fn serialize_node<'a, T: Tree<'a>>(
+        &mut self,
+        tree: &T,
+        id: T::Id,
+        raw_text: bool,
+        depth: usize,
+    ) {
+        let elm = match tree.node_type(id) {
+            NodeType::Element(elm) => elm,
+            NodeType::Text(text) => {
+                let text = match self.mode {
+                    SerializeMode::Compact => text.as_str(),
+                    SerializeMode::Pretty => text.trim(),
+                };
+                if text.is_empty() {
+                    return;
+                }
+                self.start_line(depth);
+                if raw_text {
+                    self.output.push_str(text);
+                } else {
+                    escape(&mut self.output, text, false);
+                }
+                return;
+            }
+        };
+
+        self.start_line(depth);
+        self.output.push('<');
+        self.output.push_str(&elm.tag_name);
+        for (name, value) in elm.attributes.iter() {
+            self.output.push(' ');
+            self.output.push_str(name);
+            self.output.push_str("=\"");
+            escape(&mut self.output, value, true);
+            self.output.push('"');
+        }
+        self.output.push('>');
+
+        let tag_name = elm.tag_name.to_ascii_lowercase();
+        if VOID_ELEMENTS.contains(&tag_name.as_str()) {
+            return;
+        }
+        // The parser ignores the first newline, so another one keeps a leading newline of the text.
+        if LEADING_NEWLINE_ELEMENTS.contains(&tag_name.as_str()) {
+            let first_child = tree.children(id).into_iter().next();
+            if let Some(NodeType::Text(text)) = first_child.map(|child| tree.node_type(child)) {
+                if text.starts_with('\n') {
+                    self.output.push('\n');
+                }
+            }
+        }
+        let has_children = self.serialize_children(tree, id, depth + 1);
+        if has_children {
+            self.start_line(depth);
+        }
+        self.output.push_str("</");
+        self.output.push_str(&elm.tag_name);
+        self.output.push('>');
+    }
+
+    /// Return whether some children are serialized.
+    fn serialize_children<'a, T: Tree<'a>>(&mut self, tree: &T, id: T::Id, depth: usize) -> bool {
+        let raw_text = match tree.node_type(id) {
+            NodeType::Element(elm) => {
+                RAW_TEXT_ELEMENTS.contains(&elm.tag_name.to_ascii_lowercase().as_str())
+            }
+            NodeType::Text(_) => false,
+        };
+        let len = self.output.len();
+        for child in tree.children(id) {
+            self.serialize_node(tree, child, raw_text, depth);
+        }
+        self.output.len() != len
+    }
+
+    fn start_line(&mut self, depth: usize) {
+        if self.mode == SerializeMode::Pretty && !self.output.is_empty() {
+            self.output.push('\n');
+            self.output.push_str(&INDENT.repeat(depth));
+        }
+    }
+}
+
+// refer: https://html.spec.whatwg.org/multipage/parsing.html#escapingString
+fn escape(output: &mut String, text: &str, attribute_mode: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '\u{a0}' => output.push_str("&nbsp;"),
+            '"' if attribute_mode => output.push_str("&quot;"),
+            '<' if !attribute_mode => output.push_str("&lt;"),
+            '>' if !attribute_mode => output.push_str("&gt;"),
+            c => output.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dom::AttrMap;
+    use crate::parser::html::HTMLParser;
+
+    #[test]
+    fn test_serialize() {
+        let mut attrs = AttrMap::new();
+        attrs.insert("title".into(), "a \"b\" & c".into());
+        attrs.insert("class".into(), "note".into());
+        let node = Node::new_element(
+            "div".into(),
+            attrs,
+            vec![
+                Node::new_text("1 < 2 & 3\u{a0}".into()),
+                Node::new_element("br".into(), AttrMap::new(), vec![]),
+                Node::new_element(
+                    "style".into(),
+                    AttrMap::new(),
+                    vec![Node::new_text("p > a { color: red; }".into())],
+                ),
+            ],
+        );
+        assert_eq!(
+            node.outer_html(SerializeMode::Compact),
+            "<div title=\"a &quot;b&quot; &amp; c\" class=\"note\">1 &lt; 2 &amp; 3&nbsp;<br><style>p > a { color: red; }</style></div>"
+        );
+        assert_eq!(
+            node.inner_html(SerializeMode::Compact),
+            "1 &lt; 2 &amp; 3&nbsp;<br><style>p > a { color: red; }</style>"
+        );
+    }
+
+    #[test]
+    fn test_serialize_pretty() {
+        let html = "<body>\n<div id='main'><p>a <em>b</em></p></div><p></p></body>";
+        let document = Document::new(HTMLParser::new(html.into()).run());
+        assert_eq!(
+            document.outer_html(document.root(), SerializeMode::Pretty),
+            "\
+<body>
+  <div id=\"main\">
+    <p>
+      a
+      <em>
+        b
+      </em>
+    </p>
+  </div>
+  <p></p>
+</body>"
+        );
+    }
+}
@@ -1,50 +1,89 @@
-// TODO
-// - [ ] Computed Values
-// - [ ] Initial
-
-use std::collections::HashMap;
-
-use crate::{cssom, dom, font_list, layout, parser};
+use std::collections::{HashMap, HashSet};
+use std::ptr;
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+use crate::{
+    bloom, computed_values, cssom, custom_properties, dom, layout, parser, properties,
+    selector_map, style_sharing,
+};
+use bloom::StyleBloom;
+use computed_values::{ComputedValues, LineHeight};
 use cssom::*;
-use dom::{ElementData, Node, NodeType};
-use font_list::{get_generic_fonts, DEFAULT_FONT_FAMILY_NAME};
+use custom_properties::CustomPropertyMap;
+use dom::{Document, ElementData, NodeData, NodeId, NodeType};
 use layout::font::{FontStyle, FontWeight};
 use parser::css::CSSParser;
+use properties::LonghandId;
+use selector_map::{MatchedRule, SelectorMap};
+use style_sharing::{StyleSharingCache, StyleSharingCounters, StyleSharingKey, StyleSharingStats};
 
-// Map from CSS property names to values.
-type PropertyMap = HashMap<String, Value>;
+// Map from CSS property names to declared values.
+pub type PropertyMap = HashMap<String, Value>;
 
 // A node with associated style data.
 #[derive(Debug)]
 pub struct StyledNode<'a> {
-    pub node: &'a Node,
-    pub specified_values: PropertyMap,
+    pub node: &'a NodeData,
+    pub style: ComputedValues,
+    /// Computed custom properties, which are always inherited and shared with the parent
+    /// unless the element declares some.
+    pub custom_properties: Arc<CustomPropertyMap>,
     pub children: Vec<StyledNode<'a>>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Display {
     Inline,
     Block,
+    /// An inline-level box which is laid out as a block container, and placed as an atom in lines.
+    InlineBlock,
+    /// A block box which establishes a new block formatting context.
+    FlowRoot,
+    /// A block-level flex container.
+    Flex,
+    /// A block-level grid container.
+    Grid,
+    /// A block-level table, which is wrapped in an anonymous table wrapper box with its captions.
+    Table,
+    TableRowGroup,
+    TableHeaderGroup,
+    TableFooterGroup,
+    TableRow,
+    TableColumnGroup,
+    TableColumn,
+    TableCell,
+    TableCaption,
     None,
 }
 
-const XX_LARGE: f32 = 2.5;
-const X_LARGE: f32 = 1.8;
-const LARGE: f32 = 1.5;
-const MEDIUM: f32 = 1.3;
-const SMALL: f32 = 1.1;
-const X_SMALL: f32 = 1.;
+impl Display {
+    /// Whether the box is a part of a table, which is laid out by the table.
+    // refer: https://www.w3.org/TR/CSS21/tables.html#table-display
+    pub fn is_table_internal(&self) -> bool {
+        matches!(
+            self,
+            Display::TableRowGroup
+                | Display::TableHeaderGroup
+                | Display::TableFooterGroup
+                | Display::TableRow
+                | Display::TableColumnGroup
+                | Display::TableColumn
+                | Display::TableCell
+                | Display::TableCaption
+        )
+    }
 
-const INHERITABLE_PROPERTY_LIST: [&str; 7] = [
-    "font-size",
-    "color",
-    "line-height",
-    "font-family",
-    "font-weight",
-    "font-style",
-    "word-break",
-];
+    pub fn is_table_row_group(&self) -> bool {
+        matches!(
+            self,
+            Display::TableRowGroup | Display::TableHeaderGroup | Display::TableFooterGroup
+        )
+    }
+}
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WordBreak {
     Normal,
     BreakAll,
@@ -59,177 +98,270 @@ impl WordBreak {
 
 impl<'a> StyledNode<'a> {
     pub fn new(
-        node: &'a Node,
-        specified_values: PropertyMap,
+        node: &'a NodeData,
+        style: ComputedValues,
+        custom_properties: Arc<CustomPropertyMap>,
         children: Vec<StyledNode<'a>>,
     ) -> StyledNode<'a> {
         StyledNode {
             node,
-            specified_values,
+            style,
+            custom_properties,
             children,
         }
     }
 
-    pub fn value(&self, name: &str) -> Option<Value> {
-        self.specified_values.get(name).cloned()
+    pub fn display(&self) -> Display {
+        self.style.box_.display
     }
 
-    pub fn display(&self) -> Display {
-        match self.value("display") {
-            Some(Value::Keyword(s)) => match &*s {
-                "block" => Display::Block,
-                "none" => Display::None,
-                _ => Display::Inline,
-            },
-            _ => Display::Inline,
-        }
+    pub fn font_family(&self) -> &[FontFamily] {
+        &self.style.font.font_family
     }
 
-    pub fn lookup(&self, name: &str, fallback_name: &str, default: &Value) -> Value {
-        self.value(name)
-            .unwrap_or_else(|| self.value(fallback_name).unwrap_or_else(|| default.clone()))
+    pub fn font_size(&self) -> f32 {
+        self.style.font.font_size
     }
 
-    /// Font families are supported only for macos.
-    pub fn font_family(&self) -> Vec<String> {
-        let generic_fonts = get_generic_fonts();
-        let default_families = vec![DEFAULT_FONT_FAMILY_NAME.to_string(); 1];
-        let value = match self.value("font-family") {
-            Some(val) => val,
-            None => return default_families,
-        };
+    pub fn font_style(&self) -> FontStyle {
+        self.style.font.font_style
+    }
 
-        match value {
-            Value::Keyword(val) => match generic_fonts.get(&val) {
-                Some(val) => val.clone(),
-                None => default_families,
-            },
-            Value::KeywordArray(arr) => {
-                let mut families = vec![];
-                for item in arr.into_iter() {
-                    match generic_fonts.get(&item) {
-                        Some(val) => {
-                            for name in val {
-                                families.push(name.clone());
-                            }
-                        }
-                        None => continue,
-                    }
-                }
-                if families.is_empty() {
-                    return default_families;
-                }
-                families
-            }
-            _ => default_families,
+    pub fn font_weight(&self) -> FontWeight {
+        self.style.font.font_weight
+    }
+
+    pub fn line_height(&self) -> f32 {
+        match self.style.inherited_text.line_height {
+            LineHeight::Number(number) => self.font_size() * number,
+            LineHeight::Length(length) => length,
         }
     }
 
-    pub fn font_size(&self) -> f32 {
-        let default_font_size = Value::Length(16.0, Unit::Px);
-        self.value("font-size")
-            .unwrap_or_else(|| default_font_size)
-            .to_px()
-            * MEDIUM
+    pub fn word_break(&self) -> WordBreak {
+        self.style.inherited_text.word_break
     }
+}
 
-    pub fn font_style(&self) -> FontStyle {
-        let default_font_style = Value::Keyword("normal".to_string());
-        let val = self
-            .value("font-style")
-            .unwrap_or_else(|| default_font_style);
-        let keyword = match val {
-            Value::Keyword(s) => s,
-            _ => return FontStyle::Normal,
-        };
-        match &keyword[..] {
-            "italic" => FontStyle::Italic,
-            "oblique" => FontStyle::Oblique,
-            _ => FontStyle::Normal,
+pub fn create_style_tree<'a>(document: &'a Document, stylesheet: &'a Stylesheet) -> StyledNode<'a> {
+    create_style_tree_with_stats(document, stylesheet, Traversal::Sequential).0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Traversal {
+    Sequential,
+    /// Style subtrees on the global rayon thread pool.
+    /// The result is the same as `Sequential`, except for style sharing between subtrees.
+    Parallel,
+}
+
+/// Same as `create_style_tree`, and return counters of the style sharing cache.
+pub fn create_style_tree_with_stats<'a>(
+    document: &'a Document,
+    stylesheet: &'a Stylesheet,
+    traversal: Traversal,
+) -> (StyledNode<'a>, StyleSharingStats) {
+    let shared = SharedStyleContext::new(stylesheet, traversal);
+    let mut local = ThreadLocalStyleContext::new();
+    let styled_node = build_style_tree(
+        document,
+        document.root(),
+        &shared,
+        &mut local,
+        None,
+        &Arc::new(CustomPropertyMap::new()),
+    );
+    (styled_node, shared.sharing_stats())
+}
+
+/// Immutable state shared by all threads during a traversal of the DOM tree.
+pub(crate) struct SharedStyleContext<'a> {
+    selector_map: SelectorMap<'a>,
+    traversal: Traversal,
+    sharing_counters: StyleSharingCounters,
+}
+
+impl<'a> SharedStyleContext<'a> {
+    pub(crate) fn new(stylesheet: &'a Stylesheet, traversal: Traversal) -> SharedStyleContext<'a> {
+        SharedStyleContext {
+            selector_map: SelectorMap::new(stylesheet),
+            traversal,
+            sharing_counters: StyleSharingCounters::default(),
         }
     }
 
-    pub fn font_weight(&self) -> FontWeight {
-        let normal = 400.;
-        let default_font_weight = Value::Number(normal);
-        let val = self
-            .value("font-weight")
-            .unwrap_or_else(|| default_font_weight);
-        let num = match val {
-            Value::Number(n) => n,
-            _ => return FontWeight(normal),
-        };
-        FontWeight(num)
+    pub(crate) fn sharing_stats(&self) -> StyleSharingStats {
+        self.sharing_counters.stats()
     }
+}
 
-    pub fn line_height(&self) -> f32 {
-        let default_line_height = Value::Number(1.2);
-        let line_height = self
-            .value("line-height")
-            .unwrap_or_else(|| default_line_height)
-            .to_px();
-        self.font_size() * line_height
+/// State owned by a thread, which is updated while walking down the DOM tree.
+struct ThreadLocalStyleContext<'a> {
+    /// Ancestors of the node being styled, to reject descendant selectors quickly.
+    bloom: StyleBloom,
+    /// Elements from the root to the parent of the node being styled.
+    ancestors: Vec<&'a ElementData>,
+    sharing_cache: StyleSharingCache,
+}
+
+impl<'a> ThreadLocalStyleContext<'a> {
+    fn new() -> ThreadLocalStyleContext<'a> {
+        ThreadLocalStyleContext {
+            bloom: StyleBloom::new(),
+            ancestors: vec![],
+            sharing_cache: StyleSharingCache::new(),
+        }
     }
 
-    pub fn word_break(&self) -> WordBreak {
-        let word_break = self.value("word-break");
-        let value = match word_break {
-            Some(val) => val,
-            None => return WordBreak::Normal,
-        };
+    fn push(&mut self, elm: &'a ElementData) {
+        self.bloom.push(elm);
+        self.ancestors.push(elm);
+    }
 
-        let keyword = match value {
-            Value::Keyword(keyword) => keyword,
-            _ => return WordBreak::Normal,
-        };
+    fn pop(&mut self) {
+        self.bloom.pop();
+        self.ancestors.pop();
+    }
 
-        match &keyword[..] {
-            "break-all" => WordBreak::BreakAll,
-            "keep-all" => WordBreak::KeepAll,
-            _ => WordBreak::Normal,
+    /// Make `ancestors` current, keeping common ancestors in the Bloom filter.
+    /// A thread may continue from a subtree which is not related to the previous one.
+    fn set_ancestors(&mut self, ancestors: &[&'a ElementData]) {
+        let common = self
+            .ancestors
+            .iter()
+            .zip(ancestors)
+            .take_while(|(a, b)| ptr::eq(**a, **b))
+            .count();
+        while self.ancestors.len() > common {
+            self.pop();
+        }
+        for elm in &ancestors[common..] {
+            self.push(elm);
         }
     }
 }
 
-pub fn create_style_tree<'a>(
-    root: &'a Node,
-    stylesheet: &'a Stylesheet,
-    inherited_specified_values: Option<PropertyMap>,
+fn build_style_tree<'a>(
+    document: &'a Document,
+    id: NodeId,
+    shared: &SharedStyleContext<'a>,
+    local: &mut ThreadLocalStyleContext<'a>,
+    parent_style: Option<&ComputedValues>,
+    inherited_custom_properties: &Arc<CustomPropertyMap>,
 ) -> StyledNode<'a> {
-    let inherited_specified_values = inherited_specified_values.unwrap_or_default();
-    let root_specified_values = match &root.node_type {
-        NodeType::Element(elm) => specified_values(elm, stylesheet, inherited_specified_values),
-        NodeType::Text(_) => inherited_specified_values,
+    let node = document.node(id);
+    let elm = match &node.node_type {
+        NodeType::Element(elm) => elm,
+        NodeType::Text(_) => {
+            let style =
+                ComputedValues::inherit_from(parent_style.unwrap_or(ComputedValues::initial()));
+            return StyledNode::new(node, style, inherited_custom_properties.clone(), vec![]);
+        }
     };
-
-    let new_inherited_specified_values: PropertyMap = root_specified_values
-        .iter()
-        .filter(|(k, _)| INHERITABLE_PROPERTY_LIST.contains(&k.as_str()))
-        .map(|(k, v)| (k.clone(), v.clone()))
-        .collect();
-    StyledNode::new(
-        root,
-        root_specified_values,
-        root.children
-            .iter()
-            .map(|node| {
-                create_style_tree(
-                    node,
-                    stylesheet,
-                    Some(new_inherited_specified_values.clone()),
+    let (style, custom_properties) = style_element(
+        elm,
+        shared,
+        &local.bloom,
+        &local.ancestors,
+        &mut local.sharing_cache,
+        parent_style,
+        inherited_custom_properties,
+    );
+
+    local.push(elm);
+    let children: Vec<NodeId> = document.children(id).collect();
+    let children = match shared.traversal {
+        Traversal::Parallel if children.len() > 1 => {
+            let ancestors = local.ancestors.clone();
+            children
+                .par_iter()
+                .map_init(ThreadLocalStyleContext::new, |local, child| {
+                    local.set_ancestors(&ancestors);
+                    build_style_tree(
+                        document,
+                        *child,
+                        shared,
+                        local,
+                        Some(&style),
+                        &custom_properties,
+                    )
+                })
+                .collect()
+        }
+        _ => children
+            .into_iter()
+            .map(|child| {
+                build_style_tree(
+                    document,
+                    child,
+                    shared,
+                    local,
+                    Some(&style),
+                    &custom_properties,
                 )
             })
             .collect(),
-    )
+    };
+    local.pop();
+
+    StyledNode::new(node, style, custom_properties, children)
+}
+
+/// Reuse the style of a sibling or a cousin if possible, otherwise match rules and cascade them.
+/// `ancestors` are elements from the root to the parent of `elm`, which are in `bloom`.
+pub(crate) fn style_element(
+    elm: &ElementData,
+    shared: &SharedStyleContext,
+    bloom: &StyleBloom,
+    ancestors: &[&ElementData],
+    sharing_cache: &mut StyleSharingCache,
+    parent_style: Option<&ComputedValues>,
+    inherited_custom_properties: &Arc<CustomPropertyMap>,
+) -> (ComputedValues, Arc<CustomPropertyMap>) {
+    let sharing_key = if StyleSharingKey::can_share(elm) {
+        let revalidation_results = shared
+            .selector_map
+            .get_revalidation_results(elm, ancestors, bloom);
+        Some(StyleSharingKey::new(
+            elm,
+            parent_style,
+            inherited_custom_properties,
+            revalidation_results,
+        ))
+    } else {
+        None
+    };
+    if let Some(key) = &sharing_key {
+        let shared_style = sharing_cache.lookup(key);
+        shared.sharing_counters.count(shared_style.is_some());
+        if let Some(shared_style) = shared_style {
+            return shared_style;
+        }
+    }
+
+    let rules = shared
+        .selector_map
+        .get_matching_rules(elm, ancestors, bloom);
+    let (declared_values, custom_properties) =
+        declared_values(elm, rules, inherited_custom_properties);
+    let style = ComputedValues::compute(&declared_values, parent_style);
+
+    if let Some(key) = sharing_key {
+        sharing_cache.insert(key, &style, &custom_properties);
+    }
+    (style, custom_properties)
 }
 
-fn specified_values(
+/// Cascade declarations of an element.
+/// Return declared values of the element and its computed custom properties.
+/// `rules` are matched rules in order of the stylesheet.
+fn declared_values(
     elm: &ElementData,
-    stylesheet: &Stylesheet,
-    inherited_specified_values: PropertyMap,
-) -> PropertyMap {
-    let mut values = inherited_specified_values;
-    let mut rules = match_rules(elm, stylesheet);
+    mut rules: Vec<MatchedRule>,
+    inherited_custom_properties: &Arc<CustomPropertyMap>,
+) -> (PropertyMap, Arc<CustomPropertyMap>) {
+    let mut values = PropertyMap::new();
+    let mut custom_properties = CustomPropertyMap::new();
 
     rules.sort_by(|&(specificity1, rule1), &(specificity2, rule2)| {
         if rule1.level != rule2.level {
@@ -237,69 +369,169 @@ fn specified_values(
         }
         specificity1.cmp(&specificity2)
     });
-    for (_, rule) in rules {
-        for declaration in &rule.declarations {
-            values.insert(declaration.name.clone(), declaration.value.clone());
-        }
-    }
+    let mut declarations: Vec<(Origin, Declaration)> = rules
+        .into_iter()
+        .flat_map(|(_, rule)| {
+            rule.declarations
+                .iter()
+                .map(move |declaration| (rule.level.clone(), declaration.clone()))
+        })
+        .collect();
 
     if let Some(style) = elm.attributes.get("style") {
         let mut p = CSSParser::new(style.clone());
-        let declarations = p.parse_declarations();
-        for declaration in declarations {
-            values.insert(declaration.name.clone(), declaration.value.clone());
-        }
+        let style_declarations = p.parse_declarations().into_iter();
+        declarations.extend(style_declarations.map(|declaration| (Origin::Author, declaration)));
     }
 
-    values
-}
+    // Values cascaded only from user agent origin, which `revert` rolls back to.
+    let mut ua_values = PropertyMap::new();
+    let mut ua_custom_properties = CustomPropertyMap::new();
+    // Properties whose values are from user agent origin, where `revert` is the same as `unset`.
+    let mut ua_declared = HashSet::new();
+    for (origin, declaration) in declarations {
+        // An invalid declaration is dropped, so that it does not override earlier ones.
+        if declaration.value == Value::None {
+            continue;
+        }
+        let is_ua = origin == Origin::UA;
+        // A shorthand overrides its longhands declared before it,
+        // so a remaining longhand takes precedence over the shorthand.
+        if let Some(longhands) = LonghandId::from_shorthand(&declaration.name) {
+            for id in longhands {
+                values.remove(id.name());
+                if is_ua {
+                    ua_values.remove(id.name());
+                }
+            }
+        }
+        match declaration.value {
+            Value::Unparsed(tokens) if declaration.name.starts_with("--") => {
+                if is_revert(&tokens) {
+                    // Custom properties are inherited, so `unset` is the same as no declaration.
+                    match ua_custom_properties
+                        .get(&declaration.name)
+                        .filter(|_| !is_ua)
+                    {
+                        Some(tokens) => {
+                            custom_properties.insert(declaration.name, tokens.clone());
+                        }
+                        None => {
+                            custom_properties.remove(&declaration.name);
+                        }
+                    }
+                    continue;
+                }
+                if is_ua {
+                    ua_custom_properties.insert(declaration.name.clone(), tokens.clone());
+                }
+                custom_properties.insert(declaration.name, tokens);
+            }
+            Value::CssWide(CssWideKeyword::Revert)
+            | Value::CssWide(CssWideKeyword::RevertLayer)
+                if !is_ua =>
+            {
+                let value = reverted_value(&declaration.name, &ua_values);
+                ua_declared.insert(declaration.name.clone());
+                values.insert(declaration.name, value);
+            }
+            value => {
+                if is_ua {
+                    ua_values.insert(declaration.name.clone(), value.clone());
+                    ua_declared.insert(declaration.name.clone());
+                } else {
+                    ua_declared.remove(&declaration.name);
+                }
+                values.insert(declaration.name, value);
+            }
+        }
+    }
 
-type MatchedRule<'a> = (Specificity, &'a Rule);
+    let custom_properties =
+        custom_properties::compute(inherited_custom_properties, custom_properties);
+    substitute_vars(&mut values, &custom_properties);
 
-fn match_rules<'a>(elm: &ElementData, stylesheet: &'a Stylesheet) -> Vec<MatchedRule<'a>> {
-    stylesheet
-        .rules
-        .iter()
-        .filter_map(|rule| match_rule(elm, rule))
-        .collect()
-}
+    // `revert` can also be a result of `var()` substitution.
+    let mut has_reverted = false;
+    for (name, value) in values.iter_mut() {
+        let is_revert = matches!(
+            value,
+            Value::CssWide(CssWideKeyword::Revert) | Value::CssWide(CssWideKeyword::RevertLayer)
+        );
+        if is_revert && !ua_declared.contains(name) {
+            *value = reverted_value(name, &ua_values);
+            has_reverted = true;
+        }
+    }
+    if has_reverted {
+        substitute_vars(&mut values, &custom_properties);
+    }
 
-fn match_rule<'a>(elm: &ElementData, rule: &'a Rule) -> Option<MatchedRule<'a>> {
-    rule.selectors
-        .iter()
-        .find(|selector| matches(elm, *selector))
-        .map(|selector| (selector.specificity(), rule))
+    (values, custom_properties)
 }
 
-fn matches(elm: &ElementData, selector: &Selector) -> bool {
-    match selector {
-        Selector::Simple(simple) => matches_simple_selector(elm, simple),
+/// Value which `revert` in author origin rolls back to, which is cascaded in user agent origin.
+/// A longhand declared by its shorthand takes the component of the shorthand,
+/// and a property which is not declared in user agent origin is `unset`.
+// refer: https://drafts.csswg.org/css-cascade/#default
+fn reverted_value(name: &str, ua_values: &PropertyMap) -> Value {
+    if let Some(value) = ua_values.get(name) {
+        return value.clone();
     }
+    LonghandId::from_name(name)
+        .and_then(|id| {
+            let shorthand = id.shorthand()?;
+            let index = LonghandId::from_shorthand(shorthand)?
+                .iter()
+                .position(|longhand| *longhand == id)?;
+            ua_values.get(shorthand).map(|value| value.component(index))
+        })
+        .unwrap_or(Value::CssWide(CssWideKeyword::Unset))
 }
 
-fn matches_simple_selector(elm: &ElementData, selector: &SimpleSelector) -> bool {
-    if selector.tag_name.iter().any(|name| *name != elm.tag_name) {
-        return false;
-    }
-
-    if selector.id.iter().any(|id| Some(id) != elm.id()) {
-        return false;
-    }
-
-    if selector
-        .class
-        .iter()
-        .any(|class| !elm.classes().contains(&**class))
-    {
-        return false;
-    }
+/// `revert-layer` behaves as `revert`, because cascade layers are not supported.
+fn is_revert(tokens: &str) -> bool {
+    matches!(
+        CssWideKeyword::from_ident(tokens),
+        Some(CssWideKeyword::Revert) | Some(CssWideKeyword::RevertLayer)
+    )
+}
 
-    true
+/// Substitute `var()` in values and parse them.
+/// An invalid value at computed-value time is removed to behave as `unset`,
+/// which inherits the parent value for inherited properties and uses the initial value otherwise.
+// refer: https://drafts.csswg.org/css-variables/#invalid-at-computed-value-time
+fn substitute_vars(values: &mut PropertyMap, custom_properties: &CustomPropertyMap) {
+    values.retain(|name, value| {
+        let tokens = match value {
+            Value::Unparsed(tokens) => tokens,
+            _ => return true,
+        };
+        let substituted =
+            custom_properties::substitute(tokens, &mut |name| custom_properties.get(name).cloned())
+                .map(|tokens| CSSParser::new(tokens).parse_substituted_value(name))
+                .filter(|value| *value != Value::None);
+
+        match substituted {
+            Some(substituted) => {
+                *value = substituted;
+                true
+            }
+            None => false,
+        }
+    });
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::computed_values::{
+        AlignItems, BoxSizing, Clear, FlexBasis, FlexDirection, FlexWrap, Float, GridAutoFlow,
+        JustifyContent, LengthOrAuto, LengthPercentage, NamedArea, Position, TrackBreadth,
+        TrackListItem, TrackSize,
+    };
+    use crate::cssom::{GridLine, RepeatCount};
+    use crate::layout::font::FontContext;
     use crate::parser::css::CSSParser;
     use crate::parser::html::HTMLParser;
 
@@ -348,52 +580,72 @@ div#foo {
         let mut html_parser = HTMLParser::new(html.into());
         let mut css_parser = CSSParser::new(css.into());
 
-        let dom = html_parser.run();
+        let dom = Document::new(html_parser.run());
 
         let rules = css_parser.parse_rules(Origin::Author);
         let cssom = Stylesheet::new(rules);
 
-        let styled_node = create_style_tree(&dom, &cssom, None);
+        let styled_node = create_style_tree(&dom, &cssom);
 
         test_element(&styled_node.node.node_type, &"body");
-        assert_eq!(&styled_node.specified_values.len(), &1);
-        assert_eq!(
-            *styled_node.specified_values.get("height").unwrap(),
-            Value::Length(100.0, Unit::Px),
-        );
+        assert_eq!(styled_node.style.box_.height, LengthOrAuto::Length(100.0));
 
-        assert_eq!(&styled_node.node.children.len(), &2);
+        assert_eq!(&styled_node.children.len(), &2);
 
         let div = &styled_node.children[0];
         test_element(&div.node.node_type, &"div");
-        assert_eq!(&div.specified_values.len(), &3);
-        assert_eq!(
-            *div.specified_values.get("color").unwrap(),
-            Value::Keyword("red".into()),
-        );
-        assert_eq!(
-            *div.specified_values.get("height").unwrap(),
-            Value::Keyword("auto".into()),
-        );
-        assert_eq!(
-            *div.specified_values.get("display").unwrap(),
-            Value::Keyword("block".into()),
-        );
+        assert_eq!(div.style.inherited_text.color, Color::new(255, 0, 0, 1.0));
+        assert_eq!(div.style.box_.height, LengthOrAuto::Auto);
+        assert_eq!(div.display(), Display::Block);
 
         let div = &styled_node.children[1];
         test_element(&div.node.node_type, &"div");
-        assert_eq!(&div.specified_values.len(), &2);
-        assert_eq!(
-            *div.specified_values.get("color").unwrap(),
-            Value::ColorValue(Color::new(204, 0, 0, 1.0)),
-        );
-        assert_eq!(
-            *div.specified_values.get("display").unwrap(),
-            Value::Keyword("block".into()),
-        );
+        assert_eq!(div.style.inherited_text.color, Color::new(204, 0, 0, 1.0));
+        assert_eq!(div.display(), Display::Block);
 
         let text = &div.children[0];
-        assert_eq!(&text.specified_values.len(), &1,);
+        assert_eq!(text.display(), Display::Inline);
+    }
+
+    #[test]
+    fn test_combinator() {
+        let html = "
+<body>
+  <div class='a'>
+    <p><span>test</span></p>
+  </div>
+  <p><span>test</span></p>
+</body>
+";
+        let css = "
+.a span {
+  color: red;
+}
+
+body > p {
+  display: block;
+}
+";
+        let mut html_parser = HTMLParser::new(html.into());
+        let mut css_parser = CSSParser::new(css.into());
+
+        let dom = Document::new(html_parser.run());
+
+        let rules = css_parser.parse_rules(Origin::Author);
+        let cssom = Stylesheet::new(rules);
+
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        let p = &styled_node.children[0].children[0];
+        assert_eq!(p.display(), Display::Inline);
+        let span = &p.children[0];
+        assert_eq!(span.style.inherited_text.color, Color::new(255, 0, 0, 1.0));
+
+        // The ancestor `.a` has been popped from the Bloom filter.
+        let p = &styled_node.children[1];
+        assert_eq!(p.display(), Display::Block);
+        let span = &p.children[0];
+        assert_eq!(span.style.inherited_text.color, Color::new(0, 0, 0, 1.0));
     }
 
     #[test]
@@ -442,7 +694,7 @@ div#foo.baz {
         let mut ua_css_parser = CSSParser::new(ua_css.into());
         let mut author_css_parser = CSSParser::new(author_css.into());
 
-        let dom = html_parser.run();
+        let dom = Document::new(html_parser.run());
 
         let ua_rules = ua_css_parser.parse_rules(Origin::UA);
         let mut author_rules = author_css_parser.parse_rules(Origin::Author);
@@ -451,40 +703,66 @@ div#foo.baz {
 
         let cssom = Stylesheet::new(author_rules);
 
-        let styled_node = create_style_tree(&dom, &cssom, None);
+        let styled_node = create_style_tree(&dom, &cssom);
 
         test_element(&styled_node.node.node_type, &"body");
-        assert_eq!(&styled_node.specified_values.len(), &3);
-        assert_eq!(
-            *styled_node.specified_values.get("display").unwrap(),
-            Value::Keyword("block".into()),
-        );
+        assert_eq!(styled_node.display(), Display::Block);
         assert_eq!(
-            *styled_node.specified_values.get("margin").unwrap(),
-            Value::Length(0.0, Unit::Px),
-        );
-        assert_eq!(
-            *styled_node.specified_values.get("height").unwrap(),
-            Value::Length(100.0, Unit::Px),
+            styled_node.style.margin.margin_top,
+            LengthOrAuto::Length(0.0),
         );
+        assert_eq!(styled_node.style.box_.height, LengthOrAuto::Length(100.0));
 
-        assert_eq!(&styled_node.node.children.len(), &1);
+        assert_eq!(&styled_node.children.len(), &1);
 
         let div = &styled_node.children[0];
         test_element(&div.node.node_type, &"div");
-        assert_eq!(&div.specified_values.len(), &3);
-        assert_eq!(
-            *div.specified_values.get("color").unwrap(),
-            Value::Keyword("red".into()),
-        );
-        assert_eq!(
-            *div.specified_values.get("display").unwrap(),
-            Value::Keyword("inline".into()),
-        );
-        assert_eq!(
-            *div.specified_values.get("margin").unwrap(),
-            Value::Keyword("auto".into()),
-        );
+        assert_eq!(div.style.inherited_text.color, Color::new(255, 0, 0, 1.0));
+        assert_eq!(div.display(), Display::Inline);
+        assert_eq!(div.style.margin.margin_left, LengthOrAuto::Auto);
+    }
+
+    #[test]
+    fn test_shorthand_precedence() {
+        let html = "
+<body>
+  <div id='foo' class='bar'></div>
+</body>
+";
+
+        let author_css = "
+.bar {
+  margin-left: 1px;
+  margin: 2px;
+  padding: 3px;
+  padding-top: 4px;
+}
+
+#foo {
+  margin-right: 5px;
+}
+
+div {
+  margin-top: 6px;
+}
+";
+
+        let mut html_parser = HTMLParser::new(html.into());
+        let mut css_parser = CSSParser::new(author_css.into());
+
+        let dom = Document::new(html_parser.run());
+
+        let cssom = Stylesheet::new(css_parser.parse_rules(Origin::Author));
+
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        let div = &styled_node.children[0];
+        // Declarations follow specificity, and then source order.
+        assert_eq!(div.style.margin.margin_left, LengthOrAuto::Length(2.0));
+        assert_eq!(div.style.margin.margin_right, LengthOrAuto::Length(5.0));
+        assert_eq!(div.style.margin.margin_top, LengthOrAuto::Length(2.0));
+        assert_eq!(div.style.padding.padding_top, 4.0);
+        assert_eq!(div.style.padding.padding_left, 3.0);
     }
 
     #[test]
@@ -509,34 +787,24 @@ div#foo.baz {
         let mut html_parser = HTMLParser::new(html.into());
         let mut css_parser = CSSParser::new(author_css.into());
 
-        let dom = html_parser.run();
+        let dom = Document::new(html_parser.run());
 
         let author_rules = css_parser.parse_rules(Origin::Author);
 
         let cssom = Stylesheet::new(author_rules);
 
-        let styled_node = create_style_tree(&dom, &cssom, None);
+        let styled_node = create_style_tree(&dom, &cssom);
 
         test_element(&styled_node.node.node_type, &"body");
-        assert_eq!(&styled_node.specified_values.len(), &0);
+        assert_eq!(styled_node.style, *ComputedValues::initial());
 
-        assert_eq!(&styled_node.node.children.len(), &1);
+        assert_eq!(&styled_node.children.len(), &1);
 
         let div = &styled_node.children[0];
         test_element(&div.node.node_type, &"div");
-        assert_eq!(&div.specified_values.len(), &3);
-        assert_eq!(
-            *div.specified_values.get("color").unwrap(),
-            Value::Keyword("green".into()),
-        );
-        assert_eq!(
-            *div.specified_values.get("display").unwrap(),
-            Value::Keyword("block".into()),
-        );
-        assert_eq!(
-            *div.specified_values.get("height").unwrap(),
-            Value::Keyword("auto".into()),
-        );
+        assert_eq!(div.style.inherited_text.color, Color::new(0, 128, 0, 1.0));
+        assert_eq!(div.display(), Display::Block);
+        assert_eq!(div.style.box_.height, LengthOrAuto::Auto);
     }
 
     #[test]
@@ -562,53 +830,631 @@ div#foo.baz {
         let mut html_parser = HTMLParser::new(html.into());
         let mut css_parser = CSSParser::new(author_css.into());
 
-        let dom = html_parser.run();
+        let dom = Document::new(html_parser.run());
 
         let author_rules = css_parser.parse_rules(Origin::Author);
 
         let cssom = Stylesheet::new(author_rules);
 
-        let styled_node = create_style_tree(&dom, &cssom, None);
+        let styled_node = create_style_tree(&dom, &cssom);
 
         test_element(&styled_node.node.node_type, &"body");
-        assert_eq!(&styled_node.specified_values.len(), &0);
+        assert_eq!(styled_node.style, *ComputedValues::initial());
 
-        assert_eq!(&styled_node.node.children.len(), &1);
+        assert_eq!(&styled_node.children.len(), &1);
 
         let div = &styled_node.children[0];
         test_element(&div.node.node_type, &"div");
-        assert_eq!(&div.specified_values.len(), &1);
-        assert_eq!(
-            *div.specified_values.get("color").unwrap(),
-            Value::Keyword("green".into()),
-        );
+        assert_eq!(div.style.inherited_text.color, Color::new(0, 128, 0, 1.0));
 
         assert_eq!(&div.children.len(), &1);
 
         let p = &div.children[0];
         test_element(&p.node.node_type, &"p");
-        assert_eq!(&p.specified_values.len(), &2);
-        assert_eq!(
-            *p.specified_values.get("color").unwrap(),
-            Value::Keyword("green".into()),
-        );
-        assert_eq!(
-            *p.specified_values.get("font-size").unwrap(),
-            Value::Length(16.0, Unit::Px),
-        );
+        assert_eq!(p.style.inherited_text.color, Color::new(0, 128, 0, 1.0));
+        assert_eq!(p.font_size(), 16.0);
 
         assert_eq!(&p.children.len(), &1);
 
         let text = &p.children[0];
         test_text(&text.node.node_type, &"test");
-        assert_eq!(&text.specified_values.len(), &2);
+        // Inherited style structs are shared with the parent.
+        assert!(Arc::ptr_eq(&text.style.font, &p.style.font));
+        assert!(Arc::ptr_eq(
+            &text.style.inherited_text,
+            &p.style.inherited_text
+        ));
+        // Reset style structs are shared with initial values.
+        assert!(Arc::ptr_eq(
+            &p.style.margin,
+            &ComputedValues::initial().margin
+        ));
+        assert_eq!(text.style.inherited_text.color, Color::new(0, 128, 0, 1.0));
+        assert_eq!(text.font_size(), 16.0);
+    }
+
+    #[test]
+    fn test_custom_properties() {
+        let html = "
+<body class='root'>
+  <div class='foo' style='--gap: 4px; height: var(--gap)'>
+    <p class='bar'>test</p>
+  </div>
+</body>
+";
+
+        let author_css = "
+.root {
+  --main-color: red;
+  --a: var(--b);
+  --b: var(--a);
+  color: blue;
+}
+
+.foo {
+  --text-color: var(--main-color);
+  --size: 3px;
+  --self: 1px;
+  color: var(--text-color);
+  width: var(--undefined, 10px);
+  margin: var(--a, 1px);
+}
+
+.bar {
+  --main-color: green;
+  --size: var(--undefined);
+  --self: var(--self);
+  color: var(--undefined);
+  display: var(--undefined);
+}
+";
+
+        let mut html_parser = HTMLParser::new(html.into());
+        let mut css_parser = CSSParser::new(author_css.into());
+
+        let dom = Document::new(html_parser.run());
+
+        let cssom = Stylesheet::new(css_parser.parse_rules(Origin::Author));
+
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        // `--a` and `--b` make a cycle.
+        assert_eq!(styled_node.custom_properties.len(), 1);
+        assert_eq!(styled_node.custom_properties["--main-color"], "red");
+
+        let div = &styled_node.children[0];
+        assert_eq!(div.custom_properties["--text-color"], "red");
+        assert_eq!(div.custom_properties["--gap"], "4px");
+        assert_eq!(div.style.inherited_text.color, Color::new(255, 0, 0, 1.0));
+        assert_eq!(div.style.box_.width, LengthOrAuto::Length(10.0));
+        assert_eq!(div.style.margin.margin_top, LengthOrAuto::Length(1.0));
+        assert_eq!(div.style.box_.height, LengthOrAuto::Length(4.0));
+
+        let p = &div.children[0];
+        // Custom properties are inherited, and are not substituted again in descendants.
+        assert_eq!(p.custom_properties["--main-color"], "green");
+        assert_eq!(p.custom_properties["--text-color"], "red");
+        // Invalid at computed-value time, so the parent value is not inherited.
+        assert_eq!(p.custom_properties.get("--size"), None);
+        assert_eq!(p.custom_properties.get("--self"), None);
+        // Invalid at computed-value time, so `color` is inherited and `display` is initial.
+        assert_eq!(p.style.inherited_text.color, Color::new(255, 0, 0, 1.0));
+        assert_eq!(p.display(), Display::Inline);
+    }
+
+    #[test]
+    fn test_css_wide_keywords() {
+        let html = "
+<body class='root'>
+  <div class='a'></div>
+  <div class='b'></div>
+  <div class='c'></div>
+</body>
+";
+        let ua_css = "
+div {
+  display: block;
+  margin: 5px;
+  grid-row: 2 / 4;
+}
+";
+        let author_css = "
+.root {
+  color: green;
+  width: 300px;
+  --x: 1px;
+}
+
+div {
+  display: inline;
+  margin: 10px;
+  grid-row: 1 / 3;
+}
+
+.a {
+  width: inherit;
+  color: initial;
+  display: revert;
+}
+
+.b {
+  color: unset;
+  width: unset;
+  display: revert-layer;
+  margin-left: revert;
+  grid-row-end: revert;
+}
+
+.c {
+  --x: initial;
+  margin: initial;
+  margin-top: var(--undefined, revert);
+}
+";
+
+        let mut html_parser = HTMLParser::new(html.into());
+        let mut ua_css_parser = CSSParser::new(ua_css.into());
+        let mut author_css_parser = CSSParser::new(author_css.into());
+
+        let dom = Document::new(html_parser.run());
+
+        let mut rules = author_css_parser.parse_rules(Origin::Author);
+        rules.extend(ua_css_parser.parse_rules(Origin::UA));
+        let cssom = Stylesheet::new(rules);
+
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        let a = &styled_node.children[0];
+        assert_eq!(a.style.box_.width, LengthOrAuto::Length(300.0));
+        assert_eq!(a.style.inherited_text.color, Color::new(0, 0, 0, 1.0));
+        assert_eq!(a.display(), Display::Block);
+        assert_eq!(a.style.margin.margin_left, LengthOrAuto::Length(10.0));
+
+        let b = &styled_node.children[1];
+        assert_eq!(b.style.inherited_text.color, Color::new(0, 128, 0, 1.0));
+        assert_eq!(b.style.box_.width, LengthOrAuto::Auto);
+        assert_eq!(b.display(), Display::Block);
+        // Reverted to `margin` shorthand in user agent stylesheet.
+        assert_eq!(b.style.margin.margin_left, LengthOrAuto::Length(5.0));
+        assert_eq!(b.style.margin.margin_top, LengthOrAuto::Length(10.0));
+        // Reverted to the component of `grid-row` shorthand in user agent stylesheet.
+        assert_eq!(b.style.grid.grid_row_start, GridLine::Line(1));
+        assert_eq!(b.style.grid.grid_row_end, GridLine::Line(4));
+
+        let c = &styled_node.children[2];
+        assert_eq!(c.custom_properties.get("--x"), None);
+        assert_eq!(c.style.margin.margin_left, LengthOrAuto::Length(0.0));
+        // `revert` substituted by `var()` also rolls back to user agent origin.
+        assert_eq!(c.style.margin.margin_top, LengthOrAuto::Length(5.0));
+        assert_eq!(b.custom_properties["--x"], "1px");
+    }
+
+    #[test]
+    fn test_sizing_properties() {
+        let html = "<div><p></p></div>";
+        let css = "
+div { width: 50%; min-width: 10em; max-width: 100%; box-sizing: border-box; }
+p { max-width: 100px; max-height: none; min-height: 20%; height: auto; }
+";
+
+        let dom = Document::new(HTMLParser::new(html.into()).run());
+        let cssom = Stylesheet::new(CSSParser::new(css.into()).parse_rules(Origin::Author));
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        let div = &styled_node.style.box_;
+        assert_eq!(div.width, LengthOrAuto::Percentage(0.5));
+        assert_eq!(div.min_width, Some(LengthPercentage::Length(160.)));
+        assert_eq!(div.max_width, Some(LengthPercentage::Percentage(1.)));
+        assert_eq!(div.max_height, None);
+        assert_eq!(div.box_sizing, BoxSizing::BorderBox);
+
+        let p = &styled_node.children[0].style.box_;
+        assert_eq!(p.max_width, Some(LengthPercentage::Length(100.)));
+        assert_eq!(p.min_height, Some(LengthPercentage::Percentage(0.2)));
+        assert_eq!(p.height, LengthOrAuto::Auto);
+        // `box-sizing` is not inherited.
+        assert_eq!(p.box_sizing, BoxSizing::ContentBox);
+    }
+
+    #[test]
+    fn test_position_properties() {
+        let html = "<div><span></span><em></em></div>";
+        let css = "
+div { display: block; }
+span { position: absolute; top: 10%; left: 5px; z-index: -1; }
+em { position: relative; }
+";
+
+        let dom = Document::new(HTMLParser::new(html.into()).run());
+        let cssom = Stylesheet::new(CSSParser::new(css.into()).parse_rules(Origin::Author));
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        // Absolutely positioned boxes are blockified.
+        let span = &styled_node.children[0].style.box_;
+        assert_eq!(span.display, Display::Block);
+        assert_eq!(span.position, Position::Absolute);
+        assert_eq!(span.top, LengthOrAuto::Percentage(0.1));
+        assert_eq!(span.left, LengthOrAuto::Length(5.));
+        assert_eq!(span.bottom, LengthOrAuto::Auto);
+        assert_eq!(span.z_index, Some(-1));
+
+        let em = &styled_node.children[1].style.box_;
+        assert_eq!(em.display, Display::Inline);
+        assert_eq!(em.z_index, None);
+    }
+
+    #[test]
+    fn test_float_properties() {
+        let html = "<div><span></span><em></em><b></b></div>";
+        let css = "
+div { display: block; }
+span { float: left; clear: both; }
+em { float: right; position: fixed; }
+b { display: flow-root; clear: right; }
+";
+
+        let dom = Document::new(HTMLParser::new(html.into()).run());
+        let cssom = Stylesheet::new(CSSParser::new(css.into()).parse_rules(Origin::Author));
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        // Floats are blockified.
+        let span = &styled_node.children[0].style.box_;
+        assert_eq!(span.display, Display::Block);
+        assert_eq!(span.float, Float::Left);
+        assert_eq!(span.clear, Clear::Both);
+
+        // Absolutely positioned boxes do not float.
+        let em = &styled_node.children[1].style.box_;
+        assert_eq!(em.float, Float::None);
+
+        let b = &styled_node.children[2].style.box_;
+        assert_eq!(b.display, Display::FlowRoot);
+        assert_eq!(b.float, Float::None);
+        assert_eq!(b.clear, Clear::Right);
+    }
+
+    #[test]
+    fn test_flex_properties() {
+        let html = "<div><span></span><em></em></div>";
+        let css = "
+div { display: flex; flex-direction: column-reverse; flex-wrap: wrap; justify-content: space-between; align-items: center; gap: 10px; }
+span { float: left; flex-grow: 2; flex-shrink: 0; flex-basis: 50%; order: -1; align-self: baseline; }
+em { flex-basis: content; row-gap: 5%; }
+";
+
+        let dom = Document::new(HTMLParser::new(html.into()).run());
+        let cssom = Stylesheet::new(CSSParser::new(css.into()).parse_rules(Origin::Author));
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        let div = &styled_node.style;
+        assert_eq!(div.box_.display, Display::Flex);
+        assert_eq!(div.flex.flex_direction, FlexDirection::ColumnReverse);
+        assert_eq!(div.flex.flex_wrap, FlexWrap::Wrap);
+        assert_eq!(div.flex.justify_content, JustifyContent::SpaceBetween);
+        assert_eq!(div.flex.align_items, AlignItems::Center);
+        assert_eq!(div.flex.row_gap, LengthPercentage::Length(10.));
+        assert_eq!(div.flex.column_gap, LengthPercentage::Length(10.));
+
+        // Flex items are blockified and not floated.
+        let span = &styled_node.children[0].style;
+        assert_eq!(span.box_.display, Display::Block);
+        assert_eq!(span.box_.float, Float::None);
+        assert_eq!(span.flex.flex_grow, 2.);
+        assert_eq!(span.flex.flex_shrink, 0.);
+        assert_eq!(
+            span.flex.flex_basis,
+            FlexBasis::Size(LengthOrAuto::Percentage(0.5))
+        );
+        assert_eq!(span.flex.order, -1);
+        assert_eq!(span.flex.align_self, Some(AlignItems::Baseline));
+
+        // Flex properties are not inherited.
+        let em = &styled_node.children[1].style;
+        assert_eq!(em.flex.flex_direction, FlexDirection::Row);
+        assert_eq!(em.flex.flex_basis, FlexBasis::Content);
+        assert_eq!(em.flex.row_gap, LengthPercentage::Percentage(0.05));
+        assert_eq!(em.flex.align_self, None);
+    }
+
+    #[test]
+    fn test_grid_properties() {
+        let html = "<div><span></span><em></em></div>";
+        let css = "
+div { display: grid; grid-template-columns: 100px repeat(auto-fill, minmax(10%, 1fr)); grid-template-areas: 'a a .' 'b c c'; grid-auto-flow: column dense; justify-items: end; }
+span { grid-area: a; justify-self: center; }
+em { grid-row: 2 / span 3; grid-column: -1; }
+";
+
+        let dom = Document::new(HTMLParser::new(html.into()).run());
+        let cssom = Stylesheet::new(CSSParser::new(css.into()).parse_rules(Origin::Author));
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        let div = &styled_node.style;
+        assert_eq!(div.box_.display, Display::Grid);
+        assert_eq!(
+            div.grid.grid_template_columns,
+            vec![
+                TrackListItem::Size(TrackSize {
+                    min: TrackBreadth::Length(100.),
+                    max: TrackBreadth::Length(100.),
+                }),
+                TrackListItem::Repeat(
+                    RepeatCount::AutoFill,
+                    vec![TrackSize {
+                        min: TrackBreadth::Percentage(0.1),
+                        max: TrackBreadth::Flex(1.),
+                    }]
+                ),
+            ]
+        );
+        // Cells of the same name form an area, and `.` is a null cell.
+        let areas = div.grid.grid_template_areas.as_ref().unwrap();
+        assert_eq!((areas.rows, areas.columns), (2, 3));
         assert_eq!(
-            *text.specified_values.get("color").unwrap(),
-            Value::Keyword("green".into()),
+            areas.areas[0],
+            NamedArea {
+                name: "a".into(),
+                rows: 0..1,
+                columns: 0..2,
+            }
         );
+        assert_eq!(areas.areas.len(), 3);
         assert_eq!(
-            *text.specified_values.get("font-size").unwrap(),
-            Value::Length(16.0, Unit::Px),
+            div.grid.grid_auto_flow,
+            GridAutoFlow {
+                column: true,
+                dense: true,
+            }
+        );
+        assert_eq!(div.grid.justify_items, AlignItems::FlexEnd);
+
+        // `grid-area` with a name sets all of the lines to the name.
+        let span = &styled_node.children[0].style;
+        assert_eq!(span.grid.grid_row_start, GridLine::Name("a".into()));
+        assert_eq!(span.grid.grid_column_end, GridLine::Name("a".into()));
+        assert_eq!(span.grid.justify_self, Some(AlignItems::Center));
+
+        let em = &styled_node.children[1].style;
+        assert_eq!(em.grid.grid_row_start, GridLine::Line(2));
+        assert_eq!(em.grid.grid_row_end, GridLine::Span(3));
+        assert_eq!(em.grid.grid_column_start, GridLine::Line(-1));
+        assert_eq!(em.grid.grid_column_end, GridLine::Auto);
+        // Grid properties are not inherited.
+        assert_eq!(em.grid.grid_template_columns, vec![]);
+        assert_eq!(em.grid.justify_self, None);
+    }
+
+    #[test]
+    fn test_font_size() {
+        let html = "
+<body class='root'>
+  <div class='a'>
+    <p class='b'><span class='c'>test</span></p>
+  </div>
+  <div class='d'></div>
+  <div class='e'><span class='f'></span></div>
+</body>
+";
+        let author_css = "
+.root {
+  font-size: large;
+}
+
+.a {
+  font-size: 150%;
+}
+
+.b {
+  font-size: smaller;
+  margin: 2em;
+}
+
+.c {
+  font-size: 0.5em;
+}
+
+.d {
+  font-size: larger;
+}
+
+.e {
+  font-size: 2px;
+}
+
+.f {
+  font-size: 2em;
+}
+";
+
+        let mut html_parser = HTMLParser::new(html.into());
+        let mut css_parser = CSSParser::new(author_css.into());
+
+        let dom = Document::new(html_parser.run());
+
+        let cssom = Stylesheet::new(css_parser.parse_rules(Origin::Author));
+
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        let assert_size = |actual: f32, expected: f32| {
+            assert!(
+                (actual - expected).abs() < 0.001,
+                "{} should be {}",
+                actual,
+                expected
+            );
+        };
+
+        assert_size(styled_node.font_size(), 19.2);
+
+        let a = &styled_node.children[0];
+        assert_size(a.font_size(), 28.8);
+
+        let b = &a.children[0];
+        assert_size(b.font_size(), 24.);
+        assert_size(b.style.margin.margin_top.to_px(), 48.);
+
+        let c = &b.children[0];
+        assert_size(c.font_size(), 12.);
+        // Text inherits the computed size.
+        assert_size(c.children[0].font_size(), 12.);
+
+        assert_size(styled_node.children[1].font_size(), 23.04);
+
+        // The minimum font size applies to used sizes, and `em` is relative to the computed size.
+        let e = &styled_node.children[2];
+        assert_size(e.font_size(), 2.);
+        assert_size(e.children[0].font_size(), 4.);
+        let mut font_context = FontContext::new();
+        font_context.minimum_font_size = 6.;
+        assert_size(font_context.used_font_size(e), 6.);
+        assert_size(font_context.used_font_size(&e.children[0]), 6.);
+        assert_size(font_context.used_font_size(&styled_node), 19.2);
+    }
+
+    #[test]
+    fn test_line_height() {
+        let html = "<body><div class='a'><p class='b'></p></div><div class='c'><p class='b'></p></div></body>";
+        let author_css = "
+.a {
+  font-size: 10px;
+  line-height: 1.5em;
+}
+
+.b {
+  font-size: 20px;
+}
+
+.c {
+  font-size: 10px;
+  line-height: 150%;
+}
+";
+
+        let dom = Document::new(HTMLParser::new(html.into()).run());
+        let cssom = Stylesheet::new(CSSParser::new(author_css.into()).parse_rules(Origin::Author));
+        let styled_node = create_style_tree(&dom, &cssom);
+
+        // `em` is resolved against the font size of the element, and inherited as a length.
+        let a = &styled_node.children[0];
+        assert_eq!(a.style.inherited_text.line_height, LineHeight::Length(15.));
+        assert_eq!(a.children[0].line_height(), 15.);
+
+        // A percentage is inherited like a number, which is multiplied by the font size.
+        let c = &styled_node.children[1];
+        assert_eq!(c.style.inherited_text.line_height, LineHeight::Number(1.5));
+        assert_eq!(c.line_height(), 15.);
+        assert_eq!(c.children[0].line_height(), 30.);
+    }
+
+    #[test]
+    fn test_style_sharing() {
+        let html = "
+<body>
+  <ul class='a'>
+    <li class='item'>1</li>
+    <li class='item'>2</li>
+    <li class='item' id='third'>3</li>
+  </ul>
+  <ul class='b'>
+    <li class='item'>4</li>
+    <li class='item' style='color: blue;'>5</li>
+  </ul>
+  <ul class='c'>
+    <li class='item'>6</li>
+  </ul>
+</body>
+";
+        let css = "
+.item {
+  color: green;
+}
+
+.a > .item {
+  color: red;
+}
+";
+        let mut html_parser = HTMLParser::new(html.into());
+        let mut css_parser = CSSParser::new(css.into());
+
+        let dom = Document::new(html_parser.run());
+
+        let rules = css_parser.parse_rules(Origin::Author);
+        let cssom = Stylesheet::new(rules);
+
+        let (styled_node, stats) =
+            create_style_tree_with_stats(&dom, &cssom, Traversal::Sequential);
+
+        let color = |ul: usize, li: usize| {
+            styled_node.children[ul].children[li]
+                .style
+                .inherited_text
+                .color
+                .clone()
+        };
+        assert_eq!(color(0, 0), Color::new(255, 0, 0, 1.0));
+        assert_eq!(color(0, 1), Color::new(255, 0, 0, 1.0));
+        assert_eq!(color(0, 2), Color::new(255, 0, 0, 1.0));
+        // Cousins are not shared if a selector depending on ancestors matches differently.
+        assert_eq!(color(1, 0), Color::new(0, 128, 0, 1.0));
+        assert_eq!(color(1, 1), Color::new(0, 0, 255, 1.0));
+        assert_eq!(color(2, 0), Color::new(0, 128, 0, 1.0));
+
+        let first = &styled_node.children[0].children[0].style;
+        let second = &styled_node.children[0].children[1].style;
+        assert!(first.ptr_eq(second));
+        let cousin = &styled_node.children[2].children[0].style;
+        assert!(styled_node.children[1].children[0].style.ptr_eq(cousin));
+
+        // The second `li` in `.a` and the `li` in `.c` hit, and `body` and `ul` elements miss.
+        // Elements with an id or a `style` attribute are never looked up.
+        assert_eq!(stats, StyleSharingStats { hits: 2, misses: 6 });
+        assert_eq!(stats.hit_rate(), 0.25);
+    }
+
+    #[test]
+    fn test_parallel_traversal() {
+        let item = "<li class='item'><span>a</span><span class='b'>b</span></li>";
+        let list = format!("<ul class='list'>{}</ul>", item.repeat(20));
+        let html = format!(
+            "<body><div class='a'>{}</div><div style='--x: 2px;'>{}</div>{}</body>",
+            list, list, list
         );
+        let css = "
+.list {
+  font-size: 2em;
+}
+
+.a .item {
+  color: red;
+}
+
+li > .b {
+  margin: var(--x, 1px);
+}
+";
+        let mut html_parser = HTMLParser::new(html);
+        let mut css_parser = CSSParser::new(css.into());
+
+        let dom = Document::new(html_parser.run());
+
+        let rules = css_parser.parse_rules(Origin::Author);
+        let cssom = Stylesheet::new(rules);
+
+        let (sequential, _) = create_style_tree_with_stats(&dom, &cssom, Traversal::Sequential);
+        let (parallel, _) = create_style_tree_with_stats(&dom, &cssom, Traversal::Parallel);
+
+        fn assert_same(a: &StyledNode, b: &StyledNode) {
+            assert!(ptr::eq(a.node, b.node));
+            assert_eq!(a.style, b.style);
+            assert_eq!(a.custom_properties, b.custom_properties);
+            assert_eq!(a.children.len(), b.children.len());
+            for (a, b) in a.children.iter().zip(&b.children) {
+                assert_same(a, b);
+            }
+        }
+        assert_same(&sequential, &parallel);
+
+        let span = &parallel.children[1].children[0].children[19].children[1];
+        assert_eq!(span.style.margin.margin_top, LengthOrAuto::Length(2.));
     }
 }
@@ -0,0 +1,145 @@
+// refer: https://github.com/servo/servo/blob/master/components/style/sharing/mod.rs
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::atom::Atom;
+use crate::computed_values::ComputedValues;
+use crate::custom_properties::CustomPropertyMap;
+use crate::dom::ElementData;
+
+const STYLE_SHARING_CACHE_SIZE: usize = 31;
+
+/// Everything which decides the style of an element.
+/// Elements with the same key match the same rules, and inherit the same values.
+pub struct StyleSharingKey {
+    local_name: Atom,
+    classes: Vec<Atom>,
+    parent_style: Option<ComputedValues>,
+    inherited_custom_properties: Arc<CustomPropertyMap>,
+    /// Rules with selectors which depend on ancestors, and match the element.
+    revalidation_results: Vec<usize>,
+}
+
+impl StyleSharingKey {
+    pub fn new(
+        elm: &ElementData,
+        parent_style: Option<&ComputedValues>,
+        inherited_custom_properties: &Arc<CustomPropertyMap>,
+        revalidation_results: Vec<usize>,
+    ) -> StyleSharingKey {
+        StyleSharingKey {
+            local_name: *elm.local_name(),
+            classes: elm.classes().to_vec(),
+            parent_style: parent_style.cloned(),
+            inherited_custom_properties: inherited_custom_properties.clone(),
+            revalidation_results,
+        }
+    }
+
+    /// An id or a `style` attribute is unique to the element in most cases,
+    /// so that such elements are never shared.
+    pub fn can_share(elm: &ElementData) -> bool {
+        elm.id().is_none() && !elm.attributes.contains_key("style")
+    }
+
+    fn matches(&self, other: &StyleSharingKey) -> bool {
+        let same_parent_style = match (&self.parent_style, &other.parent_style) {
+            (Some(a), Some(b)) => a.ptr_eq(b),
+            (None, None) => true,
+            _ => false,
+        };
+        self.local_name == other.local_name
+            && self.classes == other.classes
+            && same_parent_style
+            && self.revalidation_results == other.revalidation_results
+            && (Arc::ptr_eq(
+                &self.inherited_custom_properties,
+                &other.inherited_custom_properties,
+            ) || self.inherited_custom_properties == other.inherited_custom_properties)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StyleSharingStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl StyleSharingStats {
+    pub fn hit_rate(&self) -> f32 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.;
+        }
+        self.hits as f32 / lookups as f32
+    }
+}
+
+/// Counters of lookups in the caches of all threads.
+#[derive(Default)]
+pub struct StyleSharingCounters {
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl StyleSharingCounters {
+    pub fn count(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> StyleSharingStats {
+        StyleSharingStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+struct StyleSharingCandidate {
+    key: StyleSharingKey,
+    style: ComputedValues,
+    custom_properties: Arc<CustomPropertyMap>,
+}
+
+/// Styles of recently styled elements, which siblings and cousins can reuse without matching rules.
+/// The most recently used candidate is at the front.
+#[derive(Default)]
+pub struct StyleSharingCache {
+    candidates: VecDeque<StyleSharingCandidate>,
+}
+
+impl StyleSharingCache {
+    pub fn new() -> StyleSharingCache {
+        StyleSharingCache::default()
+    }
+
+    pub fn lookup(
+        &mut self,
+        key: &StyleSharingKey,
+    ) -> Option<(ComputedValues, Arc<CustomPropertyMap>)> {
+        let index = self.candidates.iter().position(|c| c.key.matches(key))?;
+        let candidate = self.candidates.remove(index).unwrap();
+        let shared = (candidate.style.clone(), candidate.custom_properties.clone());
+        self.candidates.push_front(candidate);
+        Some(shared)
+    }
+
+    pub fn insert(
+        &mut self,
+        key: StyleSharingKey,
+        style: &ComputedValues,
+        custom_properties: &Arc<CustomPropertyMap>,
+    ) {
+        if self.candidates.len() == STYLE_SHARING_CACHE_SIZE {
+            self.candidates.pop_back();
+        }
+        self.candidates.push_front(StyleSharingCandidate {
+            key,
+            style: style.clone(),
+            custom_properties: custom_properties.clone(),
+        });
+    }
+}
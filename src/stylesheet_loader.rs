@@ -0,0 +1,347 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use encoding_rs::Encoding;
+
+use crate::cssom::{FontFaceSource, Origin, Stylesheet};
+use crate::encoding::decode_stylesheet;
+use crate::media_queries::Device;
+use crate::parser::css::CSSParser;
+
+#[derive(Debug)]
+pub enum ImportError {
+    /// Chain of stylesheets which imports the first stylesheet again.
+    Cycle(Vec<PathBuf>),
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Cycle(chain) => {
+                let chain: Vec<String> = chain.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "@import cycle detected: {}", chain.join(" -> "))
+            }
+            ImportError::Io(path, err) => write!(f, "failed to load {}: {}", path.display(), err),
+        }
+    }
+}
+
+/// Load stylesheets and resolve their `@import` rules.
+/// Rules of an imported stylesheet are spliced at the position of its `@import` rule,
+/// so that they precede rules of the importing stylesheet in the cascade.
+/// A stylesheet imported by several `@import` rules is applied at each of them.
+pub struct StylesheetLoader<'a> {
+    level: Origin,
+    device: &'a Device,
+    /// Stylesheets currently being loaded, from the entry to the innermost import.
+    loading: Vec<PathBuf>,
+    /// Encodings of `loading`, which imported stylesheets fall back to.
+    encodings: Vec<&'static Encoding>,
+    /// Encoding of the document, which entry stylesheets fall back to.
+    document_encoding: Option<&'static Encoding>,
+    pub errors: Vec<ImportError>,
+}
+
+impl<'a> StylesheetLoader<'a> {
+    pub fn new(level: Origin, device: &'a Device) -> StylesheetLoader<'a> {
+        StylesheetLoader {
+            level,
+            device,
+            loading: vec![],
+            encodings: vec![],
+            document_encoding: None,
+            errors: vec![],
+        }
+    }
+
+    pub fn set_document_encoding(&mut self, encoding: &'static Encoding) {
+        self.document_encoding = Some(encoding);
+    }
+
+    /// Load every entry stylesheet in order.
+    /// An entry which another entry imports directly or transitively by an `@import` rule
+    /// whose media query matches is loaded only through the rule,
+    /// otherwise it would be applied twice at different cascade positions.
+    pub fn load_entries(&mut self, paths: &[PathBuf]) -> Stylesheet {
+        let mut imported = HashSet::new();
+        for path in paths {
+            let path = normalize(path);
+            let mut targets = HashSet::new();
+            self.collect_import_targets(&path, &mut targets);
+            // An entry which imports itself through a cycle is still loaded.
+            targets.remove(&path);
+            imported.extend(targets);
+        }
+
+        let mut stylesheet = Stylesheet::new(vec![]);
+        for path in paths {
+            if imported.contains(&normalize(path)) {
+                continue;
+            }
+            self.load_into(path, &mut stylesheet);
+        }
+        stylesheet
+    }
+
+    pub fn load(&mut self, path: &Path) -> Stylesheet {
+        let mut stylesheet = Stylesheet::new(vec![]);
+        self.load_into(path, &mut stylesheet);
+        stylesheet
+    }
+
+    /// Parse `css` and resolve its `@import` rules and `@font-face` sources relative to `base`.
+    pub fn load_from_str(&mut self, css: String, base: &Path) -> Stylesheet {
+        let mut stylesheet = Stylesheet::new(vec![]);
+        self.load_str_into(css, base, &mut stylesheet);
+        stylesheet
+    }
+
+    fn load_into(&mut self, path: &Path, output: &mut Stylesheet) {
+        let path = normalize(path);
+
+        if let Some(start) = self.loading.iter().position(|p| *p == path) {
+            let mut chain = self.loading[start..].to_vec();
+            chain.push(path);
+            self.errors.push(ImportError::Cycle(chain));
+            return;
+        }
+
+        let referrer = self.encodings.last().copied().or(self.document_encoding);
+        let (css, encoding) = match fs::read(&path) {
+            Ok(bytes) => decode_stylesheet(&bytes, referrer),
+            Err(err) => {
+                self.errors.push(ImportError::Io(path, err));
+                return;
+            }
+        };
+
+        let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        self.loading.push(path);
+        self.encodings.push(encoding);
+        self.load_str_into(css, &base, output);
+        self.encodings.pop();
+        self.loading.pop();
+    }
+
+    fn load_str_into(&mut self, css: String, base: &Path, output: &mut Stylesheet) {
+        let stylesheet = CSSParser::new(css).parse_stylesheet(self.level.clone());
+
+        for import in &stylesheet.imports {
+            if !import.media.evaluate(self.device) {
+                continue;
+            }
+            self.load_into(&base.join(&import.url), output);
+        }
+
+        for mut font_face in stylesheet.font_faces {
+            for source in &mut font_face.sources {
+                if let FontFaceSource::Url { url, .. } = source {
+                    *url = base.join(&url).to_string_lossy().into_owned();
+                }
+            }
+            output.font_faces.push(font_face);
+        }
+
+        output.rules.extend(stylesheet.rules);
+    }
+
+    /// Collect canonical paths of stylesheets which `path` imports directly or transitively
+    /// by `@import` rules whose media queries match.
+    fn collect_import_targets(&self, path: &Path, targets: &mut HashSet<PathBuf>) {
+        let css = match fs::read(path) {
+            Ok(bytes) => decode_stylesheet(&bytes, self.document_encoding).0,
+            Err(_) => return,
+        };
+        let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let stylesheet = CSSParser::new(css).parse_stylesheet(self.level.clone());
+        for import in &stylesheet.imports {
+            if !import.media.evaluate(self.device) {
+                continue;
+            }
+            let target = normalize(&base.join(&import.url));
+            if targets.insert(target.clone()) {
+                self.collect_import_targets(&target, targets);
+            }
+        }
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cssom::Rule;
+
+    fn write_stylesheets(dir_name: &str, files: &[(&str, &str)]) -> PathBuf {
+        write_encoded_stylesheets(dir_name, encoding_rs::UTF_8, files)
+    }
+
+    fn write_encoded_stylesheets(
+        dir_name: &str,
+        encoding: &'static Encoding,
+        files: &[(&str, &str)],
+    ) -> PathBuf {
+        let dir = std::env::temp_dir().join(dir_name);
+        for (name, css) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, encoding.encode(css).0).unwrap();
+        }
+        dir
+    }
+
+    fn first_class(rule: &Rule) -> &str {
+        &rule.selectors[0].subject().class[0]
+    }
+
+    #[test]
+    fn test_import_order() {
+        let dir = write_stylesheets(
+            "kamaitachi_test_import_order",
+            &[
+                (
+                    "main.css",
+                    "@import url(base.css);\n@import \"print.css\" print;\n.main {}",
+                ),
+                ("base.css", "@import url('nested/reset.css');\n.base {}"),
+                ("nested/reset.css", ".reset {}"),
+                ("print.css", ".print {}"),
+            ],
+        );
+
+        let device = Device::new(1200., 800.);
+        let mut loader = StylesheetLoader::new(Origin::Author, &device);
+        let stylesheet = loader.load_entries(&[
+            dir.join("base.css"),
+            dir.join("main.css"),
+            dir.join("print.css"),
+        ]);
+
+        let classes: Vec<&str> = stylesheet.rules.iter().map(first_class).collect();
+        // `base.css` is applied through its `@import` rule, but `print.css` is applied
+        // as an entry, since its `@import` rule is only for `print` media.
+        assert_eq!(classes, vec!["reset", "base", "main", "print"]);
+        assert!(loader.errors.is_empty());
+    }
+
+    #[test]
+    fn test_transitive_imports() {
+        let dir = write_stylesheets(
+            "kamaitachi_test_transitive_imports",
+            &[
+                ("a.css", "@import url(common.css);\n.a {}"),
+                (
+                    "b.css",
+                    "@import url(./common.css);\n@import url(a.css);\n.b {}",
+                ),
+                ("common.css", "@import url(leaf.css);\n.common {}"),
+                ("leaf.css", ".leaf {}"),
+            ],
+        );
+
+        let device = Device::new(1200., 800.);
+        let mut loader = StylesheetLoader::new(Origin::Author, &device);
+        let stylesheet = loader.load_entries(&[dir.join("leaf.css"), dir.join("b.css")]);
+
+        // `leaf.css` is imported transitively, and shared imports are applied at each `@import`.
+        let classes: Vec<&str> = stylesheet.rules.iter().map(first_class).collect();
+        assert_eq!(classes, vec!["leaf", "common", "leaf", "common", "a", "b"]);
+        assert!(loader.errors.is_empty());
+    }
+
+    #[test]
+    fn test_import_cycle() {
+        let dir = write_stylesheets(
+            "kamaitachi_test_import_cycle",
+            &[
+                ("a.css", "@import url(b.css);\n.a {}"),
+                ("b.css", "@import url(a.css);\n.b {}"),
+            ],
+        );
+
+        let device = Device::new(1200., 800.);
+        let mut loader = StylesheetLoader::new(Origin::Author, &device);
+        let stylesheet = loader.load(&dir.join("a.css"));
+
+        let classes: Vec<&str> = stylesheet.rules.iter().map(first_class).collect();
+        assert_eq!(classes, vec!["b", "a"]);
+        assert_eq!(loader.errors.len(), 1);
+        if let ImportError::Cycle(chain) = &loader.errors[0] {
+            assert_eq!(chain.len(), 3);
+            assert_eq!(chain[0], chain[2]);
+        } else {
+            panic!("error should be Cycle");
+        }
+    }
+
+    #[test]
+    fn test_font_face_source_relative_to_stylesheet() {
+        let dir = write_stylesheets(
+            "kamaitachi_test_font_face_source",
+            &[
+                ("main.css", "@import url(fonts/fonts.css);"),
+                (
+                    "fonts/fonts.css",
+                    "@font-face { font-family: Foo; src: url(foo.woff2) format(\"woff2\"); }",
+                ),
+            ],
+        );
+
+        let device = Device::new(1200., 800.);
+        let mut loader = StylesheetLoader::new(Origin::Author, &device);
+        let stylesheet = loader.load(&dir.join("main.css"));
+
+        assert_eq!(stylesheet.font_faces.len(), 1);
+        assert_eq!(
+            stylesheet.font_faces[0].sources,
+            vec![FontFaceSource::Url {
+                url: normalize(&dir.join("fonts"))
+                    .join("foo.woff2")
+                    .to_string_lossy()
+                    .into_owned(),
+                format: Some("woff2".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_stylesheet_encoding() {
+        let font_face = "@font-face { font-family: \"日本語\"; src: url(a.woff); }";
+        let dir = write_encoded_stylesheets(
+            "kamaitachi_test_stylesheet_encoding",
+            encoding_rs::SHIFT_JIS,
+            &[
+                (
+                    "main.css",
+                    &format!(
+                        "@charset \"shift_jis\";\n@import url(sub.css);\n{}",
+                        font_face
+                    ),
+                ),
+                ("sub.css", font_face),
+                ("entry.css", font_face),
+            ],
+        );
+
+        let device = Device::new(1200., 800.);
+        let mut loader = StylesheetLoader::new(Origin::Author, &device);
+        // `sub.css` falls back to the encoding of `main.css`,
+        // and `entry.css` to that of the document.
+        loader.set_document_encoding(encoding_rs::SHIFT_JIS);
+        let stylesheet = loader.load_entries(&[dir.join("main.css"), dir.join("entry.css")]);
+        let families: Vec<&str> = stylesheet
+            .font_faces
+            .iter()
+            .map(|font_face| font_face.family_name.as_str())
+            .collect();
+        assert_eq!(families, vec!["日本語"; 3]);
+    }
+}
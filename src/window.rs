@@ -1,41 +1,55 @@
-use iced::{scrollable, Element, Length, Sandbox, Scrollable, Settings};
+use iced::{scrollable, Element, Length, Point, Rectangle, Sandbox, Scrollable, Settings, Size};
 
 use std::cell::RefCell;
-use std::io::prelude::*;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::{env, fs, io};
 
 use crate::cssom::{Origin, Stylesheet};
-use crate::layout::{font, layout_tree, Dimensions};
+use crate::dom::Document;
+use crate::encoding;
+use crate::layout::{font, layout_tree, Dimensions, LayoutBox, Rect};
+use crate::media_queries::Device;
 use crate::painter;
-use crate::parser::{css, html};
-use crate::style::create_style_tree;
-use css::CSSParser;
+use crate::parser::html;
+use crate::serializer::SerializeMode;
+use crate::style::{create_style_tree_with_stats, Traversal};
+use crate::stylesheet_loader::StylesheetLoader;
 use html::HTMLParser;
 use painter::wrapper::Wrapper;
 use painter::{build_display_list, DisplayCommand, DisplayList};
 
-#[derive(Debug)]
-pub enum Message {}
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// The scroll position may be changed.
+    Scrolled,
+}
 
 pub struct Window {
+    layout_root: LayoutBox<'static>,
+    /// The scrollport at the scroll origin.
+    viewport: Rect,
     items: DisplayList,
     height: f32,
     width: f32,
     scroll: scrollable::State,
+    scroll_y: f32,
 }
 
 impl<'a> Sandbox for Window {
     type Message = Message;
 
     fn new() -> Self {
-        let (items, height, width) = prepare();
+        let (layout_root, viewport) = prepare();
+        let (items, height, width) = build_display_list(&layout_root);
         Window {
+            layout_root,
+            viewport,
             items,
             height,
             width,
             scroll: scrollable::State::new(),
+            scroll_y: 0.,
         }
     }
 
@@ -44,11 +58,28 @@ impl<'a> Sandbox for Window {
     }
 
     fn update(&mut self, message: Message) {
-        match message {}
+        match message {
+            Message::Scrolled => {
+                let bounds = Rectangle::new(
+                    Point::ORIGIN,
+                    Size::new(self.viewport.width, self.viewport.height),
+                );
+                let content_bounds =
+                    Rectangle::new(Point::ORIGIN, Size::new(self.width, self.height));
+                let scroll_y = self.scroll.offset(bounds, content_bounds) as f32;
+                if scroll_y == self.scroll_y {
+                    return;
+                }
+                // Fixed and sticky boxes are moved for the scroll position.
+                self.scroll_y = scroll_y;
+                self.layout_root.scroll_to(&self.viewport, 0., scroll_y);
+                self.items = build_display_list(&self.layout_root).0;
+            }
+        }
     }
 
     fn view(&mut self) -> Element<Message> {
-        let mut wrapper = Wrapper::new(self.height, self.width);
+        let mut wrapper = Wrapper::new(self.height, self.width).on_scroll(Message::Scrolled);
 
         font::with_thread_local_font_context(|font_context| {
             for item in &self.items {
@@ -75,61 +106,91 @@ impl<'a> Sandbox for Window {
     }
 }
 
-fn prepare() -> (DisplayList, f32, f32) {
-    let args: Vec<String> = env::args().collect();
-    if args.is_empty() {
-        panic!("You need to specify entry path.");
-    }
-    let path = Path::new(&args[1]);
+/// Read the first HTML file and paths of CSS files under the entry path.
+fn read_entries() -> (Vec<u8>, Vec<PathBuf>) {
+    let path = env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .expect("You need to specify entry path.");
+    let path = Path::new(&path);
 
     let mut paths = vec![];
     visit_dirs(path, &mut paths).unwrap();
 
-    let mut html = String::new();
-    let mut css_list = vec![];
+    let mut html = vec![];
+    let mut css_paths = vec![];
 
     for path in paths {
         let ext = path.extension().unwrap();
         if html.is_empty() && ext == "html" {
-            fs::File::open(path)
-                .unwrap()
-                .read_to_string(&mut html)
-                .unwrap();
+            html = fs::read(path).unwrap();
             continue;
         }
         if ext == "css" {
-            let mut css = String::new();
-            fs::File::open(path)
-                .unwrap()
-                .read_to_string(&mut css)
-                .unwrap();
-            css_list.push(css);
+            css_paths.push(path);
             continue;
         }
     }
+    (html, css_paths)
+}
 
-    paint(html, css_list)
+/// The device of the window, which is configured by options in `args`.
+/// `--minimum-font-size=<px>` sets the minimum font size.
+fn create_device(args: &[String]) -> Device {
+    let mut device = Device::new(1200.0, 800.0);
+    for arg in args {
+        if let Some(size) = arg.strip_prefix("--minimum-font-size=") {
+            match size.parse::<f32>() {
+                Ok(size) if size >= 0. => device.minimum_font_size = size,
+                _ => eprintln!("invalid minimum font size: {}", size),
+            }
+        }
+    }
+    device
 }
 
-fn paint(html: String, css_list: Vec<String>) -> (DisplayList, f32, f32) {
-    let dom = HTMLParser::new(html).run();
-    let mut author_rules = vec![];
+/// Lay out the entry, and return the layout tree and the viewport.
+fn prepare() -> (LayoutBox<'static>, Rect) {
+    let (html, css_paths) = read_entries();
+    let (html, encoding) = encoding::decode_html(&html);
 
-    for css in css_list {
-        author_rules.extend(CSSParser::new(css).parse_rules(Origin::Author));
-    }
+    let args: Vec<String> = env::args().skip(1).collect();
+    let device = create_device(&args);
 
-    let cssom = Stylesheet::new(author_rules);
+    let mut loader = StylesheetLoader::new(Origin::Author, &device);
+    loader.set_document_encoding(encoding);
+    let cssom = loader.load_entries(&css_paths);
+    for err in &loader.errors {
+        eprintln!("{}", err);
+    }
 
-    let styled_node = create_style_tree(&dom, &cssom, None);
+    font::with_thread_local_font_context(|font_context| {
+        font_context.minimum_font_size = device.minimum_font_size;
+        for font_face in &cssom.font_faces {
+            if !font_context.add_font_face(font_face) {
+                eprintln!("failed to load font face: {}", font_face.family_name);
+            }
+        }
+    });
 
     let mut viewport: Dimensions = Default::default();
-    viewport.content.width = 1200.0;
-    viewport.content.height = 800.0;
+    viewport.content.width = device.viewport_width;
+    viewport.content.height = device.viewport_height;
+    let scrollport = viewport.content.clone();
+
+    (layout(html, cssom, viewport), scrollport)
+}
+
+/// The document and the style tree live as long as the window, which keeps the layout tree
+/// to move boxes when it is scrolled.
+fn layout(html: String, cssom: Stylesheet, viewport: Dimensions) -> LayoutBox<'static> {
+    let dom = Box::leak(Box::new(Document::new(HTMLParser::new(html).run())));
+    let cssom = Box::leak(Box::new(cssom));
 
-    let layout_root = layout_tree(&styled_node, Rc::new(RefCell::new(viewport)));
+    let (styled_node, _) = create_style_tree_with_stats(dom, cssom, Traversal::Parallel);
+    let styled_node = Box::leak(Box::new(styled_node));
 
-    build_display_list(&layout_root)
+    layout_tree(styled_node, Rc::new(RefCell::new(viewport)))
 }
 
 // one possible implementation of walking a directory only visiting files
@@ -150,8 +211,45 @@ fn visit_dirs(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
     Ok(())
 }
 
+/// Print the parsed DOM tree of the entry as HTML.
+pub fn dump_dom() {
+    let (html, _) = read_entries();
+    let (html, _) = encoding::decode_html(&html);
+    let dom = HTMLParser::new(html).run();
+    println!("{}", dom.outer_html(SerializeMode::Pretty));
+}
+
 pub fn main() -> iced::Result {
     let mut settings = Settings::default();
     settings.window.size = (1200, 800);
     Window::run(settings)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::css::CSSParser;
+    use crate::style::create_style_tree;
+
+    #[test]
+    fn test_minimum_font_size() {
+        let args =
+            |args: &[&str]| -> Vec<String> { args.iter().map(|arg| arg.to_string()).collect() };
+        assert_eq!(create_device(&args(&["index.html"])).minimum_font_size, 0.);
+        assert_eq!(
+            create_device(&args(&["--minimum-font-size=-1"])).minimum_font_size,
+            0.
+        );
+        let device = create_device(&args(&["--minimum-font-size=12", "index.html"]));
+        assert_eq!(device.minimum_font_size, 12.);
+
+        let dom = Document::new(HTMLParser::new("<body><p></p></body>".into()).run());
+        let css = "body { font-size: 16px; } p { font-size: 8px; }";
+        let cssom = Stylesheet::new(CSSParser::new(css.into()).parse_rules(Origin::Author));
+        let styled_node = create_style_tree(&dom, &cssom);
+        let mut font_context = font::FontContext::new();
+        font_context.minimum_font_size = device.minimum_font_size;
+        assert_eq!(font_context.used_font_size(&styled_node), 16.);
+        assert_eq!(font_context.used_font_size(&styled_node.children[0]), 12.);
+    }
+}